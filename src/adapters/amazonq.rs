@@ -0,0 +1,275 @@
+use super::common::{apply_one_to_one, ensure_dir, resolve_target, scan_one_to_one};
+use super::conflict::{write_with_conflict, ApplyContext, ConflictMode};
+use super::helpers::unify_line_endings;
+use super::traits::{
+    ApplyResult, DetectionInfo, PresetFile, PresetFiles, ProjectMarkers, ReloadHint, ScanResult,
+    ToolAdapter,
+};
+use crate::error::Result;
+use crate::preset::config::ToolPaths;
+use std::path::{Path, PathBuf};
+
+/// Amazon Q Developer (formerly CodeWhisperer) adapter
+///
+/// Converts presets to Amazon Q format:
+/// - rules/*.md → .amazonq/rules/*.md
+/// - memory/*.md → .amazonq/rules/project-memory.md (merged)
+///
+/// Amazon Q project rules have no equivalent for commands, mcp, hooks, agents,
+/// skills or settings, so those sections are skipped.
+pub struct AmazonQAdapter {
+    project_dir: PathBuf,
+    paths: ToolPaths,
+}
+
+/// Sections without an Amazon Q equivalent
+const UNSUPPORTED_SECTIONS: &[(&str, &str)] = &[
+    ("commands", "Amazon Q has no project prompt files"),
+    ("mcp", "workspace MCP config is not managed by aidot yet"),
+    ("hooks", "Amazon Q has no hooks"),
+    ("agents", "Amazon Q has no project agent files"),
+    ("skills", "Amazon Q has no skills"),
+    ("settings", "Amazon Q has no project settings file"),
+];
+
+impl AmazonQAdapter {
+    pub fn new(project_dir: &Path) -> Self {
+        Self {
+            project_dir: project_dir.to_path_buf(),
+            paths: ToolPaths::default(),
+        }
+    }
+
+    /// Override target paths from preset config ([tools.amazonq])
+    pub fn with_paths(mut self, paths: ToolPaths) -> Self {
+        self.paths = paths;
+        self
+    }
+
+    /// Get the .amazonq directory path
+    fn amazonq_dir(&self) -> PathBuf {
+        self.project_dir.join(".amazonq")
+    }
+
+    /// Resolve a target path (override or default) to (absolute path, display path)
+    fn target(&self, override_path: &Option<String>, default: &str) -> (PathBuf, String) {
+        resolve_target(&self.project_dir, override_path.as_deref(), default)
+    }
+
+    fn rules_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.rules_dir, ".amazonq/rules")
+    }
+
+    fn memory_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.memory_file, ".amazonq/rules/project-memory.md")
+    }
+
+    /// Build the merged memory rule from memory/*.md
+    fn memory_content(files: &[PresetFile]) -> String {
+        let mut content = String::from("# Project Memory\n\n");
+        for (i, file) in files.iter().enumerate() {
+            if i > 0 {
+                content.push_str("\n\n---\n\n");
+            }
+            content.push_str(&format!(
+                "## {}\n\n",
+                file.section_path("memory").replace(".md", "")
+            ));
+            content.push_str(&file.content);
+        }
+        unify_line_endings(&content)
+    }
+}
+
+impl ToolAdapter for AmazonQAdapter {
+    fn name(&self) -> &str {
+        "Amazon Q"
+    }
+
+    fn detect_with_reason(&self, markers: &ProjectMarkers) -> DetectionInfo {
+        if markers.has(".amazonq") {
+            DetectionInfo::found("detected via .amazonq/ directory")
+        } else {
+            DetectionInfo::not_found("no .amazonq/ directory")
+        }
+    }
+
+    fn global_config_dir(&self) -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".aws").join("amazonq"))
+    }
+
+    fn unsupported_sections(&self) -> &[(&'static str, &'static str)] {
+        UNSUPPORTED_SECTIONS
+    }
+
+    fn reload_hint(&self) -> Option<ReloadHint> {
+        Some(ReloadHint {
+            message: "start a new Amazon Q chat to load the rules",
+            command: None,
+        })
+    }
+
+    fn scan(&self, preset_files: &PresetFiles, _target_dir: &Path) -> ScanResult {
+        let mut result = ScanResult::new();
+
+        let (rules_dir, rules_display) = self.rules_target();
+        scan_one_to_one(
+            &preset_files.rules,
+            "rules",
+            &rules_dir,
+            &rules_display,
+            &mut result,
+            None,
+            None,
+        );
+
+        if !preset_files.memory.is_empty() {
+            let (memory_file, memory_display) = self.memory_target();
+            result.add_change(memory_display, "memory".to_string(), memory_file.exists());
+        }
+
+        result
+    }
+
+    fn apply(
+        &self,
+        preset_files: &PresetFiles,
+        _target_dir: &Path,
+        conflict_mode: &mut ConflictMode,
+        ctx: &ApplyContext,
+    ) -> Result<ApplyResult> {
+        let mut result = ApplyResult::new();
+        if preset_files.rules.is_empty() && preset_files.memory.is_empty() {
+            return Ok(result);
+        }
+        ensure_dir(&self.amazonq_dir(), ctx)?;
+
+        // Merged memory first (may trigger interactive prompts)
+        if !preset_files.memory.is_empty() {
+            let (memory_file, memory_display) = self.memory_target();
+            if let Some(parent) = memory_file.parent() {
+                ensure_dir(parent, ctx)?;
+            }
+            write_with_conflict(
+                &memory_file,
+                &Self::memory_content(&preset_files.memory),
+                conflict_mode,
+                ctx,
+                &mut result,
+                &memory_display,
+            )?;
+        }
+
+        let (rules_dir, rules_display) = self.rules_target();
+        apply_one_to_one(
+            &preset_files.rules,
+            "rules",
+            &rules_dir,
+            &rules_display,
+            &mut result,
+            conflict_mode,
+            ctx,
+            None,
+            None,
+        )?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn file(path: &str, content: &str) -> PresetFile {
+        PresetFile {
+            relative_path: path.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_detect() {
+        let temp_dir = TempDir::new().unwrap();
+        let adapter = AmazonQAdapter::new(temp_dir.path());
+        assert!(!adapter.detect(&ProjectMarkers::scan(temp_dir.path())));
+
+        fs::create_dir_all(temp_dir.path().join(".amazonq")).unwrap();
+        let info = adapter.detect_with_reason(&ProjectMarkers::scan(temp_dir.path()));
+        assert!(info.detected);
+        assert_eq!(info.reason, "detected via .amazonq/ directory");
+    }
+
+    #[test]
+    fn test_apply_rules_and_memory() {
+        let temp_dir = TempDir::new().unwrap();
+        let adapter = AmazonQAdapter::new(temp_dir.path());
+        let preset_files = PresetFiles {
+            rules: vec![file("rules/backend/style.md", "# Style")],
+            memory: vec![
+                file("memory/context.md", "Context"),
+                file("memory/stack.md", "Stack"),
+            ],
+            commands: vec![file("commands/build.md", "# Build")],
+            ..Default::default()
+        };
+
+        let scan = adapter.scan(&preset_files, temp_dir.path());
+        let paths: Vec<_> = scan.changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                ".amazonq/rules/backend/style.md",
+                ".amazonq/rules/project-memory.md"
+            ]
+        );
+
+        let result = adapter
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
+            .unwrap();
+        assert_eq!(result.created.len(), 2);
+
+        let rule = temp_dir.path().join(".amazonq/rules/backend/style.md");
+        assert_eq!(fs::read_to_string(rule).unwrap(), "# Style");
+        let memory =
+            fs::read_to_string(temp_dir.path().join(".amazonq/rules/project-memory.md")).unwrap();
+        assert!(memory.starts_with("# Project Memory\n\n## context\n\nContext"));
+        assert!(memory.contains("## stack\n\nStack"));
+
+        // Commands have no Amazon Q equivalent
+        assert!(!temp_dir.path().join(".amazonq/commands").exists());
+        assert!(adapter
+            .unsupported_sections()
+            .iter()
+            .any(|(section, _)| *section == "commands"));
+    }
+
+    #[test]
+    fn test_apply_without_supported_sections_creates_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let adapter = AmazonQAdapter::new(temp_dir.path());
+        let preset_files = PresetFiles {
+            commands: vec![file("commands/build.md", "# Build")],
+            ..Default::default()
+        };
+
+        let result = adapter
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
+            .unwrap();
+        assert_eq!(result.total(), 0);
+        assert!(!temp_dir.path().join(".amazonq").exists());
+    }
+}
@@ -1,42 +1,106 @@
 use super::common::{
-    apply_json_merge, apply_one_to_one, ensure_dir, scan_merged_section, scan_one_to_one,
+    apply_json_merge, apply_one_to_one, ensure_dir, json_file_content, resolve_target,
+    scan_merged_section, scan_one_to_one,
+};
+use super::conflict::{write_with_conflict, ApplyContext, ConflictMode};
+use super::helpers::{is_command_available, unify_line_endings};
+use super::traits::{
+    ApplyResult, DetectionInfo, PresetFile, PresetFiles, ProjectMarkers, ReloadHint, ScanResult,
+    ToolAdapter,
 };
-use super::conflict::{write_with_conflict, ConflictMode};
-use super::helpers::{is_command_available, strip_section_prefix};
-use super::traits::{ApplyResult, PresetFile, PresetFiles, ScanResult, ToolAdapter};
 use crate::error::Result;
-use std::fs;
+use crate::preset::config::{MergeStrategy, ToolPaths};
+use serde_json::{Map, Value};
 use std::path::{Path, PathBuf};
 
+/// Hook events in Claude Code's hooks configuration
+pub const CLAUDE_HOOK_EVENTS: &[&str] = &[
+    "PreToolUse",
+    "PostToolUse",
+    "Notification",
+    "UserPromptSubmit",
+    "Stop",
+    "SubagentStop",
+    "PreCompact",
+    "SessionStart",
+    "SessionEnd",
+];
+
 /// Claude Code adapter
 pub struct ClaudeCodeAdapter {
     project_dir: PathBuf,
+    paths: ToolPaths,
 }
 
 impl ClaudeCodeAdapter {
     pub fn new(project_dir: &Path) -> Self {
         Self {
             project_dir: project_dir.to_path_buf(),
+            paths: ToolPaths::default(),
         }
     }
 
+    /// Override target paths from preset config ([tools.claude])
+    pub fn with_paths(mut self, paths: ToolPaths) -> Self {
+        self.paths = paths;
+        self
+    }
+
     /// Get the .claude directory path
     fn claude_dir(&self) -> PathBuf {
         self.project_dir.join(".claude")
     }
 
+    /// Resolve a target path (override or default) to (absolute path, display path)
+    fn target(&self, override_path: &Option<String>, default: &str) -> (PathBuf, String) {
+        resolve_target(&self.project_dir, override_path.as_deref(), default)
+    }
+
+    fn rules_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.rules_dir, ".claude/rules")
+    }
+
+    fn commands_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.commands_dir, ".claude/commands")
+    }
+
+    fn agents_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.agents_dir, ".claude/agents")
+    }
+
+    fn skills_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.skills_dir, ".claude/skills")
+    }
+
+    fn memory_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.memory_file, ".claude/CLAUDE.md")
+    }
+
+    fn mcp_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.mcp_file, ".claude/settings.local.json")
+    }
+
+    fn hooks_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.hooks_file, ".claude/hooks.json")
+    }
+
+    fn settings_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.settings_file, ".claude/settings.local.json")
+    }
+
     /// Apply memory files: memory/*.md → .claude/CLAUDE.md
     fn apply_memory(
         &self,
         files: &[PresetFile],
         result: &mut ApplyResult,
         mode: &mut ConflictMode,
+        ctx: &ApplyContext,
     ) -> Result<()> {
         if files.is_empty() {
             return Ok(());
         }
 
-        let claude_md = self.claude_dir().join("CLAUDE.md");
+        let (claude_md, display) = self.memory_target();
 
         // Merge all memory files from preset
         let mut content = String::new();
@@ -47,7 +111,14 @@ impl ClaudeCodeAdapter {
             content.push_str(&file.content);
         }
 
-        write_with_conflict(&claude_md, &content, mode, result, ".claude/CLAUDE.md")?;
+        write_with_conflict(
+            &claude_md,
+            &unify_line_endings(&content),
+            mode,
+            ctx,
+            result,
+            &display,
+        )?;
 
         Ok(())
     }
@@ -56,25 +127,19 @@ impl ClaudeCodeAdapter {
     fn apply_hooks(
         &self,
         files: &[PresetFile],
+        strategy: MergeStrategy,
         result: &mut ApplyResult,
         mode: &mut ConflictMode,
+        ctx: &ApplyContext,
     ) -> Result<()> {
         if files.is_empty() {
             return Ok(());
         }
 
-        let hooks_file = self.claude_dir().join("hooks.json");
+        let (hooks_file, display) = self.hooks_target();
 
-        // Merge all hooks into one JSON object
-        let mut hooks = serde_json::Map::new();
-        for file in files {
-            let hook_name = strip_section_prefix(&file.relative_path, "hooks").replace(".json", "");
-            let hook_config: serde_json::Value = serde_json::from_str(&file.content)?;
-            hooks.insert(hook_name, hook_config);
-        }
-
-        let json_str = serde_json::to_string_pretty(&serde_json::Value::Object(hooks))?;
-        write_with_conflict(&hooks_file, &json_str, mode, result, ".claude/hooks.json")?;
+        let json_str = json_file_content(&merge_hooks(files, strategy)?, ctx)?;
+        write_with_conflict(&hooks_file, &json_str, mode, ctx, result, &display)?;
 
         Ok(())
     }
@@ -85,19 +150,18 @@ impl ClaudeCodeAdapter {
         files: &[PresetFile],
         result: &mut ApplyResult,
         mode: &mut ConflictMode,
+        ctx: &ApplyContext,
     ) -> Result<()> {
         if files.is_empty() {
             return Ok(());
         }
 
-        let settings_file = self.claude_dir().join("settings.local.json");
+        let (settings_file, display) = self.settings_target();
 
         // Read existing settings or create new
-        let mut settings: serde_json::Value = if settings_file.exists() {
-            let content = fs::read_to_string(&settings_file)?;
-            serde_json::from_str(&content)?
-        } else {
-            serde_json::json!({})
+        let mut settings: serde_json::Value = match ctx.read_target(&settings_file)? {
+            Some(content) => serde_json::from_str(&content)?,
+            None => serde_json::json!({}),
         };
 
         // Merge all settings files
@@ -112,14 +176,8 @@ impl ClaudeCodeAdapter {
             }
         }
 
-        let json_str = serde_json::to_string_pretty(&settings)?;
-        write_with_conflict(
-            &settings_file,
-            &json_str,
-            mode,
-            result,
-            ".claude/settings.local.json",
-        )?;
+        let json_str = json_file_content(&settings, ctx)?;
+        write_with_conflict(&settings_file, &json_str, mode, ctx, result, &display)?;
 
         Ok(())
     }
@@ -130,48 +188,68 @@ impl ToolAdapter for ClaudeCodeAdapter {
         "Claude Code"
     }
 
-    fn detect(&self) -> bool {
-        self.claude_dir().exists() || is_command_available("claude")
+    fn detect_with_reason(&self, markers: &ProjectMarkers) -> DetectionInfo {
+        if markers.has(".claude") {
+            DetectionInfo::found("detected via .claude/ directory")
+        } else if is_command_available("claude") {
+            DetectionInfo::found("detected via claude CLI in PATH")
+        } else {
+            DetectionInfo::not_found("no .claude/ directory or claude CLI in PATH")
+        }
+    }
+
+    fn global_config_dir(&self) -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".claude"))
+    }
+
+    fn reload_hint(&self) -> Option<ReloadHint> {
+        // Running sessions keep the CLAUDE.md and settings they started with
+        Some(ReloadHint {
+            message: "start a new Claude Code session to load the changes",
+            command: None,
+        })
     }
 
     fn scan(&self, preset_files: &PresetFiles, _target_dir: &Path) -> ScanResult {
         let mut result = ScanResult::new();
-        let claude_dir = self.claude_dir();
-        let settings_file = claude_dir.join("settings.local.json");
 
         // 1:1 mapped sections
+        let (rules_dir, rules_display) = self.rules_target();
         scan_one_to_one(
             &preset_files.rules,
             "rules",
-            &claude_dir.join("rules"),
-            ".claude/rules",
+            &rules_dir,
+            &rules_display,
             &mut result,
             None,
             None,
         );
+        let (commands_dir, commands_display) = self.commands_target();
         scan_one_to_one(
             &preset_files.commands,
             "commands",
-            &claude_dir.join("commands"),
-            ".claude/commands",
+            &commands_dir,
+            &commands_display,
             &mut result,
             None,
             None,
         );
+        let (agents_dir, agents_display) = self.agents_target();
         scan_one_to_one(
             &preset_files.agents,
             "agents",
-            &claude_dir.join("agents"),
-            ".claude/agents",
+            &agents_dir,
+            &agents_display,
             &mut result,
             None,
             None,
         );
+        let (skills_dir, skills_display) = self.skills_target();
         scan_one_to_one(
             &preset_files.skills,
             "skills",
-            &claude_dir.join("skills"),
-            ".claude/skills",
+            &skills_dir,
+            &skills_display,
             &mut result,
             None,
             None,
@@ -179,29 +257,29 @@ impl ToolAdapter for ClaudeCodeAdapter {
 
         // Merged sections
         if !preset_files.memory.is_empty() {
-            result.add_change(
-                ".claude/CLAUDE.md".to_string(),
-                "memory".to_string(),
-                claude_dir.join("CLAUDE.md").exists(),
-            );
+            let (memory_file, memory_display) = self.memory_target();
+            result.add_change(memory_display, "memory".to_string(), memory_file.exists());
         }
+        let (mcp_file, mcp_display) = self.mcp_target();
         scan_merged_section(
             &preset_files.mcp,
-            ".claude/settings.local.json",
+            &mcp_display,
             "mcp",
-            &settings_file,
+            &mcp_file,
             &mut result,
         );
+        let (hooks_file, hooks_display) = self.hooks_target();
         scan_merged_section(
             &preset_files.hooks,
-            ".claude/hooks.json",
+            &hooks_display,
             "hooks",
-            &claude_dir.join("hooks.json"),
+            &hooks_file,
             &mut result,
         );
+        let (settings_file, settings_display) = self.settings_target();
         scan_merged_section(
             &preset_files.settings,
-            ".claude/settings.local.json",
+            &settings_display,
             "settings",
             &settings_file,
             &mut result,
@@ -215,65 +293,81 @@ impl ToolAdapter for ClaudeCodeAdapter {
         preset_files: &PresetFiles,
         _target_dir: &Path,
         conflict_mode: &mut ConflictMode,
+        ctx: &ApplyContext,
     ) -> Result<ApplyResult> {
-        ensure_dir(&self.claude_dir())?;
+        ensure_dir(&self.claude_dir(), ctx)?;
 
         let mut result = ApplyResult::new();
-        let claude_dir = self.claude_dir();
 
         // Apply merged sections first (may trigger interactive prompts)
-        self.apply_memory(&preset_files.memory, &mut result, conflict_mode)?;
+        self.apply_memory(&preset_files.memory, &mut result, conflict_mode, ctx)?;
+        let (mcp_file, mcp_display) = self.mcp_target();
         apply_json_merge(
             &preset_files.mcp,
             "mcp",
-            &claude_dir.join("settings.local.json"),
-            ".claude/settings.local.json",
+            &mcp_file,
+            &mcp_display,
             "mcpServers",
             serde_json::json!({}),
             &mut result,
             conflict_mode,
+            ctx,
+        )?;
+        self.apply_hooks(
+            &preset_files.hooks,
+            preset_files.hooks_merge,
+            &mut result,
+            conflict_mode,
+            ctx,
         )?;
-        self.apply_hooks(&preset_files.hooks, &mut result, conflict_mode)?;
-        self.apply_settings(&preset_files.settings, &mut result, conflict_mode)?;
+        self.apply_settings(&preset_files.settings, &mut result, conflict_mode, ctx)?;
 
         // 1:1 mapped sections (resolved immediately from PreResolved map)
+        let (rules_dir, rules_display) = self.rules_target();
         apply_one_to_one(
             &preset_files.rules,
             "rules",
-            &claude_dir.join("rules"),
-            ".claude/rules",
+            &rules_dir,
+            &rules_display,
             &mut result,
             conflict_mode,
+            ctx,
             None,
             None,
         )?;
+        let (commands_dir, commands_display) = self.commands_target();
         apply_one_to_one(
             &preset_files.commands,
             "commands",
-            &claude_dir.join("commands"),
-            ".claude/commands",
+            &commands_dir,
+            &commands_display,
             &mut result,
             conflict_mode,
+            ctx,
             None,
             None,
         )?;
+        let (agents_dir, agents_display) = self.agents_target();
         apply_one_to_one(
             &preset_files.agents,
             "agents",
-            &claude_dir.join("agents"),
-            ".claude/agents",
+            &agents_dir,
+            &agents_display,
             &mut result,
             conflict_mode,
+            ctx,
             None,
             None,
         )?;
+        let (skills_dir, skills_display) = self.skills_target();
         apply_one_to_one(
             &preset_files.skills,
             "skills",
-            &claude_dir.join("skills"),
-            ".claude/skills",
+            &skills_dir,
+            &skills_display,
             &mut result,
             conflict_mode,
+            ctx,
             None,
             None,
         )?;
@@ -282,9 +376,119 @@ impl ToolAdapter for ClaudeCodeAdapter {
     }
 }
 
+/// Merge hooks/*.json into one hooks.json object
+///
+/// Files in Claude's event format have their hooks combined per event
+/// (`concat` appends entries not already present, `replace` keeps the last
+/// file's entries). Other files are stored under their file name.
+fn merge_hooks(files: &[PresetFile], strategy: MergeStrategy) -> Result<Value> {
+    let mut merged = Map::new();
+    for file in files {
+        let config: Value = serde_json::from_str(&file.content)?;
+        let Some(events) = claude_hook_events(&config) else {
+            let hook_name = file.section_path("hooks").replace(".json", "");
+            merged.insert(hook_name, config);
+            continue;
+        };
+
+        for (event, entries) in events {
+            let entries = entries.as_array().cloned().unwrap_or_default();
+            match (strategy, merged.get_mut(event)) {
+                (MergeStrategy::Concat, Some(Value::Array(existing))) => {
+                    for entry in entries {
+                        if !existing.contains(&entry) {
+                            existing.push(entry);
+                        }
+                    }
+                }
+                _ => {
+                    merged.insert(event.clone(), Value::Array(entries));
+                }
+            }
+        }
+    }
+    Ok(Value::Object(merged))
+}
+
+/// Event map of a hooks file in Claude's format
+///
+/// Accepts `{ "PreToolUse": [...] }` and the settings form
+/// `{ "hooks": { "PreToolUse": [...] } }`; every key must be a known event
+/// holding an array.
+fn claude_hook_events(config: &Value) -> Option<&Map<String, Value>> {
+    let events = unwrap_hooks_key(config.as_object()?);
+    let valid = !events.is_empty()
+        && events.iter().all(|(event, entries)| {
+            CLAUDE_HOOK_EVENTS.contains(&event.as_str()) && entries.is_array()
+        });
+    valid.then_some(events)
+}
+
+fn unwrap_hooks_key(map: &Map<String, Value>) -> &Map<String, Value> {
+    match map.get("hooks").and_then(Value::as_object) {
+        Some(inner) if map.len() == 1 => inner,
+        _ => map,
+    }
+}
+
+/// Problems with a hooks file that uses Claude's event names
+///
+/// Files without any Claude event are not checked, since they are stored
+/// under their file name (and may target another tool).
+pub fn claude_hook_problems(config: &Value) -> Vec<String> {
+    let Some(map) = config.as_object() else {
+        return vec!["hooks file must be a JSON object".to_string()];
+    };
+    let events = unwrap_hooks_key(map);
+    if !events
+        .keys()
+        .any(|event| CLAUDE_HOOK_EVENTS.contains(&event.as_str()))
+    {
+        return Vec::new();
+    }
+
+    let mut problems = Vec::new();
+    for (event, entries) in events {
+        if !CLAUDE_HOOK_EVENTS.contains(&event.as_str()) {
+            problems.push(format!("unknown hook event '{}'", event));
+            continue;
+        }
+        let Some(entries) = entries.as_array() else {
+            problems.push(format!("'{}' must be an array of hook entries", event));
+            continue;
+        };
+        for (i, entry) in entries.iter().enumerate() {
+            let Some(hooks) = entry.get("hooks").and_then(Value::as_array) else {
+                problems.push(format!("'{}'[{}] has no \"hooks\" array", event, i));
+                continue;
+            };
+            for hook in hooks {
+                match hook.get("type").and_then(Value::as_str) {
+                    None => {
+                        problems.push(format!("'{}'[{}] has a hook without \"type\"", event, i))
+                    }
+                    Some("command") if hook.get("command").and_then(Value::as_str).is_none() => {
+                        problems.push(format!(
+                            "'{}'[{}] has a command hook without \"command\"",
+                            event, i
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    if claude_hook_events(config).is_none() {
+        problems
+            .push("not in Claude's event format, so it is stored under its file name".to_string());
+    }
+    problems
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
     use tempfile::TempDir;
 
     fn create_test_adapter() -> (TempDir, ClaudeCodeAdapter) {
@@ -310,7 +514,7 @@ mod tests {
     fn test_detect_with_claude_dir() {
         let (temp_dir, adapter) = create_test_adapter();
         fs::create_dir_all(temp_dir.path().join(".claude")).unwrap();
-        assert!(adapter.detect());
+        assert!(adapter.detect(&ProjectMarkers::scan(temp_dir.path())));
     }
 
     #[test]
@@ -321,12 +525,18 @@ mod tests {
             rules: vec![PresetFile {
                 relative_path: "rules/code-style.md".to_string(),
                 content: "# Code Style Rules".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
 
         let result = adapter
-            .apply(&preset_files, temp_dir.path(), &mut ConflictMode::Force)
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
             .unwrap();
 
         assert_eq!(result.created.len(), 1);
@@ -349,12 +559,18 @@ mod tests {
             memory: vec![PresetFile {
                 relative_path: "memory/context.md".to_string(),
                 content: "# Project Context".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
 
         let result = adapter
-            .apply(&preset_files, temp_dir.path(), &mut ConflictMode::Force)
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
             .unwrap();
 
         assert!(result.created.iter().any(|f| f.contains("CLAUDE.md")));
@@ -379,12 +595,18 @@ mod tests {
             memory: vec![PresetFile {
                 relative_path: "memory/new.md".to_string(),
                 content: "# New Content".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
 
         let result = adapter
-            .apply(&preset_files, temp_dir.path(), &mut ConflictMode::Force)
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
             .unwrap();
 
         // Should update existing file
@@ -399,12 +621,18 @@ mod tests {
             commands: vec![PresetFile {
                 relative_path: "commands/build.md".to_string(),
                 content: "# Build Command".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
 
         let result = adapter
-            .apply(&preset_files, temp_dir.path(), &mut ConflictMode::Force)
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
             .unwrap();
 
         assert!(result.created.iter().any(|f| f.contains("build.md")));
@@ -413,6 +641,90 @@ mod tests {
         assert!(cmd_file.exists());
     }
 
+    #[test]
+    fn test_apply_with_path_overrides() {
+        let (temp_dir, _adapter) = create_test_adapter();
+        let adapter = ClaudeCodeAdapter::new(temp_dir.path()).with_paths(ToolPaths {
+            rules_dir: Some(".claude/myrules/".to_string()),
+            memory_file: Some("CLAUDE.md".to_string()),
+            ..Default::default()
+        });
+
+        let preset_files = PresetFiles {
+            rules: vec![PresetFile {
+                relative_path: "rules/style.md".to_string(),
+                content: "# Style".to_string(),
+                ..Default::default()
+            }],
+            memory: vec![PresetFile {
+                relative_path: "memory/ctx.md".to_string(),
+                content: "# Context".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let scan = adapter.scan(&preset_files, temp_dir.path());
+        let paths: Vec<_> = scan.changes.iter().map(|c| c.path.as_str()).collect();
+        assert!(paths.contains(&".claude/myrules/style.md"));
+        assert!(paths.contains(&"CLAUDE.md"));
+
+        let result = adapter
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
+            .unwrap();
+        assert_eq!(result.created.len(), 2);
+        assert!(temp_dir.path().join(".claude/myrules/style.md").exists());
+        assert!(temp_dir.path().join("CLAUDE.md").exists());
+        assert!(!temp_dir.path().join(".claude/rules").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_apply_preserves_executable_bit() {
+        use std::os::unix::fs::PermissionsExt;
+        let (temp_dir, adapter) = create_test_adapter();
+
+        let preset_files = PresetFiles {
+            skills: vec![
+                PresetFile {
+                    relative_path: "skills/run.sh".to_string(),
+                    content: "#!/bin/sh".to_string(),
+                    executable: true,
+                    ..Default::default()
+                },
+                PresetFile {
+                    relative_path: "skills/notes.md".to_string(),
+                    content: "# Notes".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        adapter
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
+            .unwrap();
+
+        let mode = |name: &str| {
+            fs::metadata(temp_dir.path().join(".claude/skills").join(name))
+                .unwrap()
+                .permissions()
+                .mode()
+        };
+        assert_ne!(mode("run.sh") & 0o111, 0);
+        assert_eq!(mode("notes.md") & 0o111, 0);
+    }
+
     #[test]
     fn test_scan_creates() {
         let (_temp_dir, adapter) = create_test_adapter();
@@ -421,10 +733,12 @@ mod tests {
             rules: vec![PresetFile {
                 relative_path: "rules/test.md".to_string(),
                 content: "# Test".to_string(),
+                ..Default::default()
             }],
             memory: vec![PresetFile {
                 relative_path: "memory/ctx.md".to_string(),
                 content: "# Context".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
@@ -448,6 +762,7 @@ mod tests {
             memory: vec![PresetFile {
                 relative_path: "memory/new.md".to_string(),
                 content: "# New".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
@@ -458,4 +773,76 @@ mod tests {
         assert_eq!(result.conflicts().len(), 1);
         assert!(result.conflicts()[0].path.contains("CLAUDE.md"));
     }
+
+    fn hooks_file(path: &str, content: &str) -> PresetFile {
+        PresetFile {
+            relative_path: path.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_merge_hooks_by_event() {
+        let files = vec![
+            hooks_file(
+                "hooks/fmt.json",
+                r#"{ "PostToolUse": [{ "matcher": "Edit", "hooks": [{ "type": "command", "command": "fmt" }] }] }"#,
+            ),
+            hooks_file(
+                "hooks/lint.json",
+                r#"{ "hooks": {
+                    "PostToolUse": [{ "matcher": "Edit", "hooks": [{ "type": "command", "command": "lint" }] }],
+                    "Stop": [{ "hooks": [{ "type": "command", "command": "notify" }] }]
+                } }"#,
+            ),
+            hooks_file(
+                "hooks/fmt-again.json",
+                r#"{ "PostToolUse": [{ "matcher": "Edit", "hooks": [{ "type": "command", "command": "fmt" }] }] }"#,
+            ),
+            hooks_file("hooks/legacy.json", r#"{ "command": "echo" }"#),
+        ];
+
+        let merged = merge_hooks(&files, MergeStrategy::Concat).unwrap();
+        let commands: Vec<&str> = merged["PostToolUse"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["hooks"][0]["command"].as_str().unwrap())
+            .collect();
+        assert_eq!(commands, vec!["fmt", "lint"]);
+        assert_eq!(merged["Stop"].as_array().unwrap().len(), 1);
+        assert_eq!(merged["legacy"]["command"], "echo");
+
+        let replaced = merge_hooks(&files[..2], MergeStrategy::Replace).unwrap();
+        let post = replaced["PostToolUse"].as_array().unwrap();
+        assert_eq!(post.len(), 1);
+        assert_eq!(post[0]["hooks"][0]["command"], "lint");
+    }
+
+    #[test]
+    fn test_apply_hooks_writes_event_format() {
+        let (temp_dir, adapter) = create_test_adapter();
+        let preset_files = PresetFiles {
+            hooks: vec![hooks_file(
+                "hooks/fmt.json",
+                r#"{ "PreToolUse": [{ "matcher": "Bash", "hooks": [{ "type": "command", "command": "check" }] }] }"#,
+            )],
+            ..Default::default()
+        };
+
+        adapter
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
+            .unwrap();
+        let content = fs::read_to_string(temp_dir.path().join(".claude/hooks.json")).unwrap();
+        assert!(content.ends_with("}\n"));
+        let written: Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(written["PreToolUse"][0]["matcher"], "Bash");
+        assert!(written.get("fmt").is_none());
+    }
 }
@@ -1,9 +1,17 @@
-use super::conflict::{write_with_conflict, ConflictMode};
-use super::helpers::strip_section_prefix;
+use super::conflict::{write_with_conflict, ApplyContext, ConflictMode};
 use super::traits::{ApplyResult, PresetFile, ScanResult};
 use crate::error::Result;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Content of a generated JSON file: pretty-printed, with a trailing newline unless disabled
+pub fn json_file_content(value: &serde_json::Value, ctx: &ApplyContext) -> Result<String> {
+    let mut content = serde_json::to_string_pretty(value)?;
+    if ctx.json_final_newline {
+        content.push('\n');
+    }
+    Ok(content)
+}
 
 /// Transform function that receives (stripped_filename, content) and returns the final filename
 pub type FilenameTransform<'a> = Option<&'a dyn Fn(&str, &str) -> String>;
@@ -11,10 +19,89 @@ pub type FilenameTransform<'a> = Option<&'a dyn Fn(&str, &str) -> String>;
 /// Transform function that receives content and returns transformed content
 pub type ContentTransform<'a> = Option<&'a dyn Fn(&str) -> String>;
 
-/// Ensure a directory exists, creating it if necessary
-pub fn ensure_dir(dir: &Path) -> Result<()> {
-    if !dir.exists() {
-        fs::create_dir_all(dir)?;
+/// Resolve a target path relative to the project, preferring a config override
+///
+/// Returns `(absolute path, display path)`, e.g. `(<project>/.claude/rules, ".claude/rules")`.
+pub fn resolve_target(
+    project_dir: &Path,
+    override_path: Option<&str>,
+    default: &str,
+) -> (PathBuf, String) {
+    let display = override_path
+        .unwrap_or(default)
+        .replace('\\', "/")
+        .trim_end_matches('/')
+        .to_string();
+    (project_dir.join(&display), display)
+}
+
+/// Ensure a directory exists, creating it if necessary (not while writes are simulated)
+pub fn ensure_dir(dir: &Path, ctx: &ApplyContext) -> Result<()> {
+    ctx.check_inside_root(dir)
+        .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {}", dir.display(), e)))?;
+    if !dir.exists() && !ctx.dry_run {
+        fs::create_dir_all(dir)
+            .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {}", dir.display(), e)))?;
+    }
+    Ok(())
+}
+
+/// Add executable bits to a written file, mirroring its read bits (Unix only)
+///
+/// Existing bits are never removed. On Windows this is a no-op.
+#[cfg(unix)]
+pub fn set_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = fs::metadata(path)?.permissions();
+    let mode = permissions.mode();
+    let exec_bits = (mode & 0o444) >> 2;
+    if mode & exec_bits != exec_bits {
+        permissions.set_mode(mode | exec_bits);
+        fs::set_permissions(path, permissions)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn set_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Whether `set_executable` would change an existing file (Unix only)
+///
+/// This is the only mode difference apply fixes, so `diff` reports exactly it.
+#[cfg(unix)]
+pub fn lacks_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| {
+        let mode = m.permissions().mode();
+        let exec_bits = (mode & 0o444) >> 2;
+        mode & exec_bits != exec_bits
+    })
+}
+
+#[cfg(not(unix))]
+pub fn lacks_executable(_path: &Path) -> bool {
+    false
+}
+
+/// Write a preset file and carry over its executable bit unless the user skipped it
+///
+/// Files are always written as copies, so the bit is set on the copy itself.
+pub fn write_preset_file(
+    file: &PresetFile,
+    target_path: &Path,
+    content: &str,
+    mode: &mut ConflictMode,
+    ctx: &ApplyContext,
+    result: &mut ApplyResult,
+    display_path: &str,
+) -> Result<()> {
+    let (skipped_before, failed_before) = (result.skipped.len(), result.failed.len());
+    write_with_conflict(target_path, content, mode, ctx, result, display_path)?;
+    let written = result.skipped.len() == skipped_before && result.failed.len() == failed_before;
+    if file.executable && written && !ctx.dry_run {
+        set_executable(target_path)?;
     }
     Ok(())
 }
@@ -36,6 +123,7 @@ pub fn apply_one_to_one(
     display_prefix: &str,
     result: &mut ApplyResult,
     mode: &mut ConflictMode,
+    ctx: &ApplyContext,
     filename_transform: FilenameTransform<'_>,
     content_transform: ContentTransform<'_>,
 ) -> Result<()> {
@@ -43,10 +131,10 @@ pub fn apply_one_to_one(
         return Ok(());
     }
 
-    fs::create_dir_all(target_dir)?;
+    ensure_dir(target_dir, ctx)?;
 
     for file in files {
-        let stripped = strip_section_prefix(&file.relative_path, section);
+        let stripped = file.section_path(section);
         let filename = match filename_transform {
             Some(transform) => transform(&stripped, &file.content),
             None => stripped,
@@ -59,7 +147,15 @@ pub fn apply_one_to_one(
             None => file.content.clone(),
         };
 
-        write_with_conflict(&target_path, &content, mode, result, &display_path)?;
+        write_preset_file(
+            file,
+            &target_path,
+            &content,
+            mode,
+            ctx,
+            result,
+            &display_path,
+        )?;
     }
 
     Ok(())
@@ -82,7 +178,7 @@ pub fn scan_one_to_one(
     content_transform: ContentTransform<'_>,
 ) {
     for file in files {
-        let stripped = strip_section_prefix(&file.relative_path, section);
+        let stripped = file.section_path(section);
         let filename = match filename_transform {
             Some(transform) => transform(&stripped, &file.content),
             None => stripped,
@@ -98,6 +194,7 @@ pub fn scan_one_to_one(
         scan_result.add_change_with_content(
             display_path,
             section.to_string(),
+            &file.relative_path,
             &target_path,
             &content,
         );
@@ -122,6 +219,7 @@ pub fn apply_json_merge(
     default_json: serde_json::Value,
     result: &mut ApplyResult,
     mode: &mut ConflictMode,
+    ctx: &ApplyContext,
 ) -> Result<()> {
     if files.is_empty() {
         return Ok(());
@@ -129,15 +227,13 @@ pub fn apply_json_merge(
 
     // Ensure parent directory exists
     if let Some(parent) = target_path.parent() {
-        fs::create_dir_all(parent)?;
+        ensure_dir(parent, ctx)?;
     }
 
     // Read existing or use default
-    let mut config: serde_json::Value = if target_path.exists() {
-        let content = fs::read_to_string(target_path)?;
-        serde_json::from_str(&content)?
-    } else {
-        default_json
+    let mut config: serde_json::Value = match ctx.read_target(target_path)? {
+        Some(content) => serde_json::from_str(&content)?,
+        None => default_json,
     };
 
     // Ensure wrapper key exists
@@ -147,13 +243,13 @@ pub fn apply_json_merge(
 
     // Merge each file's content
     for file in files {
-        let entry_name = strip_section_prefix(&file.relative_path, section).replace(".json", "");
+        let entry_name = file.section_path(section).replace(".json", "");
         let entry_config: serde_json::Value = serde_json::from_str(&file.content)?;
         config[wrapper_key][entry_name] = entry_config;
     }
 
-    let json_str = serde_json::to_string_pretty(&config)?;
-    write_with_conflict(target_path, &json_str, mode, result, display_path)?;
+    let json_str = json_file_content(&config, ctx)?;
+    write_with_conflict(target_path, &json_str, mode, ctx, result, display_path)?;
 
     Ok(())
 }
@@ -1,7 +1,220 @@
-use super::helpers::normalize_content;
+use super::helpers::{is_unchanged, resolves_inside};
 use super::traits::ApplyResult;
-use std::collections::HashMap;
-use std::path::Path;
+use crate::config::ConflictPolicy;
+use crate::output::{self, DiffPart, DiffTheme};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What to do when the file to write is a symlink
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymlinkPolicy {
+    /// Warn and leave the link and its target alone
+    #[default]
+    Skip,
+    /// Write to the file the link points to (--follow-symlinks)
+    Follow,
+    /// Remove the link and write a regular file in its place (--replace-symlinks)
+    Replace,
+}
+
+/// Conflict diffs of files with more lines than this are summarized (`diff_max_lines`)
+pub const DEFAULT_DIFF_MAX_LINES: usize = 2000;
+
+/// Settings and state of one apply, passed to every write next to the `ConflictMode`
+///
+/// Pull builds one per preset from its options and ~/.aidot/config.toml; the default
+/// writes for real without a root check, as adapter tests do.
+#[derive(Debug)]
+pub struct ApplyContext {
+    /// Directory every write must stay inside (None disables the check)
+    pub root: Option<PathBuf>,
+    /// Simulate writes: outcomes and line stats are recorded in `ApplyResult`, nothing is written
+    pub dry_run: bool,
+    /// Record write errors in `ApplyResult::failed` and keep going (--continue-on-error)
+    pub continue_on_error: bool,
+    /// How symlinked targets are written
+    pub symlinks: SymlinkPolicy,
+    /// Policy used for conflicts when no answer can be read
+    pub no_input_policy: ConflictPolicy,
+    /// Show conflict diffs through `$PAGER` (default `less -R`) when stdout is a terminal (--pager)
+    pub pager: bool,
+    /// Line limit for conflict diffs (`diff_max_lines` in ~/.aidot/config.toml, 0 = no limit)
+    pub diff_max_lines: usize,
+    /// End generated JSON files with a newline (disabled by `normalize = "none"`)
+    pub json_final_newline: bool,
+    /// Contents of simulated writes, so later merges into the same file see earlier ones
+    simulated: RefCell<BTreeMap<PathBuf, String>>,
+    /// Whether the "no input" notice was already printed
+    no_input_announced: Cell<bool>,
+}
+
+impl Default for ApplyContext {
+    fn default() -> Self {
+        Self {
+            root: None,
+            dry_run: false,
+            continue_on_error: false,
+            symlinks: SymlinkPolicy::Skip,
+            no_input_policy: ConflictPolicy::Skip,
+            pager: false,
+            diff_max_lines: DEFAULT_DIFF_MAX_LINES,
+            json_final_newline: true,
+            simulated: RefCell::new(BTreeMap::new()),
+            no_input_announced: Cell::new(false),
+        }
+    }
+}
+
+impl ApplyContext {
+    /// The same settings with simulated writes, starting from the files on disk
+    pub fn for_dry_run(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            dry_run: true,
+            continue_on_error: self.continue_on_error,
+            symlinks: self.symlinks,
+            no_input_policy: self.no_input_policy,
+            pager: self.pager,
+            diff_max_lines: self.diff_max_lines,
+            json_final_newline: self.json_final_newline,
+            ..Self::default()
+        }
+    }
+
+    /// Current content of a target file (None if missing), including simulated writes
+    ///
+    /// Merged files (memory, mcp, settings) are built on top of the existing file, so they
+    /// must be read through here for dry-run to see what an earlier section would write.
+    pub fn read_target(&self, path: &Path) -> std::io::Result<Option<String>> {
+        if let Some(content) = self.simulated_content(path) {
+            return Ok(Some(content));
+        }
+        if path.exists() {
+            fs::read_to_string(path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn simulated_content(&self, path: &Path) -> Option<String> {
+        self.simulated.borrow().get(path).cloned()
+    }
+
+    fn simulate_write(&self, path: &Path, content: &str) {
+        self.simulated
+            .borrow_mut()
+            .insert(path.to_path_buf(), content.to_string());
+    }
+
+    /// Error unless `path` resolves inside the apply root
+    ///
+    /// Refuses writes resolving outside it through `..`, absolute path overrides or symlinks.
+    pub fn check_inside_root(&self, path: &Path) -> std::io::Result<()> {
+        match &self.root {
+            Some(root) if !resolves_inside(root, path) => Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!(
+                    "refusing to write outside {} (resolves through '..', an absolute path or a symlink)",
+                    root.display()
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Read one line of user input
+    ///
+    /// Returns `None` when stdin is closed (EOF) or unreadable. The first time this
+    /// happens a notice is printed, since all remaining conflicts follow the default policy.
+    pub fn read_answer(&self) -> Option<String> {
+        use colored::Colorize;
+
+        let mut input = String::new();
+        match std::io::stdin().read_line(&mut input) {
+            Ok(n) if n > 0 => Some(input),
+            _ => {
+                if !self.no_input_announced.replace(true) {
+                    println!();
+                    println!(
+                        "  {} {}",
+                        "No input available:".yellow(),
+                        format!(
+                            "applying default policy ({}) to remaining conflicts",
+                            self.no_input_policy.as_str()
+                        )
+                        .white()
+                    );
+                }
+                None
+            }
+        }
+    }
+
+    /// Print text, through the pager when enabled
+    ///
+    /// Waits for the pager to exit, so a following prompt appears right after it.
+    /// Falls back to printing directly when the pager cannot be started.
+    fn print_paged(&self, text: &str) {
+        use std::io::IsTerminal;
+
+        if self.pager && std::io::stdout().is_terminal() && run_pager(text) {
+            return;
+        }
+        print!("{}", text);
+    }
+}
+
+/// Whether `path` itself is a link (symlinks; on Windows also junctions and other
+/// name-surrogate reparse points)
+fn is_link(path: &Path) -> bool {
+    path.symlink_metadata()
+        .is_ok_and(|meta| meta.file_type().is_symlink())
+}
+
+/// Lines added and removed between two versions of a file
+pub fn line_stats(old: &str, new: &str) -> (usize, usize) {
+    use similar::{ChangeTag, TextDiff};
+
+    let diff = TextDiff::from_lines(old, new);
+    diff.iter_all_changes()
+        .fold((0, 0), |(added, removed), change| match change.tag() {
+            ChangeTag::Insert => (added + 1, removed),
+            ChangeTag::Delete => (added, removed + 1),
+            ChangeTag::Equal => (added, removed),
+        })
+}
+
+/// Pipe text into the pager; returns false when it could not be run
+fn run_pager(text: &str) -> bool {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let pager = std::env::var("PAGER")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| "less -R".to_string());
+    let mut parts = pager.split_whitespace();
+    let Some(program) = parts.next() else {
+        return false;
+    };
+
+    let mut command = Command::new(program);
+    command.args(parts).stdin(Stdio::piped());
+    // Same defaults as git: keep colors, quit if one screen, don't clear the screen
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+    let Ok(mut child) = command.spawn() else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user may quit the pager before reading everything
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait().is_ok()
+}
 
 /// How to handle file conflicts during apply
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -20,6 +233,8 @@ pub enum ConflictMode {
         decisions: HashMap<String, bool>,
         fallback_all: Option<bool>,
     },
+    /// User quit during conflict resolution: no further files are written
+    Quit,
 }
 
 /// User's decision for a single conflict
@@ -35,22 +250,32 @@ pub enum ConflictDecision {
     SkipAll,
     /// Show diff between local and remote
     ShowDiff,
+    /// Stop applying (files written so far are kept)
+    Quit,
 }
 
 impl ConflictMode {
+    /// Whether a file was deselected up front (pre-resolved to "don't write")
+    ///
+    /// Applies to new files too, which otherwise never consult the conflict mode.
+    pub fn excludes(&self, file_path: &str) -> bool {
+        matches!(self, ConflictMode::PreResolved { decisions, .. } if decisions.get(file_path) == Some(&false))
+    }
+
     /// Resolve how to handle a conflict for a specific file.
     /// Returns whether the file should be written.
     /// May mutate self (e.g., Ask → Force when user chooses "Overwrite All").
     /// `existing_content` and `new_content` enable diff display in interactive mode.
     pub fn resolve_conflict(
         &mut self,
+        ctx: &ApplyContext,
         file_path: &str,
         existing_content: Option<&str>,
         new_content: Option<&str>,
     ) -> bool {
         match self {
             ConflictMode::Force => true,
-            ConflictMode::Skip => false,
+            ConflictMode::Skip | ConflictMode::Quit => false,
             ConflictMode::PreResolved {
                 decisions,
                 fallback_all,
@@ -65,10 +290,10 @@ impl ConflictMode {
                             let diff_available =
                                 existing_content.is_some() && new_content.is_some();
                             if let (Some(existing), Some(new)) = (existing_content, new_content) {
-                                Self::print_diff(file_path, existing, new);
+                                Self::print_diff(ctx, file_path, existing, new);
                             }
                             loop {
-                                let decision = Self::ask_user(file_path, diff_available);
+                                let decision = Self::ask_user(ctx, file_path, diff_available);
                                 match decision {
                                     ConflictDecision::Overwrite => return true,
                                     ConflictDecision::Skip => return false,
@@ -80,11 +305,15 @@ impl ConflictMode {
                                         *fallback_all = Some(false);
                                         return false;
                                     }
+                                    ConflictDecision::Quit => {
+                                        *self = ConflictMode::Quit;
+                                        return false;
+                                    }
                                     ConflictDecision::ShowDiff => {
                                         if let (Some(existing), Some(new)) =
                                             (existing_content, new_content)
                                         {
-                                            Self::print_diff(file_path, existing, new);
+                                            Self::print_diff(ctx, file_path, existing, new);
                                         }
                                     }
                                 }
@@ -97,10 +326,10 @@ impl ConflictMode {
                 let diff_available = existing_content.is_some() && new_content.is_some();
                 // Auto-show diff first if available
                 if let (Some(existing), Some(new)) = (existing_content, new_content) {
-                    Self::print_diff(file_path, existing, new);
+                    Self::print_diff(ctx, file_path, existing, new);
                 }
                 loop {
-                    let decision = Self::ask_user(file_path, diff_available);
+                    let decision = Self::ask_user(ctx, file_path, diff_available);
                     match decision {
                         ConflictDecision::Overwrite => return true,
                         ConflictDecision::Skip => return false,
@@ -112,9 +341,13 @@ impl ConflictMode {
                             *self = ConflictMode::Skip;
                             return false;
                         }
+                        ConflictDecision::Quit => {
+                            *self = ConflictMode::Quit;
+                            return false;
+                        }
                         ConflictDecision::ShowDiff => {
                             if let (Some(existing), Some(new)) = (existing_content, new_content) {
-                                Self::print_diff(file_path, existing, new);
+                                Self::print_diff(ctx, file_path, existing, new);
                             }
                             // Loop back to ask again
                         }
@@ -124,22 +357,32 @@ impl ConflictMode {
         }
     }
 
+    /// Conflict mode matching a default policy
+    pub fn from_policy(policy: ConflictPolicy) -> Self {
+        match policy {
+            ConflictPolicy::Skip => ConflictMode::Skip,
+            ConflictPolicy::Force => ConflictMode::Force,
+        }
+    }
+
     /// Ask user what to do with a conflicting file
-    pub fn ask_user(file_path: &str, diff_available: bool) -> ConflictDecision {
+    ///
+    /// On EOF the default policy applies to this and all remaining files.
+    pub fn ask_user(ctx: &ApplyContext, file_path: &str, diff_available: bool) -> ConflictDecision {
         use colored::Colorize;
         use std::io::{self, Write};
 
         loop {
             if diff_available {
                 print!(
-                    "  {} '{}' {} [o]verwrite / [s]kip / [d]iff / [O]verwrite all / [S]kip all? ",
+                    "  {} '{}' {} [o]verwrite / [s]kip / [d]iff / [O]verwrite all / [S]kip all / [q]uit? ",
                     "Conflict:".yellow(),
                     file_path,
                     "already exists.".dimmed()
                 );
             } else {
                 print!(
-                    "  {} '{}' {} [o]verwrite / [s]kip / [O]verwrite all / [S]kip all? ",
+                    "  {} '{}' {} [o]verwrite / [s]kip / [O]verwrite all / [S]kip all / [q]uit? ",
                     "Conflict:".yellow(),
                     file_path,
                     "already exists.".dimmed()
@@ -147,11 +390,12 @@ impl ConflictMode {
             }
             io::stdout().flush().unwrap();
 
-            let mut input = String::new();
-            if io::stdin().read_line(&mut input).is_err() {
-                // If we can't read input, default to skip
-                return ConflictDecision::Skip;
-            }
+            let Some(input) = ctx.read_answer() else {
+                return match ctx.no_input_policy {
+                    ConflictPolicy::Skip => ConflictDecision::SkipAll,
+                    ConflictPolicy::Force => ConflictDecision::OverwriteAll,
+                };
+            };
 
             match input.trim() {
                 "o" | "y" | "yes" => return ConflictDecision::Overwrite,
@@ -159,89 +403,255 @@ impl ConflictMode {
                 "d" if diff_available => return ConflictDecision::ShowDiff,
                 "O" | "a" | "all" => return ConflictDecision::OverwriteAll,
                 "S" | "N" => return ConflictDecision::SkipAll,
+                "q" | "quit" => return ConflictDecision::Quit,
                 "" => return ConflictDecision::Skip, // Default to skip on Enter
                 _ => {
                     if diff_available {
-                        println!("  {} Please enter 'o', 's', 'd', 'O', or 'S'", "?".yellow());
+                        println!(
+                            "  {} Please enter 'o', 's', 'd', 'O', 'S', or 'q'",
+                            "?".yellow()
+                        );
                     } else {
-                        println!("  {} Please enter 'o', 's', 'O', or 'S'", "?".yellow());
+                        println!("  {} Please enter 'o', 's', 'O', 'S', or 'q'", "?".yellow());
                     }
                 }
             }
         }
     }
 
-    /// Print unified diff between local and preset content
-    pub fn print_diff(file_path: &str, existing: &str, new: &str) {
-        use colored::Colorize;
+    /// Print unified diff between local and preset content (through the pager with --pager)
+    pub fn print_diff(ctx: &ApplyContext, file_path: &str, existing: &str, new: &str) {
+        println!();
+        ctx.print_paged(&Self::render_diff(ctx, file_path, existing, new));
+        println!();
+    }
+
+    /// Unified diff between local and preset content, colored by the diff theme
+    ///
+    /// Binary content and files over the line limit get a one-line size summary instead.
+    fn render_diff(ctx: &ApplyContext, file_path: &str, existing: &str, new: &str) -> String {
+        Self::render_diff_with(
+            output::diff_theme(),
+            ctx.diff_max_lines,
+            file_path,
+            existing,
+            new,
+        )
+    }
+
+    fn render_diff_with(
+        theme: DiffTheme,
+        max_lines: usize,
+        file_path: &str,
+        existing: &str,
+        new: &str,
+    ) -> String {
         use similar::{ChangeTag, TextDiff};
+        use std::fmt::Write;
 
-        println!();
-        println!("  {} {}", "--- (local)".red(), file_path.dimmed());
-        println!("  {} {}", "+++ (preset)".green(), file_path.dimmed());
+        let mut out = String::new();
+        let path = theme.paint(file_path, DiffPart::Path);
+        let _ = writeln!(
+            out,
+            "  {} {}",
+            theme.paint("--- (local)", DiffPart::Removed),
+            path
+        );
+        let _ = writeln!(
+            out,
+            "  {} {}",
+            theme.paint("+++ (preset)", DiffPart::Added),
+            path
+        );
+
+        if let Some(summary) = diff_summary(existing, new, max_lines) {
+            let _ = writeln!(out, "  {}", theme.paint(&summary, DiffPart::Hunk));
+            return out;
+        }
 
         let diff = TextDiff::from_lines(existing, new);
 
         for hunk in diff.unified_diff().context_radius(3).iter_hunks() {
-            println!("  {}", format!("{}", hunk.header()).cyan());
+            let header = hunk.header().to_string();
+            let _ = writeln!(out, "  {}", theme.paint(&header, DiffPart::Hunk));
             for change in hunk.iter_changes() {
                 let line = change.to_string_lossy();
                 let line_trimmed = line.trim_end_matches('\n');
-                match change.tag() {
-                    ChangeTag::Delete => {
-                        println!("  {}", format!("-{}", line_trimmed).red());
-                    }
-                    ChangeTag::Insert => {
-                        println!("  {}", format!("+{}", line_trimmed).green());
-                    }
-                    ChangeTag::Equal => {
-                        println!("  {}", format!(" {}", line_trimmed).dimmed());
-                    }
-                }
+                let (prefix, part) = match change.tag() {
+                    ChangeTag::Delete => ('-', DiffPart::Removed),
+                    ChangeTag::Insert => ('+', DiffPart::Added),
+                    ChangeTag::Equal => (' ', DiffPart::Context),
+                };
+                let text = format!("{}{}", prefix, line_trimmed);
+                let _ = writeln!(out, "  {}", theme.paint(&text, part));
             }
         }
-        println!();
+        out
+    }
+}
+
+/// Summary shown instead of a line diff that would be meaningless or huge
+///
+/// `None` when both sides are text within `max_lines` lines (0 = no limit).
+fn diff_summary(existing: &str, new: &str, max_lines: usize) -> Option<String> {
+    let sizes = format!("{} → {} bytes", existing.len(), new.len());
+    if looks_binary(existing) || looks_binary(new) {
+        return Some(format!("binary file, {} (diff not shown)", sizes));
     }
+    let lines = (existing.lines().count(), new.lines().count());
+    if max_lines > 0 && lines.0.max(lines.1) > max_lines {
+        return Some(format!(
+            "large file ({} → {} lines), {} (diff not shown, limit diff_max_lines = {})",
+            lines.0, lines.1, sizes, max_lines
+        ));
+    }
+    None
+}
+
+/// Content with NUL bytes, or where over 10% of the first 8000 characters are control
+/// characters or U+FFFD (bytes that were not valid UTF-8)
+fn looks_binary(content: &str) -> bool {
+    if content.contains('\0') {
+        return true;
+    }
+    let (total, suspicious) =
+        content
+            .chars()
+            .take(8000)
+            .fold((0usize, 0usize), |(total, suspicious), c| {
+                let odd = c == '\u{FFFD}' || (c.is_control() && !matches!(c, '\n' | '\r' | '\t'));
+                (total + 1, suspicious + usize::from(odd))
+            });
+    suspicious * 10 > total
 }
 
 /// Helper to write a file with conflict resolution
 /// Mutates `mode` in place (e.g., Ask → Force when user chooses "Overwrite All")
+///
+/// Write errors name the file. With --continue-on-error they are recorded in
+/// `result.failed` instead and the apply goes on.
 pub fn write_with_conflict(
     target_path: &Path,
     content: &str,
     mode: &mut ConflictMode,
+    ctx: &ApplyContext,
+    result: &mut ApplyResult,
+    display_path: &str,
+) -> std::io::Result<()> {
+    match write_file(target_path, content, mode, ctx, result, display_path) {
+        Ok(()) => Ok(()),
+        Err(e) if ctx.continue_on_error => {
+            result.add_failed(display_path.to_string(), e.to_string());
+            Ok(())
+        }
+        Err(e) => Err(std::io::Error::new(
+            e.kind(),
+            format!("{}: {}", display_path, e),
+        )),
+    }
+}
+
+fn write_file(
+    target_path: &Path,
+    content: &str,
+    mode: &mut ConflictMode,
+    ctx: &ApplyContext,
     result: &mut ApplyResult,
     display_path: &str,
 ) -> std::io::Result<()> {
     use std::fs;
 
-    if target_path.exists() {
-        // Read existing content for both comparison and diff display
-        let existing_content = fs::read_to_string(target_path).ok();
+    // After [q]uit nothing else is written, not even new files
+    if *mode == ConflictMode::Quit {
+        result.add_skipped(display_path.to_string());
+        return Ok(());
+    }
 
-        // Content comparison: auto-skip if identical
+    // Writing through a link changes the file it points to, which may be shared on purpose
+    let replace_link = is_link(target_path)
+        && match ctx.symlinks {
+            SymlinkPolicy::Skip => {
+                use colored::Colorize;
+                let link = fs::read_link(target_path)
+                    .map(|target| target.display().to_string())
+                    .unwrap_or_default();
+                println!(
+                    "  {} {} is a symlink to {}; skipped (--follow-symlinks writes to its target, --replace-symlinks replaces the link)",
+                    "⚠".yellow(),
+                    display_path.white(),
+                    link
+                );
+                result.add_skipped(display_path.to_string());
+                return Ok(());
+            }
+            SymlinkPolicy::Follow => false,
+            SymlinkPolicy::Replace => true,
+        };
+    // A replaced link is written where the link is, wherever it pointed
+    match target_path.parent() {
+        Some(parent) if replace_link => ctx.check_inside_root(parent)?,
+        _ => ctx.check_inside_root(target_path)?,
+    }
+
+    let dry_run = ctx.dry_run;
+    let simulated = ctx.simulated_content(target_path);
+
+    // A link to be replaced is an existing file like any other: the conflict mode decides
+    // whether it is written, the policy only how
+    if replace_link || simulated.is_some() || target_path.exists() {
+        // Read existing content (through a link: its target) for comparison and diff display
+        let existing_content = simulated.or_else(|| fs::read_to_string(target_path).ok());
+
+        // Content comparison: auto-skip if identical (a BOM or line-ending change is
+        // an update and follows the conflict mode)
         if let Some(ref existing) = existing_content {
-            if normalize_content(existing) == normalize_content(content) {
+            if is_unchanged(existing, content) {
                 result.add_unchanged(display_path.to_string());
                 return Ok(());
             }
         }
 
-        let should_write =
-            mode.resolve_conflict(display_path, existing_content.as_deref(), Some(content));
+        let should_write = mode.resolve_conflict(
+            ctx,
+            display_path,
+            existing_content.as_deref(),
+            Some(content),
+        );
         if should_write {
-            fs::write(target_path, content)?;
+            if dry_run {
+                let existing = existing_content.as_deref().unwrap_or_default();
+                result
+                    .line_stats
+                    .insert(display_path.to_string(), line_stats(existing, content));
+                ctx.simulate_write(target_path, content);
+            } else {
+                if replace_link {
+                    fs::remove_file(target_path)?;
+                }
+                fs::write(target_path, content)?;
+            }
             result.add_updated(display_path.to_string());
         } else {
             result.add_skipped(display_path.to_string());
         }
         Ok(())
     } else {
-        // Create parent directories if needed
-        if let Some(parent) = target_path.parent() {
-            fs::create_dir_all(parent)?;
+        if mode.excludes(display_path) {
+            result.add_skipped(display_path.to_string());
+            return Ok(());
+        }
+        if dry_run {
+            result
+                .line_stats
+                .insert(display_path.to_string(), line_stats("", content));
+            ctx.simulate_write(target_path, content);
+        } else {
+            // Create parent directories if needed
+            if let Some(parent) = target_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(target_path, content)?;
         }
-        fs::write(target_path, content)?;
         result.add_created(display_path.to_string());
         Ok(())
     }
@@ -254,7 +664,7 @@ mod tests {
     #[test]
     fn test_conflict_mode_force() {
         let mut mode = ConflictMode::Force;
-        let should_write = mode.resolve_conflict("test.md", None, None);
+        let should_write = mode.resolve_conflict(&ApplyContext::default(), "test.md", None, None);
         assert!(should_write);
         assert_eq!(mode, ConflictMode::Force);
     }
@@ -262,11 +672,152 @@ mod tests {
     #[test]
     fn test_conflict_mode_skip() {
         let mut mode = ConflictMode::Skip;
-        let should_write = mode.resolve_conflict("test.md", None, None);
+        let should_write = mode.resolve_conflict(&ApplyContext::default(), "test.md", None, None);
         assert!(!should_write);
         assert_eq!(mode, ConflictMode::Skip);
     }
 
+    #[test]
+    fn test_line_stats() {
+        assert_eq!(line_stats("", "a\nb\n"), (2, 0));
+        assert_eq!(line_stats("a\nb\nc\n", "a\nB\nc\n"), (1, 1));
+        assert_eq!(line_stats("a\n", "a\n"), (0, 0));
+    }
+
+    #[test]
+    fn test_read_target_sees_simulated_writes() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let target = temp_dir.path().join("settings.json");
+        let ctx = ApplyContext::default().for_dry_run();
+        assert_eq!(ctx.read_target(&target).unwrap(), None);
+
+        ctx.simulate_write(&target, "{}\n");
+        assert_eq!(ctx.read_target(&target).unwrap().as_deref(), Some("{}\n"));
+        assert!(!target.exists());
+        // A fresh simulation starts from the files on disk again
+        assert_eq!(ctx.for_dry_run().read_target(&target).unwrap(), None);
+    }
+
+    #[test]
+    fn test_render_diff() {
+        colored::control::set_override(false);
+        let diff = ConflictMode::render_diff(
+            &ApplyContext::default(),
+            "rules/a.md",
+            "one\ntwo\n",
+            "one\nthree\n",
+        );
+        assert!(diff.starts_with("  --- (local) rules/a.md\n  +++ (preset) rules/a.md\n"));
+        assert!(diff.contains("  -two\n"));
+        assert!(diff.contains("  +three\n"));
+        assert!(diff.contains("   one\n"));
+    }
+
+    #[test]
+    fn test_render_diff_summarizes_binary_and_large_files() {
+        let render = |max_lines, existing: &str, new: &str| {
+            ConflictMode::render_diff_with(DiffTheme::Plain, max_lines, "skills/x", existing, new)
+        };
+        let binary = render(0, "PNG\0\x01\x02", "PNG\0\x03");
+        assert!(binary.ends_with("  binary file, 6 → 5 bytes (diff not shown)\n"));
+        assert!(!binary.contains("@@"));
+
+        let mostly_invalid = "\u{FFFD}\u{FFFD}ab\u{FFFD}";
+        assert!(render(0, mostly_invalid, "text\n").contains("binary file"));
+
+        let large = "line\n".repeat(5);
+        let summary = render(3, &large, "line\n");
+        assert!(summary.contains("large file (5 → 1 lines), 25 → 5 bytes"));
+        // Under the limit (or without one) the regular diff is shown
+        assert!(render(5, &large, "line\n").contains("  -line\n"));
+        assert!(render(0, &large, "line\n").contains("  -line\n"));
+    }
+
+    #[test]
+    fn test_render_diff_plain_theme() {
+        // Plain never emits escape codes, whatever the color override is
+        let plain = ConflictMode::render_diff_with(DiffTheme::Plain, 0, "a.md", "one\n", "two\n");
+        assert!(!plain.contains('\x1b'));
+        assert!(plain.contains("  --- (local) a.md\n"));
+        assert!(plain.contains("  -one\n"));
+        assert!(plain.contains("  +two\n"));
+    }
+
+    #[test]
+    fn test_scan_and_apply_agree_on_identical_content() {
+        use crate::adapters::traits::ScanResult;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let target = temp_dir.path().join("rule.md");
+        let preset = "# Rule\n\nBody text.\n";
+        let cases = [
+            ("# Rule\n\nBody text.\n", true),
+            ("\u{feff}# Rule\r\n\r\nBody text.  \r\n", true),
+            ("# Rule\n\nBody text.\n\n\n", true),
+            ("# Rule\n\nOther text.\n", false),
+        ];
+
+        for (existing, identical) in cases {
+            std::fs::write(&target, existing).unwrap();
+
+            let mut scan = ScanResult::new();
+            scan.add_change_with_content(
+                "rule.md".into(),
+                "rules".into(),
+                "rules/rule.md",
+                &target,
+                preset,
+            );
+            assert_eq!(scan.changes[0].is_identical, identical, "{:?}", existing);
+
+            let mut result = ApplyResult::new();
+            write_with_conflict(
+                &target,
+                preset,
+                &mut ConflictMode::Skip,
+                &ApplyContext::default(),
+                &mut result,
+                "rule.md",
+            )
+            .unwrap();
+            assert_eq!(result.unchanged.len() == 1, identical, "{:?}", existing);
+        }
+    }
+
+    #[test]
+    fn test_conflict_mode_from_policy() {
+        assert_eq!(
+            ConflictMode::from_policy(ConflictPolicy::Skip),
+            ConflictMode::Skip
+        );
+        assert_eq!(
+            ConflictMode::from_policy(ConflictPolicy::Force),
+            ConflictMode::Force
+        );
+    }
+
+    #[test]
+    fn test_write_with_conflict_after_quit() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let target = temp_dir.path().join("new.md");
+        let mut mode = ConflictMode::Quit;
+        let mut result = ApplyResult::new();
+
+        write_with_conflict(
+            &target,
+            "# New",
+            &mut mode,
+            &ApplyContext::default(),
+            &mut result,
+            "new.md",
+        )
+        .unwrap();
+
+        assert!(!target.exists());
+        assert_eq!(result.skipped, vec!["new.md".to_string()]);
+        assert!(!mode.resolve_conflict(&ApplyContext::default(), "other.md", None, None));
+    }
+
     #[test]
     fn test_conflict_mode_pre_resolved() {
         let mut decisions = HashMap::new();
@@ -279,8 +830,11 @@ mod tests {
         };
 
         // Look up pre-resolved decisions
-        assert!(mode.resolve_conflict("file1.md", None, None));
-        assert!(!mode.resolve_conflict("file2.md", None, None));
+        assert!(mode.resolve_conflict(&ApplyContext::default(), "file1.md", None, None));
+        assert!(!mode.resolve_conflict(&ApplyContext::default(), "file2.md", None, None));
+        assert!(mode.excludes("file2.md"));
+        assert!(!mode.excludes("file1.md"));
+        assert!(!mode.excludes("file3.md"));
         // Files not in the decision map fall back to inline Ask
         // (Cannot test in unit tests as it requires stdin)
     }
@@ -296,7 +850,15 @@ mod tests {
 
         let mut result = ApplyResult::new();
         let mut mode = ConflictMode::Force;
-        write_with_conflict(&file_path, content, &mut mode, &mut result, "test.md").unwrap();
+        write_with_conflict(
+            &file_path,
+            content,
+            &mut mode,
+            &ApplyContext::default(),
+            &mut result,
+            "test.md",
+        )
+        .unwrap();
 
         // Should be unchanged, not updated
         assert_eq!(result.unchanged.len(), 1);
@@ -304,6 +866,48 @@ mod tests {
         assert_eq!(mode, ConflictMode::Force);
     }
 
+    #[test]
+    fn test_write_with_conflict_encoding_change_is_an_update() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.md");
+        let crlf = "\u{feff}# Test Content\r\n";
+        std::fs::write(&file_path, crlf).unwrap();
+
+        // Skip leaves the user's CRLF file alone
+        let mut result = ApplyResult::new();
+        let mut mode = ConflictMode::Skip;
+        write_with_conflict(
+            &file_path,
+            "# Test Content\n",
+            &mut mode,
+            &ApplyContext::default(),
+            &mut result,
+            "test.md",
+        )
+        .unwrap();
+        assert_eq!(result.skipped, vec!["test.md"]);
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), crlf);
+
+        let mut result = ApplyResult::new();
+        let mut mode = ConflictMode::Force;
+        write_with_conflict(
+            &file_path,
+            "# Test Content\n",
+            &mut mode,
+            &ApplyContext::default(),
+            &mut result,
+            "test.md",
+        )
+        .unwrap();
+        assert_eq!(result.updated, vec!["test.md"]);
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "# Test Content\n"
+        );
+    }
+
     #[test]
     fn test_write_with_conflict_different_content() {
         use tempfile::TempDir;
@@ -318,6 +922,7 @@ mod tests {
             &file_path,
             "# New Content\n",
             &mut mode,
+            &ApplyContext::default(),
             &mut result,
             "test.md",
         )
@@ -342,9 +947,89 @@ mod tests {
 
         let mut result = ApplyResult::new();
         let mut mode = ConflictMode::Force;
-        write_with_conflict(&file_path, "# New File\n", &mut mode, &mut result, "new.md").unwrap();
+        write_with_conflict(
+            &file_path,
+            "# New File\n",
+            &mut mode,
+            &ApplyContext::default(),
+            &mut result,
+            "new.md",
+        )
+        .unwrap();
 
         assert_eq!(result.created.len(), 1);
         assert_eq!(mode, ConflictMode::Force);
     }
+
+    #[test]
+    fn test_write_with_conflict_deselected_new_file() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("new.md");
+
+        let mut result = ApplyResult::new();
+        let mut mode = ConflictMode::PreResolved {
+            decisions: HashMap::from([("new.md".to_string(), false)]),
+            fallback_all: None,
+        };
+        write_with_conflict(
+            &file_path,
+            "# New File\n",
+            &mut mode,
+            &ApplyContext::default(),
+            &mut result,
+            "new.md",
+        )
+        .unwrap();
+
+        assert_eq!(result.skipped, vec!["new.md"]);
+        assert!(!file_path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_with_conflict_symlink_policies() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let shared = temp_dir.path().join("shared.md");
+        let link = temp_dir.path().join("CLAUDE.md");
+        let write_as = |symlinks, mut mode: ConflictMode, content: &str| {
+            let ctx = ApplyContext {
+                symlinks,
+                ..ApplyContext::default()
+            };
+            let mut result = ApplyResult::new();
+            write_with_conflict(&link, content, &mut mode, &ctx, &mut result, "CLAUDE.md").unwrap();
+            result
+        };
+        let write = |symlinks| write_as(symlinks, ConflictMode::Force, "# Preset\n");
+        std::fs::write(&shared, "# Shared\n").unwrap();
+        std::os::unix::fs::symlink(&shared, &link).unwrap();
+
+        let result = write(SymlinkPolicy::Skip);
+        assert_eq!(result.skipped, vec!["CLAUDE.md"]);
+        assert_eq!(std::fs::read_to_string(&shared).unwrap(), "# Shared\n");
+
+        let result = write(SymlinkPolicy::Follow);
+        assert_eq!(result.updated, vec!["CLAUDE.md"]);
+        assert_eq!(std::fs::read_to_string(&shared).unwrap(), "# Preset\n");
+        assert!(is_link(&link));
+
+        std::fs::write(&shared, "# Shared\n").unwrap();
+        // Replacing is how to write, not whether: skip and identical content keep the link
+        let result = write_as(SymlinkPolicy::Replace, ConflictMode::Skip, "# Preset\n");
+        assert_eq!(result.skipped, vec!["CLAUDE.md"]);
+        assert!(is_link(&link));
+        let result = write_as(SymlinkPolicy::Replace, ConflictMode::Force, "# Shared\n");
+        assert_eq!(result.unchanged, vec!["CLAUDE.md"]);
+        assert!(is_link(&link));
+
+        let result = write(SymlinkPolicy::Replace);
+        assert_eq!(result.updated, vec!["CLAUDE.md"]);
+        assert!(!is_link(&link));
+        assert_eq!(std::fs::read_to_string(&link).unwrap(), "# Preset\n");
+        assert_eq!(std::fs::read_to_string(&shared).unwrap(), "# Shared\n");
+    }
 }
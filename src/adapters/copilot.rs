@@ -1,13 +1,23 @@
 use super::common::{
-    apply_json_merge, apply_one_to_one, ensure_dir, scan_merged_section, scan_one_to_one,
+    apply_json_merge, apply_one_to_one, ensure_dir, resolve_target, scan_merged_section,
+    scan_one_to_one,
+};
+use super::conflict::{write_with_conflict, ApplyContext, ConflictMode};
+use super::helpers::{
+    add_suffix_before_ext, convert_frontmatter_key, is_command_available, legacy_memory_block,
+    memory_blocks, unify_line_endings, upsert_blocks,
+};
+use super::traits::{
+    ApplyResult, DetectionInfo, PresetFile, PresetFiles, ProjectMarkers, ReloadHint, ScanResult,
+    ToolAdapter,
 };
-use super::conflict::{write_with_conflict, ConflictMode};
-use super::helpers::{add_suffix_before_ext, convert_frontmatter_key, strip_section_prefix};
-use super::traits::{ApplyResult, PresetFile, PresetFiles, ScanResult, ToolAdapter};
 use crate::error::Result;
-use std::fs;
+use crate::preset::config::ToolPaths;
 use std::path::{Path, PathBuf};
 
+/// Heading the memory blocks in copilot-instructions.md are appended under
+const MEMORY_HEADER: &str = "\n\n---\n\n## Project Context\n";
+
 /// GitHub Copilot adapter
 ///
 /// Converts presets to GitHub Copilot format:
@@ -17,72 +27,114 @@ use std::path::{Path, PathBuf};
 /// - agents/*.md → .github/agents/*.agent.md
 /// - skills/*.ts → .github/skills/
 /// - mcp/*.json → .vscode/mcp.json
+///
+/// scan() and apply() share the name/content transforms below, so `aidot diff`
+/// (which is built on scan) compares against exactly what pull writes.
 pub struct CopilotAdapter {
     project_dir: PathBuf,
+    paths: ToolPaths,
+}
+
+/// rules/x.md → x.instructions.md
+fn instructions_name(name: &str, _content: &str) -> String {
+    add_suffix_before_ext(name, "instructions")
+}
+
+/// Cursor-style `globs:` front matter → Copilot's `applyTo:`
+fn instructions_content(content: &str) -> String {
+    convert_frontmatter_key(content, "globs", "applyTo")
+}
+
+/// commands/x.md → x.prompt.md
+fn prompt_name(name: &str, _content: &str) -> String {
+    add_suffix_before_ext(name, "prompt")
+}
+
+/// agents/x.md → x.agent.md
+fn agent_name(name: &str, _content: &str) -> String {
+    add_suffix_before_ext(name, "agent")
 }
 
 impl CopilotAdapter {
     pub fn new(project_dir: &Path) -> Self {
         Self {
             project_dir: project_dir.to_path_buf(),
+            paths: ToolPaths::default(),
         }
     }
 
+    /// Override target paths from preset config ([tools.copilot])
+    pub fn with_paths(mut self, paths: ToolPaths) -> Self {
+        self.paths = paths;
+        self
+    }
+
     /// Get the .github directory path
     fn github_dir(&self) -> PathBuf {
         self.project_dir.join(".github")
     }
 
-    /// Get the .vscode directory path
-    fn vscode_dir(&self) -> PathBuf {
-        self.project_dir.join(".vscode")
+    /// Resolve a target path (override or default) to (absolute path, display path)
+    fn target(&self, override_path: &Option<String>, default: &str) -> (PathBuf, String) {
+        resolve_target(&self.project_dir, override_path.as_deref(), default)
+    }
+
+    fn rules_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.rules_dir, ".github/instructions")
+    }
+
+    fn commands_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.commands_dir, ".github/prompts")
+    }
+
+    fn agents_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.agents_dir, ".github/agents")
     }
 
-    /// Get the copilot-instructions.md file path
-    fn copilot_instructions_file(&self) -> PathBuf {
-        self.github_dir().join("copilot-instructions.md")
+    fn skills_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.skills_dir, ".github/skills")
+    }
+
+    fn memory_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.memory_file, ".github/copilot-instructions.md")
+    }
+
+    fn mcp_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.mcp_file, ".vscode/mcp.json")
     }
 
     /// Apply memory files: memory/*.md → .github/copilot-instructions.md (appended)
     fn apply_memory(
         &self,
         files: &[PresetFile],
+        preset: &str,
         result: &mut ApplyResult,
         mode: &mut ConflictMode,
+        ctx: &ApplyContext,
     ) -> Result<()> {
         if files.is_empty() {
             return Ok(());
         }
 
-        let instructions_file = self.copilot_instructions_file();
-
-        // Build memory content
-        let mut memory_content = String::from("\n\n---\n\n## Project Context\n\n");
-        for (i, file) in files.iter().enumerate() {
-            if i > 0 {
-                memory_content.push_str("\n\n---\n\n");
-            }
-            memory_content.push_str(&format!(
-                "### {}\n\n",
-                strip_section_prefix(&file.relative_path, "memory").replace(".md", "")
-            ));
-            memory_content.push_str(&file.content);
-        }
+        let (instructions_file, display) = self.memory_target();
+
+        // One marked block per memory file, replaced in place on later pulls
+        let blocks = memory_blocks(files, "###");
 
         // Read existing content or create header
-        let base = if instructions_file.exists() {
-            fs::read_to_string(&instructions_file)?
-        } else {
+        let base = ctx.read_target(&instructions_file)?.unwrap_or_else(|| {
             String::from("# GitHub Copilot Instructions\n\n<!-- Generated by aidot -->\n")
-        };
-        let content = format!("{}{}", base, memory_content);
+        });
+        let legacy = legacy_memory_block(files, MEMORY_HEADER, "###");
+        let content = upsert_blocks(&base, MEMORY_HEADER, preset, &blocks, Some(&legacy));
 
         write_with_conflict(
             &instructions_file,
-            &content,
+            &unify_line_endings(&content),
             mode,
+            ctx,
             result,
-            ".github/copilot-instructions.md",
+            &display,
         )?;
 
         Ok(())
@@ -94,78 +146,101 @@ impl ToolAdapter for CopilotAdapter {
         "GitHub Copilot"
     }
 
-    fn detect(&self) -> bool {
-        self.copilot_instructions_file().exists()
-            || self.github_dir().join("instructions").exists()
-            || self.github_dir().exists()
-            || self.vscode_dir().exists()
+    fn detect_with_reason(&self, markers: &ProjectMarkers) -> DetectionInfo {
+        if markers.has(".github/copilot-instructions.md") {
+            DetectionInfo::found("detected via .github/copilot-instructions.md")
+        } else if markers.has(".github/instructions") {
+            DetectionInfo::found("detected via .github/instructions/ directory")
+        } else if markers.has(".github") {
+            DetectionInfo::found("detected via .github/ directory")
+        } else if markers.has(".vscode") {
+            DetectionInfo::found("detected via .vscode/ directory")
+        } else {
+            DetectionInfo::not_found("no .github/ or .vscode/ directory")
+        }
+    }
+
+    /// VS Code user directory (user prompts and instructions live under prompts/)
+    fn global_config_dir(&self) -> Option<PathBuf> {
+        dirs::config_dir().map(|config| config.join("Code").join("User"))
+    }
+
+    fn unsupported_sections(&self) -> &[(&'static str, &'static str)] {
+        &[
+            ("hooks", "GitHub Copilot has no hooks"),
+            ("settings", "GitHub Copilot has no project settings file"),
+        ]
+    }
+
+    fn reload_hint(&self) -> Option<ReloadHint> {
+        Some(ReloadHint {
+            message: "reload the VS Code window if new prompts or MCP servers don't show up",
+            command: is_command_available("code").then_some(&["code", "--reuse-window"]),
+        })
     }
 
     fn scan(&self, preset_files: &PresetFiles, _target_dir: &Path) -> ScanResult {
         let mut result = ScanResult::new();
-        let github_dir = self.github_dir();
 
         // Rules with filename and content transforms
-        let rule_fn = |name: &str, _content: &str| add_suffix_before_ext(name, "instructions");
-        let rule_content_fn = |content: &str| convert_frontmatter_key(content, "globs", "applyTo");
+        let (rules_dir, rules_display) = self.rules_target();
         scan_one_to_one(
             &preset_files.rules,
             "rules",
-            &github_dir.join("instructions"),
-            ".github/instructions",
+            &rules_dir,
+            &rules_display,
             &mut result,
-            Some(&rule_fn),
-            Some(&rule_content_fn),
+            Some(&instructions_name),
+            Some(&instructions_content),
         );
 
         // Memory → .github/copilot-instructions.md (appended)
         if !preset_files.memory.is_empty() {
-            result.add_change(
-                ".github/copilot-instructions.md".to_string(),
-                "memory".to_string(),
-                self.copilot_instructions_file().exists(),
-            );
+            let (memory_file, memory_display) = self.memory_target();
+            result.add_change(memory_display, "memory".to_string(), memory_file.exists());
         }
 
         // Commands with prompt suffix
-        let cmd_fn = |name: &str, _content: &str| add_suffix_before_ext(name, "prompt");
+        let (commands_dir, commands_display) = self.commands_target();
         scan_one_to_one(
             &preset_files.commands,
             "commands",
-            &github_dir.join("prompts"),
-            ".github/prompts",
+            &commands_dir,
+            &commands_display,
             &mut result,
-            Some(&cmd_fn),
+            Some(&prompt_name),
             None,
         );
 
         // MCP → .vscode/mcp.json
+        let (mcp_file, mcp_display) = self.mcp_target();
         scan_merged_section(
             &preset_files.mcp,
-            ".vscode/mcp.json",
+            &mcp_display,
             "mcp",
-            &self.vscode_dir().join("mcp.json"),
+            &mcp_file,
             &mut result,
         );
 
         // Agents with agent suffix
-        let agent_fn = |name: &str, _content: &str| add_suffix_before_ext(name, "agent");
+        let (agents_dir, agents_display) = self.agents_target();
         scan_one_to_one(
             &preset_files.agents,
             "agents",
-            &github_dir.join("agents"),
-            ".github/agents",
+            &agents_dir,
+            &agents_display,
             &mut result,
-            Some(&agent_fn),
+            Some(&agent_name),
             None,
         );
 
         // Skills (no transform)
+        let (skills_dir, skills_display) = self.skills_target();
         scan_one_to_one(
             &preset_files.skills,
             "skills",
-            &github_dir.join("skills"),
-            ".github/skills",
+            &skills_dir,
+            &skills_display,
             &mut result,
             None,
             None,
@@ -179,70 +254,82 @@ impl ToolAdapter for CopilotAdapter {
         preset_files: &PresetFiles,
         _target_dir: &Path,
         conflict_mode: &mut ConflictMode,
+        ctx: &ApplyContext,
     ) -> Result<ApplyResult> {
-        ensure_dir(&self.github_dir())?;
+        ensure_dir(&self.github_dir(), ctx)?;
 
         let mut result = ApplyResult::new();
-        let github_dir = self.github_dir();
 
         // Apply merged sections first (may trigger interactive prompts)
-        self.apply_memory(&preset_files.memory, &mut result, conflict_mode)?;
+        self.apply_memory(
+            &preset_files.memory,
+            &preset_files.preset,
+            &mut result,
+            conflict_mode,
+            ctx,
+        )?;
+        let (mcp_file, mcp_display) = self.mcp_target();
         apply_json_merge(
             &preset_files.mcp,
             "mcp",
-            &self.vscode_dir().join("mcp.json"),
-            ".vscode/mcp.json",
+            &mcp_file,
+            &mcp_display,
             "servers",
             serde_json::json!({"inputs": [], "servers": {}}),
             &mut result,
             conflict_mode,
+            ctx,
         )?;
 
         // 1:1 mapped sections (resolved immediately from PreResolved map)
-        let rule_fn = |name: &str, _content: &str| add_suffix_before_ext(name, "instructions");
-        let rule_content_fn = |content: &str| convert_frontmatter_key(content, "globs", "applyTo");
+        let (rules_dir, rules_display) = self.rules_target();
         apply_one_to_one(
             &preset_files.rules,
             "rules",
-            &github_dir.join("instructions"),
-            ".github/instructions",
+            &rules_dir,
+            &rules_display,
             &mut result,
             conflict_mode,
-            Some(&rule_fn),
-            Some(&rule_content_fn),
+            ctx,
+            Some(&instructions_name),
+            Some(&instructions_content),
         )?;
 
-        let cmd_fn = |name: &str, _content: &str| add_suffix_before_ext(name, "prompt");
+        let (commands_dir, commands_display) = self.commands_target();
         apply_one_to_one(
             &preset_files.commands,
             "commands",
-            &github_dir.join("prompts"),
-            ".github/prompts",
+            &commands_dir,
+            &commands_display,
             &mut result,
             conflict_mode,
-            Some(&cmd_fn),
+            ctx,
+            Some(&prompt_name),
             None,
         )?;
 
-        let agent_fn = |name: &str, _content: &str| add_suffix_before_ext(name, "agent");
+        let (agents_dir, agents_display) = self.agents_target();
         apply_one_to_one(
             &preset_files.agents,
             "agents",
-            &github_dir.join("agents"),
-            ".github/agents",
+            &agents_dir,
+            &agents_display,
             &mut result,
             conflict_mode,
-            Some(&agent_fn),
+            ctx,
+            Some(&agent_name),
             None,
         )?;
 
+        let (skills_dir, skills_display) = self.skills_target();
         apply_one_to_one(
             &preset_files.skills,
             "skills",
-            &github_dir.join("skills"),
-            ".github/skills",
+            &skills_dir,
+            &skills_display,
             &mut result,
             conflict_mode,
+            ctx,
             None,
             None,
         )?;
@@ -254,6 +341,7 @@ impl ToolAdapter for CopilotAdapter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
     use tempfile::TempDir;
 
     fn create_test_adapter() -> (TempDir, CopilotAdapter) {
@@ -280,14 +368,14 @@ mod tests {
     fn test_detect_with_github_dir() {
         let (temp_dir, adapter) = create_test_adapter();
         fs::create_dir_all(temp_dir.path().join(".github")).unwrap();
-        assert!(adapter.detect());
+        assert!(adapter.detect(&ProjectMarkers::scan(temp_dir.path())));
     }
 
     #[test]
     fn test_detect_with_vscode_dir() {
         let (temp_dir, adapter) = create_test_adapter();
         fs::create_dir_all(temp_dir.path().join(".vscode")).unwrap();
-        assert!(adapter.detect());
+        assert!(adapter.detect(&ProjectMarkers::scan(temp_dir.path())));
     }
 
     #[test]
@@ -299,7 +387,8 @@ mod tests {
             "# Instructions",
         )
         .unwrap();
-        assert!(adapter.detect());
+        let info = adapter.detect_with_reason(&ProjectMarkers::scan(temp_dir.path()));
+        assert_eq!(info.reason, "detected via .github/copilot-instructions.md");
     }
 
     #[test]
@@ -310,12 +399,18 @@ mod tests {
             rules: vec![PresetFile {
                 relative_path: "rules/code-style.md".to_string(),
                 content: "# Code Style".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
 
         let result = adapter
-            .apply(&preset_files, temp_dir.path(), &mut ConflictMode::Force)
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
             .unwrap();
 
         assert!(!result.created.is_empty());
@@ -338,12 +433,18 @@ mod tests {
                 relative_path: "rules/rust.md".to_string(),
                 content: "---\ndescription: Rust rules\nglobs: \"**/*.rs\"\n---\n# Rust Rules"
                     .to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
 
         adapter
-            .apply(&preset_files, temp_dir.path(), &mut ConflictMode::Force)
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
             .unwrap();
 
         let file = temp_dir
@@ -365,12 +466,18 @@ mod tests {
             rules: vec![PresetFile {
                 relative_path: "rules/general.md".to_string(),
                 content: "# General Rules\n\nNo frontmatter here.".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
 
         adapter
-            .apply(&preset_files, temp_dir.path(), &mut ConflictMode::Force)
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
             .unwrap();
 
         let file = temp_dir
@@ -390,17 +497,24 @@ mod tests {
                 PresetFile {
                     relative_path: "rules/code-style.md".to_string(),
                     content: "# Code Style".to_string(),
+                    ..Default::default()
                 },
                 PresetFile {
                     relative_path: "rules/security.md".to_string(),
                     content: "# Security".to_string(),
+                    ..Default::default()
                 },
             ],
             ..Default::default()
         };
 
         let result = adapter
-            .apply(&preset_files, temp_dir.path(), &mut ConflictMode::Force)
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
             .unwrap();
 
         assert_eq!(result.created.len(), 2);
@@ -431,12 +545,18 @@ mod tests {
             rules: vec![PresetFile {
                 relative_path: "rules/existing.md".to_string(),
                 content: "# New Rules".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
 
         let result = adapter
-            .apply(&preset_files, temp_dir.path(), &mut ConflictMode::Force)
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
             .unwrap();
 
         assert!(result
@@ -453,12 +573,18 @@ mod tests {
             commands: vec![PresetFile {
                 relative_path: "commands/build.md".to_string(),
                 content: "# Build Command".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
 
         let result = adapter
-            .apply(&preset_files, temp_dir.path(), &mut ConflictMode::Force)
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
             .unwrap();
 
         assert!(result
@@ -478,6 +604,7 @@ mod tests {
             rules: vec![PresetFile {
                 relative_path: "rules/test.md".to_string(),
                 content: "# Test".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
@@ -493,6 +620,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_scan_matches_apply() {
+        let (temp_dir, adapter) = create_test_adapter();
+        let file = |path: &str, content: &str| PresetFile {
+            relative_path: path.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        };
+        let preset_files = PresetFiles {
+            rules: vec![file(
+                "rules/rust.md",
+                "---\nglobs: \"**/*.rs\"\n---\n# Rust\n",
+            )],
+            commands: vec![file("commands/build.prompt.md", "# Build\n")],
+            agents: vec![file("agents/review.md", "# Review\n")],
+            ..Default::default()
+        };
+
+        let mut applied = adapter
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
+            .unwrap()
+            .created;
+        applied.sort();
+        assert_eq!(
+            applied,
+            vec![
+                ".github/agents/review.agent.md",
+                ".github/instructions/rust.instructions.md",
+                ".github/prompts/build.prompt.md",
+            ]
+        );
+
+        // Same paths, and the converted content counts as already applied
+        let scan = adapter.scan(&preset_files, temp_dir.path());
+        let mut scanned: Vec<&str> = scan.changes.iter().map(|c| c.path.as_str()).collect();
+        scanned.sort();
+        assert_eq!(scanned, applied);
+        assert!(scan.changes.iter().all(|c| c.is_identical));
+    }
+
     #[test]
     fn test_scan_conflicts() {
         let (temp_dir, adapter) = create_test_adapter();
@@ -506,6 +678,7 @@ mod tests {
             rules: vec![PresetFile {
                 relative_path: "rules/new.md".to_string(),
                 content: "# New".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
@@ -523,12 +696,18 @@ mod tests {
             memory: vec![PresetFile {
                 relative_path: "memory/context.md".to_string(),
                 content: "# Project Context".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
 
         adapter
-            .apply(&preset_files, temp_dir.path(), &mut ConflictMode::Force)
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
             .unwrap();
 
         let instructions = temp_dir.path().join(".github/copilot-instructions.md");
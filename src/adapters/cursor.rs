@@ -1,13 +1,23 @@
 use super::common::{
-    apply_json_merge, apply_one_to_one, ensure_dir, scan_merged_section, scan_one_to_one,
+    apply_json_merge, apply_one_to_one, ensure_dir, json_file_content, resolve_target,
+    scan_merged_section, scan_one_to_one,
+};
+use super::conflict::{write_with_conflict, ApplyContext, ConflictMode};
+use super::helpers::{
+    has_frontmatter, is_command_available, legacy_memory_block, memory_blocks, unify_line_endings,
+    upsert_blocks,
+};
+use super::traits::{
+    ApplyResult, DetectionInfo, PresetFile, PresetFiles, ProjectMarkers, ReloadHint, ScanResult,
+    ToolAdapter,
 };
-use super::conflict::{write_with_conflict, ConflictMode};
-use super::helpers::{has_frontmatter, is_command_available, strip_section_prefix};
-use super::traits::{ApplyResult, PresetFile, PresetFiles, ScanResult, ToolAdapter};
 use crate::error::Result;
-use std::fs;
+use crate::preset::config::ToolPaths;
 use std::path::{Path, PathBuf};
 
+/// Heading the memory blocks in .cursorrules are appended under
+const MEMORY_HEADER: &str = "\n\n---\n\n# Project Memory\n";
+
 /// Cursor adapter
 ///
 /// Converts presets to Cursor format:
@@ -20,23 +30,59 @@ use std::path::{Path, PathBuf};
 /// - skills/*.ts → .cursor/skills/
 pub struct CursorAdapter {
     project_dir: PathBuf,
+    paths: ToolPaths,
 }
 
 impl CursorAdapter {
     pub fn new(project_dir: &Path) -> Self {
         Self {
             project_dir: project_dir.to_path_buf(),
+            paths: ToolPaths::default(),
         }
     }
 
+    /// Override target paths from preset config ([tools.cursor])
+    pub fn with_paths(mut self, paths: ToolPaths) -> Self {
+        self.paths = paths;
+        self
+    }
+
     /// Get the .cursor directory path
     fn cursor_dir(&self) -> PathBuf {
         self.project_dir.join(".cursor")
     }
 
-    /// Get the .cursorrules file path
-    fn cursorrules_file(&self) -> PathBuf {
-        self.project_dir.join(".cursorrules")
+    /// Resolve a target path (override or default) to (absolute path, display path)
+    fn target(&self, override_path: &Option<String>, default: &str) -> (PathBuf, String) {
+        resolve_target(&self.project_dir, override_path.as_deref(), default)
+    }
+
+    fn rules_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.rules_dir, ".cursor/rules")
+    }
+
+    fn commands_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.commands_dir, ".cursor/commands")
+    }
+
+    fn agents_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.agents_dir, ".cursor/agents")
+    }
+
+    fn skills_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.skills_dir, ".cursor/skills")
+    }
+
+    fn memory_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.memory_file, ".cursorrules")
+    }
+
+    fn mcp_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.mcp_file, ".cursor/mcp.json")
+    }
+
+    fn hooks_target(&self) -> (PathBuf, String) {
+        self.target(&self.paths.hooks_file, ".cursor/hooks.json")
     }
 
     /// Determine the target filename for a rule file based on frontmatter presence.
@@ -57,37 +103,35 @@ impl CursorAdapter {
     fn apply_memory(
         &self,
         files: &[PresetFile],
+        preset: &str,
         result: &mut ApplyResult,
         mode: &mut ConflictMode,
+        ctx: &ApplyContext,
     ) -> Result<()> {
         if files.is_empty() {
             return Ok(());
         }
 
-        let cursorrules = self.cursorrules_file();
+        let (cursorrules, display) = self.memory_target();
 
-        // Build memory content
-        let mut memory_content = String::from("\n\n---\n\n# Project Memory\n\n");
-        for (i, file) in files.iter().enumerate() {
-            if i > 0 {
-                memory_content.push_str("\n\n---\n\n");
-            }
-            memory_content.push_str(&format!(
-                "## {}\n\n",
-                strip_section_prefix(&file.relative_path, "memory").replace(".md", "")
-            ));
-            memory_content.push_str(&file.content);
-        }
+        // One marked block per memory file, replaced in place on later pulls
+        let blocks = memory_blocks(files, "##");
 
         // Read existing content or create header
-        let base = if cursorrules.exists() {
-            fs::read_to_string(&cursorrules)?
-        } else {
-            String::from("# Cursor Rules\n# Generated by aidot\n")
-        };
-        let content = format!("{}{}", base, memory_content);
-
-        write_with_conflict(&cursorrules, &content, mode, result, ".cursorrules")?;
+        let base = ctx
+            .read_target(&cursorrules)?
+            .unwrap_or_else(|| String::from("# Cursor Rules\n# Generated by aidot\n"));
+        let legacy = legacy_memory_block(files, MEMORY_HEADER, "##");
+        let content = upsert_blocks(&base, MEMORY_HEADER, preset, &blocks, Some(&legacy));
+
+        write_with_conflict(
+            &cursorrules,
+            &unify_line_endings(&content),
+            mode,
+            ctx,
+            result,
+            &display,
+        )?;
 
         Ok(())
     }
@@ -98,12 +142,13 @@ impl CursorAdapter {
         files: &[PresetFile],
         result: &mut ApplyResult,
         mode: &mut ConflictMode,
+        ctx: &ApplyContext,
     ) -> Result<()> {
         if files.is_empty() {
             return Ok(());
         }
 
-        let hooks_file = self.cursor_dir().join("hooks.json");
+        let (hooks_file, display) = self.hooks_target();
 
         // Merge all hooks into one JSON object with standard structure
         let mut hooks_config = serde_json::json!({
@@ -113,7 +158,7 @@ impl CursorAdapter {
 
         for file in files {
             let hook_config: serde_json::Value = serde_json::from_str(&file.content)?;
-            let hook_name = strip_section_prefix(&file.relative_path, "hooks").replace(".json", "");
+            let hook_name = file.section_path("hooks").replace(".json", "");
 
             // Add to hooks object
             if let Some(hooks_obj) = hooks_config.get_mut("hooks") {
@@ -121,8 +166,8 @@ impl CursorAdapter {
             }
         }
 
-        let json_str = serde_json::to_string_pretty(&hooks_config)?;
-        write_with_conflict(&hooks_file, &json_str, mode, result, ".cursor/hooks.json")?;
+        let json_str = json_file_content(&hooks_config, ctx)?;
+        write_with_conflict(&hooks_file, &json_str, mode, ctx, result, &display)?;
 
         Ok(())
     }
@@ -133,24 +178,45 @@ impl ToolAdapter for CursorAdapter {
         "Cursor"
     }
 
-    fn detect(&self) -> bool {
-        self.cursorrules_file().exists()
-            || self.cursor_dir().exists()
-            || is_command_available("cursor")
+    fn detect_with_reason(&self, markers: &ProjectMarkers) -> DetectionInfo {
+        if markers.has(".cursorrules") {
+            DetectionInfo::found("detected via .cursorrules file")
+        } else if markers.has(".cursor") {
+            DetectionInfo::found("detected via .cursor/ directory")
+        } else if is_command_available("cursor") {
+            DetectionInfo::found("detected via cursor CLI in PATH")
+        } else {
+            DetectionInfo::not_found("no .cursorrules, .cursor/ directory or cursor CLI in PATH")
+        }
+    }
+
+    fn global_config_dir(&self) -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".cursor"))
+    }
+
+    fn unsupported_sections(&self) -> &[(&'static str, &'static str)] {
+        &[("settings", "Cursor has no project settings file")]
+    }
+
+    fn reload_hint(&self) -> Option<ReloadHint> {
+        Some(ReloadHint {
+            message:
+                "reload the Cursor window (Developer: Reload Window) to load rules and MCP servers",
+            command: is_command_available("cursor").then_some(&["cursor", "--reuse-window"]),
+        })
     }
 
     fn scan(&self, preset_files: &PresetFiles, _target_dir: &Path) -> ScanResult {
         let mut result = ScanResult::new();
-        let cursor_dir = self.cursor_dir();
-        let mcp_file = cursor_dir.join("mcp.json");
 
         // Rules with frontmatter-aware filename transform
         let rule_fn = |n: &str, c: &str| Self::rule_filename(n, c);
+        let (rules_dir, rules_display) = self.rules_target();
         scan_one_to_one(
             &preset_files.rules,
             "rules",
-            &cursor_dir.join("rules"),
-            ".cursor/rules",
+            &rules_dir,
+            &rules_display,
             &mut result,
             Some(&rule_fn),
             None,
@@ -158,55 +224,57 @@ impl ToolAdapter for CursorAdapter {
 
         // Memory → .cursorrules (appended)
         if !preset_files.memory.is_empty() {
-            result.add_change(
-                ".cursorrules".to_string(),
-                "memory".to_string(),
-                self.cursorrules_file().exists(),
-            );
+            let (memory_file, memory_display) = self.memory_target();
+            result.add_change(memory_display, "memory".to_string(), memory_file.exists());
         }
 
         // 1:1 sections
+        let (commands_dir, commands_display) = self.commands_target();
         scan_one_to_one(
             &preset_files.commands,
             "commands",
-            &cursor_dir.join("commands"),
-            ".cursor/commands",
+            &commands_dir,
+            &commands_display,
             &mut result,
             None,
             None,
         );
+        let (agents_dir, agents_display) = self.agents_target();
         scan_one_to_one(
             &preset_files.agents,
             "agents",
-            &cursor_dir.join("agents"),
-            ".cursor/agents",
+            &agents_dir,
+            &agents_display,
             &mut result,
             None,
             None,
         );
+        let (skills_dir, skills_display) = self.skills_target();
         scan_one_to_one(
             &preset_files.skills,
             "skills",
-            &cursor_dir.join("skills"),
-            ".cursor/skills",
+            &skills_dir,
+            &skills_display,
             &mut result,
             None,
             None,
         );
 
         // Merged sections
+        let (mcp_file, mcp_display) = self.mcp_target();
         scan_merged_section(
             &preset_files.mcp,
-            ".cursor/mcp.json",
+            &mcp_display,
             "mcp",
             &mcp_file,
             &mut result,
         );
+        let (hooks_file, hooks_display) = self.hooks_target();
         scan_merged_section(
             &preset_files.hooks,
-            ".cursor/hooks.json",
+            &hooks_display,
             "hooks",
-            &cursor_dir.join("hooks.json"),
+            &hooks_file,
             &mut result,
         );
 
@@ -218,65 +286,81 @@ impl ToolAdapter for CursorAdapter {
         preset_files: &PresetFiles,
         _target_dir: &Path,
         conflict_mode: &mut ConflictMode,
+        ctx: &ApplyContext,
     ) -> Result<ApplyResult> {
-        ensure_dir(&self.cursor_dir())?;
+        ensure_dir(&self.cursor_dir(), ctx)?;
 
         let mut result = ApplyResult::new();
-        let cursor_dir = self.cursor_dir();
 
         // Apply merged sections first (may trigger interactive prompts)
-        self.apply_memory(&preset_files.memory, &mut result, conflict_mode)?;
+        self.apply_memory(
+            &preset_files.memory,
+            &preset_files.preset,
+            &mut result,
+            conflict_mode,
+            ctx,
+        )?;
+        let (mcp_file, mcp_display) = self.mcp_target();
         apply_json_merge(
             &preset_files.mcp,
             "mcp",
-            &cursor_dir.join("mcp.json"),
-            ".cursor/mcp.json",
+            &mcp_file,
+            &mcp_display,
             "mcpServers",
             serde_json::json!({}),
             &mut result,
             conflict_mode,
+            ctx,
         )?;
-        self.apply_hooks(&preset_files.hooks, &mut result, conflict_mode)?;
+        self.apply_hooks(&preset_files.hooks, &mut result, conflict_mode, ctx)?;
 
         // 1:1 mapped sections (resolved immediately from PreResolved map)
         let rule_fn = |n: &str, c: &str| Self::rule_filename(n, c);
+        let (rules_dir, rules_display) = self.rules_target();
         apply_one_to_one(
             &preset_files.rules,
             "rules",
-            &cursor_dir.join("rules"),
-            ".cursor/rules",
+            &rules_dir,
+            &rules_display,
             &mut result,
             conflict_mode,
+            ctx,
             Some(&rule_fn),
             None,
         )?;
+        let (commands_dir, commands_display) = self.commands_target();
         apply_one_to_one(
             &preset_files.commands,
             "commands",
-            &cursor_dir.join("commands"),
-            ".cursor/commands",
+            &commands_dir,
+            &commands_display,
             &mut result,
             conflict_mode,
+            ctx,
             None,
             None,
         )?;
+        let (agents_dir, agents_display) = self.agents_target();
         apply_one_to_one(
             &preset_files.agents,
             "agents",
-            &cursor_dir.join("agents"),
-            ".cursor/agents",
+            &agents_dir,
+            &agents_display,
             &mut result,
             conflict_mode,
+            ctx,
             None,
             None,
         )?;
+        let (skills_dir, skills_display) = self.skills_target();
         apply_one_to_one(
             &preset_files.skills,
             "skills",
-            &cursor_dir.join("skills"),
-            ".cursor/skills",
+            &skills_dir,
+            &skills_display,
             &mut result,
             conflict_mode,
+            ctx,
             None,
             None,
         )?;
@@ -288,6 +372,7 @@ impl ToolAdapter for CursorAdapter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
     use tempfile::TempDir;
 
     fn create_test_adapter() -> (TempDir, CursorAdapter) {
@@ -314,14 +399,14 @@ mod tests {
     fn test_detect_with_cursorrules() {
         let (temp_dir, adapter) = create_test_adapter();
         fs::write(temp_dir.path().join(".cursorrules"), "# Rules").unwrap();
-        assert!(adapter.detect());
+        assert!(adapter.detect(&ProjectMarkers::scan(temp_dir.path())));
     }
 
     #[test]
     fn test_detect_with_cursor_dir() {
         let (temp_dir, adapter) = create_test_adapter();
         fs::create_dir_all(temp_dir.path().join(".cursor")).unwrap();
-        assert!(adapter.detect());
+        assert!(adapter.detect(&ProjectMarkers::scan(temp_dir.path())));
     }
 
     #[test]
@@ -332,12 +417,18 @@ mod tests {
             rules: vec![PresetFile {
                 relative_path: "rules/code-style.md".to_string(),
                 content: "# Code Style".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
 
         let result = adapter
-            .apply(&preset_files, temp_dir.path(), &mut ConflictMode::Force)
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
             .unwrap();
 
         assert!(!result.created.is_empty());
@@ -360,12 +451,18 @@ mod tests {
             rules: vec![PresetFile {
                 relative_path: "rules/code-style.md".to_string(),
                 content: content_with_frontmatter.clone(),
+                ..Default::default()
             }],
             ..Default::default()
         };
 
         let result = adapter
-            .apply(&preset_files, temp_dir.path(), &mut ConflictMode::Force)
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
             .unwrap();
 
         assert!(!result.created.is_empty());
@@ -386,17 +483,24 @@ mod tests {
                 PresetFile {
                     relative_path: "rules/plain.md".to_string(),
                     content: "# Plain rule".to_string(),
+                    ..Default::default()
                 },
                 PresetFile {
                     relative_path: "rules/with-meta.md".to_string(),
                     content: "---\ndescription: Meta rule\n---\n# Meta rule".to_string(),
+                    ..Default::default()
                 },
             ],
             ..Default::default()
         };
 
         let result = adapter
-            .apply(&preset_files, temp_dir.path(), &mut ConflictMode::Force)
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
             .unwrap();
 
         assert_eq!(result.created.len(), 2);
@@ -420,12 +524,18 @@ mod tests {
             rules: vec![PresetFile {
                 relative_path: "rules/existing.md".to_string(),
                 content: "# New Rules".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
 
         let result = adapter
-            .apply(&preset_files, temp_dir.path(), &mut ConflictMode::Force)
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
             .unwrap();
 
         assert!(result
@@ -442,12 +552,18 @@ mod tests {
             commands: vec![PresetFile {
                 relative_path: "commands/test.md".to_string(),
                 content: "# Test Command".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
 
         let result = adapter
-            .apply(&preset_files, temp_dir.path(), &mut ConflictMode::Force)
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut ConflictMode::Force,
+                &ApplyContext::default(),
+            )
             .unwrap();
 
         assert!(result.created.iter().any(|f| f.contains("test.md")));
@@ -464,6 +580,7 @@ mod tests {
             rules: vec![PresetFile {
                 relative_path: "rules/test.md".to_string(),
                 content: "# Test".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
@@ -484,6 +601,7 @@ mod tests {
             rules: vec![PresetFile {
                 relative_path: "rules/test.md".to_string(),
                 content: "---\ndescription: test\n---\n# Test".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
@@ -508,6 +626,7 @@ mod tests {
             rules: vec![PresetFile {
                 relative_path: "rules/new.md".to_string(),
                 content: "# New".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
@@ -1,70 +1,408 @@
+use super::amazonq::AmazonQAdapter;
 use super::claude_code::ClaudeCodeAdapter;
 use super::copilot::CopilotAdapter;
 use super::cursor::CursorAdapter;
+use super::helpers::edit_distance;
+use super::traits::ProjectMarkers;
 use super::ToolAdapter;
-use std::path::Path;
+use crate::error::{AidotError, Result};
+use crate::output::tilde_path;
+use crate::preset::config::ToolsSection;
+use std::path::{Path, PathBuf};
+
+/// Canonical tool names in adapter order
+pub const TOOL_NAMES: &[&str] = &["Claude Code", "Cursor", "GitHub Copilot", "Amazon Q"];
+
+/// Short aliases accepted by `--tools` (alias → canonical tool name)
+const TOOL_ALIASES: &[(&str, &str)] = &[
+    ("claude", "Claude Code"),
+    ("claude-code", "Claude Code"),
+    ("claudecode", "Claude Code"),
+    ("cursor", "Cursor"),
+    ("copilot", "GitHub Copilot"),
+    ("github-copilot", "GitHub Copilot"),
+    ("gh-copilot", "GitHub Copilot"),
+    ("amazonq", "Amazon Q"),
+    ("amazon-q", "Amazon Q"),
+    ("codewhisperer", "Amazon Q"),
+];
 
 /// Detected tool information
 #[derive(Debug, Clone)]
 pub struct DetectedTool {
     pub name: String,
     pub detected: bool,
+    /// Why the tool was (or was not) detected
+    pub reason: String,
 }
 
 /// Detect all available LLM tools in the current directory
-pub fn detect_tools(project_dir: &Path) -> Vec<Box<dyn ToolAdapter>> {
-    let mut tools: Vec<Box<dyn ToolAdapter>> = Vec::new();
+/// Target path overrides from the preset config are applied to each adapter
+///
+/// The project root is scanned once for tool markers and CLI lookups are cached,
+/// so adapters share one detection pass.
+pub fn detect_tools(project_dir: &Path, overrides: &ToolsSection) -> Vec<Box<dyn ToolAdapter>> {
+    let markers = ProjectMarkers::scan(project_dir);
+    all_tools(project_dir, overrides)
+        .into_iter()
+        .filter(|tool| tool.detect(&markers))
+        .collect()
+}
+
+/// Create all tool adapters regardless of detection status.
+/// Used when --tools filter is specified to allow deploying to tools
+/// that haven't been set up yet.
+pub fn all_tools(project_dir: &Path, overrides: &ToolsSection) -> Vec<Box<dyn ToolAdapter>> {
+    vec![
+        Box::new(
+            ClaudeCodeAdapter::new(project_dir)
+                .with_paths(overrides.claude.clone().unwrap_or_default()),
+        ),
+        Box::new(
+            CursorAdapter::new(project_dir)
+                .with_paths(overrides.cursor.clone().unwrap_or_default()),
+        ),
+        Box::new(
+            CopilotAdapter::new(project_dir)
+                .with_paths(overrides.copilot.clone().unwrap_or_default()),
+        ),
+        Box::new(
+            AmazonQAdapter::new(project_dir)
+                .with_paths(overrides.amazonq.clone().unwrap_or_default()),
+        ),
+    ]
+}
+
+/// Tools whose user-level configuration mirrors the project layout under the
+/// home directory (~/.claude, ~/.cursor), so `pull --global` can apply to them
+pub const GLOBAL_TOOL_NAMES: &[&str] = &["Claude Code", "Cursor"];
 
-    // Check Claude Code
-    let claude_adapter = ClaudeCodeAdapter::new(project_dir);
-    if claude_adapter.detect() {
-        tools.push(Box::new(claude_adapter));
+/// Adapters rooted at the home directory instead of a project (`pull --global`)
+pub fn global_tools(home_dir: &Path, overrides: &ToolsSection) -> Vec<Box<dyn ToolAdapter>> {
+    all_tools(home_dir, overrides)
+        .into_iter()
+        .filter(|tool| GLOBAL_TOOL_NAMES.contains(&tool.name()))
+        .collect()
+}
+
+/// Get list of detected tool names
+pub fn get_detected_tool_names(project_dir: &Path) -> Vec<DetectedTool> {
+    let markers = ProjectMarkers::scan(project_dir);
+    all_tools(project_dir, &ToolsSection::default())
+        .into_iter()
+        .map(|tool| {
+            let info = tool.detect_with_reason(&markers);
+            DetectedTool {
+                name: tool.name().to_string(),
+                detected: info.detected,
+                reason: info.reason,
+            }
+        })
+        .collect()
+}
+
+/// User-level configuration directory of a tool
+#[derive(Debug, Clone)]
+pub struct GlobalToolConfig {
+    pub name: String,
+    pub dir: PathBuf,
+    /// `dir` with the home directory shown as `~`
+    pub display: String,
+    pub exists: bool,
+}
+
+/// Global (home directory) configuration of every tool that has one
+pub fn get_global_tool_configs() -> Vec<GlobalToolConfig> {
+    let home = dirs::home_dir();
+    all_tools(Path::new("."), &ToolsSection::default())
+        .into_iter()
+        .filter_map(|tool| {
+            let dir = tool.global_config_dir()?;
+            Some(GlobalToolConfig {
+                name: tool.name().to_string(),
+                display: tilde_path(&dir, home.as_deref()),
+                exists: dir.is_dir(),
+                dir,
+            })
+        })
+        .collect()
+}
+
+/// Resolve a single `--tools` entry to a canonical tool name
+///
+/// Resolution order: exact tool name → alias → unique prefix of a name or alias.
+/// Ambiguous prefixes (e.g., "c") and unknown names are rejected with the candidate list.
+pub fn resolve_tool_name(input: &str) -> Result<&'static str> {
+    let needle = input.trim().to_lowercase();
+
+    // 1. Exact tool name (case-insensitive)
+    if let Some(name) = TOOL_NAMES.iter().find(|n| n.to_lowercase() == needle) {
+        return Ok(name);
+    }
+
+    // 2. Exact alias
+    if let Some((_, name)) = TOOL_ALIASES.iter().find(|(alias, _)| *alias == needle) {
+        return Ok(name);
     }
 
-    // Check Cursor
-    let cursor_adapter = CursorAdapter::new(project_dir);
-    if cursor_adapter.detect() {
-        tools.push(Box::new(cursor_adapter));
+    // 3. Prefix of a tool name or alias
+    let mut candidates: Vec<&'static str> = Vec::new();
+    if !needle.is_empty() {
+        let prefixed = TOOL_NAMES
+            .iter()
+            .filter(|n| n.to_lowercase().starts_with(&needle))
+            .copied()
+            .chain(
+                TOOL_ALIASES
+                    .iter()
+                    .filter(|(alias, _)| alias.starts_with(&needle))
+                    .map(|(_, name)| *name),
+            );
+        for name in prefixed {
+            if !candidates.contains(&name) {
+                candidates.push(name);
+            }
+        }
     }
 
-    // Check GitHub Copilot
-    let copilot_adapter = CopilotAdapter::new(project_dir);
-    if copilot_adapter.detect() {
-        tools.push(Box::new(copilot_adapter));
+    match candidates.as_slice() {
+        [name] => Ok(name),
+        [] => {
+            let hint = suggest_tool(&needle)
+                .map(|suggestion| format!(" Did you mean '{}'?", suggestion))
+                .unwrap_or_default();
+            Err(AidotError::InvalidInput(format!(
+                "Unknown tool '{}'.{} Available tools: {}",
+                input,
+                hint,
+                tool_choices()
+            )))
+        }
+        _ => Err(AidotError::InvalidInput(format!(
+            "Ambiguous tool '{}' matches: {}. Please be more specific.",
+            input,
+            candidates.join(", ")
+        ))),
     }
+}
 
+/// Closest tool name or alias to a mistyped `--tools` entry (e.g., "cluade" → "claude")
+///
+/// Only candidates within a third of the input length (at least 1) are suggested.
+/// The suggestion is a hint for the error message; it is never applied.
+fn suggest_tool(needle: &str) -> Option<&'static str> {
+    let max_distance = (needle.chars().count() / 3).max(1);
+    // Aliases first: on a tie, suggest what users usually type
+    TOOL_ALIASES
+        .iter()
+        .map(|(alias, _)| (*alias, edit_distance(needle, alias)))
+        .chain(
+            TOOL_NAMES
+                .iter()
+                .map(|name| (*name, edit_distance(needle, &name.to_lowercase()))),
+        )
+        .filter(|(_, distance)| *distance <= max_distance)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(candidate, _)| candidate)
+}
+
+/// Resolve all `--tools` entries, keeping the given order and dropping duplicates
+///
+/// The order is the apply order (see `sort_tools`).
+pub fn resolve_tool_filter(filter: &[String]) -> Result<Vec<&'static str>> {
+    let mut resolved = Vec::new();
+    for input in filter {
+        let name = resolve_tool_name(input)?;
+        if !resolved.contains(&name) {
+            resolved.push(name);
+        }
+    }
+    Ok(resolved)
+}
+
+/// Keep the adapters named in `selected` (--tools), in that order
+///
+/// Detection is not consulted, so tools that aren't set up yet can be selected too.
+pub fn select_tools(
+    candidates: Vec<Box<dyn ToolAdapter>>,
+    selected: &[&str],
+) -> Vec<Box<dyn ToolAdapter>> {
+    let mut tools: Vec<_> = candidates
+        .into_iter()
+        .filter(|tool| selected.contains(&tool.name()))
+        .collect();
+    sort_tools(&mut tools, selected);
     tools
 }
 
-/// Create all tool adapters regardless of detection status.
-/// Used when --tools filter is specified to allow deploying to tools
-/// that haven't been set up yet.
-pub fn all_tools(project_dir: &Path) -> Vec<Box<dyn ToolAdapter>> {
-    vec![
-        Box::new(ClaudeCodeAdapter::new(project_dir)),
-        Box::new(CursorAdapter::new(project_dir)),
-        Box::new(CopilotAdapter::new(project_dir)),
-    ]
+/// Sort adapters into `order` (canonical tool names)
+///
+/// Tools not in `order` follow in adapter order, so the result is deterministic:
+/// scan output and an "all" conflict answer always go through tools the same way.
+pub fn sort_tools(tools: &mut [Box<dyn ToolAdapter>], order: &[&str]) {
+    tools.sort_by_key(|tool| {
+        order
+            .iter()
+            .position(|name| *name == tool.name())
+            .unwrap_or(order.len())
+    });
 }
 
-/// Get list of detected tool names
-pub fn get_detected_tool_names(project_dir: &Path) -> Vec<DetectedTool> {
-    let claude_adapter = ClaudeCodeAdapter::new(project_dir);
-    let cursor_adapter = CursorAdapter::new(project_dir);
-    let copilot_adapter = CopilotAdapter::new(project_dir);
+/// Human-readable list of accepted tool names (e.g., "claude, cursor, copilot")
+pub fn tool_choices() -> String {
+    TOOL_NAMES
+        .iter()
+        .filter_map(|name| {
+            TOOL_ALIASES
+                .iter()
+                .find(|(_, n)| n == name)
+                .map(|(alias, _)| *alias)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
-    vec![
-        DetectedTool {
-            name: "Claude Code".to_string(),
-            detected: claude_adapter.detect(),
-        },
-        DetectedTool {
-            name: "Cursor".to_string(),
-            detected: cursor_adapter.detect(),
-        },
-        DetectedTool {
-            name: "GitHub Copilot".to_string(),
-            detected: copilot_adapter.detect(),
-        },
-    ]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_project_markers_scan() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join(".github/instructions")).unwrap();
+        fs::write(temp_dir.path().join(".cursorrules"), "").unwrap();
+        fs::create_dir_all(temp_dir.path().join("src/.claude")).unwrap();
+
+        let markers = ProjectMarkers::scan(temp_dir.path());
+        assert!(markers.has(".github"));
+        assert!(markers.has(".github/instructions"));
+        assert!(markers.has(".cursorrules"));
+        assert!(!markers.has(".github/copilot-instructions.md"));
+        // Only the project root counts
+        assert!(!markers.has(".claude"));
+
+        let tools = get_detected_tool_names(temp_dir.path());
+        let reason = |name: &str| {
+            tools
+                .iter()
+                .find(|tool| tool.name == name)
+                .map(|tool| tool.reason.clone())
+                .unwrap()
+        };
+        assert_eq!(reason("Cursor"), "detected via .cursorrules file");
+        assert_eq!(
+            reason("GitHub Copilot"),
+            "detected via .github/instructions/ directory"
+        );
+        assert_eq!(reason("Amazon Q"), "no .amazonq/ directory");
+    }
+
+    #[test]
+    fn test_get_detected_tool_names_reason() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join(".cursor")).unwrap();
+
+        let tools = get_detected_tool_names(temp_dir.path());
+        let names: Vec<_> = tools.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, TOOL_NAMES);
+
+        let cursor = &tools[1];
+        assert!(cursor.detected);
+        assert_eq!(cursor.reason, "detected via .cursor/ directory");
+        let copilot = &tools[2];
+        assert!(!copilot.detected);
+        assert_eq!(copilot.reason, "no .github/ or .vscode/ directory");
+        assert!(!tools[3].detected);
+    }
+
+    #[test]
+    fn test_resolve_tool_name_exact() {
+        assert_eq!(resolve_tool_name("Claude Code").unwrap(), "Claude Code");
+        assert_eq!(resolve_tool_name("cursor").unwrap(), "Cursor");
+        assert_eq!(
+            resolve_tool_name("github copilot").unwrap(),
+            "GitHub Copilot"
+        );
+    }
+
+    #[test]
+    fn test_resolve_tool_name_alias() {
+        assert_eq!(resolve_tool_name("claude").unwrap(), "Claude Code");
+        assert_eq!(resolve_tool_name("copilot").unwrap(), "GitHub Copilot");
+        assert_eq!(resolve_tool_name("CLAUDE").unwrap(), "Claude Code");
+        assert_eq!(resolve_tool_name("codewhisperer").unwrap(), "Amazon Q");
+        assert_eq!(resolve_tool_name("amazon q").unwrap(), "Amazon Q");
+    }
+
+    #[test]
+    fn test_resolve_tool_name_prefix() {
+        assert_eq!(resolve_tool_name("cur").unwrap(), "Cursor");
+        assert_eq!(resolve_tool_name("cop").unwrap(), "GitHub Copilot");
+        assert_eq!(resolve_tool_name("cla").unwrap(), "Claude Code");
+    }
+
+    #[test]
+    fn test_resolve_tool_name_ambiguous() {
+        let err = resolve_tool_name("c").unwrap_err().to_string();
+        assert!(err.contains("Ambiguous"));
+        assert!(err.contains("Claude Code"));
+        assert!(err.contains("Cursor"));
+    }
+
+    #[test]
+    fn test_resolve_tool_name_unknown() {
+        let err = resolve_tool_name("vim").unwrap_err().to_string();
+        assert!(err.contains("Unknown tool"));
+        assert!(err.contains("claude, cursor, copilot"));
+        assert!(!err.contains("Did you mean"));
+    }
+
+    #[test]
+    fn test_resolve_tool_name_suggests_closest() {
+        let err = resolve_tool_name("cluade").unwrap_err().to_string();
+        assert!(err.contains("Unknown tool 'cluade'. Did you mean 'claude'?"));
+
+        assert_eq!(suggest_tool("cursr"), Some("cursor"));
+        assert_eq!(suggest_tool("copilto"), Some("copilot"));
+        assert_eq!(suggest_tool("amazn q"), Some("Amazon Q"));
+        assert_eq!(suggest_tool("vim"), None);
+    }
+
+    #[test]
+    fn test_resolve_tool_filter_dedup_and_order() {
+        let filter = vec![
+            "copilot".to_string(),
+            "claude".to_string(),
+            "Claude Code".to_string(),
+        ];
+        assert_eq!(
+            resolve_tool_filter(&filter).unwrap(),
+            vec!["GitHub Copilot", "Claude Code"]
+        );
+    }
+
+    #[test]
+    fn test_sort_tools() {
+        let temp_dir = TempDir::new().unwrap();
+        let names = |tools: &[Box<dyn ToolAdapter>]| {
+            tools
+                .iter()
+                .map(|t| t.name().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let mut tools = all_tools(temp_dir.path(), &ToolsSection::default());
+        sort_tools(&mut tools, &["Cursor", "Amazon Q"]);
+        assert_eq!(
+            names(&tools),
+            vec!["Cursor", "Amazon Q", "Claude Code", "GitHub Copilot"]
+        );
+
+        // Unlisted tools keep their relative order
+        let before = names(&tools);
+        sort_tools(&mut tools, &[]);
+        assert_eq!(names(&tools), before);
+    }
 }
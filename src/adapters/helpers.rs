@@ -1,3 +1,10 @@
+use super::traits::PresetFile;
+use crate::manifest::content_hash;
+use crate::preset::config::LineEnding;
+use std::collections::HashMap;
+use std::path::{Component, Path};
+use std::sync::{Mutex, OnceLock};
+
 /// Check if content starts with YAML front matter (---\n...\n---)
 pub fn has_frontmatter(content: &str) -> bool {
     let trimmed = content.trim_start();
@@ -29,10 +36,15 @@ pub fn has_frontmatter(content: &str) -> bool {
 /// strip_section_prefix("rules/code-style.md", "rules") // → "code-style.md"
 /// strip_section_prefix("commands/build.md", "commands") // → "build.md"
 /// ```
+///
+/// Preset paths are `/`-separated (normalized by the parser), and only the leading
+/// section is removed.
 pub fn strip_section_prefix(relative_path: &str, section: &str) -> String {
     relative_path
-        .replace(&format!("{}/", section), "")
-        .replace(&format!("{}\\", section), "")
+        .strip_prefix(section)
+        .and_then(|rest| rest.strip_prefix('/'))
+        .unwrap_or(relative_path)
+        .to_string()
 }
 
 /// Insert a suffix before the `.md` extension in a filename
@@ -44,8 +56,13 @@ pub fn strip_section_prefix(relative_path: &str, section: &str) -> String {
 /// add_suffix_before_ext("build.md", "prompt")           // → "build.prompt.md"
 /// add_suffix_before_ext("code-style.md", "instructions") // → "code-style.instructions.md"
 /// add_suffix_before_ext("readme", "prompt")              // → "readme.prompt.md"
+/// add_suffix_before_ext("build.prompt.md", "prompt")    // → "build.prompt.md"
 /// ```
 pub fn add_suffix_before_ext(filename: &str, suffix: &str) -> String {
+    // Already in the target form (e.g., a preset that ships build.prompt.md)
+    if filename.ends_with(&format!(".{}.md", suffix)) {
+        return filename.to_string();
+    }
     if let Some(stem) = filename.strip_suffix(".md") {
         format!("{}.{}.md", stem, suffix)
     } else {
@@ -56,7 +73,8 @@ pub fn add_suffix_before_ext(filename: &str, suffix: &str) -> String {
 /// Convert a specific key to another key within YAML front matter
 ///
 /// Returns the original content unchanged if no front matter exists.
-/// Handles both `from_key:` and `from_key :` forms.
+/// Handles both `from_key:` and `from_key :` forms. Everything else, including line
+/// endings and the closing `---`, is kept byte for byte.
 ///
 /// # Examples
 /// ```
@@ -69,35 +87,116 @@ pub fn convert_frontmatter_key(content: &str, from_key: &str, to_key: &str) -> S
     }
 
     let trimmed = content.trim_start();
+    let leading = &content[..content.len() - trimmed.len()];
     let after_opening = &trimmed[3..];
-    if let Some(close_pos) = after_opening.find("\n---") {
-        let frontmatter = &after_opening[..close_pos + 1];
-        let rest = &after_opening[close_pos + 1..];
-
-        let from_colon = format!("{}:", from_key);
-        let from_space_colon = format!("{} :", from_key);
-
-        let converted_frontmatter = frontmatter
-            .lines()
-            .map(|line| {
-                if line.starts_with(&from_colon) || line.starts_with(&from_space_colon) {
-                    line.replacen(from_key, to_key, 1)
-                } else {
-                    line.to_string()
-                }
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        format!("---{}{}", converted_frontmatter, rest)
+    let Some(close_pos) = after_opening.find("\n---") else {
+        return content.to_string();
+    };
+    // The frontmatter keeps its final newline; `rest` starts at the closing ---
+    let (frontmatter, rest) = after_opening.split_at(close_pos + 1);
+
+    let from_colon = format!("{}:", from_key);
+    let from_space_colon = format!("{} :", from_key);
+    let converted_frontmatter: String = frontmatter
+        .split_inclusive('\n')
+        .map(|line| {
+            if line.starts_with(&from_colon) || line.starts_with(&from_space_colon) {
+                line.replacen(from_key, to_key, 1)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+
+    format!("{}---{}{}", leading, converted_frontmatter, rest)
+}
+
+/// Strip a leading UTF-8 byte order mark
+pub fn strip_bom(content: &str) -> &str {
+    content.strip_prefix('\u{feff}').unwrap_or(content)
+}
+
+/// Convert line endings according to the given policy
+pub fn convert_line_endings(content: &str, policy: LineEnding) -> String {
+    match policy {
+        LineEnding::Preserve => content.to_string(),
+        LineEnding::Lf => content.replace("\r\n", "\n"),
+        LineEnding::Crlf => content.replace("\r\n", "\n").replace('\n', "\r\n"),
+    }
+}
+
+/// Make merged content consistent with its parts
+///
+/// If any part already uses CRLF, the LF-only separators added while merging are converted too.
+pub fn unify_line_endings(content: &str) -> String {
+    if content.contains("\r\n") {
+        convert_line_endings(content, LineEnding::Crlf)
     } else {
         content.to_string()
     }
 }
 
-/// Normalize content for comparison (trim trailing whitespace, normalize line endings)
+/// Check whether two contents differ only by a UTF-8 BOM or line endings
+pub fn differs_only_in_encoding(a: &str, b: &str) -> bool {
+    a != b
+        && convert_line_endings(strip_bom(a), LineEnding::Lf)
+            == convert_line_endings(strip_bom(b), LineEnding::Lf)
+}
+
+/// Match a path against a glob pattern (globset syntax)
+///
+/// `*` and `?` stay within one path component, `**` spans directories, `[...]` and
+/// `{a,b}` work as usual and `\` escapes a special character. A pattern without `/`
+/// is matched against the file name only. An invalid pattern matches nothing;
+/// patterns are checked up front with `glob_error`.
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let target = if pattern.contains('/') {
+        path
+    } else {
+        path.rsplit('/').next().unwrap_or(path)
+    };
+    build_glob(pattern).is_ok_and(|glob| glob.compile_matcher().is_match(target))
+}
+
+/// Why a glob pattern is invalid (e.g., an unclosed `[` or `{`)
+pub fn glob_error(pattern: &str) -> Option<String> {
+    build_glob(pattern)
+        .err()
+        .map(|e| format!("invalid glob '{}': {}", pattern, e.kind()))
+}
+
+/// Whether a preset path entry is a glob rather than a plain path
+pub fn is_glob(entry: &str) -> bool {
+    entry.contains(['*', '?', '[', '{'])
+}
+
+fn build_glob(pattern: &str) -> std::result::Result<globset::Glob, globset::Error> {
+    globset::GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .backslash_escape(true)
+        .build()
+}
+
+/// Markdown as tools expect it: front matter at the very start, exactly one trailing newline
+///
+/// Empty content stays empty.
+pub fn normalize_markdown(content: &str) -> String {
+    let body = if has_frontmatter(content) {
+        content.trim_start()
+    } else {
+        content
+    };
+    let body = body.trim_end();
+    if body.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", body)
+    }
+}
+
+/// Normalize content for comparison (strip BOM, trim trailing whitespace, normalize line endings)
 pub fn normalize_content(content: &str) -> String {
-    content
+    strip_bom(content)
         .lines()
         .map(|line| line.trim_end())
         .collect::<Vec<_>>()
@@ -106,8 +205,204 @@ pub fn normalize_content(content: &str) -> String {
         .to_string()
 }
 
+/// Memory files as `(id, "<heading> name\n\ncontent")` blocks for `upsert_blocks`
+///
+/// The id hashes the file name only, so an edited file keeps its block.
+pub fn memory_blocks(files: &[PresetFile], heading: &str) -> Vec<(String, String)> {
+    files
+        .iter()
+        .map(|file| {
+            let name = file.section_path("memory").replace(".md", "");
+            let body = format!("{} {}\n\n{}", heading, name, file.content);
+            (memory_block_id(&name), body)
+        })
+        .collect()
+}
+
+/// Short, stable id of a memory file for its block markers
+fn memory_block_id(name: &str) -> String {
+    content_hash(name.as_bytes())[..12].to_string()
+}
+
+/// The unmarked memory text earlier versions appended: `header`, a blank line, then
+/// the files separated by `---`
+pub fn legacy_memory_block(files: &[PresetFile], header: &str, heading: &str) -> String {
+    let mut content = format!("{}\n", header);
+    for (i, (_, body)) in memory_blocks(files, heading).iter().enumerate() {
+        if i > 0 {
+            content.push_str("\n\n---\n\n");
+        }
+        content.push_str(body);
+    }
+    content
+}
+
+/// Markers around one preset file's text inside a file shared with the user
+///
+/// The begin marker names the preset owning the block (`from:<preset>`), so presets
+/// writing to the same file only touch their own blocks.
+fn block_markers(id: &str, owner: &str) -> (String, String) {
+    let begin = if owner.is_empty() {
+        format!("<!-- aidot:memory:{} -->", id)
+    } else {
+        format!("<!-- aidot:memory:{} from:{} -->", id, owner)
+    };
+    (begin, format!("<!-- /aidot:memory:{} -->", id))
+}
+
+/// Preset name as it appears in a begin marker (no spaces or `-->`)
+fn block_owner(preset: &str) -> String {
+    preset
+        .chars()
+        .filter(|c| c.is_alphanumeric() || "-_.+/@".contains(*c))
+        .collect()
+}
+
+/// A marked block found in a shared file
+struct MarkedBlock {
+    id: String,
+    /// Preset named in the begin marker (empty for blocks written before owners were recorded)
+    owner: String,
+    /// Byte range from the begin marker through the end marker
+    range: std::ops::Range<usize>,
+}
+
+fn marked_blocks(content: &str) -> Vec<MarkedBlock> {
+    const BEGIN: &str = "<!-- aidot:memory:";
+    let mut blocks = Vec::new();
+    let mut from = 0;
+    while let Some(offset) = content[from..].find(BEGIN) {
+        let start = from + offset;
+        let Some(close) = content[start..].find("-->") else {
+            break;
+        };
+        let mut fields = content[start + BEGIN.len()..start + close].split_whitespace();
+        let id = fields.next().unwrap_or_default().to_string();
+        let owner = fields
+            .next()
+            .and_then(|field| field.strip_prefix("from:"))
+            .unwrap_or_default()
+            .to_string();
+        let end = format!("<!-- /aidot:memory:{} -->", id);
+        let Some(end_offset) = content[start..].find(&end) else {
+            from = start + close;
+            continue;
+        };
+        let range = start..start + end_offset + end.len();
+        from = range.end;
+        blocks.push(MarkedBlock { id, owner, range });
+    }
+    blocks
+}
+
+/// Write marked blocks into a file shared with the user (.cursorrules, copilot-instructions.md)
+///
+/// Each `(id, body)` sits between `<!-- aidot:memory:<id> from:<owner> -->` and
+/// `<!-- /aidot:memory:<id> -->`. A block already present is replaced in place, so
+/// re-applying a changed preset updates its text instead of appending another copy,
+/// and blocks of `owner` whose id is no longer in `blocks` (memory file deleted or
+/// renamed) are removed. Blocks without an owner (from earlier versions) are taken
+/// over when re-applied but never removed, since another preset may have written
+/// them. New blocks go after `header`, which is added once. `legacy` (the unmarked
+/// text earlier versions appended) is dropped in favor of the marked blocks when found.
+pub fn upsert_blocks(
+    base: &str,
+    header: &str,
+    owner: &str,
+    blocks: &[(String, String)],
+    legacy: Option<&str>,
+) -> String {
+    let crlf = base.contains("\r\n");
+    let mut content = convert_line_endings(base, LineEnding::Lf);
+    if let Some(legacy) = legacy.filter(|legacy| !legacy.is_empty()) {
+        content = content.replacen(legacy, "", 1);
+    }
+    let owner = block_owner(owner);
+
+    // Stale blocks of this preset, back to front so earlier ranges stay valid
+    for stale in marked_blocks(&content)
+        .into_iter()
+        .rev()
+        .filter(|b| b.owner == owner && !blocks.iter().any(|(id, _)| *id == b.id))
+    {
+        let mut range = stale.range;
+        // Also drop the blank line put before the block when it was appended
+        if content[range.end..].starts_with('\n') && content[..range.start].ends_with('\n') {
+            range = range.start - 1..range.end + 1;
+        }
+        content.replace_range(range, "");
+    }
+
+    let mut appended = String::new();
+    for (id, body) in blocks {
+        let (begin, end) = block_markers(id, &owner);
+        let block = format!("{}\n{}\n{}", begin, body.trim_end(), end);
+        let found = marked_blocks(&content);
+        let range = found
+            .iter()
+            .find(|b| b.id == *id && b.owner == owner)
+            .or_else(|| found.iter().find(|b| b.id == *id && b.owner.is_empty()))
+            .map(|b| b.range.clone());
+        match range {
+            Some(range) => content.replace_range(range, &block),
+            None => {
+                appended.push('\n');
+                appended.push_str(&block);
+                appended.push('\n');
+            }
+        }
+    }
+    if !appended.is_empty() {
+        if !content.contains(header) {
+            content.push_str(header);
+        }
+        content.push_str(&appended);
+    }
+
+    if crlf {
+        convert_line_endings(&content, LineEnding::Crlf)
+    } else {
+        content
+    }
+}
+
+/// Whether an existing file already matches preset content
+///
+/// The single comparison rule shared by scan, diff and apply.
+pub fn content_matches(existing: &str, preset: &str) -> bool {
+    normalize_content(existing) == normalize_content(preset)
+}
+
+/// Whether writing preset content would leave an existing file as it is
+///
+/// Like `content_matches`, except that re-encoding (removing a BOM, changing line
+/// endings) counts as a change: it rewrites the user's file, so it is reported
+/// and goes through the conflict handling like any other update.
+pub fn is_unchanged(existing: &str, preset: &str) -> bool {
+    content_matches(existing, preset) && !differs_only_in_encoding(existing, preset)
+}
+
 /// Check if a command is available on the system
+///
+/// Results are cached for the lifetime of the process, so repeated detection
+/// (detect, status, pull) spawns `which`/`where` at most once per command.
 pub fn is_command_available(cmd_name: &str) -> bool {
+    static CACHE: OnceLock<Mutex<HashMap<String, bool>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+
+    if let Some(&available) = cache.lock().unwrap().get(cmd_name) {
+        return available;
+    }
+    let available = lookup_command(cmd_name);
+    cache
+        .lock()
+        .unwrap()
+        .insert(cmd_name.to_string(), available);
+    available
+}
+
+/// Look up a command in PATH via `which` (or `where` on Windows)
+fn lookup_command(cmd_name: &str) -> bool {
     #[cfg(target_os = "windows")]
     let check = std::process::Command::new("where").arg(cmd_name).output();
     #[cfg(not(target_os = "windows"))]
@@ -115,10 +410,133 @@ pub fn is_command_available(cmd_name: &str) -> bool {
     check.map(|o| o.status.success()).unwrap_or(false)
 }
 
+/// Why a preset path could resolve outside the directory it is joined to (None if safe)
+///
+/// Both `/` and `\\` count as separators, so a path that is harmless on one OS cannot
+/// escape on another.
+pub fn unsafe_path_reason(path: &str) -> Option<&'static str> {
+    if path.is_empty() {
+        return Some("empty path");
+    }
+    if path.starts_with(['/', '\\']) || Path::new(path).is_absolute() {
+        return Some("absolute path");
+    }
+    let mut parts = path.split(['/', '\\']);
+    if parts
+        .clone()
+        .next()
+        .is_some_and(|first| first.contains(':'))
+    {
+        return Some("drive or scheme prefix");
+    }
+    if parts.any(|part| part == "..") {
+        return Some("'..' component");
+    }
+    None
+}
+
+/// Whether `path` stays inside `root` once symlinks are followed
+///
+/// The deepest existing ancestor of `path` (or `path` itself) is canonicalized, so a
+/// symlinked file or directory pointing elsewhere counts as outside. The part that does
+/// not exist yet must be plain names.
+pub fn resolves_inside(root: &Path, path: &Path) -> bool {
+    let Ok(root) = root.canonicalize() else {
+        return false;
+    };
+    let mut existing = path;
+    while !existing.exists() && existing.symlink_metadata().is_err() {
+        match existing.parent() {
+            Some(parent) => existing = parent,
+            None => return false,
+        }
+    }
+    let Ok(resolved) = existing.canonicalize() else {
+        // A dangling symlink: its target is unknown
+        return false;
+    };
+    let rest_is_plain = path
+        .strip_prefix(existing)
+        .map(|rest| rest.components().all(|c| matches!(c, Component::Normal(_))))
+        .unwrap_or(false);
+    rest_is_plain && resolved.starts_with(&root)
+}
+
+/// Levenshtein edit distance between two strings (insertions, deletions, substitutions)
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("claude", "claude"), 0);
+        assert_eq!(edit_distance("cluade", "claude"), 2);
+        assert_eq!(edit_distance("cursr", "cursor"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_unsafe_path_reason() {
+        assert_eq!(unsafe_path_reason("rules/style.md"), None);
+        assert_eq!(unsafe_path_reason("rules/..style.md"), None);
+        assert_eq!(unsafe_path_reason("../outside.md"), Some("'..' component"));
+        assert_eq!(
+            unsafe_path_reason("rules\\..\\..\\x.md"),
+            Some("'..' component")
+        );
+        assert_eq!(unsafe_path_reason("/etc/passwd"), Some("absolute path"));
+        assert_eq!(unsafe_path_reason("\\\\server\\x"), Some("absolute path"));
+        assert_eq!(
+            unsafe_path_reason("C:/x.md"),
+            Some("drive or scheme prefix")
+        );
+        assert_eq!(unsafe_path_reason(""), Some("empty path"));
+    }
+
+    #[test]
+    fn test_resolves_inside() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path().join("project");
+        std::fs::create_dir_all(root.join(".claude")).unwrap();
+
+        assert!(resolves_inside(&root, &root.join(".claude/rules/new.md")));
+        assert!(!resolves_inside(&root, &root.join("../outside.md")));
+        assert!(!resolves_inside(&root, &root.join(".claude/x/../../../y")));
+
+        #[cfg(unix)]
+        {
+            let outside = temp_dir.path().join("outside");
+            std::fs::create_dir_all(&outside).unwrap();
+            std::os::unix::fs::symlink(&outside, root.join("linked")).unwrap();
+            assert!(!resolves_inside(&root, &root.join("linked/file.md")));
+            std::os::unix::fs::symlink(root.join(".claude"), root.join("inner")).unwrap();
+            assert!(resolves_inside(&root, &root.join("inner/file.md")));
+        }
+    }
+
+    #[test]
+    fn test_is_command_available_cached() {
+        let missing = "aidot-no-such-command-for-test";
+        assert!(!is_command_available(missing));
+        // Second lookup hits the cache and agrees with the first
+        assert!(!is_command_available(missing));
+    }
+
     #[test]
     fn test_has_frontmatter_valid() {
         assert!(has_frontmatter("---\ntitle: test\n---\n# Content"));
@@ -144,6 +562,19 @@ mod tests {
         assert!(has_frontmatter("---\r\ntitle: test\r\n---\r\n# Content"));
     }
 
+    #[test]
+    fn test_normalize_markdown() {
+        assert_eq!(normalize_markdown("# Title"), "# Title\n");
+        assert_eq!(normalize_markdown("# Title\n\n\n"), "# Title\n");
+        assert_eq!(
+            normalize_markdown("  \n---\nname: a\n---\nBody"),
+            "---\nname: a\n---\nBody\n"
+        );
+        // Leading blank lines are only dropped in front of front matter
+        assert_eq!(normalize_markdown("\n# Title\n"), "\n# Title\n");
+        assert_eq!(normalize_markdown(" \n"), "");
+    }
+
     #[test]
     fn test_strip_section_prefix_unix() {
         assert_eq!(
@@ -161,15 +592,16 @@ mod tests {
     }
 
     #[test]
-    fn test_strip_section_prefix_windows() {
+    fn test_strip_section_prefix_only_leading() {
         assert_eq!(
-            strip_section_prefix("rules\\code-style.md", "rules"),
-            "code-style.md"
+            strip_section_prefix("skills/rules/SKILL.md", "skills"),
+            "rules/SKILL.md"
         );
         assert_eq!(
-            strip_section_prefix("commands\\build.md", "commands"),
-            "build.md"
+            strip_section_prefix("rules/sub/rules/a.md", "rules"),
+            "sub/rules/a.md"
         );
+        assert_eq!(strip_section_prefix("other/a.md", "rules"), "other/a.md");
     }
 
     #[test]
@@ -183,6 +615,11 @@ mod tests {
             "code-style.instructions.md"
         );
         assert_eq!(add_suffix_before_ext("agent.md", "agent"), "agent.agent.md");
+        // Already suffixed names are kept
+        assert_eq!(
+            add_suffix_before_ext("build.prompt.md", "prompt"),
+            "build.prompt.md"
+        );
     }
 
     #[test]
@@ -206,6 +643,27 @@ mod tests {
         assert!(result.contains("# Content"));
     }
 
+    #[test]
+    fn test_convert_frontmatter_key_keeps_layout() {
+        assert_eq!(
+            convert_frontmatter_key("---\nglobs: \"*.rs\"\n---\n# Content\n", "globs", "applyTo"),
+            "---\napplyTo: \"*.rs\"\n---\n# Content\n"
+        );
+        // CRLF files stay CRLF, and files without the key are untouched
+        let crlf = "---\r\ndescription: d\r\nglobs: *.rs\r\n---\r\nbody\r\n";
+        assert_eq!(
+            convert_frontmatter_key(crlf, "globs", "applyTo"),
+            crlf.replace("globs", "applyTo")
+        );
+        let no_key = "---\ndescription: test\n---\n# Content";
+        assert_eq!(convert_frontmatter_key(no_key, "globs", "applyTo"), no_key);
+        // Only the front matter is converted
+        assert_eq!(
+            convert_frontmatter_key("---\nglobs: a\n---\nglobs: b\n", "globs", "applyTo"),
+            "---\napplyTo: a\n---\nglobs: b\n"
+        );
+    }
+
     #[test]
     fn test_convert_frontmatter_key_no_frontmatter() {
         let input = "# Just content\nNo frontmatter here.";
@@ -230,6 +688,93 @@ mod tests {
         assert!(!result.contains("globs"));
     }
 
+    #[test]
+    fn test_upsert_blocks() {
+        let header = "\n\n# Project Memory\n";
+        let blocks = |body: &str| vec![("ctx".to_string(), body.to_string())];
+
+        let applied = upsert_blocks("# Rules\n", header, "", &blocks("## ctx\n\nOne\n"), None);
+        assert_eq!(
+            applied,
+            "# Rules\n\n\n# Project Memory\n\n<!-- aidot:memory:ctx -->\n## ctx\n\nOne\n<!-- /aidot:memory:ctx -->\n"
+        );
+        // Same preset again: nothing changes
+        assert_eq!(
+            upsert_blocks(&applied, header, "", &blocks("## ctx\n\nOne\n"), None),
+            applied
+        );
+        // Changed preset: replaced in place, user edits around it are kept
+        let edited = format!("{}\nMy notes\n", applied);
+        let updated = upsert_blocks(&edited, header, "", &blocks("## ctx\n\nTwo"), None);
+        assert!(updated.contains("\n## ctx\n\nTwo\n<!-- /aidot:memory:ctx -->"));
+        assert!(!updated.contains("One"));
+        assert!(updated.ends_with("My notes\n"));
+        assert_eq!(updated.matches("# Project Memory").count(), 1);
+        // CRLF files stay CRLF
+        let crlf = applied.replace('\n', "\r\n");
+        assert_eq!(
+            upsert_blocks(&crlf, header, "", &blocks("## ctx\n\nOne\n"), None),
+            crlf
+        );
+        // Unmarked text from earlier versions is replaced by the marked block
+        let legacy = "\n\n# Project Memory\n\n## ctx\n\nOne\n";
+        let migrated = upsert_blocks(
+            &format!("# Rules\n{}", legacy),
+            header,
+            "",
+            &blocks("## ctx\n\nOne\n"),
+            Some(legacy),
+        );
+        assert_eq!(migrated, applied);
+    }
+
+    #[test]
+    fn test_upsert_blocks_removes_stale_blocks_of_its_preset() {
+        let header = "\n\n# Project Memory\n";
+        let block = |id: &str| (id.to_string(), format!("## {}\n\nText", id));
+
+        let team = upsert_blocks("# Rules\n", header, "team", &[block("a"), block("b")], None);
+        let both = upsert_blocks(&team, header, "personal", &[block("a")], None);
+        // a.md renamed to c.md and b.md deleted in the team preset
+        let renamed = upsert_blocks(&both, header, "team", &[block("c")], None);
+        assert_eq!(
+            renamed,
+            "# Rules\n\n\n# Project Memory\n\n<!-- aidot:memory:a from:personal -->\n## a\n\nText\n<!-- /aidot:memory:a -->\n\n<!-- aidot:memory:c from:team -->\n## c\n\nText\n<!-- /aidot:memory:c -->\n"
+        );
+        assert_eq!(
+            upsert_blocks(&renamed, header, "team", &[block("c")], None),
+            renamed
+        );
+
+        // Unowned blocks from earlier versions are taken over, never removed
+        let old = upsert_blocks("# Rules\n", header, "", &[block("a"), block("b")], None);
+        let adopted = upsert_blocks(&old, header, "team", &[block("a")], None);
+        assert!(adopted.contains("<!-- aidot:memory:a from:team -->"));
+        assert!(adopted.contains("<!-- aidot:memory:b -->"));
+    }
+
+    #[test]
+    fn test_memory_blocks_keep_their_id_when_edited() {
+        let file = |content: &str| PresetFile {
+            relative_path: "memory/team.md".to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        };
+        let before = memory_blocks(&[file("Old\n")], "##");
+        let after = memory_blocks(&[file("New\n")], "##");
+        assert_eq!(before[0].0, after[0].0);
+        assert_eq!(before[0].0.len(), 12);
+        assert_eq!(after[0].1, "## team\n\nNew\n");
+
+        // User text outside the markers survives re-applying the edited file
+        let header = "\n\n# Project Memory\n";
+        let applied = upsert_blocks("My rules\n", header, "", &before, None);
+        let updated = upsert_blocks(&format!("{}Extra\n", applied), header, "", &after, None);
+        assert!(updated.starts_with("My rules\n"));
+        assert!(updated.ends_with("Extra\n"));
+        assert!(updated.contains("New") && !updated.contains("Old"));
+    }
+
     #[test]
     fn test_normalize_content() {
         // Trailing whitespace normalization
@@ -247,5 +792,65 @@ mod tests {
             normalize_content("hello\r\nworld"),
             normalize_content("hello\nworld")
         );
+        // UTF-8 BOM
+        assert_eq!(
+            normalize_content("\u{feff}hello\nworld"),
+            normalize_content("hello\nworld")
+        );
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("rules/security-*.md", "rules/security-web.md"));
+        assert!(!glob_match("rules/security-*.md", "rules/style.md"));
+        assert!(!glob_match("rules/*.md", "rules/nested/style.md"));
+        assert!(glob_match("rules/**/*.md", "rules/nested/style.md"));
+        assert!(glob_match("rules/**/*.md", "rules/style.md"));
+        assert!(glob_match("skills/**", "skills/api/client.ts"));
+        assert!(glob_match("skills/**/*.ts", "skills/api/client.ts"));
+        assert!(glob_match("commands/b?ild.md", "commands/build.md"));
+        // No slash: match file name only
+        assert!(glob_match("*.md", "rules/style.md"));
+        assert!(!glob_match("*.json", "rules/style.md"));
+        // Character classes, alternatives and escapes
+        assert!(glob_match("rules/[a-c]*.md", "rules/api.md"));
+        assert!(!glob_match("rules/[a-c]*.md", "rules/style.md"));
+        assert!(glob_match("*.{md,mdc}", "rules/style.mdc"));
+        assert!(glob_match("rules/\\[draft\\].md", "rules/[draft].md"));
+        assert!(!glob_match("rules/\\[draft\\].md", "rules/d.md"));
+        // Invalid patterns match nothing and are reported
+        assert!(!glob_match("rules/[a-", "rules/a"));
+        assert!(glob_error("rules/[a-").is_some());
+        assert!(glob_error("rules/{a,b}.md").is_none());
+    }
+
+    #[test]
+    fn test_strip_bom() {
+        assert_eq!(strip_bom("\u{feff}# Title"), "# Title");
+        assert_eq!(strip_bom("# Title"), "# Title");
+    }
+
+    #[test]
+    fn test_convert_line_endings() {
+        let mixed = "a\r\nb\nc";
+        assert_eq!(convert_line_endings(mixed, LineEnding::Lf), "a\nb\nc");
+        assert_eq!(convert_line_endings(mixed, LineEnding::Crlf), "a\r\nb\r\nc");
+        assert_eq!(convert_line_endings(mixed, LineEnding::Preserve), mixed);
+    }
+
+    #[test]
+    fn test_differs_only_in_encoding() {
+        assert!(differs_only_in_encoding("\u{feff}a\r\nb", "a\nb"));
+        assert!(!differs_only_in_encoding("a\nb", "a\nb"));
+        assert!(!differs_only_in_encoding("a  \nb", "a\nb"));
+    }
+
+    #[test]
+    fn test_unify_line_endings() {
+        assert_eq!(
+            unify_line_endings("a\r\nb\n\n---\n"),
+            "a\r\nb\r\n\r\n---\r\n"
+        );
+        assert_eq!(unify_line_endings("a\nb"), "a\nb");
     }
 }
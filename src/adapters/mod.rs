@@ -1,3 +1,4 @@
+pub mod amazonq;
 pub mod claude_code;
 pub mod common;
 pub mod conflict;
@@ -7,7 +8,11 @@ pub mod detector;
 pub mod helpers;
 pub mod traits;
 
-pub use conflict::{write_with_conflict, ConflictMode};
-pub use detector::{all_tools, detect_tools};
-pub use helpers::normalize_content;
+pub use common::write_preset_file;
+pub use conflict::ConflictMode;
+pub use detector::{
+    all_tools, global_tools, resolve_tool_filter, select_tools, sort_tools, tool_choices,
+    GLOBAL_TOOL_NAMES,
+};
+pub use helpers::{content_matches, is_unchanged};
 pub use traits::ToolAdapter;
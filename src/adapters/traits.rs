@@ -1,15 +1,132 @@
-use super::conflict::ConflictMode;
-use super::helpers::normalize_content;
+use super::conflict::{ApplyContext, ConflictMode};
+use super::helpers::{
+    convert_line_endings, glob_match, is_unchanged, normalize_markdown, strip_section_prefix,
+};
 use crate::error::Result;
-use std::path::Path;
+use crate::preset::condition::{
+    parse_frontmatter_condition, strip_frontmatter_condition, Condition,
+};
+use crate::preset::config::{LineEnding, MergeStrategy, Normalize};
+use crate::secret::{self, ENCRYPTED_SUFFIX};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
 
 /// Represents a preset file to be converted
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PresetFile {
     /// Relative path from preset root (e.g., "rules/code-style.md")
     pub relative_path: String,
     /// Full content of the file
     pub content: String,
+    /// Whether the source file has an executable bit (always false on Windows)
+    pub executable: bool,
+    /// Section directory the file was read from (e.g., "guidelines" for
+    /// `[rules] directory = "guidelines/"`); empty means the section name
+    pub section_dir: String,
+}
+
+impl PresetFile {
+    /// Path relative to its section directory (e.g., "guidelines/style.md" → "style.md")
+    pub fn section_path(&self, section: &str) -> String {
+        let dir = if self.section_dir.is_empty() {
+            section
+        } else {
+            &self.section_dir
+        };
+        strip_section_prefix(&self.relative_path, dir)
+    }
+}
+
+/// Detection result with the evidence behind it
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectionInfo {
+    pub detected: bool,
+    /// e.g., "detected via .claude/ directory" or "no .claude/ directory or claude CLI in PATH"
+    pub reason: String,
+}
+
+impl DetectionInfo {
+    pub fn found(reason: impl Into<String>) -> Self {
+        Self {
+            detected: true,
+            reason: reason.into(),
+        }
+    }
+
+    pub fn not_found(reason: impl Into<String>) -> Self {
+        Self {
+            detected: false,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// How to make a running tool pick up newly applied files (shown after pull)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReloadHint {
+    /// What the user has to do, e.g., "start a new session"
+    pub message: &'static str,
+    /// Program and arguments that reopen the project, run by `pull --reload` with the
+    /// project directory appended (only when the CLI is installed)
+    pub command: Option<&'static [&'static str]>,
+}
+
+/// Paths (relative to the project root) that adapters detect tools by
+const PROJECT_MARKERS: &[&str] = &[
+    ".claude",
+    ".cursor",
+    ".cursorrules",
+    ".github",
+    ".github/copilot-instructions.md",
+    ".github/instructions",
+    ".vscode",
+    ".amazonq",
+];
+
+/// Tool markers present in a project, scanned once and shared by every adapter
+///
+/// The project root (and `.github/`, when present) is listed a single time instead
+/// of each adapter statting its own paths, which adds up in large monorepos.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectMarkers {
+    found: HashSet<&'static str>,
+}
+
+impl ProjectMarkers {
+    pub fn scan(project_dir: &Path) -> Self {
+        let root = entry_names(project_dir);
+        let github = if root.contains(".github") {
+            entry_names(&project_dir.join(".github"))
+        } else {
+            HashSet::new()
+        };
+        let found = PROJECT_MARKERS
+            .iter()
+            .copied()
+            .filter(|marker| match marker.split_once('/') {
+                Some((_, name)) => github.contains(name),
+                None => root.contains(*marker),
+            })
+            .collect();
+        Self { found }
+    }
+
+    /// Whether a marker path (e.g., ".claude" or ".github/instructions") exists
+    pub fn has(&self, marker: &str) -> bool {
+        self.found.contains(marker)
+    }
+}
+
+/// Names of the entries directly in a directory (empty if it cannot be read)
+fn entry_names(dir: &Path) -> HashSet<String> {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 /// Trait for LLM tool adapters
@@ -17,8 +134,32 @@ pub trait ToolAdapter {
     /// Get the name of the tool (e.g., "Claude Code", "Cursor")
     fn name(&self) -> &str;
 
+    /// Detect if this tool is available/installed, and why
+    ///
+    /// `markers` is the shared scan of the directory this adapter is rooted at.
+    fn detect_with_reason(&self, markers: &ProjectMarkers) -> DetectionInfo;
+
     /// Detect if this tool is available/installed
-    fn detect(&self) -> bool;
+    fn detect(&self, markers: &ProjectMarkers) -> bool {
+        self.detect_with_reason(markers).detected
+    }
+
+    /// User-level configuration directory of this tool (e.g., ~/.claude), if it has one
+    fn global_config_dir(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Preset sections this tool has no equivalent for, with the reason
+    ///
+    /// Files in these sections are skipped on scan and apply.
+    fn unsupported_sections(&self) -> &[(&'static str, &'static str)] {
+        &[]
+    }
+
+    /// What to do after applying so the tool loads the changes (default: nothing)
+    fn reload_hint(&self) -> Option<ReloadHint> {
+        None
+    }
 
     /// Scan for changes without applying them
     /// Returns a list of pending changes with conflict information
@@ -33,17 +174,41 @@ pub trait ToolAdapter {
     ///   - "commands" -> Vec<PresetFile>
     ///   - etc.
     /// * `target_dir` - Project directory where files should be written
-    /// * `conflict_mode` - How to handle existing files
+    /// * `conflict_mode` - How to handle existing files (shared across root and all tools)
+    /// * `ctx` - Settings and simulated writes of this apply (dry-run, root check, symlinks)
     fn apply(
         &self,
         preset_files: &PresetFiles,
         target_dir: &Path,
         conflict_mode: &mut ConflictMode,
+        ctx: &ApplyContext,
     ) -> Result<ApplyResult>;
 }
 
+/// Preset section directory names, in `PresetFiles` field order
+pub const SECTIONS: [&str; 9] = [
+    "rules", "memory", "commands", "mcp", "hooks", "agents", "skills", "settings", "root",
+];
+
+/// How each section is combined when several presets are composed (`pull --compose`)
+///
+/// Concat accumulates the files of all presets in order; a later file with the same
+/// path replaces the earlier one. Replace keeps only the section of the last preset
+/// that has files in it, so settings come entirely from the last preset.
+pub const COMPOSE_STRATEGIES: [(&str, MergeStrategy); 9] = [
+    ("rules", MergeStrategy::Concat),
+    ("memory", MergeStrategy::Concat),
+    ("commands", MergeStrategy::Concat),
+    ("mcp", MergeStrategy::Concat),
+    ("hooks", MergeStrategy::Concat),
+    ("agents", MergeStrategy::Concat),
+    ("skills", MergeStrategy::Concat),
+    ("settings", MergeStrategy::Replace),
+    ("root", MergeStrategy::Concat),
+];
+
 /// Preset files organized by section
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct PresetFiles {
     pub rules: Vec<PresetFile>,
     pub memory: Vec<PresetFile>,
@@ -54,6 +219,255 @@ pub struct PresetFiles {
     pub skills: Vec<PresetFile>,
     pub settings: Vec<PresetFile>,
     pub root: Vec<PresetFile>,
+    /// How hooks for the same event are combined ([hooks] merge)
+    pub hooks_merge: MergeStrategy,
+    /// Name of the preset (`metadata.name`), owner of the blocks it writes to shared files
+    #[serde(skip)]
+    pub preset: String,
+}
+
+impl PresetFiles {
+    /// All sections with their preset directory names
+    fn sections_mut(&mut self) -> [(&'static str, &mut Vec<PresetFile>); 9] {
+        [
+            ("rules", &mut self.rules),
+            ("memory", &mut self.memory),
+            ("commands", &mut self.commands),
+            ("mcp", &mut self.mcp),
+            ("hooks", &mut self.hooks),
+            ("agents", &mut self.agents),
+            ("skills", &mut self.skills),
+            ("settings", &mut self.settings),
+            ("root", &mut self.root),
+        ]
+    }
+
+    fn into_sections(self) -> [(&'static str, Vec<PresetFile>); 9] {
+        [
+            ("rules", self.rules),
+            ("memory", self.memory),
+            ("commands", self.commands),
+            ("mcp", self.mcp),
+            ("hooks", self.hooks),
+            ("agents", self.agents),
+            ("skills", self.skills),
+            ("settings", self.settings),
+            ("root", self.root),
+        ]
+    }
+
+    /// Compose a later preset on top of this one (see `COMPOSE_STRATEGIES`)
+    pub fn compose(&mut self, later: PresetFiles) {
+        if !later.hooks.is_empty() {
+            self.hooks_merge = later.hooks_merge;
+        }
+        self.preset = if self.preset.is_empty() {
+            later.preset.clone()
+        } else {
+            format!("{}+{}", self.preset, later.preset)
+        };
+        let later_sections = later.into_sections();
+        for ((section, files), (_, later_files)) in
+            self.sections_mut().into_iter().zip(later_sections)
+        {
+            let strategy = COMPOSE_STRATEGIES
+                .iter()
+                .find(|(name, _)| *name == section)
+                .map(|(_, strategy)| *strategy)
+                .unwrap_or_default();
+            match strategy {
+                MergeStrategy::Replace => {
+                    if !later_files.is_empty() {
+                        *files = later_files;
+                    }
+                }
+                MergeStrategy::Concat => {
+                    for file in later_files {
+                        // Presets may use different directories for a section
+                        let path = file.section_path(section);
+                        match files.iter_mut().find(|f| f.section_path(section) == path) {
+                            Some(existing) => *existing = file,
+                            None => files.push(file),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Files of a section by its preset directory name (e.g., "rules")
+    pub fn section(&self, name: &str) -> &[PresetFile] {
+        match name {
+            "rules" => &self.rules,
+            "memory" => &self.memory,
+            "commands" => &self.commands,
+            "mcp" => &self.mcp,
+            "hooks" => &self.hooks,
+            "agents" => &self.agents,
+            "skills" => &self.skills,
+            "settings" => &self.settings,
+            "root" => &self.root,
+            _ => &[],
+        }
+    }
+
+    /// Apply a line ending policy to the content of every preset file
+    pub fn apply_line_endings(&mut self, policy: LineEnding) {
+        if policy == LineEnding::Preserve {
+            return;
+        }
+        for (_, files) in self.sections_mut() {
+            for file in files.iter_mut() {
+                file.content = convert_line_endings(&file.content, policy);
+            }
+        }
+    }
+
+    /// Normalize the format of preset files before writing (`normalize = "all"`)
+    ///
+    /// Markdown files (.md, .mdc) of every section get their front matter moved to the
+    /// very start and exactly one trailing newline. Root files are project files of any
+    /// kind, so they are left as they are.
+    pub fn apply_normalize(&mut self, policy: Normalize) {
+        if policy != Normalize::All {
+            return;
+        }
+        for (section, files) in self.sections_mut() {
+            if section == "root" {
+                continue;
+            }
+            for file in files.iter_mut() {
+                if file.relative_path.ends_with(".md") || file.relative_path.ends_with(".mdc") {
+                    file.content = normalize_markdown(&file.content);
+                }
+            }
+        }
+    }
+
+    /// Keep only files whose path matches at least one glob (e.g., "rules/security-*.md")
+    ///
+    /// Root files are matched as `root/<path>`. Returns the patterns that matched nothing.
+    pub fn retain_matching(&mut self, patterns: &[String]) -> Vec<String> {
+        let mut matched = vec![false; patterns.len()];
+        for (section, files) in self.sections_mut() {
+            files.retain(|file| {
+                let path = if section == "root" {
+                    format!("root/{}", file.relative_path)
+                } else {
+                    file.relative_path.clone()
+                };
+                let mut keep = false;
+                for (i, pattern) in patterns.iter().enumerate() {
+                    if glob_match(pattern, &path) {
+                        matched[i] = true;
+                        keep = true;
+                    }
+                }
+                keep
+            });
+        }
+        patterns
+            .iter()
+            .zip(matched)
+            .filter(|(_, matched)| !matched)
+            .map(|(pattern, _)| pattern.clone())
+            .collect()
+    }
+
+    /// Whether any preset file is encrypted (`*.enc`)
+    pub fn has_encrypted(&self) -> bool {
+        [
+            &self.rules,
+            &self.memory,
+            &self.commands,
+            &self.mcp,
+            &self.hooks,
+            &self.agents,
+            &self.skills,
+            &self.settings,
+            &self.root,
+        ]
+        .iter()
+        .flat_map(|files| files.iter())
+        .any(|file| file.relative_path.ends_with(ENCRYPTED_SUFFIX))
+    }
+
+    /// Decrypt `*.enc` files in place (the suffix is dropped from the path)
+    ///
+    /// Files that cannot be decrypted (no key, wrong key, modified file) are removed and
+    /// returned with the reason, so the rest of the preset still applies.
+    pub fn decrypt_secrets(&mut self, key: Option<&str>) -> Vec<(String, String)> {
+        let mut skipped = Vec::new();
+        for (_, files) in self.sections_mut() {
+            files.retain_mut(|file| {
+                let Some(path) = file.relative_path.strip_suffix(ENCRYPTED_SUFFIX) else {
+                    return true;
+                };
+                let decrypted = match key {
+                    Some(key) => secret::decrypt(&file.content, key).map_err(|e| e.to_string()),
+                    None => Err(format!(
+                        "no secret key ({} is not set and ~/.aidot/secret.key is missing)",
+                        secret::KEY_ENV
+                    )),
+                };
+                match decrypted {
+                    Ok(content) => {
+                        file.relative_path = path.to_string();
+                        file.content = content;
+                        true
+                    }
+                    Err(reason) => {
+                        skipped.push((file.relative_path.clone(), reason));
+                        false
+                    }
+                }
+            });
+        }
+        skipped
+    }
+
+    /// Drop files whose `when` condition (front matter or config glob) is not met
+    ///
+    /// Returns the skipped paths with the reason. Root files are matched as `root/<path>`.
+    /// The `when:` key is removed from the front matter of the files kept.
+    pub fn retain_conditions(
+        &mut self,
+        config_conditions: &BTreeMap<String, Condition>,
+        target_dir: &Path,
+    ) -> Vec<(String, String)> {
+        let mut skipped = Vec::new();
+        for (section, files) in self.sections_mut() {
+            files.retain_mut(|file| {
+                let path = if section == "root" {
+                    format!("root/{}", file.relative_path)
+                } else {
+                    file.relative_path.clone()
+                };
+                let reason = parse_frontmatter_condition(&file.content)
+                    .into_iter()
+                    .chain(
+                        config_conditions
+                            .iter()
+                            .filter(|(pattern, _)| glob_match(pattern, &path))
+                            .map(|(_, condition)| condition.clone()),
+                    )
+                    .find_map(|condition| condition.unmet_reason(target_dir));
+                match reason {
+                    Some(reason) => {
+                        skipped.push((path, reason));
+                        false
+                    }
+                    None => {
+                        if let Some(content) = strip_frontmatter_condition(&file.content) {
+                            file.content = content;
+                        }
+                        true
+                    }
+                }
+            });
+        }
+        skipped
+    }
 }
 
 /// A pending change detected during scan
@@ -69,6 +483,10 @@ pub struct PendingChange {
     pub is_identical: bool,
     /// Preset content for diff display (None for merged files like memory/mcp)
     pub preset_content: Option<String>,
+    /// Preset file producing this change (e.g., "rules/style.md"; None for merged files)
+    pub source: Option<String>,
+    /// Whether the file was edited after aidot last applied it (from the manifest hash)
+    pub modified_since_apply: bool,
 }
 
 /// Result of scanning for changes
@@ -91,20 +509,25 @@ impl ScanResult {
             is_conflict,
             is_identical: false,
             preset_content: None,
+            source: None,
+            modified_since_apply: false,
         });
     }
 
     /// Add a change with content-based comparison for 1:1 file mappings.
+    ///
+    /// `source` is the preset file's path relative to the preset root.
     pub fn add_change_with_content(
         &mut self,
         path: String,
         section: String,
+        source: &str,
         target_path: &Path,
         preset_content: &str,
     ) {
         if target_path.exists() {
             let is_identical = match std::fs::read_to_string(target_path) {
-                Ok(existing) => normalize_content(&existing) == normalize_content(preset_content),
+                Ok(existing) => is_unchanged(&existing, preset_content),
                 Err(_) => false,
             };
             self.changes.push(PendingChange {
@@ -113,6 +536,8 @@ impl ScanResult {
                 is_conflict: true,
                 is_identical,
                 preset_content: Some(preset_content.to_string()),
+                source: Some(source.to_string()),
+                modified_since_apply: false,
             });
         } else {
             self.changes.push(PendingChange {
@@ -121,6 +546,8 @@ impl ScanResult {
                 is_conflict: false,
                 is_identical: false,
                 preset_content: Some(preset_content.to_string()),
+                source: Some(source.to_string()),
+                modified_since_apply: false,
             });
         }
     }
@@ -165,6 +592,10 @@ pub struct ApplyResult {
     pub skipped: Vec<String>,
     /// Files that were identical (auto-skipped)
     pub unchanged: Vec<String>,
+    /// Files that could not be written, with the reason (--continue-on-error)
+    pub failed: Vec<(String, String)>,
+    /// Lines added/removed per created or updated file (recorded by simulated writes)
+    pub line_stats: BTreeMap<String, (usize, usize)>,
 }
 
 impl ApplyResult {
@@ -174,6 +605,8 @@ impl ApplyResult {
             updated: Vec::new(),
             skipped: Vec::new(),
             unchanged: Vec::new(),
+            failed: Vec::new(),
+            line_stats: BTreeMap::new(),
         }
     }
 
@@ -192,6 +625,15 @@ impl ApplyResult {
     pub fn add_unchanged(&mut self, path: String) {
         self.unchanged.push(path);
     }
+
+    pub fn add_failed(&mut self, path: String, reason: String) {
+        self.failed.push((path, reason));
+    }
+
+    /// Total number of files processed (created + updated + skipped + unchanged)
+    pub fn total(&self) -> usize {
+        self.created.len() + self.updated.len() + self.skipped.len() + self.unchanged.len()
+    }
 }
 
 #[cfg(test)]
@@ -203,6 +645,7 @@ mod tests {
         let file = PresetFile {
             relative_path: "rules/code-style.md".to_string(),
             content: "# Code Style Rules".to_string(),
+            ..Default::default()
         };
         assert_eq!(file.relative_path, "rules/code-style.md");
         assert_eq!(file.content, "# Code Style Rules");
@@ -222,6 +665,108 @@ mod tests {
         assert!(files.root.is_empty());
     }
 
+    #[test]
+    fn test_preset_files_retain_matching() {
+        let file = |path: &str| PresetFile {
+            relative_path: path.to_string(),
+            ..Default::default()
+        };
+        let mut files = PresetFiles {
+            rules: vec![file("rules/security-web.md"), file("rules/style.md")],
+            memory: vec![file("memory/context.md")],
+            root: vec![file(".editorconfig")],
+            ..Default::default()
+        };
+
+        let patterns = vec![
+            "rules/security-*.md".to_string(),
+            "root/.editorconfig".to_string(),
+            "agents/*.md".to_string(),
+        ];
+        let unmatched = files.retain_matching(&patterns);
+
+        assert_eq!(unmatched, vec!["agents/*.md".to_string()]);
+        assert_eq!(files.rules.len(), 1);
+        assert_eq!(files.rules[0].relative_path, "rules/security-web.md");
+        assert!(files.memory.is_empty());
+        assert_eq!(files.root.len(), 1);
+    }
+
+    #[test]
+    fn test_preset_files_compose() {
+        let file = |path: &str, content: &str| PresetFile {
+            relative_path: path.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        };
+        let mut base = PresetFiles {
+            rules: vec![file("rules/style.md", "team"), file("rules/api.md", "team")],
+            memory: vec![file("memory/team.md", "Team")],
+            settings: vec![file("settings/team.json", r#"{"model":"opus"}"#)],
+            ..Default::default()
+        };
+        base.compose(PresetFiles {
+            rules: vec![file("rules/style.md", "personal")],
+            memory: vec![file("memory/personal.md", "Personal")],
+            settings: vec![file("settings/personal.json", r#"{"model":"sonnet"}"#)],
+            ..Default::default()
+        });
+
+        // Concat: accumulated in order, same path replaced in place
+        let rules: Vec<_> = base
+            .rules
+            .iter()
+            .map(|f| (f.relative_path.as_str(), f.content.as_str()))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![("rules/style.md", "personal"), ("rules/api.md", "team")]
+        );
+        let memory: Vec<_> = base
+            .memory
+            .iter()
+            .map(|f| f.relative_path.as_str())
+            .collect();
+        assert_eq!(memory, vec!["memory/team.md", "memory/personal.md"]);
+        // Replace: only the last preset's settings
+        let settings: Vec<_> = base
+            .settings
+            .iter()
+            .map(|f| f.relative_path.as_str())
+            .collect();
+        assert_eq!(settings, vec!["settings/personal.json"]);
+
+        // A later preset without settings keeps the earlier ones
+        base.compose(PresetFiles::default());
+        assert_eq!(base.settings.len(), 1);
+    }
+
+    #[test]
+    fn test_preset_files_apply_normalize() {
+        let file = |path: &str, content: &str| PresetFile {
+            relative_path: path.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        };
+        let mut files = PresetFiles {
+            rules: vec![file(
+                "rules/a.md",
+                "\n\n---\nglobs: \"*.rs\"\n---\n# A\n\n\n",
+            )],
+            hooks: vec![file("hooks/fmt.json", "{}")],
+            root: vec![file("notes.md", "# Notes")],
+            ..Default::default()
+        };
+
+        files.apply_normalize(Normalize::Auto);
+        assert!(files.rules[0].content.starts_with("\n\n"));
+
+        files.apply_normalize(Normalize::All);
+        assert_eq!(files.rules[0].content, "---\nglobs: \"*.rs\"\n---\n# A\n");
+        assert_eq!(files.hooks[0].content, "{}");
+        assert_eq!(files.root[0].content, "# Notes");
+    }
+
     #[test]
     fn test_apply_result() {
         let mut result = ApplyResult::new();
@@ -241,6 +786,8 @@ mod tests {
 
         result.add_unchanged("file4.md".to_string());
         assert_eq!(result.unchanged.len(), 1);
+
+        assert_eq!(result.total(), 4);
     }
 
     #[test]
@@ -276,6 +823,7 @@ mod tests {
         result.add_change_with_content(
             "test.md".to_string(),
             "rules".to_string(),
+            "rules/test.md",
             &file_path,
             "# Test Content\n",
         );
@@ -286,6 +834,7 @@ mod tests {
         result.add_change_with_content(
             "test2.md".to_string(),
             "rules".to_string(),
+            "rules/test.md",
             &file_path,
             "# Different Content\n",
         );
@@ -297,6 +846,7 @@ mod tests {
         result.add_change_with_content(
             "missing.md".to_string(),
             "rules".to_string(),
+            "rules/missing.md",
             &missing_path,
             "# New Content\n",
         );
@@ -0,0 +1,181 @@
+use crate::error::{AidotError, Result};
+use crate::manifest::content_hash;
+use crate::output;
+use crate::progress;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+/// Entries archivers add next to the real content (not part of the preset)
+const IGNORED_TOP_LEVEL: &[&str] = &["__MACOSX", "pax_global_header"];
+
+/// Archive formats of presets and release assets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    TarGz,
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// Format from a file name or URL (`.tar.gz`/`.tgz`, `.zip`), ignoring `?query` and `#fragment`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let path = name
+            .split(['?', '#'])
+            .next()
+            .unwrap_or(name)
+            .to_ascii_lowercase();
+        if path.ends_with(".tar.gz") || path.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if path.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// Unpack `archive` into `dest`
+///
+/// Entries with absolute paths or `..` are not written outside `dest` (tar and zip
+/// both refuse them).
+pub fn extract(archive: &Path, format: ArchiveFormat, dest: &Path) -> Result<()> {
+    let file = File::open(archive)?;
+    fs::create_dir_all(dest)?;
+    match format {
+        ArchiveFormat::TarGz => {
+            tar::Archive::new(flate2::read::GzDecoder::new(file)).unpack(dest)?;
+        }
+        ArchiveFormat::Zip => {
+            zip::ZipArchive::new(file)
+                .and_then(|mut zip| zip.extract(dest))
+                .map_err(|e| AidotError::InvalidInput(format!("{}: {}", archive.display(), e)))?;
+        }
+    }
+    Ok(())
+}
+
+/// Directory holding the extracted content
+///
+/// Release archives usually wrap everything in one top-level directory
+/// (`preset-1.0/...`); it is stripped unless `dir` already holds a preset.
+pub fn content_root(dir: &Path) -> Result<PathBuf> {
+    if dir.join(".aidot-config.toml").exists() {
+        return Ok(dir.to_path_buf());
+    }
+    let entries: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name();
+            !IGNORED_TOP_LEVEL.iter().any(|ignored| name == *ignored)
+        })
+        .map(|entry| entry.path())
+        .collect();
+    match entries.as_slice() {
+        [only] if only.is_dir() => Ok(only.clone()),
+        _ => Ok(dir.to_path_buf()),
+    }
+}
+
+/// Download an archive into memory
+pub fn download(url: &str) -> Result<Vec<u8>> {
+    output::info(&format!("Downloading archive from {}...", url));
+    let spinner = progress::spinner("Downloading...");
+    let result = reqwest::blocking::get(url)
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.bytes());
+    spinner.finish_and_clear();
+    let bytes = result.map_err(|e| AidotError::Network(format!("{}: {}", url, e)))?;
+    Ok(bytes.to_vec())
+}
+
+/// Fail unless `bytes` has the expected SHA-256 (hex, case-insensitive)
+pub fn verify_sha256(bytes: &[u8], expected: &str, url: &str) -> Result<()> {
+    let actual = content_hash(bytes);
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        return Err(AidotError::InvalidInput(format!(
+            "Checksum mismatch for {}:\n  expected sha256 {}\n  actual   sha256 {}",
+            url,
+            expected.trim().to_ascii_lowercase(),
+            actual
+        )));
+    }
+    Ok(())
+}
+
+/// Check that a URL answers (HEAD request, nothing is downloaded)
+pub fn check_url(url: &str) -> Result<()> {
+    reqwest::blocking::Client::new()
+        .head(url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|e| AidotError::Network(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_format_from_name() {
+        assert_eq!(
+            ArchiveFormat::from_name("https://example.com/preset.tar.gz"),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(
+            ArchiveFormat::from_name("preset-1.0.TGZ"),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(
+            ArchiveFormat::from_name("https://example.com/preset.zip?token=abc"),
+            Some(ArchiveFormat::Zip)
+        );
+        assert_eq!(
+            ArchiveFormat::from_name("https://github.com/team/preset.git"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_extract_strips_single_top_level_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive_path = temp_dir.path().join("preset.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(
+            File::create(&archive_path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        for (path, content) in [
+            (
+                "preset-1.0/.aidot-config.toml",
+                "[metadata]\nname = \"t\"\n",
+            ),
+            ("preset-1.0/rules/style.md", "# Style"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let dest = temp_dir.path().join("out");
+        extract(&archive_path, ArchiveFormat::TarGz, &dest).unwrap();
+        let root = content_root(&dest).unwrap();
+        assert_eq!(root, dest.join("preset-1.0"));
+        assert!(root.join("rules/style.md").exists());
+
+        // Already at the preset root: nothing to strip
+        assert_eq!(content_root(&root).unwrap(), root);
+    }
+
+    #[test]
+    fn test_verify_sha256() {
+        let digest = content_hash(b"archive");
+        assert!(verify_sha256(b"archive", &digest.to_uppercase(), "u").is_ok());
+        assert!(verify_sha256(b"other", &digest, "u").is_err());
+    }
+}
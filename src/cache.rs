@@ -1,7 +1,79 @@
-use crate::config::Config;
-use crate::error::Result;
+use crate::archive::{self, ArchiveFormat};
+use crate::config::{Config, SourceType};
+use crate::error::{AidotError, Result};
 use crate::git;
-use std::path::PathBuf;
+use crate::manifest::content_hash;
+use crate::output;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+/// Whether cached clones are discarded and cloned again instead of updated (--reset-cache)
+static RESET_CACHE: AtomicBool = AtomicBool::new(false);
+
+/// Discard cached clones (including local edits) and clone them again (--reset-cache)
+pub fn set_reset_cache(enabled: bool) {
+    RESET_CACHE.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether caches are used as they are, without any network access (--offline)
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Environment variable enabling offline mode (`AIDOT_OFFLINE=1`)
+pub const OFFLINE_ENV: &str = "AIDOT_OFFLINE";
+
+/// Use caches as they are and never touch the network (--offline / AIDOT_OFFLINE)
+pub fn set_offline(enabled: bool) {
+    OFFLINE.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether offline mode is on
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Whether AIDOT_OFFLINE asks for offline mode (`1`, `true` or `yes`)
+pub fn offline_from_env() -> bool {
+    std::env::var(OFFLINE_ENV).is_ok_and(|value| {
+        matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes"
+        )
+    })
+}
+
+/// Fail right away when `action` would need the network in offline mode
+pub fn require_online(action: &str) -> Result<()> {
+    if is_offline() {
+        return Err(AidotError::Network(format!(
+            "{} needs the network, but aidot is offline (--offline / {})",
+            action, OFFLINE_ENV
+        )));
+    }
+    Ok(())
+}
+
+/// Error for a repository with no usable cache in offline mode
+fn not_cached_offline(repo_name: &str, url: &str) -> AidotError {
+    AidotError::Network(format!(
+        "'{}' is not cached and aidot is offline, so {} cannot be fetched.\n\
+         Pull it once while online, or drop --offline / {}",
+        repo_name, url, OFFLINE_ENV
+    ))
+}
+
+/// Local changes listed in the dirty cache error
+const MAX_LISTED_CHANGES: usize = 5;
+
+/// Marker inside a clone's .git/ whose mtime records the last use (git ignores it)
+const LAST_USED_MARKER: &str = "aidot-last-used";
+
+/// Bookkeeping directory of an extracted archive (the counterpart of a clone's .git/)
+pub const ARCHIVE_META_DIR: &str = ".aidot-archive";
+/// Files in ARCHIVE_META_DIR: the download URL and the SHA-256 of the archive
+const ARCHIVE_SOURCE_FILE: &str = "source";
+const ARCHIVE_SHA256_FILE: &str = "sha256";
 
 /// Get the cache path for a repository
 pub fn get_cache_path(repo_name: &str) -> Result<PathBuf> {
@@ -13,56 +85,388 @@ pub fn get_cache_path(repo_name: &str) -> Result<PathBuf> {
 /// Returns the path to the cached repository
 pub fn ensure_cached(repo_name: &str, repo_url: &str) -> Result<PathBuf> {
     let cache_path = get_cache_path(repo_name)?;
+    if is_offline() {
+        if RESET_CACHE.load(Ordering::Relaxed) {
+            require_online("--reset-cache")?;
+        }
+        if !(cache_path.exists() && git::is_git_repository(&cache_path)) {
+            return Err(not_cached_offline(repo_name, repo_url));
+        }
+        output::info(&format!(
+            "Offline: using cached clone of '{}' as it is",
+            repo_name
+        ));
+        mark_used(&cache_path);
+        return Ok(cache_path);
+    }
+
+    // Fail with a clear message before an existing cache is refreshed or removed
+    git::check_transport(repo_url)?;
 
     if cache_path.exists() && git::is_git_repository(&cache_path) {
         // Repository already cached, pull latest changes
-        git::pull_repository(&cache_path)?;
+        refresh_cache(repo_name, &cache_path, repo_url)?;
     } else {
-        // Clone repository
-        if cache_path.exists() {
-            std::fs::remove_dir_all(&cache_path)?;
-        }
+        clone_fresh(repo_url, &cache_path)?;
+    }
+    mark_used(&cache_path);
 
-        // Attempt to clone, clean up on failure
-        if let Err(e) = git::clone_repository(repo_url, &cache_path) {
-            // Clean up any partially created directory
-            if cache_path.exists() {
-                let _ = std::fs::remove_dir_all(&cache_path);
-            }
-            return Err(e);
+    if let Err(e) = enforce_max_size(repo_name) {
+        output::info(&format!("Warning: cache cleanup skipped: {}", e));
+    }
+
+    Ok(cache_path)
+}
+
+/// Ensure a release archive is downloaded and extracted locally
+///
+/// With `sha256` the download must match it, and an extracted cache of the same URL and
+/// checksum is reused without downloading again. Without it the archive is downloaded
+/// on every use, the way a clone is pulled. Returns the path to the extracted preset.
+pub fn ensure_archive_cached(
+    repo_name: &str,
+    repo_url: &str,
+    sha256: Option<&str>,
+) -> Result<PathBuf> {
+    let cache_path = get_cache_path(repo_name)?;
+    if is_offline() {
+        if RESET_CACHE.load(Ordering::Relaxed) {
+            require_online("--reset-cache")?;
+        }
+        let recorded = read_archive_source(&cache_path).filter(|(url, actual)| {
+            url == repo_url
+                && sha256.is_none_or(|expected| actual.eq_ignore_ascii_case(expected.trim()))
+        });
+        if recorded.is_none() {
+            return Err(not_cached_offline(repo_name, repo_url));
         }
+        output::info(&format!(
+            "Offline: using cached archive for '{}'",
+            repo_name
+        ));
+        mark_used(&cache_path);
+        return Ok(cache_path);
+    }
+
+    let pinned = sha256.is_some_and(|expected| {
+        read_archive_source(&cache_path).is_some_and(|(url, actual)| {
+            url == repo_url && actual.eq_ignore_ascii_case(expected.trim())
+        })
+    });
+    if pinned && !RESET_CACHE.load(Ordering::Relaxed) {
+        output::info(&format!("Using cached archive for '{}'", repo_name));
+    } else {
+        download_archive(repo_url, sha256, &cache_path)?;
+    }
+    mark_used(&cache_path);
+
+    if let Err(e) = enforce_max_size(repo_name) {
+        output::info(&format!("Warning: cache cleanup skipped: {}", e));
     }
 
     Ok(cache_path)
 }
 
+/// Download, verify and extract an archive, then replace `cache_path` with its content
+///
+/// Extraction happens next to the cache, so a failed download or a broken archive
+/// leaves the previous cache untouched.
+fn download_archive(url: &str, sha256: Option<&str>, cache_path: &Path) -> Result<()> {
+    let format = ArchiveFormat::from_name(url).ok_or_else(|| {
+        AidotError::InvalidInput(format!(
+            "Unknown archive format (expected .tar.gz, .tgz or .zip): {}",
+            url
+        ))
+    })?;
+    let bytes = archive::download(url)?;
+    if let Some(expected) = sha256 {
+        archive::verify_sha256(&bytes, expected, url)?;
+    }
+
+    let cache_dir = Config::cache_dir()?;
+    std::fs::create_dir_all(&cache_dir)?;
+    let staging = tempfile::Builder::new()
+        .prefix(".download-")
+        .tempdir_in(&cache_dir)?;
+    let archive_path = staging.path().join("archive");
+    std::fs::write(&archive_path, &bytes)?;
+    let extract_dir = staging.path().join("extracted");
+    archive::extract(&archive_path, format, &extract_dir)?;
+
+    let root = archive::content_root(&extract_dir)?;
+    let meta_dir = root.join(ARCHIVE_META_DIR);
+    std::fs::create_dir_all(&meta_dir)?;
+    std::fs::write(meta_dir.join(ARCHIVE_SOURCE_FILE), url)?;
+    std::fs::write(meta_dir.join(ARCHIVE_SHA256_FILE), content_hash(&bytes))?;
+
+    if cache_path.exists() {
+        std::fs::remove_dir_all(cache_path)?;
+    }
+    std::fs::rename(&root, cache_path)?;
+    output::info("Archive extracted successfully");
+    Ok(())
+}
+
+/// URL and SHA-256 an extracted archive cache was downloaded from
+fn read_archive_source(cache_path: &Path) -> Option<(String, String)> {
+    let meta_dir = cache_path.join(ARCHIVE_META_DIR);
+    let url = std::fs::read_to_string(meta_dir.join(ARCHIVE_SOURCE_FILE)).ok()?;
+    let sha256 = std::fs::read_to_string(meta_dir.join(ARCHIVE_SHA256_FILE)).ok()?;
+    Some((url.trim().to_string(), sha256.trim().to_string()))
+}
+
+/// Directory holding aidot's bookkeeping for a cache (.git/ of a clone, or ARCHIVE_META_DIR)
+fn meta_dir(cache_path: &Path) -> PathBuf {
+    let archive_meta = cache_path.join(ARCHIVE_META_DIR);
+    if archive_meta.is_dir() {
+        archive_meta
+    } else {
+        cache_path.join(".git")
+    }
+}
+
+/// Record that a cached clone was just used (for least-recently-used cleanup)
+fn mark_used(cache_path: &Path) {
+    // Failing to record the use only makes the clone look older
+    let _ = std::fs::write(meta_dir(cache_path).join(LAST_USED_MARKER), "");
+}
+
+/// Bring an existing cached clone up to date
+///
+/// Local edits would make the fast-forward fail (or be mixed into the preset), so a
+/// dirty cache stops with an error unless --reset-cache asks for a fresh clone.
+fn refresh_cache(repo_name: &str, cache_path: &Path, repo_url: &str) -> Result<()> {
+    if RESET_CACHE.load(Ordering::Relaxed) {
+        output::info(&format!("Resetting cache for '{}'...", repo_name));
+        return clone_fresh(repo_url, cache_path);
+    }
+
+    let changes = git::local_changes(cache_path)?;
+    if !changes.is_empty() {
+        let mut listed: Vec<String> = changes
+            .iter()
+            .take(MAX_LISTED_CHANGES)
+            .map(|path| format!("  {}", path))
+            .collect();
+        if changes.len() > MAX_LISTED_CHANGES {
+            listed.push(format!(
+                "  ... and {} more",
+                changes.len() - MAX_LISTED_CHANGES
+            ));
+        }
+        return Err(AidotError::Git(format!(
+            "Cache for '{}' has local changes ({}):\n{}\nThe cache is managed by aidot and is not meant to be edited; change the preset repository instead.\nRun 'aidot cache update {} --reset-cache' to discard the changes and clone again.",
+            repo_name,
+            cache_path.display(),
+            listed.join("\n"),
+            repo_name
+        )));
+    }
+
+    git::pull_repository(cache_path)
+}
+
+/// Clone into `cache_path`, removing whatever is there first
+fn clone_fresh(repo_url: &str, cache_path: &Path) -> Result<()> {
+    if cache_path.exists() {
+        std::fs::remove_dir_all(cache_path)?;
+    }
+    // Never clone on top of leftovers of the previous cache
+    if cache_path.exists() {
+        return Err(AidotError::Git(format!(
+            "Could not remove the old cache at {}",
+            cache_path.display()
+        )));
+    }
+
+    // Attempt to clone, clean up on failure
+    if let Err(e) = git::clone_repository(repo_url, cache_path) {
+        // Clean up any partially created directory
+        if cache_path.exists() {
+            let _ = std::fs::remove_dir_all(cache_path);
+        }
+        return Err(e);
+    }
+    Ok(())
+}
+
 /// Update a cached repository
 pub fn update_cache(repo_name: &str) -> Result<()> {
+    require_online("Updating caches")?;
     let cache_path = get_cache_path(repo_name)?;
 
     if !cache_path.exists() {
-        return Err(crate::error::AidotError::RepositoryNotFound(format!(
+        return Err(AidotError::RepositoryNotFound(format!(
             "Cache not found for repository '{}'",
             repo_name
         )));
     }
 
-    git::pull_repository(&cache_path)?;
-    Ok(())
+    if let Some((url, _)) = read_archive_source(&cache_path) {
+        let config = Config::load()?;
+        let sha256 = config
+            .repositories
+            .iter()
+            .find(|repo| repo.name == repo_name)
+            .and_then(|repo| repo.sha256.clone());
+        return download_archive(&url, sha256.as_deref(), &cache_path);
+    }
+
+    let repo_url = git::remote_url(&cache_path)?;
+    git::check_transport(&repo_url)?;
+    refresh_cache(repo_name, &cache_path, &repo_url)
 }
 
-/// Clear all cached repositories
+/// Remove the cached clone of a repository, if any (it is re-cloned on next use)
+pub fn remove_cache(repo_name: &str) -> Result<bool> {
+    let cache_path = get_cache_path(repo_name)?;
+    if !cache_path.exists() {
+        return Ok(false);
+    }
+    std::fs::remove_dir_all(&cache_path)?;
+    Ok(true)
+}
+
+/// Clear all cached repositories, and the parsed presets cached from them
 pub fn clear_all_caches() -> Result<()> {
-    let cache_dir = Config::cache_dir()?;
+    let parse_cache = crate::preset::parse_cache::dir()?;
+    if parse_cache.exists() {
+        std::fs::remove_dir_all(parse_cache)?;
+    }
+    clear_cache_dir(&Config::cache_dir()?)
+}
 
+/// Remove everything inside `cache_dir`, keeping the directory itself
+///
+/// Only the cache directory is touched; config.toml and state/ next to it stay intact.
+fn clear_cache_dir(cache_dir: &Path) -> Result<()> {
     if cache_dir.exists() {
-        std::fs::remove_dir_all(&cache_dir)?;
-        std::fs::create_dir_all(&cache_dir)?;
-        println!("✓ All caches cleared");
-    } else {
-        println!("No caches to clear");
+        std::fs::remove_dir_all(cache_dir)?;
     }
+    std::fs::create_dir_all(cache_dir)?;
+    Ok(())
+}
+
+/// Total size in bytes of all files under the cache directory
+pub fn cache_size() -> Result<u64> {
+    Ok(dir_size(&Config::cache_dir()?))
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// A cached clone, as considered by `cache gc`
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    pub name: String,
+    pub size: u64,
+    pub last_used: SystemTime,
+    /// Whether a registered Git or archive repository uses this cache (otherwise it was
+    /// cloned for a URL given directly to pull/diff)
+    pub registered: bool,
+}
+
+/// Every cached clone with its size and last use
+pub fn cache_entries() -> Result<Vec<CacheEntry>> {
+    let config = Config::load()?;
+    let mut entries = Vec::new();
+    for name in list_caches()? {
+        let path = get_cache_path(&name)?;
+        let last_used = std::fs::metadata(meta_dir(&path).join(LAST_USED_MARKER))
+            .or_else(|_| std::fs::metadata(&path))
+            .and_then(|m| m.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let registered = config
+            .repositories
+            .iter()
+            .any(|repo| repo.source_type != SourceType::Local && repo.name == name);
+        entries.push(CacheEntry {
+            size: dir_size(&path),
+            name,
+            last_used,
+            registered,
+        });
+    }
+    Ok(entries)
+}
+
+/// Caches to remove, in removal order
+///
+/// Caches of URLs given directly go first, then those of registered repositories,
+/// each least recently used first. With `max_size`, removal stops once the total
+/// fits; without it, only the caches of directly given URLs are removed. `keep`
+/// (the cache in use) is never removed.
+pub fn select_evictions<'a>(
+    entries: &'a [CacheEntry],
+    max_size: Option<u64>,
+    keep: Option<&str>,
+) -> Vec<&'a CacheEntry> {
+    let mut candidates: Vec<&CacheEntry> = entries
+        .iter()
+        .filter(|entry| Some(entry.name.as_str()) != keep)
+        .filter(|entry| max_size.is_some() || !entry.registered)
+        .collect();
+    candidates.sort_by_key(|entry| (entry.registered, entry.last_used));
+
+    let Some(max_size) = max_size else {
+        return candidates;
+    };
+    let mut total: u64 = entries.iter().map(|entry| entry.size).sum();
+    let mut evicted = Vec::new();
+    for entry in candidates {
+        if total <= max_size {
+            break;
+        }
+        total -= entry.size;
+        evicted.push(entry);
+    }
+    evicted
+}
 
+/// Parse a size like "500MB", "1.5 GB" or "1048576" (bytes; units are powers of 1024)
+pub fn parse_size(input: &str) -> Result<u64> {
+    let trimmed = input.trim();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" => 1024,
+        "M" | "MB" => 1024 * 1024,
+        "G" | "GB" => 1024 * 1024 * 1024,
+        _ => 0,
+    };
+    match number.parse::<f64>() {
+        Ok(value) if multiplier > 0 && value >= 0.0 => Ok((value * multiplier as f64) as u64),
+        _ => Err(AidotError::InvalidInput(format!(
+            "Invalid size '{}' (expected e.g. 500MB, 2GB or a byte count)",
+            input
+        ))),
+    }
+}
+
+/// Remove least recently used caches beyond `[cache] max_size`, if it is set
+fn enforce_max_size(keep: &str) -> Result<()> {
+    let Some(limit) = Config::load()?.cache.max_size else {
+        return Ok(());
+    };
+    let max_size = parse_size(&limit)?;
+    let entries = cache_entries()?;
+    for entry in select_evictions(&entries, Some(max_size), Some(keep)) {
+        remove_cache(&entry.name)?;
+        output::info(&format!(
+            "Removed cache '{}' (least recently used, cache.max_size = {})",
+            entry.name, limit
+        ));
+    }
     Ok(())
 }
 
@@ -75,7 +479,8 @@ pub fn list_caches() -> Result<Vec<String>> {
         for entry in std::fs::read_dir(&cache_dir)? {
             let entry = entry?;
             if entry.file_type()?.is_dir() {
-                if let Some(name) = entry.file_name().to_str() {
+                // Hidden entries are in-progress archive downloads
+                if let Some(name) = entry.file_name().to_str().filter(|n| !n.starts_with('.')) {
                     caches.push(name.to_string());
                 }
             }
@@ -84,3 +489,75 @@ pub fn list_caches() -> Result<Vec<String>> {
 
     Ok(caches)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_clear_cache_dir_keeps_config() {
+        let aidot_dir = TempDir::new().unwrap();
+        let cache_dir = aidot_dir.path().join("cache");
+        fs::create_dir_all(cache_dir.join("team/.git")).unwrap();
+        fs::write(cache_dir.join("team/rules.md"), "12345").unwrap();
+        fs::write(aidot_dir.path().join("config.toml"), "repositories = []").unwrap();
+        fs::create_dir_all(aidot_dir.path().join("state")).unwrap();
+
+        assert_eq!(dir_size(&cache_dir), 5);
+        clear_cache_dir(&cache_dir).unwrap();
+
+        assert!(cache_dir.exists());
+        assert_eq!(fs::read_dir(&cache_dir).unwrap().count(), 0);
+        assert!(aidot_dir.path().join("config.toml").exists());
+        assert!(aidot_dir.path().join("state").exists());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1048576").unwrap(), 1024 * 1024);
+        assert_eq!(parse_size("500MB").unwrap(), 500 * 1024 * 1024);
+        assert_eq!(parse_size("1.5 gb").unwrap(), 3 * 512 * 1024 * 1024);
+        assert_eq!(parse_size("2K").unwrap(), 2048);
+        assert!(parse_size("lots").is_err());
+        assert!(parse_size("10 TB").is_err());
+        assert!(parse_size("").is_err());
+    }
+
+    #[test]
+    fn test_select_evictions() {
+        let at = |secs| SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs);
+        let entry = |name: &str, size, last_used, registered| CacheEntry {
+            name: name.to_string(),
+            size,
+            last_used: at(last_used),
+            registered,
+        };
+        let entries = vec![
+            entry("team", 40, 10, true),
+            entry("docs", 30, 20, true),
+            entry("adhoc-new", 20, 30, false),
+            entry("adhoc-old", 10, 5, false),
+        ];
+        let names =
+            |evicted: Vec<&CacheEntry>| evicted.iter().map(|e| e.name.clone()).collect::<Vec<_>>();
+
+        // Directly given URLs go first, then registered repositories, oldest first
+        assert_eq!(
+            names(select_evictions(&entries, Some(50), None)),
+            vec!["adhoc-old", "adhoc-new", "team"]
+        );
+        assert!(select_evictions(&entries, Some(100), None).is_empty());
+        // The cache in use is kept
+        assert_eq!(
+            names(select_evictions(&entries, Some(50), Some("team"))),
+            vec!["adhoc-old", "adhoc-new", "docs"]
+        );
+        // Without a limit only caches of directly given URLs are removed
+        assert_eq!(
+            names(select_evictions(&entries, None, None)),
+            vec!["adhoc-old", "adhoc-new"]
+        );
+    }
+}
@@ -1,4 +1,8 @@
+use crate::commands::config::ImportConflict;
+use crate::output::DiffTheme;
+use crate::preset::config::LineEnding;
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 /// Build-time version from AIDOT_VERSION env var, falls back to Cargo.toml version
 const VERSION: &str = match option_env!("AIDOT_VERSION") {
@@ -13,7 +17,7 @@ const VERSION: &str = match option_env!("AIDOT_VERSION") {
     long_about = "aidot (AI dotfiles) is a CLI tool that manages LLM tool configurations \
 across multiple AI coding assistants. It fetches tool-agnostic configuration presets \
 from Git repositories and automatically converts them to the appropriate format for each \
-detected LLM tool (Claude Code, Cursor, GitHub Copilot, etc.)."
+detected LLM tool (Claude Code, Cursor, GitHub Copilot, Amazon Q, etc.)."
 )]
 #[command(styles = get_styles())]
 #[command(after_help = "Examples:
@@ -34,6 +38,22 @@ pub struct Cli {
     /// Suppress output (quiet mode)
     #[arg(short, long, global = true)]
     pub quiet: bool,
+
+    /// Write a run log to this file (pull logs to ~/.aidot/logs/ by default)
+    #[arg(long, value_name = "PATH", global = true)]
+    pub log: Option<PathBuf>,
+
+    /// Use cached repositories as they are and never access the network (also AIDOT_OFFLINE=1)
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Disable colored output (diffs use the plain theme)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Color theme of file diffs (default: AIDOT_DIFF_THEME or "default")
+    #[arg(long, value_enum, value_name = "THEME", global = true)]
+    pub diff_theme: Option<DiffTheme>,
 }
 
 fn get_styles() -> clap::builder::Styles {
@@ -55,6 +75,10 @@ pub enum Commands {
         #[arg(long)]
         from_existing: bool,
 
+        /// Encrypt extracted mcp/ and settings/ files to *.enc (with --from-existing)
+        #[arg(long, requires = "from_existing")]
+        encrypt: bool,
+
         /// Interactive preset creation
         #[arg(long)]
         interactive: bool,
@@ -63,6 +87,10 @@ pub enum Commands {
         #[arg(short, long)]
         force: bool,
 
+        /// Git URL the preset will be pushed to (filled into README.md and [metadata])
+        #[arg(long, value_name = "URL")]
+        repo_url: Option<String>,
+
         /// Target directory (default: current directory)
         #[arg(value_name = "DIR")]
         path: Option<String>,
@@ -78,14 +106,26 @@ pub enum Commands {
         #[arg(value_name = "REPO")]
         repositories: Vec<String>,
 
+        /// Apply the default repositories with this tag only
+        #[arg(long, value_name = "TAG", conflicts_with = "repositories")]
+        tag: Option<String>,
+
         /// Apply to specific tools only (comma-separated: cursor,claude,copilot)
         #[arg(long, value_delimiter = ',')]
         tools: Option<Vec<String>>,
 
+        /// Skip these tools even when detected (comma-separated; combined with --tools, a tool may not be in both)
+        #[arg(long, value_delimiter = ',', value_name = "TOOLS")]
+        exclude_tools: Vec<String>,
+
         /// Preview changes without applying them
         #[arg(long)]
         dry_run: bool,
 
+        /// Run the reload command of each changed tool after applying (e.g., cursor --reuse-window)
+        #[arg(long, conflicts_with = "dry_run")]
+        reload: bool,
+
         /// Force overwrite existing files without asking
         #[arg(short, long, conflicts_with = "skip")]
         force: bool,
@@ -93,23 +133,218 @@ pub enum Commands {
         /// Skip existing files without asking
         #[arg(short, long, conflicts_with = "force")]
         skip: bool,
+
+        /// Skip confirmation prompts; conflicts follow `conflict_policy` in ~/.aidot/config.toml
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Line endings for written files (overrides [apply] line_endings in the preset)
+        #[arg(long, value_enum)]
+        line_endings: Option<LineEnding>,
+
+        /// Delete previously applied files that were removed from the preset
+        #[arg(long)]
+        prune: bool,
+
+        /// Apply only preset files matching a glob (repeatable, e.g. 'rules/security-*.md')
+        #[arg(long, value_name = "GLOB", conflicts_with = "prune")]
+        only: Vec<String>,
+
+        /// Allow several preset files to map to the same target (the last one wins)
+        #[arg(long)]
+        allow_overlap: bool,
+
+        /// Run the preset's pre/post apply hooks (each command is confirmed first)
+        #[arg(long)]
+        run_hooks: bool,
+
+        /// List preset files skipped by their `when` conditions, with the reason
+        #[arg(long)]
+        show_skipped: bool,
+
+        /// Skip files that cannot be written (e.g. permission denied) and apply the rest
+        #[arg(long)]
+        continue_on_error: bool,
+
+        /// Show conflict diffs through $PAGER (default: less -R)
+        #[arg(long)]
+        pager: bool,
+
+        /// Write through symlinked target files to the files they point to (default: warn and skip)
+        #[arg(long, conflicts_with = "replace_symlinks")]
+        follow_symlinks: bool,
+
+        /// Replace symlinked target files with regular files (the linked files are left alone)
+        #[arg(long)]
+        replace_symlinks: bool,
+
+        /// Apply to the user-level configuration in the home directory (~/.claude, ~/.cursor)
+        #[arg(long)]
+        global: bool,
+
+        /// Compose all repositories into one preset and apply it once (memory accumulates, settings from the last one win)
+        #[arg(long)]
+        compose: bool,
+
+        /// Discard cached clones (including local edits) and clone them again
+        #[arg(long)]
+        reset_cache: bool,
+
+        /// Parse the preset again instead of reusing the parse cache
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Fail on preset layout problems (section directory without its config section, or the reverse)
+        #[arg(long)]
+        strict: bool,
+
+        /// Choose the files to apply from the change list (checkbox list; all files without a terminal)
+        #[arg(long, conflicts_with_all = ["dry_run", "force", "skip"])]
+        interactive_preview: bool,
+
+        /// Commit the created/updated files to the project's git repository
+        #[arg(long, conflicts_with = "dry_run")]
+        commit: bool,
+
+        /// Commit message for --commit (default: "aidot: apply <repo> preset")
+        #[arg(short = 'm', long = "message", value_name = "MSG", requires = "commit")]
+        commit_message: Option<String>,
+
+        /// Write a report of applied files (JSON if the path ends with .json, Markdown otherwise)
+        #[arg(long, value_name = "PATH", conflicts_with = "dry_run")]
+        report: Option<PathBuf>,
+    },
+
+    /// Lint preset files (empty files, duplicate titles, broken frontmatter, hook format, ...)
+    Lint {
+        /// Preset directory (default: current directory)
+        #[arg(value_name = "DIR")]
+        path: Option<String>,
+
+        /// Fix automatically fixable issues (trailing whitespace)
+        #[arg(long)]
+        fix: bool,
+
+        /// Also check that http(s) links in rules and commands answer (needs the network)
+        #[arg(long)]
+        check_urls: bool,
+    },
+
+    /// Search public presets (GitHub topic 'aidot-preset' or a manifest URL)
+    Search {
+        /// Search terms (matched against name and description)
+        #[arg(value_name = "QUERY")]
+        query: Option<String>,
+
+        /// JSON manifest URL listing presets ([{ name, url, description, stars }])
+        #[arg(long, value_name = "URL")]
+        manifest: Option<String>,
+
+        /// Maximum number of results
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
     },
 
     /// Detect installed LLM tools
-    Detect,
+    Detect {
+        /// Also detect user-level tool configuration in the home directory (e.g., ~/.claude)
+        #[arg(long)]
+        global: bool,
+    },
+
+    /// Check git, network, ~/.aidot, config, repositories, tools and cache in one go
+    Doctor {
+        /// Print the results as JSON (e.g., to attach to a support request)
+        #[arg(long)]
+        json: bool,
+    },
 
     /// Show current configuration status
-    Status,
+    Status {
+        /// Also show user-level tool configuration in the home directory (e.g., ~/.claude)
+        #[arg(long)]
+        global: bool,
+
+        /// Levels of subdirectory contents to list (default: 1)
+        #[arg(long, value_name = "N")]
+        depth: Option<usize>,
+
+        /// List every entry instead of 5 per subdirectory (the full tree unless --depth is given)
+        #[arg(long)]
+        all: bool,
+    },
 
     /// Manage cache
     #[command(subcommand)]
     Cache(CacheCommands),
 
+    /// Export or import the global configuration (repository list)
+    #[command(subcommand)]
+    Config(ConfigCommands),
+
+    /// Encrypt or decrypt preset files holding secrets (*.enc)
+    #[command(subcommand)]
+    Secret(SecretCommands),
+
+    /// Remove files previously applied from a preset
+    Unapply {
+        /// Repository name, local path, or Git URL (as given to pull)
+        #[arg(value_name = "REPO")]
+        repository: String,
+
+        /// Preview files that would be removed
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Also remove files that were modified after they were applied
+        #[arg(short, long)]
+        force: bool,
+    },
+
     /// Show diff between preset and current config
     Diff {
         /// Repository name, local path, or Git URL
         #[arg(value_name = "REPO")]
         repository: String,
+
+        /// Break the summary down per preset section (rules: +2 ~1, commands: =3)
+        #[arg(long)]
+        by_section: bool,
+
+        /// Print only the summary counts as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Compare specific tools only, even if not detected (comma-separated: cursor,claude,copilot)
+        #[arg(long, value_delimiter = ',')]
+        tools: Option<Vec<String>>,
+
+        /// Parse the preset again instead of reusing the parse cache
+        #[arg(long)]
+        no_cache: bool,
+    },
+
+    /// Render a preset into an empty directory for every tool (no project needed)
+    Render {
+        /// Repository name, local path, or Git URL
+        #[arg(value_name = "REPO")]
+        repository: String,
+
+        /// Empty or new directory to write the converted files into
+        #[arg(long, value_name = "DIR")]
+        out: PathBuf,
+
+        /// Render specific tools only (comma-separated: cursor,claude,copilot; default: all)
+        #[arg(long, value_delimiter = ',')]
+        tools: Option<Vec<String>>,
+
+        /// Parse the preset again instead of reusing the parse cache
+        #[arg(long)]
+        no_cache: bool,
     },
 
     /// Update aidot to the latest version
@@ -147,10 +382,33 @@ pub enum RepoCommands {
         /// Repository description
         #[arg(short, long)]
         description: Option<String>,
+
+        /// Preset root inside the repository (same as `<url>#path=<dir>`)
+        #[arg(long, value_name = "DIR")]
+        subdir: Option<String>,
+
+        /// Clone a Git repository and check that its preset parses before registering
+        #[arg(long)]
+        verify: bool,
+
+        /// Tag for grouping repositories (repeatable, e.g., --tag frontend --tag eslint)
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+
+        /// Expected SHA-256 of an archive URL (.tar.gz, .tgz, .zip); downloads must match it
+        #[arg(long, value_name = "HEX")]
+        sha256: Option<String>,
     },
 
     /// List registered repositories
-    List,
+    List {
+        /// Only list repositories with this tag
+        #[arg(long, value_name = "TAG")]
+        tag: Option<String>,
+    },
+
+    /// Check that every registered repository is reachable (local path, Git remote)
+    Doctor,
 
     /// Remove a repository
     Remove {
@@ -165,9 +423,67 @@ pub enum RepoCommands {
         #[arg(value_name = "NAME")]
         name: String,
 
-        /// Default flag value (true or false)
+        /// Default flag value (true or false, defaults to true when omitted)
         #[arg(value_name = "VALUE")]
-        value: bool,
+        value: Option<bool>,
+
+        /// Unset the default flag (same as VALUE=false)
+        #[arg(long, conflicts_with = "value")]
+        off: bool,
+    },
+
+    /// Unset default flag for a repository
+    UnsetDefault {
+        /// Repository name
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+
+    /// Change the URL or local path of a repository (its cache is invalidated)
+    SetUrl {
+        /// Repository name
+        #[arg(value_name = "NAME")]
+        name: String,
+
+        /// New repository URL or local path
+        #[arg(value_name = "URL")]
+        url: String,
+
+        /// Point to a local preset (path will be converted to absolute path)
+        #[arg(long)]
+        local: bool,
+
+        /// Expected SHA-256 of the new archive URL (.tar.gz, .tgz, .zip)
+        #[arg(long, value_name = "HEX")]
+        sha256: Option<String>,
+    },
+
+    /// Trust a repository's pre/post apply hooks (run on pull without --run-hooks)
+    TrustHooks {
+        /// Repository name
+        #[arg(value_name = "NAME")]
+        name: String,
+
+        /// Stop trusting the hooks
+        #[arg(long)]
+        off: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SecretCommands {
+    /// Encrypt a file to <FILE>.enc with AIDOT_SECRET_KEY (or ~/.aidot/secret.key)
+    Encrypt {
+        /// File to encrypt (e.g., settings/secrets.json)
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+    },
+
+    /// Print the decrypted content of a *.enc file
+    Decrypt {
+        /// Encrypted file (e.g., settings/secrets.json.enc)
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
     },
 }
 
@@ -182,8 +498,56 @@ pub enum CacheCommands {
         /// Update all cached repositories
         #[arg(long)]
         all: bool,
+
+        /// Discard the cached clone (including local edits) and clone it again
+        #[arg(long)]
+        reset_cache: bool,
+
+        /// Pull updated repositories again in the projects they were applied to (asks first)
+        #[arg(long)]
+        apply: bool,
     },
 
     /// Clear all cached repositories
-    Clear,
+    Clear {
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Remove least recently used caches beyond the size limit ([cache] max_size)
+    Gc {
+        /// Size limit to clean up to (e.g., 500MB), instead of [cache] max_size
+        #[arg(long, value_name = "SIZE")]
+        max_size: Option<String>,
+
+        /// Only show which caches would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommands {
+    /// Print the repository list as TOML
+    Export {
+        /// Write to a file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Merge a repository list exported on another machine
+    Import {
+        /// File written by `aidot config export`
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// What to do when a repository name is already registered
+        #[arg(long, value_enum, default_value_t = ImportConflict::Skip)]
+        on_conflict: ImportConflict,
+
+        /// Replace a local path prefix (e.g., /home/alice=/Users/alice), repeatable
+        #[arg(long, value_name = "OLD=NEW")]
+        remap: Vec<String>,
+    },
 }
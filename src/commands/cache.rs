@@ -1,11 +1,21 @@
+use super::pull::{confirm, pull_preset, PullOptions, PullTarget};
 use crate::cache;
 use crate::config::{Config, SourceType};
 use crate::error::Result;
+use crate::git;
+use crate::history::{History, HistoryEntry};
+use crate::output::display_path;
 use colored::Colorize;
+use std::path::PathBuf;
 
 /// Update cached repositories
-pub fn update_cache(repo_name: Option<String>, all: bool) -> Result<()> {
+///
+/// Repositories whose content changed are followed by the projects they were applied
+/// to (from the pull history); with `apply` they are pulled there again after asking.
+pub fn update_cache(repo_name: Option<String>, all: bool, apply: bool) -> Result<()> {
+    cache::require_online("Updating caches")?;
     let config = Config::load()?;
+    let mut changed = Vec::new();
 
     if all {
         // Update all cached repositories (skip local presets)
@@ -43,10 +53,14 @@ pub fn update_cache(repo_name: Option<String>, all: bool) -> Result<()> {
             }
 
             print!("  {} '{}'... ", "↻".cyan(), cache_name.white());
+            let before = cached_revision(&cache_name);
             match cache::update_cache(&cache_name) {
                 Ok(_) => {
                     println!("{}", "done".green());
                     success_count += 1;
+                    if before.is_none() || before != cached_revision(&cache_name) {
+                        changed.push(cache_name);
+                    }
                 }
                 Err(e) => {
                     println!("{}", "failed".red());
@@ -93,12 +107,16 @@ pub fn update_cache(repo_name: Option<String>, all: bool) -> Result<()> {
             "Updating cache for".cyan(),
             name.white().bold()
         );
+        let before = cached_revision(&name);
         cache::update_cache(&name)?;
         println!(
             "{} Cache '{}' updated successfully",
             "✓".green(),
             name.white().bold()
         );
+        if before.is_none() || before != cached_revision(&name) {
+            changed.push(name);
+        }
     } else {
         eprintln!(
             "{} Specify a repository name or use --all",
@@ -112,13 +130,206 @@ pub fn update_cache(repo_name: Option<String>, all: bool) -> Result<()> {
         std::process::exit(1);
     }
 
+    report_affected_projects(&changed, apply)
+}
+
+/// Commit of a cached Git clone (`None` for archives, whose updates cannot be told apart)
+fn cached_revision(name: &str) -> Option<String> {
+    let path = cache::get_cache_path(name).ok()?;
+    git::head_commit(&path).ok().map(|(_, id)| id)
+}
+
+/// List the projects that applied an updated repository, and pull it there with `apply`
+fn report_affected_projects(updated: &[String], apply: bool) -> Result<()> {
+    // Projects that were moved or deleted are already dropped by load
+    let history = History::load()?;
+    for name in updated {
+        let projects = history.projects_using(name);
+        if projects.is_empty() {
+            continue;
+        }
+        println!(
+            "\n{} '{}' preset was updated. Run '{}' in the affected projects:",
+            "→".cyan(),
+            name.white().bold(),
+            format!("aidot pull {}", name).cyan()
+        );
+        for entry in &projects {
+            println!(
+                "  {} {} {}",
+                "•".cyan(),
+                entry.project.white(),
+                format!("(last pulled {})", entry.pulled[name.as_str()]).dimmed()
+            );
+        }
+        if apply {
+            reapply(name, &projects)?;
+        }
+    }
+    Ok(())
+}
+
+/// Pull `name` again in each recorded project that still exists (`cache update --apply`)
+///
+/// Asks once for all projects; conflicts are asked about per file as in pull.
+fn reapply(name: &str, projects: &[&HistoryEntry]) -> Result<()> {
+    let dirs: Vec<PathBuf> = projects
+        .iter()
+        .map(|entry| PathBuf::from(&entry.project))
+        .filter(|dir| dir.is_dir())
+        .collect();
+    if dirs.is_empty() {
+        return Ok(());
+    }
+    let prompt = format!(
+        "Pull '{}' again in {} project(s)? This changes files outside the current directory.",
+        name,
+        dirs.len()
+    );
+    println!();
+    if !confirm(&prompt)? {
+        println!("{}", "Re-apply skipped.".yellow());
+        return Ok(());
+    }
+
+    for dir in dirs {
+        println!("\n{} {}", "═══".cyan(), display_path(&dir).white().bold());
+        let target = PullTarget::at(dir);
+        match pull_preset(name.to_string(), &target, &PullOptions::default()) {
+            Ok(summary) if summary.aborted => break,
+            Ok(_) => {}
+            Err(e) => eprintln!("  {} {}", "Error:".red(), e),
+        }
+    }
     Ok(())
 }
 
 /// Clear all cached repositories
-pub fn clear_cache() -> Result<()> {
-    println!("{}", "Clearing all cached repositories...".cyan());
+pub fn clear_cache(yes: bool) -> Result<()> {
+    let caches = cache::list_caches()?;
+    if caches.is_empty() {
+        println!("{}", "No cached repositories found.".yellow());
+        return Ok(());
+    }
+
+    println!(
+        "{} {} {} {}",
+        "Cached repositories:".cyan(),
+        caches.len().to_string().white().bold(),
+        "repositories,".cyan(),
+        format_size(cache::cache_size()?).white().bold()
+    );
+    for name in &caches {
+        println!("  {} {}", "•".cyan(), name.white());
+    }
+    println!(
+        "{}",
+        "Local presets are not cached and are unaffected. Cleared repositories are cloned again on the next pull."
+            .dimmed()
+    );
+    println!();
+
+    if !yes && !confirm("Delete all cached repositories?")? {
+        println!("{}", "Operation cancelled.".yellow());
+        return Ok(());
+    }
+
     cache::clear_all_caches()?;
     println!("{} {}", "✓".green(), "All caches cleared".green().bold());
     Ok(())
 }
+
+/// Remove caches beyond the size limit, least recently used first
+///
+/// The limit is `--max-size` or `[cache] max_size` in ~/.aidot/config.toml. Without
+/// either, only caches of URLs given directly to pull/diff are removed. Removed
+/// clones are cloned again on the next pull.
+pub fn gc_cache(max_size: Option<String>, dry_run: bool) -> Result<()> {
+    let limit = match max_size {
+        Some(size) => Some(size),
+        None => Config::load()?.cache.max_size,
+    };
+    let max_bytes = limit.as_deref().map(cache::parse_size).transpose()?;
+
+    let entries = cache::cache_entries()?;
+    let total: u64 = entries.iter().map(|entry| entry.size).sum();
+    match &limit {
+        Some(limit) => println!(
+            "{} {} {}",
+            "Cache size:".cyan(),
+            format_size(total).white().bold(),
+            format!("(limit {})", limit).dimmed()
+        ),
+        None => println!(
+            "{} {} {}",
+            "Cache size:".cyan(),
+            format_size(total).white().bold(),
+            "(no cache.max_size set: removing caches of unregistered URLs only)".dimmed()
+        ),
+    }
+
+    let evicted = cache::select_evictions(&entries, max_bytes, None);
+    if evicted.is_empty() {
+        println!("{}", "Nothing to clean up.".green());
+        return Ok(());
+    }
+
+    let mut freed = 0;
+    for entry in &evicted {
+        if !dry_run {
+            cache::remove_cache(&entry.name)?;
+        }
+        freed += entry.size;
+        let kind = if entry.registered {
+            "registered"
+        } else {
+            "unregistered URL"
+        };
+        println!(
+            "  {} {} {}",
+            if dry_run {
+                "would remove".yellow()
+            } else {
+                "removed".red()
+            },
+            entry.name.white(),
+            format!("({}, {})", format_size(entry.size), kind).dimmed()
+        );
+    }
+    println!(
+        "\n{} {} {} {}",
+        "✓".green(),
+        if dry_run { "Would free" } else { "Freed" },
+        format_size(freed).white().bold(),
+        "(removed caches are cloned again on the next pull)".dimmed()
+    );
+    Ok(())
+}
+
+/// Human-readable byte size (e.g., "1.5 MB")
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MB");
+    }
+}
@@ -0,0 +1,338 @@
+use crate::config::{Config, Repository, SourceType};
+use crate::error::{AidotError, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How to handle an imported repository whose name is already registered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ImportConflict {
+    /// Keep the existing repository
+    #[default]
+    Skip,
+    /// Replace the existing repository
+    Overwrite,
+    /// Import under a new name (e.g., "team-2")
+    Rename,
+}
+
+/// Portable subset of ~/.aidot/config.toml written by `aidot config export`
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ExportedConfig {
+    #[serde(default)]
+    repositories: Vec<Repository>,
+}
+
+/// Outcome of merging imported repositories
+#[derive(Debug, Default, PartialEq)]
+struct ImportSummary {
+    added: Vec<String>,
+    overwritten: Vec<String>,
+    /// (imported name, new name)
+    renamed: Vec<(String, String)>,
+    skipped: Vec<String>,
+    /// Local presets whose path doesn't exist on this machine
+    missing_paths: Vec<(String, String)>,
+    /// Imported repositories whose hooks were trusted in the exported file
+    untrusted: Vec<String>,
+}
+
+/// Print the repository list as TOML (to stdout or `output`)
+///
+/// Machine-specific state (`cached_at`) and hook trust are left out.
+pub fn export_config(output: Option<PathBuf>) -> Result<()> {
+    let config = Config::load()?;
+    let exported = ExportedConfig {
+        repositories: config
+            .repositories
+            .into_iter()
+            .map(|repo| Repository {
+                cached_at: None,
+                trust_hooks: false,
+                ..repo
+            })
+            .collect(),
+    };
+    let content = toml::to_string_pretty(&exported)?;
+
+    match output {
+        Some(path) => {
+            fs::write(&path, content)?;
+            eprintln!(
+                "{} {} repositories exported to {}",
+                "✓".green(),
+                exported.repositories.len().to_string().white().bold(),
+                path.display().to_string().white()
+            );
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+/// Merge repositories from an exported file into the global config
+///
+/// `remaps` are `OLD=NEW` path prefixes applied to local presets, since absolute
+/// paths usually differ between machines.
+pub fn import_config(file: &Path, on_conflict: ImportConflict, remaps: &[String]) -> Result<()> {
+    let content = fs::read_to_string(file)?;
+    let imported: ExportedConfig = toml::from_str(&content)?;
+    let remaps = remaps
+        .iter()
+        .map(|r| parse_remap(r))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut config = Config::load()?;
+    let summary = merge_repositories(
+        &mut config.repositories,
+        imported.repositories,
+        on_conflict,
+        &remaps,
+    );
+    config.save()?;
+
+    for name in &summary.added {
+        println!("  {} {}", "+".green(), name.white());
+    }
+    for name in &summary.overwritten {
+        println!(
+            "  {} {} {}",
+            "~".yellow(),
+            name.white(),
+            "(overwritten)".dimmed()
+        );
+    }
+    for (from, to) in &summary.renamed {
+        println!(
+            "  {} {} {}",
+            "+".green(),
+            to.white(),
+            format!("(renamed from '{}')", from).dimmed()
+        );
+    }
+    for name in &summary.skipped {
+        println!(
+            "  {} {} {}",
+            "⊘".yellow(),
+            name.white(),
+            "(already registered, skipped)".dimmed()
+        );
+    }
+
+    println!(
+        "\n{} {} added, {} overwritten, {} renamed, {} skipped",
+        "✓".green(),
+        summary.added.len(),
+        summary.overwritten.len(),
+        summary.renamed.len(),
+        summary.skipped.len()
+    );
+
+    if !summary.missing_paths.is_empty() {
+        println!(
+            "\n{} {}",
+            "Warning:".yellow().bold(),
+            "local preset paths not found on this machine:".yellow()
+        );
+        for (name, path) in &summary.missing_paths {
+            println!("    {} {}", name.white(), path.dimmed());
+        }
+        println!(
+            "  {} Re-import with {} to map paths",
+            "Tip:".cyan(),
+            "--remap <OLD_PREFIX>=<NEW_PREFIX>".white().bold()
+        );
+    }
+
+    if !summary.untrusted.is_empty() {
+        println!(
+            "\n{} {}",
+            "Note:".cyan().bold(),
+            "hooks are not trusted after import; trust them again if needed:".cyan()
+        );
+        for name in &summary.untrusted {
+            println!("    {}", format!("aidot repo trust-hooks {}", name).white());
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse an `OLD=NEW` path prefix mapping
+fn parse_remap(remap: &str) -> Result<(String, String)> {
+    match remap.split_once('=') {
+        Some((old, new)) if !old.is_empty() => Ok((old.to_string(), new.to_string())),
+        _ => Err(AidotError::InvalidInput(format!(
+            "Invalid --remap '{}': expected <OLD_PREFIX>=<NEW_PREFIX>",
+            remap
+        ))),
+    }
+}
+
+/// Strip `prefix` from `path` when it covers whole path components
+///
+/// `/home/a` matches `/home/a` and `/home/a/x`, but not `/home/alice`.
+fn strip_path_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    let rest = path.strip_prefix(prefix)?;
+    let on_boundary =
+        rest.is_empty() || rest.starts_with(['/', '\\']) || prefix.ends_with(['/', '\\']);
+    on_boundary.then_some(rest)
+}
+
+fn merge_repositories(
+    existing: &mut Vec<Repository>,
+    incoming: Vec<Repository>,
+    on_conflict: ImportConflict,
+    remaps: &[(String, String)],
+) -> ImportSummary {
+    let mut summary = ImportSummary::default();
+
+    for mut repo in incoming {
+        repo.cached_at = None;
+        // Trusting hooks runs their commands on pull; an imported file can't grant that
+        let was_trusted = std::mem::take(&mut repo.trust_hooks);
+        if repo.source_type == SourceType::Local {
+            if let Some((new, rest)) = remaps
+                .iter()
+                .find_map(|(old, new)| Some((new, strip_path_prefix(&repo.url, old)?)))
+            {
+                repo.url = format!("{}{}", new, rest);
+            }
+            if !Path::new(&repo.url).exists() {
+                summary
+                    .missing_paths
+                    .push((repo.name.clone(), repo.url.clone()));
+            }
+        }
+
+        let Some(index) = existing.iter().position(|r| r.name == repo.name) else {
+            if was_trusted {
+                summary.untrusted.push(repo.name.clone());
+            }
+            summary.added.push(repo.name.clone());
+            existing.push(repo);
+            continue;
+        };
+
+        match on_conflict {
+            ImportConflict::Skip => summary.skipped.push(repo.name),
+            ImportConflict::Overwrite => {
+                if was_trusted {
+                    summary.untrusted.push(repo.name.clone());
+                }
+                summary.overwritten.push(repo.name.clone());
+                existing[index] = repo;
+            }
+            ImportConflict::Rename => {
+                let new_name = (2..)
+                    .map(|n| format!("{}-{}", repo.name, n))
+                    .find(|name| !existing.iter().any(|r| &r.name == name))
+                    .unwrap();
+                if was_trusted {
+                    summary.untrusted.push(new_name.clone());
+                }
+                summary.renamed.push((repo.name, new_name.clone()));
+                repo.name = new_name;
+                existing.push(repo);
+            }
+        }
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(name: &str, url: &str, source_type: SourceType) -> Repository {
+        Repository {
+            name: name.to_string(),
+            url: url.to_string(),
+            source_type,
+            default: false,
+            cached_at: Some("2026-01-12T10:00:00Z".to_string()),
+            description: None,
+            subdir: None,
+            sha256: None,
+            trust_hooks: false,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_merge_repositories_conflicts() {
+        let incoming = || {
+            vec![
+                repo("team", "https://example.com/new.git", SourceType::Git),
+                repo("common", "https://example.com/common.git", SourceType::Git),
+            ]
+        };
+        let base = || vec![repo("team", "https://example.com/old.git", SourceType::Git)];
+
+        let mut existing = base();
+        let summary = merge_repositories(&mut existing, incoming(), ImportConflict::Skip, &[]);
+        assert_eq!(summary.skipped, vec!["team"]);
+        assert_eq!(summary.added, vec!["common"]);
+        assert_eq!(existing[0].url, "https://example.com/old.git");
+        assert!(existing[1].cached_at.is_none());
+
+        let mut existing = base();
+        merge_repositories(&mut existing, incoming(), ImportConflict::Overwrite, &[]);
+        assert_eq!(existing[0].url, "https://example.com/new.git");
+
+        let mut existing = base();
+        existing.push(repo("team-2", "https://example.com/2.git", SourceType::Git));
+        let summary = merge_repositories(&mut existing, incoming(), ImportConflict::Rename, &[]);
+        assert_eq!(
+            summary.renamed,
+            vec![("team".to_string(), "team-3".to_string())]
+        );
+        assert_eq!(existing.len(), 4);
+    }
+
+    #[test]
+    fn test_merge_repositories_drops_hook_trust() {
+        let mut trusted = repo("team", "https://example.com/team.git", SourceType::Git);
+        trusted.trust_hooks = true;
+
+        let mut existing = Vec::new();
+        let summary = merge_repositories(&mut existing, vec![trusted], ImportConflict::Skip, &[]);
+
+        assert!(!existing[0].trust_hooks);
+        assert_eq!(summary.untrusted, vec!["team"]);
+    }
+
+    #[test]
+    fn test_merge_repositories_remaps_local_paths() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let new_prefix = temp_dir.path().to_string_lossy().to_string();
+        std::fs::create_dir_all(temp_dir.path().join("dev")).unwrap();
+
+        let incoming = vec![
+            repo("dev", "/home/alice/presets/dev", SourceType::Local),
+            repo("gone", "/home/alice/elsewhere/x", SourceType::Local),
+            repo("other", "/home/alice/presets-old/x", SourceType::Local),
+        ];
+        let remaps = vec![parse_remap(&format!("/home/alice/presets={}", new_prefix)).unwrap()];
+
+        let mut existing = Vec::new();
+        let summary = merge_repositories(&mut existing, incoming, ImportConflict::Skip, &remaps);
+
+        assert_eq!(existing[0].url, format!("{}/dev", new_prefix));
+        // The prefix must end on a path separator
+        assert_eq!(existing[2].url, "/home/alice/presets-old/x");
+        assert_eq!(
+            summary.missing_paths,
+            vec![
+                ("gone".to_string(), "/home/alice/elsewhere/x".to_string()),
+                ("other".to_string(), "/home/alice/presets-old/x".to_string()),
+            ]
+        );
+        assert_eq!(strip_path_prefix("C:\\a\\b", "C:\\a"), Some("\\b"));
+        assert_eq!(strip_path_prefix("/home/a/", "/home/a/"), Some(""));
+        assert_eq!(strip_path_prefix("/home/alice", "/home/a"), None);
+        assert!(parse_remap("no-separator").is_err());
+    }
+}
@@ -1,10 +1,12 @@
-use crate::adapters::detector::get_detected_tool_names;
+use crate::adapters::detector::{get_detected_tool_names, get_global_tool_configs};
 use crate::error::Result;
 use colored::Colorize;
 use std::env;
 
 /// Detect installed LLM tools
-pub fn detect_tools() -> Result<()> {
+///
+/// With `global`, user-level configuration in the home directory is listed as well.
+pub fn detect_tools(global: bool) -> Result<()> {
     let current_dir = env::current_dir()?;
 
     println!(
@@ -23,7 +25,7 @@ pub fn detect_tools() -> Result<()> {
                 "  {} {} {}",
                 "✓".green(),
                 tool.name.white().bold(),
-                "(detected)".green()
+                format!("({})", tool.reason).green()
             );
             detected_count += 1;
         } else {
@@ -31,7 +33,7 @@ pub fn detect_tools() -> Result<()> {
                 "  {} {} {}",
                 "✗".red(),
                 tool.name.dimmed(),
-                "(not detected)".dimmed()
+                format!("(not detected: {})", tool.reason).dimmed()
             );
         }
     }
@@ -56,6 +58,12 @@ pub fn detect_tools() -> Result<()> {
             "•".cyan(),
             "GitHub Copilot".white()
         );
+        println!(
+            "  {} Create a {} directory for {}",
+            "•".cyan(),
+            ".amazonq".white(),
+            "Amazon Q Developer".white()
+        );
     } else {
         println!(
             "{} {} {}",
@@ -65,5 +73,31 @@ pub fn detect_tools() -> Result<()> {
         );
     }
 
+    if global {
+        print_global_tools();
+    }
+
     Ok(())
 }
+
+/// List the global configuration directory of each tool
+fn print_global_tools() {
+    println!("\n{}\n", "Global configuration (home directory):".cyan());
+    for config in get_global_tool_configs() {
+        if config.exists {
+            println!(
+                "  {} {} {}",
+                "✓".green(),
+                config.name.white().bold(),
+                format!("({})", config.display).green()
+            );
+        } else {
+            println!(
+                "  {} {} {}",
+                "✗".red(),
+                config.name.dimmed(),
+                format!("(not found: {})", config.display).dimmed()
+            );
+        }
+    }
+}
@@ -1,66 +1,249 @@
-use crate::adapters::detector::detect_tools;
-use crate::adapters::normalize_content;
+use super::pull::simulate_apply;
+use crate::adapters::common::lacks_executable;
+use crate::adapters::conflict::ApplyContext;
+use crate::adapters::content_matches;
+use crate::adapters::detector::{all_tools, detect_tools, resolve_tool_filter, select_tools};
+use crate::adapters::helpers::normalize_content;
+use crate::adapters::traits::{ProjectMarkers, SECTIONS};
 use crate::error::Result;
-use crate::preset::parser::parse_preset;
+use crate::manifest::Manifest;
+use crate::output::display_path;
+use crate::preset::config::Normalize;
+use crate::preset::parser::parse_preset_cached;
 use crate::repository::resolve_repository_source;
 use colored::Colorize;
+use serde::Serialize;
+use similar::{DiffTag, TextDiff};
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
 use std::path::Path;
 
+/// File counts for one preset section (or the whole preset)
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct DiffCounts {
+    pub new: usize,
+    pub modified: usize,
+    /// Same content, but the preset file is executable and the applied copy is not
+    pub mode_changed: usize,
+    pub unchanged: usize,
+    pub orphaned: usize,
+}
+
+impl DiffCounts {
+    /// Compact form, omitting zero counts (e.g., "+2 ~1", "=3", "*1" for mode changes)
+    fn compact(&self) -> String {
+        let parts: Vec<String> = [
+            ("+", self.new),
+            ("~", self.modified),
+            ("*", self.mode_changed),
+            ("=", self.unchanged),
+            ("!", self.orphaned),
+        ]
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(sign, count)| format!("{}{}", sign, count))
+        .collect();
+        parts.join(" ")
+    }
+}
+
+/// Totals of a diff run, also aggregated per preset section
+#[derive(Debug, Default, Serialize)]
+pub struct DiffSummary {
+    pub preset: String,
+    #[serde(flatten)]
+    pub total: DiffCounts,
+    /// Counts per section (only filled with --by-section)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub sections: BTreeMap<String, DiffCounts>,
+}
+
+/// Status of a single file compared with the preset
+#[derive(Debug, Clone, Copy)]
+enum DiffStatus {
+    New,
+    Modified,
+    ModeChanged,
+    Unchanged,
+    Orphaned,
+}
+
+impl DiffSummary {
+    fn record(&mut self, section: &str, status: DiffStatus, by_section: bool) {
+        let mut counts = vec![&mut self.total];
+        if by_section {
+            counts.push(self.sections.entry(section.to_string()).or_default());
+        }
+        for counts in counts {
+            match status {
+                DiffStatus::New => counts.new += 1,
+                DiffStatus::Modified => counts.modified += 1,
+                DiffStatus::ModeChanged => counts.mode_changed += 1,
+                DiffStatus::Unchanged => counts.unchanged += 1,
+                DiffStatus::Orphaned => counts.orphaned += 1,
+            }
+        }
+    }
+}
+
+/// Options for `aidot diff`
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// Compare only these tools, detected or not (--tools)
+    pub tools_filter: Option<Vec<String>>,
+    /// Break the summary down per preset section (--by-section)
+    pub by_section: bool,
+    /// Print only the summary as JSON (--json)
+    pub json: bool,
+}
+
+/// Per-tool file lists for display
+struct ToolDiff {
+    name: String,
+    new_files: Vec<String>,
+    modified_files: Vec<(String, Option<String>)>,
+    mode_changed_files: Vec<String>,
+    unchanged_files: Vec<String>,
+}
+
 /// Show diff between preset and current configuration
-pub fn show_diff(repo_source: String) -> Result<()> {
+pub fn show_diff(repo_source: String, options: DiffOptions) -> Result<()> {
     let target_dir = std::env::current_dir()?;
 
     // Resolve repository source
     let preset_path = resolve_repository_source(&repo_source)?;
 
     // Parse preset
-    let (_config, preset_files) = parse_preset(&preset_path)?;
+    let (config, mut preset_files) = parse_preset_cached(&preset_path)?;
+    preset_files.apply_normalize(config.apply.normalize);
+    let mut ctx = ApplyContext::default();
+    ctx.json_final_newline = config.apply.normalize != Normalize::None;
+    preset_files.apply_line_endings(config.apply.line_endings);
+    let skipped = preset_files.retain_conditions(&config.when, &target_dir);
 
-    println!(
-        "{} '{}'\n",
-        "Comparing preset".cyan().bold(),
-        repo_source.white()
-    );
+    let mut summary = DiffSummary {
+        preset: repo_source.clone(),
+        ..Default::default()
+    };
 
-    // Detect tools
-    let tools = detect_tools(&target_dir);
+    // Detected tools, or the ones chosen with --tools (same selection as pull)
+    let tools = match &options.tools_filter {
+        Some(filter) => select_tools(
+            all_tools(&target_dir, &config.tools),
+            &resolve_tool_filter(filter)?,
+        ),
+        None => detect_tools(&target_dir, &config.tools),
+    };
 
-    if tools.is_empty() {
+    if !options.json {
         println!(
-            "{} {}",
-            "⚠".yellow(),
-            "No LLM tools detected in current directory.".yellow()
+            "{} '{}' {}\n",
+            "Comparing preset".cyan().bold(),
+            repo_source.white(),
+            format!("({})", display_path(&preset_path)).dimmed()
         );
-        return Ok(());
+        for (path, reason) in &skipped {
+            println!(
+                "{} {} {}",
+                "Skipped".dimmed(),
+                path.white(),
+                format!("({})", reason).dimmed()
+            );
+        }
+        if !skipped.is_empty() {
+            println!();
+        }
+        if options.tools_filter.is_some() {
+            let markers = ProjectMarkers::scan(&target_dir);
+            for tool in tools.iter().filter(|tool| !tool.detect(&markers)) {
+                println!(
+                    "{} {}",
+                    tool.name().white(),
+                    "is not set up in this project; its files would be created.".dimmed()
+                );
+            }
+        }
     }
 
-    let mut total_new = 0;
-    let mut total_modified = 0;
-    let mut total_unchanged = 0;
+    if tools.is_empty() {
+        if options.json {
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        } else if options.tools_filter.is_some() {
+            println!(
+                "{} {}",
+                "⚠".yellow(),
+                "No tools selected by --tools.".yellow()
+            );
+        } else {
+            println!(
+                "{} {}",
+                "⚠".yellow(),
+                "No LLM tools detected in current directory.".yellow()
+            );
+            println!(
+                "Use '{}' to compare tools that are not set up yet.",
+                "aidot diff <repo> --tools claude".cyan()
+            );
+        }
+        return Ok(());
+    }
 
-    for tool in &tools {
-        println!("{}", format!("═══ {} ═══", tool.name()).cyan().bold());
+    // Preset files whose executable bit apply carries over (always empty on Windows)
+    let executable_sources: HashSet<&str> = SECTIONS
+        .iter()
+        .flat_map(|section| preset_files.section(section))
+        .filter(|file| file.executable)
+        .map(|file| file.relative_path.as_str())
+        .collect();
 
-        // Use scan() to compute changes (handles all adapter-specific logic)
-        let scan = tool.scan(&preset_files, &target_dir);
+    // Every path the preset currently produces (for orphan detection)
+    let mut current_paths: HashSet<String> = preset_files
+        .root
+        .iter()
+        .map(|file| file.relative_path.clone())
+        .collect();
 
-        if scan.changes.is_empty() {
-            println!("  {} No preset files for this tool\n", "○".dimmed());
-            continue;
+    // Use scan() to compute changes (handles all adapter-specific logic)
+    let mut all_changes = Vec::new();
+    for tool in &tools {
+        for change in tool.scan(&preset_files, &target_dir).changes {
+            current_paths.insert(change.path.clone());
+            all_changes.push((tool.name().to_string(), change));
         }
+    }
+    // Merged files (memory, mcp, hooks, settings) are only known after merging: run the
+    // real apply with simulated writes, as `pull --dry-run` does
+    let line_stats = simulate_apply(&preset_files, &tools, &target_dir, &ctx, &mut all_changes)?;
 
-        let mut new_files = Vec::new();
-        let mut modified_files: Vec<(String, Option<String>)> = Vec::new();
-        let mut unchanged_files = Vec::new();
+    let mut tool_diffs = Vec::new();
+    for tool in &tools {
+        let mut tool_diff = ToolDiff {
+            name: tool.name().to_string(),
+            new_files: Vec::new(),
+            modified_files: Vec::new(),
+            mode_changed_files: Vec::new(),
+            unchanged_files: Vec::new(),
+        };
 
-        for change in &scan.changes {
-            if !change.is_conflict {
+        for (_, change) in all_changes.iter().filter(|(name, _)| name == tool.name()) {
+            let status = if !change.is_conflict {
                 // File doesn't exist → new
-                new_files.push(change.path.clone());
+                tool_diff.new_files.push(change.path.clone());
+                DiffStatus::New
+            } else if change.is_identical
+                && change
+                    .source
+                    .as_deref()
+                    .is_some_and(|source| executable_sources.contains(source))
+                && lacks_executable(&target_dir.join(&change.path))
+            {
+                // Same content, but pull would add the executable bit → mode changed
+                tool_diff.mode_changed_files.push(change.path.clone());
+                DiffStatus::ModeChanged
             } else if change.is_identical {
                 // File exists with same content → unchanged
-                unchanged_files.push(change.path.clone());
+                tool_diff.unchanged_files.push(change.path.clone());
+                DiffStatus::Unchanged
             } else {
                 // File exists with different content → modified
                 let diff_info = change
@@ -70,84 +253,259 @@ pub fn show_diff(repo_source: String) -> Result<()> {
                         let full_path = target_dir.join(&change.path);
                         compute_diff_info(&full_path, preset_content)
                     })
+                    .or_else(|| {
+                        line_stats
+                            .get(&(tool_diff.name.clone(), change.path.clone()))
+                            .map(|(added, removed)| format!("+{} -{}", added, removed))
+                    })
                     .or_else(|| Some("will be updated".to_string()));
-                modified_files.push((change.path.clone(), diff_info));
-            }
+                tool_diff
+                    .modified_files
+                    .push((change.path.clone(), diff_info));
+                DiffStatus::Modified
+            };
+            summary.record(&change.section, status, options.by_section);
         }
+        tool_diffs.push(tool_diff);
+    }
 
-        // New files (would be created)
-        if !new_files.is_empty() {
-            println!("  {} New files:", "+".green().bold());
-            for file in &new_files {
-                println!("    {} {}", "+".green(), file.white());
-            }
-            total_new += new_files.len();
-        }
+    // Orphaned files (applied before, but removed from the preset since)
+    let tool_names: Vec<&str> = std::iter::once("Root")
+        .chain(tools.iter().map(|tool| tool.name()))
+        .collect();
+    let orphans: Vec<_> = Manifest::load(&target_dir)?
+        .orphans(&repo_source, &tool_names, &current_paths)
+        .into_iter()
+        .filter(|entry| target_dir.join(&entry.path).exists())
+        .collect();
+    for entry in &orphans {
+        summary.record(&entry.section, DiffStatus::Orphaned, options.by_section);
+    }
 
-        // Modified files (content differs)
-        if !modified_files.is_empty() {
-            println!("  {} Modified files:", "~".yellow().bold());
-            for (file, diff_info) in &modified_files {
-                println!("    {} {}", "~".yellow(), file.white());
-                if let Some(info) = diff_info {
-                    println!("      {}", info.dimmed());
-                }
-            }
-            total_modified += modified_files.len();
-        }
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
 
-        // Unchanged files
-        if !unchanged_files.is_empty() {
-            println!("  {} Unchanged files:", "=".dimmed());
-            for file in &unchanged_files {
-                println!("    {} {}", "=".dimmed(), file.dimmed());
-            }
-            total_unchanged += unchanged_files.len();
-        }
+    for tool_diff in &tool_diffs {
+        print_tool_diff(tool_diff);
+    }
 
+    if !orphans.is_empty() {
+        println!("{}", "═══ Orphaned ═══".cyan().bold());
+        println!("  {} Removed from preset:", "!".red().bold());
+        for entry in &orphans {
+            println!(
+                "    {} {} {}",
+                "!".red(),
+                entry.path.white(),
+                format!("[{}]", entry.tool).dimmed()
+            );
+        }
         println!();
     }
 
     // Summary
+    let total = &summary.total;
     println!("{}", "═══ Summary ═══".cyan().bold());
-    println!(
+    print!(
         "  {} {} new, {} {} modified, {} {} unchanged",
-        total_new.to_string().green().bold(),
+        total.new.to_string().green().bold(),
         "files".green(),
-        total_modified.to_string().yellow().bold(),
+        total.modified.to_string().yellow().bold(),
         "files".yellow(),
-        total_unchanged.to_string().dimmed(),
+        total.unchanged.to_string().dimmed(),
         "files".dimmed()
     );
+    if total.mode_changed > 0 {
+        print!(
+            ", {} {} mode changed",
+            total.mode_changed.to_string().cyan().bold(),
+            "files".cyan()
+        );
+    }
+    if total.orphaned > 0 {
+        print!(
+            ", {} {} orphaned",
+            total.orphaned.to_string().red().bold(),
+            "files".red()
+        );
+    }
+    println!();
+
+    if options.by_section && !summary.sections.is_empty() {
+        let width = summary.sections.keys().map(String::len).max().unwrap_or(0);
+        for (section, counts) in &summary.sections {
+            println!(
+                "    {}  {}",
+                format!("{:<width$}", format!("{}:", section), width = width + 1).white(),
+                counts.compact()
+            );
+        }
+    }
 
-    if total_new > 0 || total_modified > 0 {
+    if total.new > 0 || total.modified > 0 || total.mode_changed > 0 {
+        let pull_command = match &options.tools_filter {
+            Some(filter) => format!("aidot pull {} --tools {}", repo_source, filter.join(",")),
+            None => format!("aidot pull {}", repo_source),
+        };
         println!(
             "\n  {} Run {} to apply changes",
             "Tip:".cyan(),
-            format!("aidot pull {}", repo_source).white().bold()
+            pull_command.white().bold()
+        );
+    }
+    if total.orphaned > 0 {
+        println!(
+            "  {} Run {} to remove orphaned files",
+            "Tip:".cyan(),
+            format!("aidot pull {} --prune", repo_source).white().bold()
         );
     }
 
     Ok(())
 }
 
-/// Compute diff info (line count comparison) between an existing file and preset content
+/// Print the new / modified / unchanged files of one tool
+fn print_tool_diff(tool_diff: &ToolDiff) {
+    println!("{}", format!("═══ {} ═══", tool_diff.name).cyan().bold());
+
+    if tool_diff.new_files.is_empty()
+        && tool_diff.modified_files.is_empty()
+        && tool_diff.mode_changed_files.is_empty()
+        && tool_diff.unchanged_files.is_empty()
+    {
+        println!("  {} No preset files for this tool\n", "○".dimmed());
+        return;
+    }
+
+    // New files (would be created)
+    if !tool_diff.new_files.is_empty() {
+        println!("  {} New files:", "+".green().bold());
+        for file in &tool_diff.new_files {
+            println!("    {} {}", "+".green(), file.white());
+        }
+    }
+
+    // Modified files (content differs)
+    if !tool_diff.modified_files.is_empty() {
+        println!("  {} Modified files:", "~".yellow().bold());
+        for (file, diff_info) in &tool_diff.modified_files {
+            println!("    {} {}", "~".yellow(), file.white());
+            if let Some(info) = diff_info {
+                println!("      {}", info.dimmed());
+            }
+        }
+    }
+
+    // Mode changed files (same content, executable bit missing)
+    if !tool_diff.mode_changed_files.is_empty() {
+        println!("  {} Mode changed files:", "*".cyan().bold());
+        for file in &tool_diff.mode_changed_files {
+            println!("    {} {}", "*".cyan(), file.white());
+            println!("      {}", "executable bit will be set".dimmed());
+        }
+    }
+
+    // Unchanged files
+    if !tool_diff.unchanged_files.is_empty() {
+        println!("  {} Unchanged files:", "=".dimmed());
+        for file in &tool_diff.unchanged_files {
+            println!("    {} {}", "=".dimmed(), file.dimmed());
+        }
+    }
+
+    println!();
+}
+
+/// Compute diff info (added/removed line counts) between an existing file and preset content
 fn compute_diff_info(target_path: &Path, preset_content: &str) -> Option<String> {
     let existing_content = fs::read_to_string(target_path).ok()?;
-    let existing_normalized = normalize_content(&existing_content);
-    let preset_normalized = normalize_content(preset_content);
 
-    if existing_normalized == preset_normalized {
+    if content_matches(&existing_content, preset_content) {
         return None;
     }
 
-    let existing_lines = existing_content.lines().count();
-    let preset_lines = preset_content.lines().count();
-    Some(if preset_lines > existing_lines {
-        format!("+{} lines", preset_lines - existing_lines)
-    } else if existing_lines > preset_lines {
-        format!("-{} lines", existing_lines - preset_lines)
-    } else {
+    let (added, removed) = count_changed_lines(
+        &normalize_content(&existing_content),
+        &normalize_content(preset_content),
+    );
+    Some(if added == 0 && removed == 0 {
         "content differs".to_string()
+    } else {
+        format!("+{} -{}", added, removed)
     })
 }
+
+/// Count added and removed lines from diff ops (no hunk rendering)
+///
+/// Both sides get a trailing newline so a missing final newline doesn't count as a change.
+fn count_changed_lines(old: &str, new: &str) -> (usize, usize) {
+    let old = format!("{}\n", old.trim_end_matches('\n'));
+    let new = format!("{}\n", new.trim_end_matches('\n'));
+    let diff = TextDiff::from_lines(&old, &new);
+    diff.ops()
+        .iter()
+        .fold((0, 0), |(added, removed), op| match op.as_tag_tuple() {
+            (DiffTag::Insert, _, new_range) => (added + new_range.len(), removed),
+            (DiffTag::Delete, old_range, _) => (added, removed + old_range.len()),
+            (DiffTag::Replace, old_range, new_range) => {
+                (added + new_range.len(), removed + old_range.len())
+            }
+            (DiffTag::Equal, _, _) => (added, removed),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_count_changed_lines() {
+        // Same line count, one line changed
+        assert_eq!(count_changed_lines("a\nb\nc", "a\nB\nc"), (1, 1));
+        // Mixed insertion and deletion
+        assert_eq!(count_changed_lines("a\nb\nc", "a\nc\nd\ne"), (2, 1));
+        assert_eq!(count_changed_lines("a\nb", "a\nb"), (0, 0));
+    }
+
+    #[test]
+    fn test_compute_diff_info() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("rule.md");
+
+        fs::write(&target, "# Rule\r\nold line\r\n").unwrap();
+        assert_eq!(
+            compute_diff_info(&target, "# Rule\nnew line\nextra\n").as_deref(),
+            Some("+2 -1")
+        );
+        assert_eq!(compute_diff_info(&target, "# Rule\nold line\n"), None);
+    }
+
+    #[test]
+    fn test_diff_summary_by_section() {
+        let mut summary = DiffSummary::default();
+        summary.record("rules", DiffStatus::New, true);
+        summary.record("rules", DiffStatus::New, true);
+        summary.record("rules", DiffStatus::Modified, true);
+        summary.record("commands", DiffStatus::Unchanged, true);
+        summary.record("commands", DiffStatus::ModeChanged, true);
+
+        assert_eq!(summary.total.new, 2);
+        assert_eq!(summary.sections["rules"].compact(), "+2 ~1");
+        assert_eq!(summary.sections["commands"].compact(), "*1 =1");
+
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["modified"], 1);
+        assert_eq!(json["sections"]["rules"]["new"], 2);
+
+        let mut totals_only = DiffSummary::default();
+        totals_only.record("rules", DiffStatus::Orphaned, false);
+        assert!(totals_only.sections.is_empty());
+        assert!(serde_json::to_value(&totals_only)
+            .unwrap()
+            .get("sections")
+            .is_none());
+    }
+}
@@ -0,0 +1,325 @@
+use super::cache::format_size;
+use super::repo::check_repository;
+use crate::adapters::detector::get_detected_tool_names;
+use crate::cache;
+use crate::config::Config;
+use crate::error::{AidotError, Result};
+use crate::git;
+use colored::Colorize;
+use serde::Serialize;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::Command;
+use std::time::Duration;
+
+/// Host probed by the network check (where most preset repositories live)
+const NETWORK_PROBE: (&str, u16) = ("github.com", 443);
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// One line of the `aidot doctor` report
+#[derive(Debug, Serialize)]
+struct Check {
+    name: String,
+    status: CheckStatus,
+    message: String,
+    /// How to fix a warning or failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: CheckStatus::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: impl Into<String>, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: CheckStatus::Warn,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: impl Into<String>, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Fail,
+            ..Self::warn(name, message, fix)
+        }
+    }
+}
+
+/// Everything `aidot doctor --json` reports (attach it to support requests)
+#[derive(Debug, Serialize)]
+struct DoctorReport {
+    version: &'static str,
+    os: &'static str,
+    checks: Vec<Check>,
+}
+
+/// Check the environment aidot depends on in one go
+///
+/// Covers git, network, ~/.aidot permissions, the global config, registered
+/// repositories, detected tools and the cache. Fails when any check fails.
+pub fn run_doctor(json: bool) -> Result<()> {
+    if !json {
+        println!("{}", "Checking aidot environment...".cyan().bold());
+    }
+
+    let mut checks = vec![check_git(), check_network(), check_aidot_dir()];
+    match check_config() {
+        Ok((check, config)) => {
+            checks.push(check);
+            checks.extend(check_repositories(&config));
+        }
+        Err(check) => checks.push(check),
+    }
+    checks.push(check_tools());
+    checks.extend(check_cache());
+
+    let failed = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Fail)
+        .count();
+    let report = DoctorReport {
+        version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        checks,
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+
+    if failed > 0 {
+        return Err(AidotError::InvalidInput(format!(
+            "{} check(s) failed",
+            failed
+        )));
+    }
+    Ok(())
+}
+
+fn print_report(report: &DoctorReport) {
+    let width = report
+        .checks
+        .iter()
+        .map(|c| c.name.len())
+        .max()
+        .unwrap_or(0);
+    for check in &report.checks {
+        let (mark, message) = match check.status {
+            CheckStatus::Ok => ("✓".green(), check.message.normal()),
+            CheckStatus::Warn => ("!".yellow(), check.message.yellow()),
+            CheckStatus::Fail => ("✗".red(), check.message.red()),
+        };
+        // Pad before coloring: escape codes would count toward the width
+        let name = format!("{:<width$}", check.name, width = width);
+        println!("  {} {}  {}", mark, name.white().bold(), message);
+        if let Some(fix) = &check.fix {
+            println!("      {} {}", "→".dimmed(), fix.cyan());
+        }
+    }
+
+    let count = |status| report.checks.iter().filter(|c| c.status == status).count();
+    println!(
+        "\n{} ok, {} warning(s), {} failure(s)",
+        count(CheckStatus::Ok),
+        count(CheckStatus::Warn),
+        count(CheckStatus::Fail)
+    );
+}
+
+fn check_git() -> Check {
+    if git::check_git_available().is_err() {
+        return Check::fail(
+            "git",
+            "git is not installed or not in PATH (needed for Git repositories)",
+            "install git: https://git-scm.com/downloads",
+        );
+    }
+    let version = Command::new("git")
+        .arg("--version")
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default();
+    Check::ok("git", format!("{}, {}", version, git::libgit2_info()))
+}
+
+/// Whether the host most presets are cloned from can be reached (TCP connect only)
+fn check_network() -> Check {
+    let (host, port) = NETWORK_PROBE;
+    if cache::is_offline() {
+        return Check::ok("network", "not checked (offline mode)");
+    }
+    let reachable = (host, port)
+        .to_socket_addrs()
+        .map(|mut addrs| {
+            addrs.any(|addr| TcpStream::connect_timeout(&addr, NETWORK_TIMEOUT).is_ok())
+        })
+        .unwrap_or(false);
+    if reachable {
+        Check::ok("network", format!("{} is reachable", host))
+    } else {
+        Check::warn(
+            "network",
+            format!("cannot reach {}:{}", host, port),
+            "check your network or proxy settings (local presets still work)",
+        )
+    }
+}
+
+/// ~/.aidot must be writable for the config, cache, state and logs
+fn check_aidot_dir() -> Check {
+    let dir = match Config::config_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            return Check::fail(
+                "~/.aidot",
+                e.to_string(),
+                "set the HOME environment variable",
+            )
+        }
+    };
+    let writable = std::fs::create_dir_all(&dir)
+        .and_then(|_| tempfile::NamedTempFile::new_in(&dir))
+        .map_err(|e| e.to_string());
+    match writable {
+        Ok(_) => Check::ok("~/.aidot", format!("{} is writable", dir.display())),
+        Err(e) => Check::fail(
+            "~/.aidot",
+            format!("{} is not writable ({})", dir.display(), e),
+            format!("check the owner and permissions of {}", dir.display()),
+        ),
+    }
+}
+
+fn check_config() -> std::result::Result<(Check, Config), Check> {
+    let path = Config::config_file()
+        .map_err(|e| Check::fail("config", e.to_string(), "set the HOME environment variable"))?;
+    if !path.exists() {
+        return Ok((
+            Check::ok("config", "not created yet (created on first use)"),
+            Config::default(),
+        ));
+    }
+    match Config::load() {
+        Ok(config) => Ok((
+            Check::ok("config", format!("{} parses", path.display())),
+            config,
+        )),
+        Err(e) => Err(Check::fail(
+            "config",
+            e.to_string(),
+            format!("fix {} or move it aside to start over", path.display()),
+        )),
+    }
+}
+
+fn check_repositories(config: &Config) -> Vec<Check> {
+    if config.repositories.is_empty() {
+        return vec![Check::warn(
+            "repositories",
+            "no repositories registered",
+            "aidot repo add <name> <url>",
+        )];
+    }
+    config
+        .repositories
+        .iter()
+        .map(|repo| {
+            let name = format!("repo {}", repo.name);
+            match check_repository(repo) {
+                None => Check::ok(name, "reachable"),
+                Some(problem) => {
+                    Check::fail(name, problem.message, problem.suggestions.join(" or "))
+                }
+            }
+        })
+        .collect()
+}
+
+fn check_tools() -> Check {
+    let Ok(current_dir) = std::env::current_dir() else {
+        return Check::warn(
+            "tools",
+            "current directory is not accessible",
+            "cd into a project",
+        );
+    };
+    let detected: Vec<String> = get_detected_tool_names(&current_dir)
+        .into_iter()
+        .filter(|tool| tool.detected)
+        .map(|tool| tool.name)
+        .collect();
+    if detected.is_empty() {
+        Check::warn(
+            "tools",
+            format!("no LLM tools detected in {}", current_dir.display()),
+            "run 'aidot detect' for details, or pull with --tools",
+        )
+    } else {
+        Check::ok("tools", detected.join(", "))
+    }
+}
+
+/// Cache size, and cached clones with local edits (their next pull fails)
+fn check_cache() -> Vec<Check> {
+    let caches = match cache::list_caches() {
+        Ok(caches) => caches,
+        Err(e) => return vec![Check::fail("cache", e.to_string(), "aidot cache clear")],
+    };
+    let size = cache::cache_size().unwrap_or(0);
+    let mut checks = vec![Check::ok(
+        "cache",
+        format!(
+            "{} cached repositories ({})",
+            caches.len(),
+            format_size(size)
+        ),
+    )];
+    for name in caches {
+        let Ok(path) = cache::get_cache_path(&name) else {
+            continue;
+        };
+        let changes = git::local_changes(&path).unwrap_or_default();
+        if !changes.is_empty() {
+            checks.push(Check::warn(
+                format!("cache {}", name),
+                format!("{} local change(s) in the cached clone", changes.len()),
+                format!("aidot cache update {} --reset-cache", name),
+            ));
+        }
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_json() {
+        let ok = serde_json::to_value(Check::ok("git", "git version 2.43.0")).unwrap();
+        assert_eq!(ok["status"], "ok");
+        assert!(ok.get("fix").is_none());
+
+        let fail = serde_json::to_value(Check::fail("config", "bad", "fix it")).unwrap();
+        assert_eq!(fail["status"], "fail");
+        assert_eq!(fail["fix"], "fix it");
+    }
+}
@@ -1,4 +1,6 @@
+use super::pull::confirm;
 use crate::error::{AidotError, Result};
+use crate::secret::{self, ENCRYPTED_SUFFIX};
 use colored::Colorize;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -30,12 +32,24 @@ impl ExtractedFiles {
     }
 }
 
+/// Project files showing a directory already holds LLM tool settings
+const TOOL_CONFIG_MARKERS: &[&str] = &[
+    ".claude",
+    ".cursor",
+    ".cursorrules",
+    ".github/copilot-instructions.md",
+    ".github/instructions",
+    ".amazonq",
+];
+
 /// Initialize a new preset repository
 pub fn init_preset(
     path: Option<String>,
     from_existing: bool,
+    encrypt: bool,
     _interactive: bool,
     force: bool,
+    repo_url: Option<&str>,
 ) -> Result<()> {
     let target_dir = if let Some(p) = path {
         PathBuf::from(p)
@@ -50,9 +64,27 @@ pub fn init_preset(
     }
 
     if from_existing {
-        init_from_existing(&target_dir)?;
+        init_from_existing(&target_dir, encrypt, repo_url)?;
     } else {
-        init_empty_preset(&target_dir)?;
+        // An empty preset mixed into a project directory pollutes the project
+        let markers = tool_config_markers(&target_dir);
+        if !markers.is_empty() && !force {
+            println!(
+                "{} {} already has LLM tool settings ({}).",
+                "Warning:".yellow(),
+                target_dir.display(),
+                markers.join(", ")
+            );
+            println!(
+                "  A preset repository usually starts in an empty directory. Did you mean '{}'?",
+                "aidot init --from-existing".cyan()
+            );
+            if !confirm("Create an empty preset here anyway?")? {
+                println!("{}", "Operation cancelled.".yellow());
+                return Ok(());
+            }
+        }
+        init_empty_preset(&target_dir, repo_url)?;
     }
 
     println!(
@@ -63,8 +95,17 @@ pub fn init_preset(
     Ok(())
 }
 
+/// LLM tool settings already present in a directory (e.g., ".claude")
+fn tool_config_markers(dir: &Path) -> Vec<&'static str> {
+    TOOL_CONFIG_MARKERS
+        .iter()
+        .copied()
+        .filter(|marker| dir.join(marker).exists())
+        .collect()
+}
+
 /// Initialize an empty preset repository
-fn init_empty_preset(path: &Path) -> Result<()> {
+fn init_empty_preset(path: &Path, repo_url: Option<&str>) -> Result<()> {
     println!(
         "{}\n",
         "Initializing empty aidot preset repository...".cyan()
@@ -88,7 +129,7 @@ fn init_empty_preset(path: &Path) -> Result<()> {
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("llm-preset");
-    let config_content = create_config_template(preset_name);
+    let config_content = create_config_template(preset_name, repo_url);
     fs::write(path.join(".aidot-config.toml"), config_content)?;
     println!(
         "  {} {} {}",
@@ -98,7 +139,7 @@ fn init_empty_preset(path: &Path) -> Result<()> {
     );
 
     // Create README.md
-    create_readme(path, preset_name)?;
+    create_readme(path, preset_name, repo_url)?;
 
     println!("\n{}", "Preset repository initialized!".green().bold());
     println!("\n{}:", "Next steps".cyan().bold());
@@ -123,14 +164,21 @@ fn init_empty_preset(path: &Path) -> Result<()> {
     println!(
         "  {} Use with: {}",
         "5.".white(),
-        "aidot repo add <name> <url>".cyan()
+        repo_add_command(preset_name, repo_url).cyan()
     );
 
     Ok(())
 }
 
 /// Initialize preset from existing LLM configurations
-fn init_from_existing(path: &Path) -> Result<()> {
+fn init_from_existing(path: &Path, encrypt: bool, repo_url: Option<&str>) -> Result<()> {
+    // Fail before extracting anything when there is no key to encrypt with
+    let secret_key = if encrypt {
+        Some(secret::require_key()?)
+    } else {
+        None
+    };
+
     println!(
         "{}\n",
         "Extracting preset from existing LLM configurations...".cyan()
@@ -154,6 +202,11 @@ fn init_from_existing(path: &Path) -> Result<()> {
         sources_found.push(format!("GitHub Copilot ({} files)", count));
     }
 
+    // Scan for Amazon Q configurations
+    if let Some(count) = extract_amazonq(path, &mut extracted)? {
+        sources_found.push(format!("Amazon Q ({} files)", count));
+    }
+
     if extracted.is_empty() {
         println!(
             "{} {}",
@@ -162,7 +215,7 @@ fn init_from_existing(path: &Path) -> Result<()> {
         );
         println!(
             "  {}",
-            "Looked for: .claude/, .cursor/, .cursorrules, .github/".dimmed()
+            "Looked for: .claude/, .cursor/, .cursorrules, .github/, .amazonq/".dimmed()
         );
         println!(
             "\n  {} {}",
@@ -191,6 +244,18 @@ fn init_from_existing(path: &Path) -> Result<()> {
         }
     }
 
+    // mcp/ and settings/ may hold tokens: keep only their encrypted form
+    if let Some(key) = &secret_key {
+        for (filename, content) in extracted
+            .mcp
+            .iter_mut()
+            .chain(extracted.settings.iter_mut())
+        {
+            *content = secret::encrypt(content, key)?;
+            filename.push_str(ENCRYPTED_SUFFIX);
+        }
+    }
+
     // Write extracted files
     let mut written_count = 0;
 
@@ -208,7 +273,7 @@ fn init_from_existing(path: &Path) -> Result<()> {
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("llm-preset");
-    let config_content = create_config_template(preset_name);
+    let config_content = create_config_template(preset_name, repo_url);
     fs::write(path.join(".aidot-config.toml"), config_content)?;
     println!(
         "  {} {} {}",
@@ -218,7 +283,7 @@ fn init_from_existing(path: &Path) -> Result<()> {
     );
 
     // Create README.md
-    create_readme(path, preset_name)?;
+    create_readme(path, preset_name, repo_url)?;
 
     println!(
         "\n{} {} files extracted from existing configurations",
@@ -247,6 +312,11 @@ fn init_from_existing(path: &Path) -> Result<()> {
         "4.".white(),
         "git init && git add . && git commit -m 'Initial preset'".dimmed()
     );
+    println!(
+        "  {} Push, then use with: {}",
+        "5.".white(),
+        repo_add_command(preset_name, repo_url).cyan()
+    );
 
     Ok(())
 }
@@ -284,6 +354,51 @@ fn write_extracted_files(
     Ok(count)
 }
 
+/// Split Claude settings into mcp/<server>.json files and the remaining settings
+///
+/// Applying the result merges it back into the same JSON: mcpServers is only split
+/// out when it is a non-empty object whose server names survive as file names
+/// (`mcp/<name>.json` → `<name>`); otherwise it stays in the settings file as is.
+fn split_claude_settings(mut json: serde_json::Value) -> (Vec<(String, String)>, Option<String>) {
+    let mut mcp_files = Vec::new();
+    let Some(obj) = json.as_object_mut() else {
+        // Not an object: keep the whole document as settings
+        return (mcp_files, serde_json::to_string_pretty(&json).ok());
+    };
+
+    let splittable = obj
+        .get("mcpServers")
+        .and_then(|servers| servers.as_object())
+        .is_some_and(|servers| {
+            !servers.is_empty() && servers.keys().all(|name| is_file_safe_name(name))
+        });
+    if splittable {
+        if let Some(serde_json::Value::Object(servers)) = obj.remove("mcpServers") {
+            for (name, config) in servers {
+                let mcp_content = serde_json::to_string_pretty(&config).unwrap_or_default();
+                mcp_files.push((format!("{}.json", name), mcp_content));
+            }
+        }
+    }
+
+    let settings = if obj.is_empty() {
+        None
+    } else {
+        serde_json::to_string_pretty(&json).ok()
+    };
+    (mcp_files, settings)
+}
+
+/// Whether an MCP server name can be used as `mcp/<name>.json` and read back unchanged
+fn is_file_safe_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && !name.contains(".json")
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '@'))
+}
+
 /// Extract configurations from Claude Code (.claude/)
 fn extract_claude_code(
     source_path: &Path,
@@ -300,6 +415,7 @@ fn extract_claude_code(
     let rules_dir = claude_dir.join("rules");
     if rules_dir.exists() {
         for entry in WalkDir::new(&rules_dir)
+            .sort_by_file_name()
             .into_iter()
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
@@ -345,6 +461,7 @@ fn extract_claude_code(
     let commands_dir = claude_dir.join("commands");
     if commands_dir.exists() {
         for entry in WalkDir::new(&commands_dir)
+            .sort_by_file_name()
             .into_iter()
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
@@ -369,30 +486,14 @@ fn extract_claude_code(
     if settings_file.exists() {
         if let Ok(content) = fs::read_to_string(&settings_file) {
             if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-                // Extract mcpServers section
-                if let Some(mcp_servers) = json.get("mcpServers") {
-                    if let Some(obj) = mcp_servers.as_object() {
-                        for (name, config) in obj {
-                            let mcp_content =
-                                serde_json::to_string_pretty(config).unwrap_or_default();
-                            extracted.mcp.push((format!("{}.json", name), mcp_content));
-                            count += 1;
-                        }
-                    }
-                }
-
-                // Extract other settings (exclude mcpServers)
-                let mut settings_obj = json.clone();
-                if let Some(obj) = settings_obj.as_object_mut() {
-                    obj.remove("mcpServers");
-                    if !obj.is_empty() {
-                        let settings_content =
-                            serde_json::to_string_pretty(&settings_obj).unwrap_or_default();
-                        extracted
-                            .settings
-                            .push(("claude-settings.json".to_string(), settings_content));
-                        count += 1;
-                    }
+                let (mcp_files, settings) = split_claude_settings(json);
+                count += mcp_files.len();
+                extracted.mcp.extend(mcp_files);
+                if let Some(settings_content) = settings {
+                    extracted
+                        .settings
+                        .push(("claude-settings.json".to_string(), settings_content));
+                    count += 1;
                 }
             }
         }
@@ -413,6 +514,7 @@ fn extract_claude_code(
     let agents_dir = claude_dir.join("agents");
     if agents_dir.exists() {
         for entry in WalkDir::new(&agents_dir)
+            .sort_by_file_name()
             .into_iter()
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
@@ -432,6 +534,7 @@ fn extract_claude_code(
     let skills_dir = claude_dir.join("skills");
     if skills_dir.exists() {
         for entry in WalkDir::new(&skills_dir)
+            .sort_by_file_name()
             .into_iter()
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
@@ -479,6 +582,7 @@ fn extract_cursor(source_path: &Path, extracted: &mut ExtractedFiles) -> Result<
         let rules_dir = cursor_dir.join("rules");
         if rules_dir.exists() {
             for entry in WalkDir::new(&rules_dir)
+                .sort_by_file_name()
                 .into_iter()
                 .filter_map(|e| e.ok())
                 .filter(|e| e.file_type().is_file())
@@ -509,6 +613,7 @@ fn extract_cursor(source_path: &Path, extracted: &mut ExtractedFiles) -> Result<
         let commands_dir = cursor_dir.join("commands");
         if commands_dir.exists() {
             for entry in WalkDir::new(&commands_dir)
+                .sort_by_file_name()
                 .into_iter()
                 .filter_map(|e| e.ok())
                 .filter(|e| e.file_type().is_file())
@@ -561,6 +666,7 @@ fn extract_cursor(source_path: &Path, extracted: &mut ExtractedFiles) -> Result<
         let agents_dir = cursor_dir.join("agents");
         if agents_dir.exists() {
             for entry in WalkDir::new(&agents_dir)
+                .sort_by_file_name()
                 .into_iter()
                 .filter_map(|e| e.ok())
                 .filter(|e| e.file_type().is_file())
@@ -580,6 +686,7 @@ fn extract_cursor(source_path: &Path, extracted: &mut ExtractedFiles) -> Result<
         let skills_dir = cursor_dir.join("skills");
         if skills_dir.exists() {
             for entry in WalkDir::new(&skills_dir)
+                .sort_by_file_name()
                 .into_iter()
                 .filter_map(|e| e.ok())
                 .filter(|e| e.file_type().is_file())
@@ -627,6 +734,7 @@ fn extract_copilot(source_path: &Path, extracted: &mut ExtractedFiles) -> Result
     let instructions_dir = github_dir.join("instructions");
     if instructions_dir.exists() {
         for entry in WalkDir::new(&instructions_dir)
+            .sort_by_file_name()
             .into_iter()
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
@@ -651,6 +759,7 @@ fn extract_copilot(source_path: &Path, extracted: &mut ExtractedFiles) -> Result
     let prompts_dir = github_dir.join("prompts");
     if prompts_dir.exists() {
         for entry in WalkDir::new(&prompts_dir)
+            .sort_by_file_name()
             .into_iter()
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
@@ -695,6 +804,7 @@ fn extract_copilot(source_path: &Path, extracted: &mut ExtractedFiles) -> Result
     let agents_dir = github_dir.join("agents");
     if agents_dir.exists() {
         for entry in WalkDir::new(&agents_dir)
+            .sort_by_file_name()
             .into_iter()
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
@@ -717,6 +827,7 @@ fn extract_copilot(source_path: &Path, extracted: &mut ExtractedFiles) -> Result
     let skills_dir = github_dir.join("skills");
     if skills_dir.exists() {
         for entry in WalkDir::new(&skills_dir)
+            .sort_by_file_name()
             .into_iter()
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
@@ -739,13 +850,74 @@ fn extract_copilot(source_path: &Path, extracted: &mut ExtractedFiles) -> Result
     }
 }
 
+/// Extract configurations from Amazon Q Developer (.amazonq/rules/)
+fn extract_amazonq(source_path: &Path, extracted: &mut ExtractedFiles) -> Result<Option<usize>> {
+    let rules_dir = source_path.join(".amazonq").join("rules");
+    if !rules_dir.exists() {
+        return Ok(None);
+    }
+
+    let mut count = 0;
+    for entry in WalkDir::new(&rules_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("md") {
+            continue;
+        }
+        if let Ok(content) = fs::read_to_string(path) {
+            let relative = path
+                .strip_prefix(&rules_dir)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .replace(['/', '\\'], "-");
+            // .amazonq/rules/project-memory.md is written from memory/ by aidot
+            if relative == "project-memory.md" {
+                extracted
+                    .memory
+                    .push(("amazonq-project-memory.md".to_string(), content));
+            } else {
+                extracted
+                    .rules
+                    .push((format!("amazonq-{}", relative), content));
+            }
+            count += 1;
+        }
+    }
+
+    if count > 0 {
+        Ok(Some(count))
+    } else {
+        Ok(None)
+    }
+}
+
+/// `aidot repo add` line for this preset (placeholder URL when none is known)
+fn repo_add_command(preset_name: &str, repo_url: Option<&str>) -> String {
+    format!(
+        "aidot repo add {} {}",
+        preset_name,
+        repo_url.unwrap_or("<repository-url>")
+    )
+}
+
 /// Create .aidot-config.toml template with comments
-fn create_config_template(preset_name: &str) -> String {
+fn create_config_template(preset_name: &str, repo_url: Option<&str>) -> String {
+    let repository = match repo_url {
+        Some(url) => format!("repository = {}", toml::Value::String(url.to_string())),
+        None => "# repository = \"https://github.com/your-org/your-preset.git\"  # Replace with this repository's Git URL".to_string(),
+    };
     format!(
         r#"[metadata]
-name = "{}"
+name = {}
 version = "1.0.0"
 description = "LLM configuration preset"
+# authors = ["Your Name <you@example.com>"]
+# homepage = "https://example.com/docs"
+{}
 
 # Rules: LLM behavioral rules and coding guidelines
 # You can specify individual files or use a directory
@@ -786,17 +958,35 @@ directory = "settings/"
 # Note: Cannot contain .claude/, .cursor/, .github/, .vscode/ folders
 [root]
 directory = "root/"
+
+# Tools: Override target paths per tool (relative to project root)
+# Keys: rules_dir, memory_file, commands_dir, agents_dir, skills_dir,
+#       mcp_file, hooks_file, settings_file
+# [tools.claude]
+# rules_dir = ".claude/rules/"
+# memory_file = ".claude/CLAUDE.md"
+
+# Apply: How files are written (UTF-8 BOM is always removed)
+# line_endings: "lf" (default), "crlf", or "preserve"
+# [apply]
+# line_endings = "lf"
 "#,
-        preset_name
+        toml::Value::String(preset_name.to_string()),
+        repository
     )
 }
 
 /// Create README.md for the preset repository
-fn create_readme(path: &Path, preset_name: &str) -> Result<()> {
+fn create_readme(path: &Path, preset_name: &str, repo_url: Option<&str>) -> Result<()> {
+    let add_comment = if repo_url.is_some() {
+        ""
+    } else {
+        "# Replace <repository-url> with this repository's Git URL\n"
+    };
     let readme = format!(
         r#"# {} - LLM Configuration Preset
 
-This repository contains LLM tool configurations managed by [aidot](https://github.com/yourorg/aidot).
+This repository contains LLM tool configurations managed by [aidot](https://github.com/Jooss287/aidot).
 
 ## Structure
 
@@ -869,13 +1059,14 @@ Use dedicated sections (rules/, memory/, etc.) for LLM tool configurations.
 ### Install aidot
 
 ```bash
-# Installation instructions for aidot
+# macOS / Linux (for Windows, see https://github.com/Jooss287/aidot)
+curl -fsSL https://raw.githubusercontent.com/Jooss287/aidot/main/scripts/install.sh | bash
 ```
 
 ### Add this preset
 
 ```bash
-aidot repo add {} <repository-url>
+{}{}
 ```
 
 ### Apply to your project
@@ -896,11 +1087,15 @@ This preset is compatible with:
 - Cursor
 - Aider
 - GitHub Copilot
+- Amazon Q Developer
 - Continue
 
 aidot automatically converts these configurations to the appropriate format for each tool.
 "#,
-        preset_name, preset_name, preset_name
+        preset_name,
+        add_comment,
+        repo_add_command(preset_name, repo_url),
+        preset_name
     );
 
     fs::write(path.join("README.md"), readme)?;
@@ -913,3 +1108,92 @@ aidot automatically converts these configurations to the appropriate format for
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_extract_order_is_sorted() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let rules_dir = temp_dir.path().join(".claude/rules");
+        fs::create_dir_all(rules_dir.join("team")).unwrap();
+        for name in ["z.md", "team/b.md", "a.md", "team/a.md"] {
+            fs::write(rules_dir.join(name), name).unwrap();
+        }
+
+        let mut extracted = ExtractedFiles::default();
+        extract_claude_code(temp_dir.path(), &mut extracted).unwrap();
+        let names: Vec<_> = extracted
+            .rules
+            .iter()
+            .map(|(name, _)| name.replace('\\', "/"))
+            .collect();
+        assert_eq!(names, vec!["a.md", "team/a.md", "team/b.md", "z.md"]);
+    }
+
+    #[test]
+    fn test_split_claude_settings() {
+        let (mcp, settings) = split_claude_settings(json!({
+            "mcpServers": { "github": { "command": "gh", "env": { "A": "1" } } },
+            "permissions": { "allow": ["Bash(ls)"], "deny": [] }
+        }));
+        assert_eq!(mcp.len(), 1);
+        assert_eq!(mcp[0].0, "github.json");
+        let settings: serde_json::Value = serde_json::from_str(&settings.unwrap()).unwrap();
+        assert_eq!(
+            settings,
+            json!({ "permissions": { "allow": ["Bash(ls)"], "deny": [] } })
+        );
+    }
+
+    #[test]
+    fn test_split_claude_settings_keeps_unsplittable_mcp_servers() {
+        // Empty object, non-object and names that don't survive as file names stay in settings
+        for servers in [
+            json!({}),
+            json!(["github"]),
+            json!({ "github": {}, "team/server": {} }),
+            json!({ "config.json": {} }),
+        ] {
+            let original = json!({ "mcpServers": servers });
+            let (mcp, settings) = split_claude_settings(original.clone());
+            assert!(mcp.is_empty());
+            let settings: serde_json::Value = serde_json::from_str(&settings.unwrap()).unwrap();
+            assert_eq!(settings, original);
+        }
+
+        let (mcp, settings) = split_claude_settings(json!({ "mcpServers": { "db": {} } }));
+        assert_eq!(mcp.len(), 1);
+        assert!(settings.is_none());
+    }
+
+    #[test]
+    fn test_repo_url_fills_config_and_readme() {
+        let url = "https://github.com/team/preset.git";
+        let config: crate::preset::PresetConfig =
+            toml::from_str(&create_config_template("team-preset", Some(url))).unwrap();
+        assert_eq!(config.metadata.repository.as_deref(), Some(url));
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        create_readme(temp_dir.path(), "team-preset", Some(url)).unwrap();
+        let readme = fs::read_to_string(temp_dir.path().join("README.md")).unwrap();
+        assert!(readme.contains(&format!("aidot repo add team-preset {}", url)));
+        assert!(!readme.contains("<repository-url>"));
+    }
+
+    #[test]
+    fn test_without_repo_url_keeps_marked_placeholder() {
+        let config: crate::preset::PresetConfig =
+            toml::from_str(&create_config_template("team-preset", None)).unwrap();
+        assert!(config.metadata.repository.is_none());
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        create_readme(temp_dir.path(), "team-preset", None).unwrap();
+        let readme = fs::read_to_string(temp_dir.path().join("README.md")).unwrap();
+        assert!(readme.contains("# Replace <repository-url> with this repository's Git URL"));
+        assert!(readme.contains("aidot repo add team-preset <repository-url>"));
+        assert!(!readme.contains("yourorg"));
+    }
+}
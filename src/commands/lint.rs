@@ -0,0 +1,91 @@
+use crate::archive;
+use crate::cache;
+use crate::error::{AidotError, Result};
+use crate::preset::lint::{check_urls, fix_issues, lint_preset_files, Severity};
+use crate::preset::parse_preset;
+use crate::progress;
+use colored::Colorize;
+use std::path::PathBuf;
+
+/// Lint the Markdown files (rules, commands) and hooks JSON files of a preset
+///
+/// Fails when any error-level issue remains, so it can gate CI. Relative links are
+/// always checked; with `urls` external http(s) links are requested as well.
+pub fn lint_preset(path: Option<String>, fix: bool, urls: bool) -> Result<()> {
+    let preset_dir = match path {
+        Some(p) => PathBuf::from(p),
+        None => std::env::current_dir()?,
+    };
+    let (_, preset_files) = parse_preset(&preset_dir)?;
+
+    println!(
+        "{} {}\n",
+        "Linting preset".cyan(),
+        preset_dir.display().to_string().white()
+    );
+
+    let mut issues = lint_preset_files(&preset_files);
+    if urls {
+        cache::require_online("--check-urls")?;
+        let spinner = progress::spinner("Checking links...");
+        issues.extend(check_urls(&preset_files, archive::check_url));
+        spinner.finish_and_clear();
+        issues.sort_by(|a, b| a.severity.cmp(&b.severity).then(a.path.cmp(&b.path)));
+    }
+
+    if fix {
+        let fixed = fix_issues(&preset_dir, &issues)?;
+        for path in &fixed {
+            println!("  {} {}", "fixed".green().bold(), path.white());
+        }
+        if !fixed.is_empty() {
+            println!();
+        }
+        issues.retain(|i| !i.fixable);
+    }
+
+    if issues.is_empty() {
+        println!("{}", "No issues found.".green());
+        return Ok(());
+    }
+
+    for issue in &issues {
+        let label = match issue.severity {
+            Severity::Error => issue.severity.as_str().red().bold(),
+            Severity::Warning => issue.severity.as_str().yellow().bold(),
+            Severity::Info => issue.severity.as_str().dimmed(),
+        };
+        let fixable = if issue.fixable { " (fixable)" } else { "" };
+        println!(
+            "  {:<7} {} {}{}",
+            label,
+            format!("{}:", issue.path).white(),
+            issue.message,
+            fixable.dimmed()
+        );
+    }
+
+    let count = |severity| issues.iter().filter(|i| i.severity == severity).count();
+    let errors = count(Severity::Error);
+    println!(
+        "\n{} error(s), {} warning(s), {} info",
+        errors,
+        count(Severity::Warning),
+        count(Severity::Info)
+    );
+    if issues.iter().any(|i| i.fixable) {
+        println!(
+            "  {} Run {} to fix automatically fixable issues",
+            "Tip:".cyan(),
+            "aidot lint --fix".white().bold()
+        );
+    }
+
+    if errors > 0 {
+        return Err(AidotError::InvalidPreset(format!(
+            "{} lint error(s) found",
+            errors
+        )));
+    }
+    Ok(())
+}
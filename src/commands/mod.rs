@@ -1,17 +1,34 @@
 pub mod cache;
+pub mod config;
 pub mod detect;
 pub mod diff;
+pub mod doctor;
 pub mod init;
+pub mod lint;
 pub mod pull;
+pub mod render;
 pub mod repo;
+pub mod search;
+pub mod secret;
 pub mod status;
+pub mod unapply;
 pub mod update;
 
-pub use cache::{clear_cache, update_cache};
+pub use cache::{clear_cache, gc_cache, update_cache};
+pub use config::{export_config, import_config};
 pub use detect::detect_tools;
-pub use diff::show_diff;
+pub use diff::{show_diff, DiffOptions};
+pub use doctor::run_doctor;
 pub use init::init_preset;
-pub use pull::pull_preset;
-pub use repo::{add_repo, list_repos, remove_repo, set_default_repo};
-pub use status::show_status;
+pub use lint::lint_preset;
+pub use pull::{dedup_sources, pull_composed, pull_preset, PullOptions, PullTarget};
+pub use render::render_preset;
+pub use repo::{
+    add_repo, doctor_repos, list_repos, remove_repo, set_default_repo, set_repo_url,
+    trust_repo_hooks,
+};
+pub use search::search_presets;
+pub use secret::{decrypt_file, encrypt_file};
+pub use status::{show_status, TreeLimits};
+pub use unapply::unapply_preset;
 pub use update::check_update;
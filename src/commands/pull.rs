@@ -1,94 +1,632 @@
-use crate::adapters::conflict::ConflictDecision;
-use crate::adapters::traits::{ApplyResult, PendingChange};
+use super::cache::format_size;
+use crate::adapters::conflict::{
+    ApplyContext, ConflictDecision, SymlinkPolicy, DEFAULT_DIFF_MAX_LINES,
+};
+use crate::adapters::helpers::{glob_error, resolves_inside};
+use crate::adapters::traits::{
+    ApplyResult, PendingChange, PresetFiles, ProjectMarkers, ToolAdapter,
+};
 use crate::adapters::{
-    all_tools, detect_tools, normalize_content, write_with_conflict, ConflictMode,
+    all_tools, global_tools, is_unchanged, resolve_tool_filter, select_tools, sort_tools,
+    tool_choices, write_preset_file, ConflictMode, GLOBAL_TOOL_NAMES,
 };
-use crate::error::Result;
-use crate::preset::parse_preset;
+use crate::config::{Config, ConflictPolicy};
+use crate::error::{AidotError, Result};
+use crate::git;
+use crate::history::History;
+use crate::log;
+use crate::manifest::{content_hash, project_key, Manifest, ManifestEntry};
+use crate::output::{self, display_path};
+use crate::preset::config::{ApplyHook, LineEnding, Normalize, PresetConfig};
+use crate::preset::parser::parse_loaded_preset_cached;
+use crate::preset::parser::section_layout_problems;
+use crate::progress;
+use crate::report::{now_rfc3339, FileStatus, ReportEntry};
 use crate::repository;
+use crate::select;
 use colored::Colorize;
-use std::collections::HashMap;
-use std::io::{self, Write};
-use std::path::Path;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+/// Merged files larger than this (KB) are reported after pull unless `merged_warn_kb` says otherwise
+const DEFAULT_MERGED_WARN_KB: u64 = 512;
+/// Largest sections listed in a merged file size warning
+const MERGED_WARN_SECTIONS: usize = 5;
+
+/// Options for `aidot pull`
+#[derive(Debug, Clone, Default)]
+pub struct PullOptions {
+    /// Apply to specific tools only (--tools)
+    pub tools_filter: Option<Vec<String>>,
+    /// Leave these tools out of the detected or selected ones (--exclude-tools)
+    pub exclude_tools: Vec<String>,
+    /// Preview changes without applying them
+    pub dry_run: bool,
+    /// Overwrite existing files without asking
+    pub force: bool,
+    /// Skip existing files without asking
+    pub skip: bool,
+    /// Skip confirmation prompts
+    pub yes: bool,
+    /// Line ending override (falls back to [apply] line_endings)
+    pub line_endings: Option<LineEnding>,
+    /// Delete files removed from the preset since the last pull
+    pub prune: bool,
+    /// Apply only preset files matching these globs (--only)
+    pub only: Vec<String>,
+    /// Let the last preset file win when several map to the same target
+    pub allow_overlap: bool,
+    /// Run [hooks.pre_apply] / [hooks.post_apply] commands of the preset
+    pub run_hooks: bool,
+    /// Explain skipped sections (--verbose)
+    pub verbose: bool,
+    /// List files skipped by `when` conditions with the reason
+    pub show_skipped: bool,
+    /// Skip files that cannot be written and keep applying the rest
+    pub continue_on_error: bool,
+    /// Show conflict diffs through $PAGER
+    pub pager: bool,
+    /// How to write target files that are symlinks
+    pub symlinks: SymlinkPolicy,
+    /// Warn about merged files larger than this many bytes (from `merged_warn_kb`)
+    pub merged_warn_bytes: Option<u64>,
+    /// Apply to the user-level configuration in the home directory (~/.claude, ~/.cursor)
+    pub global: bool,
+    /// Treat preset layout problems as errors instead of warnings
+    pub strict: bool,
+    /// Choose the files to apply from the change list before applying
+    pub interactive_preview: bool,
+    /// Commit the created/updated files to the project's git repository
+    pub commit: bool,
+    /// Commit message for --commit (default: "aidot: apply <repo> preset")
+    pub commit_message: Option<String>,
+    /// Run the reload command of each changed tool after applying (--reload)
+    pub reload: bool,
+    /// Apply order without --tools (`tool_order` in ~/.aidot/config.toml, set by pull)
+    pub tool_order: Vec<&'static str>,
+    /// Conflict policy when no answer can be given (`conflict_policy`, set by pull)
+    pub conflict_policy: ConflictPolicy,
+    /// Line limit for conflict diffs (`diff_max_lines`, set by pull)
+    pub diff_max_lines: Option<usize>,
+}
+
+/// Outcome of a single `pull_preset` run
+#[derive(Debug, Default)]
+pub struct PullSummary {
+    /// Per-file outcomes (for --report)
+    pub entries: Vec<ReportEntry>,
+    /// Whether the user quit during conflict resolution
+    pub aborted: bool,
+    /// Files that could not be written, with the reason (--continue-on-error)
+    pub failed: Vec<(String, String)>,
+}
+
+/// Directory a pull applies to and the tools set up there
+///
+/// Computed once per `aidot pull` and shared by every repository it applies, so the
+/// project is scanned, the tool prompt is asked and the tool list is printed only
+/// once however many repositories are given.
+#[derive(Debug)]
+pub struct PullTarget {
+    dir: PathBuf,
+    markers: ProjectMarkers,
+    /// Tools picked at the prompt when none were detected (reused by later repositories)
+    chosen: RefCell<Option<Vec<String>>>,
+    /// The tool list was printed and confirmed by an earlier repository
+    announced: Cell<bool>,
+}
+
+impl PullTarget {
+    /// The current directory, or the home directory with --global
+    pub fn resolve(global: bool) -> Result<Self> {
+        Ok(Self::at(target_dir(global)?))
+    }
+
+    /// A given project directory (re-applying from `cache update --apply`)
+    pub fn at(dir: PathBuf) -> Self {
+        let markers = ProjectMarkers::scan(&dir);
+        Self {
+            dir,
+            markers,
+            chosen: RefCell::new(None),
+            announced: Cell::new(false),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
 
 /// Pull and apply preset configurations
 pub fn pull_preset(
     preset_source: String,
-    tools_filter: Option<Vec<String>>,
-    dry_run: bool,
-    force: bool,
-    skip: bool,
-) -> Result<()> {
+    target: &PullTarget,
+    options: &PullOptions,
+) -> Result<PullSummary> {
+    let (global_config, options) = prepare_pull(options)?;
+    let options = &options;
+    let hooks_allowed = options.run_hooks || is_hooks_trusted(&global_config, &preset_source);
+
+    let (config, preset_files) = load_preset(&preset_source, target.dir(), options)?;
+    let summary = apply_preset(
+        &preset_source,
+        config,
+        preset_files,
+        target,
+        hooks_allowed,
+        options,
+    )?;
+    record_history(
+        target,
+        std::slice::from_ref(&preset_source),
+        options,
+        &summary,
+    );
+    Ok(summary)
+}
+
+/// Compose several presets into one and apply it once (`pull --compose`)
+///
+/// Sections are combined in the given order following `COMPOSE_STRATEGIES` (memory
+/// accumulates, settings come from the last preset). Tool path overrides of later
+/// presets win per tool; other config (apply options, hooks) comes from the last preset.
+/// Applied files are recorded under the joined name (e.g., "team+personal").
+pub fn pull_composed(
+    preset_sources: &[String],
+    target: &PullTarget,
+    options: &PullOptions,
+) -> Result<PullSummary> {
+    let (global_config, options) = prepare_pull(options)?;
+    let options = &options;
+    let hooks_allowed = options.run_hooks
+        || preset_sources
+            .iter()
+            .all(|source| is_hooks_trusted(&global_config, source));
+
+    let mut composed: Option<(PresetConfig, PresetFiles)> = None;
+    for preset_source in preset_sources {
+        let (mut config, preset_files) = load_preset(preset_source, target.dir(), options)?;
+        composed = Some(match composed {
+            None => (config, preset_files),
+            Some((mut base_config, mut base_files)) => {
+                base_files.compose(preset_files);
+                base_config.tools.compose(std::mem::take(&mut config.tools));
+                config.tools = base_config.tools;
+                (config, base_files)
+            }
+        });
+    }
+    let Some((config, preset_files)) = composed else {
+        return Ok(PullSummary::default());
+    };
+
+    let label = preset_sources.join("+");
+    println!(
+        "{} {} {}",
+        "Composed".cyan(),
+        format!("{} presets", preset_sources.len()).white().bold(),
+        "(memory accumulates, settings from the last preset win)".dimmed()
+    );
+    log::info(&format!("composed {}", label));
+    let summary = apply_preset(&label, config, preset_files, target, hooks_allowed, options)?;
+    record_history(target, preset_sources, options, &summary);
+    Ok(summary)
+}
+
+/// Remember which repositories were applied to the project (~/.aidot/state/history.json)
+///
+/// `cache update` lists these projects when a repository changes. Dry runs, --global,
+/// aborted and empty pulls are not recorded; a history that cannot be written only
+/// gets logged, since the files are applied already.
+fn record_history(
+    target: &PullTarget,
+    preset_sources: &[String],
+    options: &PullOptions,
+    summary: &PullSummary,
+) {
+    if options.dry_run || options.global || summary.aborted || summary.entries.is_empty() {
+        return;
+    }
+    let result = History::load().and_then(|mut history| {
+        history.record(&project_key(target.dir()), preset_sources, &now_rfc3339());
+        history.save()
+    });
+    if let Err(e) = result {
+        log::error(&format!("could not record apply history: {}", e));
+    }
+}
+
+/// Drop repeated repositories, keeping the first occurrence of each
+///
+/// Returns the remaining sources in their original order and the dropped duplicates.
+pub fn dedup_sources(sources: Vec<String>) -> (Vec<String>, Vec<String>) {
+    let mut seen = HashSet::new();
+    let mut duplicates = Vec::new();
+    let unique = sources
+        .into_iter()
+        .filter_map(|source| {
+            if seen.insert(source.clone()) {
+                Some(source)
+            } else {
+                duplicates.push(source);
+                None
+            }
+        })
+        .collect();
+    (unique, duplicates)
+}
+
+/// Load the global config for this pull
+///
+/// The returned options carry the configured tool order, conflict policy and limits.
+fn prepare_pull(options: &PullOptions) -> Result<(Config, PullOptions)> {
+    let global_config = Config::load()?;
+
+    let tool_order = resolve_tool_filter(&global_config.tool_order).map_err(|e| {
+        AidotError::ConfigParse(format!("tool_order in ~/.aidot/config.toml: {}", e))
+    })?;
+    let merged_warn_kb = global_config
+        .merged_warn_kb
+        .unwrap_or(DEFAULT_MERGED_WARN_KB);
+    let options = PullOptions {
+        tool_order,
+        merged_warn_bytes: (merged_warn_kb > 0).then(|| merged_warn_kb * 1024),
+        conflict_policy: global_config.conflict_policy,
+        diff_max_lines: global_config.diff_max_lines,
+        ..options.clone()
+    };
+    Ok((global_config, options))
+}
+
+/// Settings of one apply into `target_dir`, from the pull options and the preset
+fn apply_context(target_dir: &Path, config: &PresetConfig, options: &PullOptions) -> ApplyContext {
+    let mut ctx = ApplyContext::default();
+    ctx.root = Some(target_dir.to_path_buf());
+    ctx.continue_on_error = options.continue_on_error;
+    ctx.symlinks = options.symlinks;
+    ctx.no_input_policy = options.conflict_policy;
+    ctx.pager = options.pager;
+    ctx.diff_max_lines = options.diff_max_lines.unwrap_or(DEFAULT_DIFF_MAX_LINES);
+    ctx.json_final_newline = config.apply.normalize != Normalize::None;
+    ctx
+}
+
+fn is_hooks_trusted(global_config: &Config, preset_source: &str) -> bool {
+    global_config
+        .repositories
+        .iter()
+        .any(|r| r.name == preset_source && r.trust_hooks)
+}
+
+/// Resolve and parse a preset, then prepare its files for the target directory
+///
+/// Applies the preset's normalize and line ending options and drops files whose
+/// `when` conditions are not met.
+fn load_preset(
+    preset_source: &str,
+    target_dir: &Path,
+    options: &PullOptions,
+) -> Result<(PresetConfig, PresetFiles)> {
     // Resolve repository source (local path, Git URL, or registered repo name)
-    let preset_path = repository::resolve_repository_source(&preset_source)?;
+    let preset_path = repository::resolve_repository_source(preset_source)?;
+
+    let config = PresetConfig::load(&preset_path)?;
+    if options.strict {
+        let problems = section_layout_problems(&preset_path, &config);
+        if !problems.is_empty() {
+            return Err(AidotError::InvalidPreset(format!(
+                "{}\n(reported as errors because of --strict)",
+                problems.join("\n")
+            )));
+        }
+    }
 
     println!(
         "{} {}",
         "Loading preset from".cyan(),
-        preset_path.display().to_string().white()
+        display_path(&preset_path).white()
     );
+    log::info(&format!(
+        "pull {} (resolved to {})",
+        preset_source,
+        preset_path.display()
+    ));
 
     // Parse preset
-    let (_config, preset_files) = parse_preset(&preset_path)?;
+    let (config, mut preset_files) = parse_loaded_preset_cached(&preset_path, config)?;
+    preset_files.apply_normalize(config.apply.normalize);
+    preset_files.apply_line_endings(options.line_endings.unwrap_or(config.apply.line_endings));
+
+    // Drop files whose `when` conditions (OS, files_exist) are not met here
+    let skipped = preset_files.retain_conditions(&config.when, target_dir);
+    for (path, reason) in &skipped {
+        log::debug(&format!("skipped {} ({})", path, reason));
+    }
+    if !skipped.is_empty() {
+        if options.show_skipped {
+            println!("{}", "Skipped by conditions:".yellow());
+            for (path, reason) in &skipped {
+                println!(
+                    "  {} {} {}",
+                    "-".dimmed(),
+                    path.white(),
+                    format!("({})", reason).dimmed()
+                );
+            }
+        } else {
+            println!(
+                "{} {} {}",
+                "Skipped".yellow(),
+                skipped.len().to_string().white().bold(),
+                "file(s) whose conditions are not met (use --show-skipped for details)".yellow()
+            );
+        }
+    }
+
+    Ok((config, preset_files))
+}
+
+/// Scan, confirm and apply prepared preset files to the target directory
+fn apply_preset(
+    preset_source: &str,
+    config: PresetConfig,
+    mut preset_files: PresetFiles,
+    target: &PullTarget,
+    hooks_allowed: bool,
+    options: &PullOptions,
+) -> Result<PullSummary> {
+    let target_dir = target.dir();
+    let ctx = apply_context(target_dir, &config, options);
+
+    // Fail before writing anything when the result could not be committed
+    if options.commit && !options.dry_run {
+        git::ensure_committable(target_dir)?;
+    }
+
+    // Narrow down to files matching --only globs
+    if !options.only.is_empty() {
+        if let Some(error) = options.only.iter().find_map(|pattern| glob_error(pattern)) {
+            return Err(AidotError::InvalidInput(format!("--only: {}", error)));
+        }
+        let unmatched = preset_files.retain_matching(&options.only);
+        if unmatched.len() == options.only.len() {
+            return Err(AidotError::InvalidInput(format!(
+                "No preset files match --only {}",
+                options.only.join(", ")
+            )));
+        }
+        for pattern in &unmatched {
+            println!("{} {}", "⚠ No preset files match".yellow(), pattern.white());
+        }
+    }
+
+    if options.global && !preset_files.root.is_empty() {
+        println!(
+            "{} {} {}",
+            "Skipped".yellow(),
+            preset_files.root.len().to_string().white().bold(),
+            "root file(s) (project files are not applied with --global)".yellow()
+        );
+        preset_files.root.clear();
+    }
+
+    log::info(&format!("target {}", target_dir.display()));
+
+    // Tools selected via --tools that are not set up yet (their config dirs will be created)
+    let mut undetected: Vec<String> = Vec::new();
+
+    // Tools to set up: --tools, or chosen interactively when nothing is detected
+    // (an earlier repository of this pull may already have asked for them)
+    let mut chosen_interactively = target.chosen.borrow().is_some();
+    let mut tools_filter = options
+        .tools_filter
+        .clone()
+        .or_else(|| target.chosen.borrow().clone());
+    let mut detected = Vec::new();
+    let excluded = resolve_tool_filter(&options.exclude_tools)?;
+    if tools_filter.is_none() && options.global {
+        detected = global_tools(target_dir, &config.tools)
+            .into_iter()
+            .filter(|tool| tool.detect(&target.markers))
+            .collect();
+        if detected.is_empty() {
+            println!(
+                "{} {}",
+                "No global tool configuration detected".yellow(),
+                format!("(supported: {}).", GLOBAL_TOOL_NAMES.join(", ")).dimmed()
+            );
+            println!(
+                "Use '{}' to create it.",
+                "aidot pull <repo> --global --tools claude".cyan()
+            );
+            return Ok(PullSummary::default());
+        }
+        if exclude_detected(&mut detected, &excluded) {
+            return Ok(PullSummary::default());
+        }
+    } else if tools_filter.is_none() {
+        detected = all_tools(target_dir, &config.tools)
+            .into_iter()
+            .filter(|tool| tool.detect(&target.markers))
+            .collect();
+        if detected.is_empty() {
+            println!("{}", "No LLM tools detected in current directory.".yellow());
+            if options.yes || !io::stdin().is_terminal() {
+                println!(
+                    "Use '{}' to create configuration for specific tools.",
+                    "aidot pull <repo> --tools claude,cursor,copilot".cyan()
+                );
+                println!("Run '{}' to see detection details.", "aidot detect".cyan());
+                return Ok(PullSummary::default());
+            }
+            let Some(selected) = ask_tools_to_create()? else {
+                println!("{}", "Operation cancelled.".yellow());
+                return Ok(PullSummary::default());
+            };
+            *target.chosen.borrow_mut() = Some(selected.clone());
+            tools_filter = Some(selected);
+            chosen_interactively = true;
+        } else if exclude_detected(&mut detected, &excluded) {
+            return Ok(PullSummary::default());
+        }
+    }
 
-    // Get current directory as target
-    let target_dir = std::env::current_dir()?;
+    // Later repositories of the same pull apply to the tools listed by the first
+    let announce = !target.announced.get();
+    // --quiet leaves the tool list out altogether
+    let list_tools = announce && !output::is_quiet();
 
-    // Detect or create tools based on --tools filter
+    // Detect or create tools based on --tools filter, in apply order
     let tools = if let Some(ref filter) = tools_filter {
         // When --tools is specified, use all adapters (bypass detection)
         // so users can deploy to tools that haven't been set up yet
-        let all = all_tools(&target_dir);
-        let filter_lower: Vec<String> = filter.iter().map(|s| s.to_lowercase()).collect();
-        let filtered: Vec<_> = all
-            .into_iter()
-            .filter(|tool| {
-                let tool_name = tool.name().to_lowercase();
-                filter_lower.iter().any(|f| {
-                    tool_name.contains(f)
-                        || f.contains(&tool_name)
-                        || match f.as_str() {
-                            "claude" => tool_name.contains("claude"),
-                            "cursor" => tool_name.contains("cursor"),
-                            "copilot" => tool_name.contains("copilot"),
-                            _ => false,
-                        }
-                })
-            })
+        let mut selected = resolve_tool_filter(filter)?;
+        // Naming a tool in both lists is almost certainly a mistake, so refuse it
+        // instead of guessing which one wins
+        if let Some(name) = selected.iter().find(|name| excluded.contains(name)) {
+            if !chosen_interactively {
+                return Err(AidotError::InvalidInput(format!(
+                    "{} is given in both --tools and --exclude-tools",
+                    name
+                )));
+            }
+        }
+        selected.retain(|name| !excluded.contains(name));
+        if selected.is_empty() {
+            println!(
+                "{}",
+                "All selected tools are excluded by --exclude-tools; nothing to apply.".yellow()
+            );
+            return Ok(PullSummary::default());
+        }
+        let candidates = if options.global {
+            if let Some(name) = selected
+                .iter()
+                .find(|name| !GLOBAL_TOOL_NAMES.contains(name))
+            {
+                return Err(AidotError::InvalidInput(format!(
+                    "{} cannot be applied with --global (supported: {})",
+                    name,
+                    GLOBAL_TOOL_NAMES.join(", ")
+                )));
+            }
+            global_tools(target_dir, &config.tools)
+        } else {
+            all_tools(target_dir, &config.tools)
+        };
+        // --tools order wins over tool_order
+        let filtered = select_tools(candidates, &selected);
+        undetected = filtered
+            .iter()
+            .filter(|tool| !tool.detect(&target.markers))
+            .map(|tool| tool.name().to_string())
             .collect();
 
-        if filtered.is_empty() {
+        if list_tools {
             println!(
-                "{} {}",
-                "No tools matched the filter:".yellow(),
-                filter.join(", ").white()
+                "{} {} {}",
+                "Selected".green(),
+                filtered.len().to_string().white().bold(),
+                "tool(s):".green()
             );
-            return Ok(());
         }
         filtered
     } else {
-        let detected = detect_tools(&target_dir);
-        if detected.is_empty() {
-            println!("{}", "No LLM tools detected in current directory.".yellow());
-            println!("Run '{}' to see detection details.", "aidot detect".cyan());
-            return Ok(());
+        if list_tools {
+            println!(
+                "{} {} {}",
+                "Detected".green(),
+                detected.len().to_string().white().bold(),
+                "tool(s):".green()
+            );
         }
+        sort_tools(&mut detected, &options.tool_order);
         detected
     };
 
-    println!(
-        "{} {} {}",
-        "Detected".green(),
-        tools.len().to_string().white().bold(),
-        "tool(s):".green()
-    );
+    let tool_list: Vec<&str> = tools.iter().map(|tool| tool.name()).collect();
+    log::info(&format!("tools: {}", tool_list.join(", ")));
+    let mut listed = list_tools;
     for tool in &tools {
-        println!("  {} {}", "-".cyan(), tool.name().white());
+        // Skipped sections depend on the preset, so --verbose lists them for each one
+        let skips: Vec<(&str, usize, &str)> = tool
+            .unsupported_sections()
+            .iter()
+            .map(|(section, reason)| (*section, preset_files.section(section).len(), *reason))
+            .filter(|(_, count, _)| options.verbose && *count > 0)
+            .collect();
+        if !list_tools && skips.is_empty() {
+            continue;
+        }
+        listed = true;
+        if list_tools && undetected.iter().any(|name| name == tool.name()) {
+            println!(
+                "  {} {} {}",
+                "-".cyan(),
+                tool.name().white(),
+                "(will be created)".yellow()
+            );
+        } else {
+            println!("  {} {}", "-".cyan(), tool.name().white());
+        }
+        for (section, count, reason) in skips {
+            println!(
+                "      {} {}",
+                format!("skips {}/ ({} file(s)):", section, count).dimmed(),
+                reason.dimmed()
+            );
+        }
     }
-    println!();
+    if listed {
+        println!();
+    }
+
+    // Global changes reach every project of this user
+    if announce && options.global && !options.dry_run {
+        println!(
+            "{} {}",
+            "Warning:".yellow().bold(),
+            format!(
+                "--global writes to {} and affects every project on this machine.",
+                display_path(target_dir)
+            )
+            .yellow()
+        );
+        if !options.force && !options.yes && !confirm("Apply to the global configuration?")? {
+            println!("{}", "Operation cancelled.".yellow());
+            return Ok(PullSummary::default());
+        }
+        println!();
+    }
+
+    // Confirm before creating configuration for tools that aren't set up yet
+    if announce
+        && !undetected.is_empty()
+        && !chosen_interactively
+        && !options.dry_run
+        && !options.force
+        && !options.yes
+    {
+        let prompt = format!(
+            "{} not detected in this project. Its configuration directory will be newly created. Continue?",
+            undetected.join(", ")
+        );
+        if !confirm(&prompt)? {
+            println!("{}", "Operation cancelled.".yellow());
+            return Ok(PullSummary::default());
+        }
+        println!();
+    }
+    target.announced.set(true);
 
     // Phase 1: Scan all tools for changes
-    println!("{}", "Scanning...".cyan());
+    let scan_spinner = progress::spinner("Scanning...");
+    if scan_spinner.is_hidden() {
+        println!("{}", "Scanning...".cyan());
+    }
 
     let mut all_changes: Vec<(String, PendingChange)> = Vec::new();
 
@@ -97,9 +635,7 @@ pub fn pull_preset(
         let target_path = target_dir.join(&root_file.relative_path);
         let (is_conflict, is_identical) = if target_path.exists() {
             let is_identical = match std::fs::read_to_string(&target_path) {
-                Ok(existing) => {
-                    normalize_content(&existing) == normalize_content(&root_file.content)
-                }
+                Ok(existing) => is_unchanged(&existing, &root_file.content),
                 Err(_) => false,
             };
             (true, is_identical)
@@ -114,191 +650,1350 @@ pub fn pull_preset(
                 is_conflict,
                 is_identical,
                 preset_content: Some(root_file.content.clone()),
+                source: Some(root_file.relative_path.clone()),
+                modified_since_apply: false,
             },
         ));
     }
 
-    // Scan tool-specific files
-    for tool in &tools {
-        let scan_result = tool.scan(&preset_files, &target_dir);
-        for change in scan_result.changes {
-            all_changes.push((tool.name().to_string(), change));
+    // Scan tool-specific files
+    for tool in &tools {
+        let scan_result = tool.scan(&preset_files, target_dir);
+        for change in scan_result.changes {
+            all_changes.push((tool.name().to_string(), change));
+        }
+    }
+
+    // A root file targeting a path a tool writes from its own section is written once:
+    // the explicit section wins, so root/ can't clobber (or be clobbered by) it
+    let shadowed = shadowed_root_files(&all_changes);
+    if !shadowed.is_empty() {
+        println!(
+            "{} {}",
+            "Warning:".yellow().bold(),
+            "root/ files also written by a tool section are skipped (the section wins):".yellow()
+        );
+        for (path, writer) in &shadowed {
+            println!("  {} ← {}", path, writer);
+        }
+        println!();
+        let is_shadowed = |path: &str| {
+            shadowed
+                .iter()
+                .any(|(shadowed_path, _)| shadowed_path == path)
+        };
+        all_changes.retain(|(tool, change)| tool != "Root" || !is_shadowed(&change.path));
+        preset_files
+            .root
+            .retain(|file| !is_shadowed(&file.relative_path));
+    }
+
+    // Compare existing files with the hashes recorded when they were last applied
+    let mut manifest = Manifest::load(target_dir)?;
+    for (_, change) in all_changes.iter_mut().filter(|(_, c)| c.is_conflict) {
+        change.modified_since_apply = manifest.is_modified_since_apply(target_dir, &change.path);
+    }
+    scan_spinner.finish_and_clear();
+
+    // Every target must stay inside the project: checked before anything is shown or written
+    let escaping = escaping_targets(target_dir, &all_changes, options.symlinks);
+    if !escaping.is_empty() {
+        return Err(AidotError::InvalidInput(format!(
+            "Refusing to write outside {} (resolves through '..', an absolute path or a symlink):\n{}\nNothing was written.",
+            display_path(target_dir),
+            escaping.join("\n")
+        )));
+    }
+
+    // Dry-run: run the real apply with simulated writes so merged files get exact verdicts
+    let line_stats = if options.dry_run {
+        simulate_apply(&preset_files, &tools, target_dir, &ctx, &mut all_changes)?
+    } else {
+        BTreeMap::new()
+    };
+    let stats_label = |tool_name: &str, path: &str| match line_stats
+        .get(&(tool_name.to_string(), path.to_string()))
+    {
+        Some((added, removed)) => format!(" +{} -{}", added, removed),
+        None => String::new(),
+    };
+
+    // Several preset files writing the same target would silently overwrite each other
+    let overlaps = find_overlaps(&all_changes);
+    if !overlaps.is_empty() {
+        let lines: Vec<String> = overlaps
+            .iter()
+            .map(|overlap| {
+                format!(
+                    "  {} ← {} [{}]",
+                    overlap.path,
+                    overlap.sources.join(", "),
+                    overlap.tool
+                )
+            })
+            .collect();
+        if !options.allow_overlap {
+            return Err(AidotError::InvalidInput(format!(
+                "Multiple preset files map to the same target:\n{}\nRename one of them, or pass --allow-overlap to let the last one win.",
+                lines.join("\n")
+            )));
+        }
+        println!(
+            "{} {}",
+            "Warning:".yellow().bold(),
+            "multiple preset files map to the same target (last one wins):".yellow()
+        );
+        for line in &lines {
+            println!("{}", line);
+        }
+        println!();
+    }
+
+    // Files applied by this preset before that it no longer produces
+    // With --only the scan is partial, so those files are kept without being reported
+    let tool_names: Vec<&str> = std::iter::once("Root")
+        .chain(tools.iter().map(|tool| tool.name()))
+        .collect();
+    let current_paths: HashSet<String> = all_changes.iter().map(|(_, c)| c.path.clone()).collect();
+    let not_in_scan: Vec<ManifestEntry> = manifest
+        .orphans(preset_source, &tool_names, &current_paths)
+        .into_iter()
+        .filter(|entry| target_dir.join(&entry.path).exists())
+        .collect();
+    let (orphans, untouched) = if options.only.is_empty() {
+        (not_in_scan, Vec::new())
+    } else {
+        (Vec::new(), not_in_scan)
+    };
+
+    if all_changes.is_empty() && orphans.is_empty() {
+        println!("{}", "No changes to apply.".yellow());
+        return Ok(PullSummary::default());
+    }
+    // Orphans edited after they were applied are only pruned with --force
+    let (prunable, modified_orphans): (Vec<ManifestEntry>, Vec<ManifestEntry>) = orphans
+        .iter()
+        .cloned()
+        .partition(|entry| options.force || !entry.is_modified(target_dir));
+
+    // Phase 2: Display changes
+    println!();
+    println!("{}", "Changes to apply:".white().bold());
+
+    let creates: Vec<_> = all_changes.iter().filter(|(_, c)| !c.is_conflict).collect();
+    let conflicts: Vec<_> = all_changes
+        .iter()
+        .filter(|(_, c)| c.is_conflict && !c.is_identical)
+        .collect();
+    let unchanged: Vec<_> = all_changes.iter().filter(|(_, c)| c.is_identical).collect();
+
+    for (tool_name, change) in &unchanged {
+        println!(
+            "  {} {} {} {}",
+            "UNCHANGED".dimmed(),
+            change.path.dimmed(),
+            format!("({})", change.section).dimmed(),
+            format!("[{}]", tool_name).dimmed()
+        );
+    }
+
+    for (tool_name, change) in &creates {
+        println!(
+            "  {} {} {} {}{}",
+            "CREATE".green().bold(),
+            change.path.white(),
+            format!("({})", change.section).dimmed(),
+            format!("[{}]", tool_name).dimmed(),
+            stats_label(tool_name, &change.path).dimmed()
+        );
+    }
+
+    for (tool_name, change) in &conflicts {
+        let label = if change.modified_since_apply {
+            "(conflict, modified locally)"
+        } else {
+            "(conflict)"
+        };
+        println!(
+            "  {} {} {} {} {}{}",
+            "UPDATE".yellow().bold(),
+            change.path.white(),
+            label.red(),
+            format!("({})", change.section).dimmed(),
+            format!("[{}]", tool_name).dimmed(),
+            stats_label(tool_name, &change.path).dimmed()
+        );
+    }
+
+    for entry in &orphans {
+        let modified = modified_orphans.contains(entry);
+        let (label, note) = match (options.prune, modified) {
+            (true, false) => ("DELETE".red().bold(), "(removed from preset)"),
+            (true, true) => (
+                "KEEP".yellow().bold(),
+                "(removed from preset, modified locally; --force deletes it)",
+            ),
+            (false, _) => ("ORPHAN".red(), "(removed from preset)"),
+        };
+        println!(
+            "  {} {} {} {}",
+            label,
+            entry.path.white(),
+            note.dimmed(),
+            format!("[{}]", entry.tool).dimmed()
+        );
+    }
+
+    println!();
+
+    // Phase 3: Handle dry-run mode
+    if options.dry_run {
+        println!("{}", "═══ DRY RUN MODE ═══".yellow().bold());
+        if !conflicts.is_empty() {
+            let mut summary_parts = vec![format!("{} conflict(s) found.", conflicts.len())];
+            if !unchanged.is_empty() {
+                summary_parts.push(format!("{} file(s) unchanged.", unchanged.len()));
+            }
+            println!(
+                "{} {}",
+                summary_parts.join(" ").yellow(),
+                "Run without --dry-run to apply.".cyan()
+            );
+        } else if !unchanged.is_empty() {
+            println!(
+                "{} {}",
+                format!("No conflicts. {} file(s) unchanged.", unchanged.len()).green(),
+                "Run without --dry-run to apply.".cyan()
+            );
+        } else {
+            println!("{}", "No conflicts. Run without --dry-run to apply.".cyan());
+        }
+        if !orphans.is_empty() && !options.prune {
+            print_prune_hint(preset_source, orphans.len());
+        }
+        return Ok(PullSummary::default());
+    }
+
+    // Phase 3.5: Let the user pick the files to apply (--interactive-preview)
+    let mut preview_decisions = None;
+    if options.interactive_preview {
+        if io::stdin().is_terminal() && io::stdout().is_terminal() {
+            let Some(decisions) = select_changes(&all_changes)? else {
+                println!("{}", "Operation cancelled.".yellow());
+                return Ok(PullSummary::default());
+            };
+            if !decisions.values().any(|write| *write) && orphans.is_empty() {
+                println!("{}", "No files selected.".yellow());
+                return Ok(PullSummary::default());
+            }
+            preview_decisions = Some(decisions);
+        } else {
+            println!(
+                "{}",
+                "--interactive-preview needs a terminal; including all files.".dimmed()
+            );
+        }
+    }
+
+    // Phase 4: Determine conflict mode
+    let mut conflict_mode = if let Some(decisions) = preview_decisions {
+        // The selection already answers every conflict
+        ConflictMode::PreResolved {
+            decisions,
+            fallback_all: None,
+        }
+    } else if options.force {
+        ConflictMode::Force
+    } else if options.skip {
+        ConflictMode::Skip
+    } else if conflicts.is_empty() {
+        // No conflicts, proceed directly
+        ConflictMode::Force
+    } else if options.yes || !io::stdin().is_terminal() {
+        // Non-interactive: never prompt, follow the configured default policy
+        let policy = ctx.no_input_policy;
+        println!(
+            "{} {} {}",
+            conflicts.len().to_string().yellow().bold(),
+            "conflict(s) found.".yellow(),
+            format!("Applying default policy ({}).", policy.as_str()).white()
+        );
+        ConflictMode::from_policy(policy)
+    } else {
+        // Ask user how to handle conflicts
+        ask_conflict_resolution(conflicts.len(), &ctx)?
+    };
+
+    let hooks = config.hooks.clone().unwrap_or_default();
+    let interactive = matches!(conflict_mode, ConflictMode::Ask);
+
+    // Phase 4.5: In interactive mode, write all new files before asking about conflicts,
+    // so they are in place even when the conflict prompts are quit
+    let mut first_pass: Vec<(String, ApplyResult)> = Vec::new();
+    if interactive && !creates.is_empty() {
+        if let Some(hook) = &hooks.pre_apply {
+            run_apply_hook("pre_apply", hook, hooks_allowed, target_dir, options.yes)?;
+        }
+        println!("{}", "Applying new files...".cyan());
+        first_pass = apply_new_files(&preset_files, &tools, target_dir, &ctx)?;
+        for (tool_name, result) in &first_pass {
+            print_apply_result(tool_name, result, &all_changes, options.verbose);
+            log_apply_result(tool_name, result);
+        }
+        let created: usize = first_pass.iter().map(|(_, r)| r.created.len()).sum();
+        println!(
+            "\n{} {}",
+            "✓".green().bold(),
+            format!("{} new file(s) applied.", created).green()
+        );
+    }
+
+    // Then resolve the conflicts one by one and apply them in batch
+    if interactive {
+        conflict_mode = match pre_resolve_conflicts(&conflicts, target_dir, &ctx) {
+            Some(decisions) => ConflictMode::PreResolved {
+                decisions,
+                fallback_all: None,
+            },
+            // New files already written are kept and recorded like after [q]uit
+            None if !first_pass.is_empty() => ConflictMode::Quit,
+            None => {
+                println!("{}", "Operation cancelled.".yellow());
+                return Ok(PullSummary::default());
+            }
+        };
+    }
+
+    if first_pass.is_empty() {
+        if let Some(hook) = &hooks.pre_apply {
+            run_apply_hook("pre_apply", hook, hooks_allowed, target_dir, options.yes)?;
+        }
+    }
+
+    // Phase 5: Apply changes
+    if conflict_mode != ConflictMode::Quit {
+        println!("{}", "Applying...".cyan());
+    }
+
+    // Progress bar is hidden when merged files may still prompt inline
+    let apply_bar = if matches!(
+        conflict_mode,
+        ConflictMode::PreResolved { .. } | ConflictMode::Quit
+    ) {
+        indicatif::ProgressBar::hidden()
+    } else {
+        progress::file_bar("Applying", all_changes.len() as u64)
+    };
+
+    let mut results: Vec<(String, ApplyResult)> = Vec::new();
+
+    // Apply root files first (tool-agnostic)
+    if !preset_files.root.is_empty() {
+        let mut root_result =
+            apply_root_files(&preset_files.root, target_dir, &mut conflict_mode, &ctx)?;
+        without_first_pass(&mut root_result, "Root", &first_pass);
+        apply_bar.inc(root_result.total() as u64);
+        apply_bar
+            .suspend(|| print_apply_result("Root", &root_result, &all_changes, options.verbose));
+        log_apply_result("Root", &root_result);
+        results.push(("Root".to_string(), root_result));
+    }
+
+    // Apply tool-specific files (stops after [q]uit)
+    for tool in &tools {
+        if conflict_mode == ConflictMode::Quit {
+            break;
+        }
+        let mut result = tool.apply(&preset_files, target_dir, &mut conflict_mode, &ctx)?;
+        without_first_pass(&mut result, tool.name(), &first_pass);
+        apply_bar.inc(result.total() as u64);
+        apply_bar
+            .suspend(|| print_apply_result(tool.name(), &result, &all_changes, options.verbose));
+        log_apply_result(tool.name(), &result);
+        results.push((tool.name().to_string(), result));
+    }
+    apply_bar.finish_and_clear();
+    merge_first_pass(&mut results, first_pass);
+    let aborted = conflict_mode == ConflictMode::Quit;
+    if aborted {
+        log::info("aborted by the user ([q]uit)");
+    }
+
+    // Phase 6: Remove orphaned files (--prune, skipped after [q]uit)
+    if options.prune && !orphans.is_empty() && !aborted {
+        println!(
+            "\n{} {}",
+            "Pruned".cyan(),
+            "files removed from preset".white().bold()
+        );
+        for entry in &prunable {
+            match std::fs::remove_file(target_dir.join(&entry.path)) {
+                // Already gone counts as pruned
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+            log::info(&format!("pruned {}", entry.path));
+            println!("    {} {}", "-".red(), entry.path.white());
+        }
+        for entry in &modified_orphans {
+            log::info(&format!("kept modified orphan {}", entry.path));
+            println!(
+                "    {} {} {}",
+                "!".yellow(),
+                entry.path.white(),
+                "(modified, kept)".yellow()
+            );
+        }
+    }
+
+    // Phase 7: Record applied files for orphan detection and unapply
+    let kept: Vec<ManifestEntry> = if options.prune && !aborted {
+        modified_orphans.into_iter().chain(untouched).collect()
+    } else {
+        orphans.iter().cloned().chain(untouched).collect()
+    };
+    record_applied_files(
+        &mut manifest,
+        target_dir,
+        preset_source,
+        &tool_names,
+        &all_changes,
+        &results,
+        &kept,
+    );
+    manifest.save(target_dir)?;
+
+    let pruned: &[ManifestEntry] = if options.prune && !aborted {
+        &prunable
+    } else {
+        &[]
+    };
+    let summary = PullSummary {
+        entries: report_entries(preset_source, &all_changes, &results, pruned),
+        aborted,
+        failed: results
+            .iter()
+            .flat_map(|(_, r)| r.failed.iter().cloned())
+            .collect(),
+    };
+
+    if !options.commit {
+        print_tracked_warning(target_dir, &results);
+    }
+    if let Some(limit) = options.merged_warn_bytes {
+        print_merged_size_warnings(target_dir, &all_changes, &results, limit);
+    }
+
+    println!();
+    if aborted {
+        println!(
+            "{}",
+            "Stopped by user. Files written so far were kept; remaining files were not applied."
+                .yellow()
+                .bold()
+        );
+        println!(
+            "{} {} {}",
+            "Run".dimmed(),
+            format!("aidot unapply {}", preset_source).cyan(),
+            "to remove files applied by this preset.".dimmed()
+        );
+        if options.commit {
+            println!("{}", "Nothing was committed.".yellow());
+        }
+        return Ok(summary);
+    }
+
+    if !summary.failed.is_empty() {
+        println!(
+            "{}",
+            format!("Failed to apply {} file(s):", summary.failed.len())
+                .red()
+                .bold()
+        );
+        for (path, reason) in &summary.failed {
+            println!(
+                "  {} {} {}",
+                "✗".red(),
+                path.white(),
+                format!("({})", reason).dimmed()
+            );
+        }
+        if options.commit {
+            println!("{}", "Nothing was committed.".yellow());
+        }
+        return Ok(summary);
+    }
+
+    println!("{}", "Preset applied successfully!".green().bold());
+    if options.commit {
+        commit_applied_files(
+            target_dir,
+            preset_source,
+            &results,
+            options.commit_message.as_deref(),
+        )?;
+    }
+    if let Some(hook) = &hooks.post_apply {
+        run_apply_hook("post_apply", hook, hooks_allowed, target_dir, options.yes)?;
+    }
+    print_reload_hints(&tools, &results, target_dir, options);
+    if !orphans.is_empty() && !options.prune {
+        print_prune_hint(preset_source, orphans.len());
+    }
+
+    Ok(summary)
+}
+
+/// Warn about written files that are tracked by Git (e.g. a committed settings.local.json)
+fn print_tracked_warning(target_dir: &Path, results: &[(String, ApplyResult)]) {
+    let written: Vec<String> = results
+        .iter()
+        .flat_map(|(_, r)| r.created.iter().chain(&r.updated).cloned())
+        .collect();
+    let tracked = git::tracked_paths(target_dir, &written);
+    if tracked.is_empty() {
+        return;
+    }
+
+    println!(
+        "\n{} {}",
+        "Warning:".yellow().bold(),
+        "these files are tracked by git and may be committed:".yellow()
+    );
+    for path in &tracked {
+        println!("    {} {}", "!".yellow(), path.white());
+    }
+}
+
+/// Targets that resolve outside `target_dir`, as `  path [tool]` lines
+///
+/// Mirrors the check made on every write: a symlinked target that is skipped or
+/// replaced (see `SymlinkPolicy`) only needs its directory inside.
+fn escaping_targets(
+    target_dir: &Path,
+    all_changes: &[(String, PendingChange)],
+    symlinks: SymlinkPolicy,
+) -> Vec<String> {
+    let mut escaping: Vec<String> = all_changes
+        .iter()
+        .filter(|(_, change)| {
+            let path = target_dir.join(&change.path);
+            let is_link = path
+                .symlink_metadata()
+                .is_ok_and(|meta| meta.file_type().is_symlink());
+            let checked = match path.parent() {
+                Some(parent) if is_link && symlinks != SymlinkPolicy::Follow => parent,
+                _ => &path,
+            };
+            !resolves_inside(target_dir, checked)
+        })
+        .map(|(tool, change)| format!("  {} [{}]", change.path, tool))
+        .collect();
+    escaping.dedup();
+    escaping
+}
+
+/// Warn about written merged files that grew past `limit` bytes
+///
+/// Merged files are read by the tool on every request, so a file of several MB
+/// slows it down. The sections contributing most (by markdown heading) are listed,
+/// and a heading appearing more than once hints at duplicated content.
+fn print_merged_size_warnings(
+    target_dir: &Path,
+    all_changes: &[(String, PendingChange)],
+    results: &[(String, ApplyResult)],
+    limit: u64,
+) {
+    let written: HashSet<&String> = results
+        .iter()
+        .flat_map(|(_, r)| r.created.iter().chain(&r.updated))
+        .collect();
+    let mut merged: Vec<&String> = all_changes
+        .iter()
+        .filter(|(_, change)| change.source.is_none() && written.contains(&change.path))
+        .map(|(_, change)| &change.path)
+        .collect();
+    merged.sort();
+    merged.dedup();
+
+    for path in merged {
+        let Ok(content) = std::fs::read_to_string(target_dir.join(path)) else {
+            continue;
+        };
+        let size = content.len() as u64;
+        if size <= limit {
+            continue;
+        }
+        println!(
+            "\n{} {} {}",
+            "Warning:".yellow().bold(),
+            path.white(),
+            format!(
+                "is {} (over {}, merged_warn_kb in ~/.aidot/config.toml); large files slow tools down",
+                format_size(size),
+                format_size(limit)
+            )
+            .yellow()
+        );
+        for section in section_sizes(&content).iter().take(MERGED_WARN_SECTIONS) {
+            let repeated = if section.count > 1 {
+                format!(" ({}×, possibly duplicated)", section.count)
+                    .yellow()
+                    .to_string()
+            } else {
+                String::new()
+            };
+            println!(
+                "    {:>9}  {}{}",
+                format_size(section.bytes as u64),
+                section.heading.white(),
+                repeated
+            );
+        }
+    }
+}
+
+/// Bytes of a markdown file under each top-level (`#`/`##`) heading
+struct SectionSize {
+    heading: String,
+    bytes: usize,
+    /// How many times the heading appears
+    count: usize,
+}
+
+/// Sections of a merged markdown file, largest first (text before the first
+/// heading counts as its own section; headings inside code fences are ignored)
+fn section_sizes(content: &str) -> Vec<SectionSize> {
+    let mut sections: Vec<SectionSize> = Vec::new();
+    let mut current: Option<usize> = None;
+    let mut in_fence = false;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+        }
+        let is_heading = !in_fence && (trimmed.starts_with("# ") || trimmed.starts_with("## "));
+        if is_heading || current.is_none() {
+            let heading = if is_heading {
+                trimmed.to_string()
+            } else {
+                "(before the first heading)".to_string()
+            };
+            current = Some(match sections.iter().position(|s| s.heading == heading) {
+                Some(i) => {
+                    sections[i].count += 1;
+                    i
+                }
+                None => {
+                    sections.push(SectionSize {
+                        heading,
+                        bytes: 0,
+                        count: 1,
+                    });
+                    sections.len() - 1
+                }
+            });
+        }
+        if let Some(i) = current {
+            sections[i].bytes += line.len();
+        }
+    }
+    sections.sort_by_key(|s| std::cmp::Reverse(s.bytes));
+    sections
+}
+
+/// Commit the files created or updated by this pull (`--commit`)
+///
+/// Skipped and unchanged files are not staged, and no commit is made when nothing changed.
+fn commit_applied_files(
+    target_dir: &Path,
+    preset_source: &str,
+    results: &[(String, ApplyResult)],
+    message: Option<&str>,
+) -> Result<()> {
+    let written: Vec<String> = results
+        .iter()
+        .flat_map(|(_, r)| r.created.iter().chain(&r.updated).cloned())
+        .collect();
+    if written.is_empty() {
+        println!("{}", "No files changed, nothing to commit.".dimmed());
+        return Ok(());
+    }
+
+    let message = message
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("aidot: apply {} preset", preset_source));
+    let outcome = git::commit_paths(target_dir, &written, &message)?;
+    for path in &outcome.ignored {
+        println!(
+            "    {} {} {}",
+            "-".dimmed(),
+            path.white(),
+            "(ignored by .gitignore, not committed)".dimmed()
+        );
+    }
+    match outcome.id {
+        Some(id) => {
+            log::info(&format!(
+                "committed {} as {}",
+                written.len() - outcome.ignored.len(),
+                id
+            ));
+            println!(
+                "{} {} {}",
+                "✓ Committed".green().bold(),
+                format!("{} file(s)", written.len() - outcome.ignored.len()).white(),
+                format!("as {} ({})", id, message).dimmed()
+            );
+        }
+        None => println!("{}", "No files changed, nothing to commit.".dimmed()),
+    }
+    Ok(())
+}
+
+/// Suggest `--prune` for files removed from the preset
+fn print_prune_hint(preset_source: &str, count: usize) {
+    println!(
+        "{} {} {}",
+        format!("{} file(s) were removed from the preset.", count).yellow(),
+        "Run".dimmed(),
+        format!("aidot pull {} --prune", preset_source).cyan()
+    );
+}
+
+/// Record the outcome of every file of one tool in the run log
+fn log_apply_result(tool_name: &str, result: &ApplyResult) {
+    let outcomes = [
+        ("created", &result.created),
+        ("updated", &result.updated),
+        ("skipped", &result.skipped),
+        ("unchanged", &result.unchanged),
+    ];
+    for (outcome, paths) in outcomes {
+        for path in paths {
+            log::info(&format!("[{}] {} {}", tool_name, outcome, path));
+        }
+    }
+    for (path, reason) in &result.failed {
+        log::error(&format!("[{}] failed {}: {}", tool_name, path, reason));
+    }
+}
+
+/// Directory presets are applied to: the current project, or the home directory with --global
+///
+/// A plain pull in the home directory is refused, since it would silently change the
+/// global configuration that --global guards with a warning.
+fn target_dir(global: bool) -> Result<std::path::PathBuf> {
+    let home = dirs::home_dir()
+        .ok_or_else(|| AidotError::ConfigParse("Could not find home directory".to_string()));
+    if global {
+        return home;
+    }
+
+    let current_dir = std::env::current_dir()?;
+    if let Ok(home) = home {
+        let same = |a: &Path, b: &Path| match (a.canonicalize(), b.canonicalize()) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => a == b,
+        };
+        if same(&current_dir, &home) {
+            return Err(AidotError::InvalidInput(
+                "The current directory is your home directory, so this would change the global tool configuration. Use 'aidot pull --global' to apply globally.".to_string(),
+            ));
+        }
+    }
+    Ok(current_dir)
+}
+
+/// Record 1:1 and root files applied (or previously applied and kept) by this preset
+///
+/// `kept` are recorded files outside this scan that stay tracked (unpruned orphans,
+/// files filtered out by --only), so they keep showing up in diff/pull.
+/// Applied files get the hash of their content on disk; files skipped this time
+/// keep the hash from their last apply, so later edits are still detected.
+fn record_applied_files(
+    manifest: &mut Manifest,
+    target_dir: &Path,
+    preset_source: &str,
+    tool_names: &[&str],
+    all_changes: &[(String, PendingChange)],
+    results: &[(String, ApplyResult)],
+    kept: &[ManifestEntry],
+) {
+    let applied: HashSet<&str> = results
+        .iter()
+        .flat_map(|(_, r)| r.created.iter().chain(&r.updated).chain(&r.unchanged))
+        .map(String::as_str)
+        .collect();
+
+    let mut entries: Vec<ManifestEntry> = all_changes
+        .iter()
+        .filter(|(_, change)| change.preset_content.is_some())
+        .filter(|(_, change)| {
+            applied.contains(change.path.as_str()) || manifest.contains(preset_source, &change.path)
+        })
+        .map(|(tool_name, change)| {
+            let hash = if applied.contains(change.path.as_str()) {
+                std::fs::read(target_dir.join(&change.path))
+                    .ok()
+                    .map(|content| content_hash(&content))
+            } else {
+                manifest
+                    .files
+                    .iter()
+                    .find(|e| e.preset == preset_source && e.path == change.path)
+                    .and_then(|e| e.hash.clone())
+            };
+            ManifestEntry {
+                path: change.path.clone(),
+                tool: tool_name.clone(),
+                section: change.section.clone(),
+                preset: preset_source.to_string(),
+                hash,
+            }
+        })
+        .collect();
+    entries.extend(kept.iter().cloned());
+
+    manifest.replace(preset_source, tool_names, entries);
+}
+
+/// Collect per-file outcomes of this pull for `--report`
+fn report_entries(
+    preset_source: &str,
+    all_changes: &[(String, PendingChange)],
+    results: &[(String, ApplyResult)],
+    pruned: &[ManifestEntry],
+) -> Vec<ReportEntry> {
+    let timestamp = now_rfc3339();
+
+    let mut entries = Vec::new();
+    for (tool_name, result) in results {
+        let outcomes = [
+            (FileStatus::Created, &result.created),
+            (FileStatus::Updated, &result.updated),
+            (FileStatus::Skipped, &result.skipped),
+            (FileStatus::Unchanged, &result.unchanged),
+        ];
+        let failed: Vec<String> = result.failed.iter().map(|(path, _)| path.clone()).collect();
+        let outcomes = outcomes
+            .into_iter()
+            .chain(std::iter::once((FileStatus::Failed, &failed)));
+        for (status, paths) in outcomes {
+            for path in paths {
+                entries.push(ReportEntry {
+                    file: ManifestEntry {
+                        path: path.clone(),
+                        tool: tool_name.clone(),
+                        section: section_of(all_changes, tool_name, path),
+                        preset: preset_source.to_string(),
+                        hash: None,
+                    },
+                    status,
+                    timestamp: timestamp.clone(),
+                });
+            }
+        }
+    }
+    entries.extend(pruned.iter().map(|entry| ReportEntry {
+        file: entry.clone(),
+        status: FileStatus::Deleted,
+        timestamp: timestamp.clone(),
+    }));
+    entries
+}
+
+/// Section of an applied file, looked up from the scanned changes
+fn section_of(all_changes: &[(String, PendingChange)], tool_name: &str, path: &str) -> String {
+    all_changes
+        .iter()
+        .find(|(tool, change)| tool == tool_name && change.path == path)
+        .map(|(_, change)| change.section.clone())
+        .unwrap_or_default()
+}
+
+/// Preset sections in display order
+const SECTION_ORDER: &[&str] = &[
+    "root", "rules", "memory", "commands", "mcp", "hooks", "agents", "skills", "settings",
+];
+
+/// One file line in the apply tree
+struct TreeLine {
+    sign: &'static str,
+    path: String,
+    reason: Option<String>,
+}
+
+impl TreeLine {
+    fn paint(&self) -> String {
+        let text = match &self.reason {
+            Some(reason) => format!("{} {} ({})", self.sign, self.path, reason),
+            None => format!("{} {}", self.sign, self.path),
+        };
+        match self.sign {
+            "+" => text.green().to_string(),
+            "~" => text.yellow().to_string(),
+            "!" => text.red().to_string(),
+            _ => text.dimmed().to_string(),
         }
     }
+}
 
-    if all_changes.is_empty() {
-        println!("{}", "No changes to apply.".yellow());
-        return Ok(());
+/// Compact per-sign counts (e.g., "+2 ~1 =3"), omitting zeros
+fn tree_counts(lines: &[&TreeLine]) -> String {
+    ["+", "~", "!", "-", "="]
+        .iter()
+        .map(|sign| (sign, lines.iter().filter(|l| l.sign == *sign).count()))
+        .filter(|(_, count)| *count > 0)
+        .map(|(sign, count)| format!("{}{}", sign, count))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Print apply result for a tool or root as a tool > section > file tree
+///
+/// Sections where every file was unchanged are only listed with --verbose.
+/// Box-drawing glyphs fall back to ASCII when stdout is not a terminal.
+fn print_apply_result(
+    name: &str,
+    result: &ApplyResult,
+    all_changes: &[(String, PendingChange)],
+    verbose: bool,
+) {
+    let plain = |sign: &'static str, paths: &[String]| -> Vec<TreeLine> {
+        paths
+            .iter()
+            .map(|path| TreeLine {
+                sign,
+                path: path.clone(),
+                reason: None,
+            })
+            .collect()
+    };
+    let mut lines = plain("+", &result.created);
+    lines.extend(plain("~", &result.updated));
+    lines.extend(result.failed.iter().map(|(path, reason)| TreeLine {
+        sign: "!",
+        path: path.clone(),
+        reason: Some(reason.clone()),
+    }));
+    lines.extend(plain("-", &result.skipped));
+    lines.extend(plain("=", &result.unchanged));
+    if lines.is_empty() {
+        return;
     }
 
-    // Phase 2: Display changes
-    println!();
-    println!("{}", "Changes to apply:".white().bold());
+    let mut sections: Vec<(String, Vec<&TreeLine>)> = Vec::new();
+    for line in &lines {
+        let section = section_of(all_changes, name, &line.path);
+        match sections.iter_mut().find(|(s, _)| *s == section) {
+            Some((_, group)) => group.push(line),
+            None => sections.push((section, vec![line])),
+        }
+    }
+    sections.sort_by_key(|(section, _)| {
+        SECTION_ORDER
+            .iter()
+            .position(|s| s == section)
+            .unwrap_or(SECTION_ORDER.len())
+    });
 
-    let creates: Vec<_> = all_changes.iter().filter(|(_, c)| !c.is_conflict).collect();
-    let conflicts: Vec<_> = all_changes
-        .iter()
-        .filter(|(_, c)| c.is_conflict && !c.is_identical)
-        .collect();
-    let unchanged: Vec<_> = all_changes.iter().filter(|(_, c)| c.is_identical).collect();
+    let all: Vec<&TreeLine> = lines.iter().collect();
+    println!(
+        "\n{} {} {}",
+        "Applied to".cyan(),
+        name.white().bold(),
+        format!("({})", tree_counts(&all)).dimmed()
+    );
 
-    for (tool_name, change) in &unchanged {
+    let (hidden, shown): (Vec<_>, Vec<_>) = sections
+        .into_iter()
+        .partition(|(_, group)| !verbose && group.iter().all(|l| l.sign == "="));
+
+    let (branch, last, pipe) = if io::stdout().is_terminal() {
+        ("├─", "└─", "│ ")
+    } else {
+        ("|-", "`-", "| ")
+    };
+    for (i, (section, group)) in shown.iter().enumerate() {
+        let is_last = i + 1 == shown.len();
+        let label = if section.is_empty() { "other" } else { section };
         println!(
-            "  {} {} {} {}",
-            "UNCHANGED".dimmed(),
-            change.path.dimmed(),
-            format!("({})", change.section).dimmed(),
-            format!("[{}]", tool_name).dimmed()
+            "  {} {} {}",
+            if is_last { last } else { branch },
+            label.white(),
+            format!("({})", tree_counts(group)).dimmed()
         );
+        for (j, line) in group.iter().enumerate() {
+            println!(
+                "  {}  {} {}",
+                if is_last { "  " } else { pipe },
+                if j + 1 == group.len() { last } else { branch },
+                line.paint()
+            );
+        }
     }
-
-    for (tool_name, change) in &creates {
+    if !hidden.is_empty() {
         println!(
-            "  {} {} {} {}",
-            "CREATE".green().bold(),
-            change.path.white(),
-            format!("({})", change.section).dimmed(),
-            format!("[{}]", tool_name).dimmed()
+            "  {}",
+            format!(
+                "{} unchanged section(s) hidden (use --verbose to show)",
+                hidden.len()
+            )
+            .dimmed()
         );
     }
+}
 
-    for (tool_name, change) in &conflicts {
+/// Run a preset apply hook in the project directory
+///
+/// Hooks only run when allowed (--run-hooks or a trusted repository), and the
+/// command is always shown first. A non-zero exit status only produces a warning.
+fn run_apply_hook(
+    stage: &str,
+    hook: &ApplyHook,
+    allowed: bool,
+    target_dir: &Path,
+    yes: bool,
+) -> Result<()> {
+    println!();
+    if !allowed {
         println!(
-            "  {} {} {} {} {}",
-            "UPDATE".yellow().bold(),
-            change.path.white(),
-            "(conflict)".red(),
-            format!("({})", change.section).dimmed(),
-            format!("[{}]", tool_name).dimmed()
+            "{} Preset defines a {} hook (not run): {}",
+            "Note:".yellow(),
+            stage,
+            hook.command.white()
         );
+        println!("  Pass {} to run it.", "--run-hooks".cyan());
+        return Ok(());
     }
 
-    println!();
+    println!(
+        "{} {} {}",
+        "Hook".cyan(),
+        stage.white().bold(),
+        format!("$ {}", hook.command).white()
+    );
+    if !yes && !confirm("Run this command?")? {
+        println!("{}", "Hook skipped.".yellow());
+        return Ok(());
+    }
 
-    // Phase 3: Handle dry-run mode
-    if dry_run {
-        println!("{}", "═══ DRY RUN MODE ═══".yellow().bold());
-        if !conflicts.is_empty() {
-            let mut summary_parts = vec![format!("{} conflict(s) found.", conflicts.len())];
-            if !unchanged.is_empty() {
-                summary_parts.push(format!("{} file(s) unchanged.", unchanged.len()));
-            }
+    match shell_command(&hook.command)
+        .current_dir(target_dir)
+        .status()
+    {
+        Ok(status) if status.success() => {
+            println!("{} {} hook finished", "✓".green(), stage);
+        }
+        Ok(status) => {
+            let code = status
+                .code()
+                .map_or("a signal".to_string(), |c| format!("exit code {}", c));
             println!(
-                "{} {}",
-                summary_parts.join(" ").yellow(),
-                "Run without --dry-run to apply.".cyan()
+                "{} {} hook failed with {}",
+                "Warning:".yellow().bold(),
+                stage,
+                code
             );
-        } else if !unchanged.is_empty() {
+        }
+        Err(e) => {
             println!(
-                "{} {}",
-                format!("No conflicts. {} file(s) unchanged.", unchanged.len()).green(),
-                "Run without --dry-run to apply.".cyan()
+                "{} could not run {} hook: {}",
+                "Warning:".yellow().bold(),
+                stage,
+                e
             );
-        } else {
-            println!("{}", "No conflicts. Run without --dry-run to apply.".cyan());
         }
-        return Ok(());
     }
+    Ok(())
+}
 
-    // Phase 4: Determine conflict mode
-    let mut conflict_mode = if force {
-        ConflictMode::Force
-    } else if skip {
-        ConflictMode::Skip
-    } else if conflicts.is_empty() {
-        // No conflicts, proceed directly
-        ConflictMode::Force
-    } else {
-        // Ask user how to handle conflicts
-        ask_conflict_resolution(conflicts.len())?
+/// Tell how each tool with created or updated files loads them
+///
+/// Tools are never restarted automatically; with --reload the suggested command
+/// (which reopens the project in the running editor) is run. Commands are not
+/// suggested for --global, which has no project to reopen.
+fn print_reload_hints(
+    tools: &[Box<dyn ToolAdapter>],
+    results: &[(String, ApplyResult)],
+    target_dir: &Path,
+    options: &PullOptions,
+) {
+    let changed = |name: &str| {
+        results
+            .iter()
+            .any(|(tool, r)| tool == name && (!r.created.is_empty() || !r.updated.is_empty()))
     };
-
-    // Phase 4.5: Pre-resolve all conflicts when interactive mode selected
-    // When interactive mode is selected, resolve all conflicts first then apply in batch
-    if matches!(conflict_mode, ConflictMode::Ask) {
-        let decisions = pre_resolve_conflicts(&conflicts, &target_dir);
-        conflict_mode = ConflictMode::PreResolved {
-            decisions,
-            fallback_all: None,
-        };
+    let hints: Vec<_> = tools
+        .iter()
+        .filter(|tool| changed(tool.name()))
+        .filter_map(|tool| tool.reload_hint().map(|hint| (tool.name(), hint)))
+        .collect();
+    if hints.is_empty() {
+        return;
     }
 
-    // Phase 5: Apply changes
-    println!("{}", "Applying...".cyan());
-
-    // Apply root files first (tool-agnostic)
-    if !preset_files.root.is_empty() {
-        let root_result = apply_root_files(&preset_files.root, &target_dir, &mut conflict_mode)?;
-        print_apply_result("Root", &root_result);
+    println!("\n{}", "To load the changes:".cyan());
+    let mut suggested = false;
+    for (name, hint) in &hints {
+        println!("  {} {}: {}", "-".cyan(), name.white().bold(), hint.message);
+        let Some(command) = hint.command.filter(|_| !options.global) else {
+            continue;
+        };
+        let line = format!("{} {}", command.join(" "), target_dir.display());
+        if !options.reload {
+            println!("    {} {}", "run:".dimmed(), line.cyan());
+            suggested = true;
+            continue;
+        }
+        log::info(&format!("reload: {}", line));
+        match std::process::Command::new(command[0])
+            .args(&command[1..])
+            .arg(target_dir)
+            .status()
+        {
+            Ok(status) if status.success() => println!("    {} {}", "✓ Ran".green(), line),
+            Ok(_) | Err(_) => println!("    {} could not run {}", "Warning:".yellow().bold(), line),
+        }
     }
-
-    // Apply tool-specific files
-    for tool in tools {
-        let result = tool.apply(&preset_files, &target_dir, &mut conflict_mode)?;
-        print_apply_result(tool.name(), &result);
+    if suggested {
+        println!(
+            "  {}",
+            "Pass --reload to run these commands after applying.".dimmed()
+        );
     }
+}
 
-    println!();
-    println!("{}", "Preset applied successfully!".green().bold());
-
-    Ok(())
+/// Command running `command` through the platform shell
+fn shell_command(command: &str) -> std::process::Command {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut cmd = std::process::Command::new(shell);
+    cmd.arg(flag).arg(command);
+    cmd
 }
 
-/// Print apply result for a tool or root
-fn print_apply_result(name: &str, result: &ApplyResult) {
-    let has_changes = !result.created.is_empty()
-        || !result.updated.is_empty()
-        || !result.skipped.is_empty()
-        || !result.unchanged.is_empty();
+/// Target path written by more than one preset file
+#[derive(Debug, PartialEq)]
+struct Overlap {
+    path: String,
+    tool: String,
+    /// Preset files (or merged sections) writing the path, in apply order
+    sources: Vec<String>,
+}
 
-    if has_changes {
-        println!("\n{} {}", "Applied to".cyan(), name.white().bold());
+/// Root files whose target a tool also writes, with the tool and section writing it
+///
+/// Root changes are scanned first, so every later change to the same path comes
+/// from a tool adapter.
+fn shadowed_root_files(changes: &[(String, PendingChange)]) -> Vec<(String, String)> {
+    changes
+        .iter()
+        .filter(|(tool, _)| tool == "Root")
+        .filter_map(|(_, root)| {
+            let (tool, change) = changes
+                .iter()
+                .find(|(tool, change)| tool != "Root" && change.path == root.path)?;
+            let source = match &change.source {
+                Some(source) => source.clone(),
+                None => format!("{}/ (merged)", change.section),
+            };
+            Some((root.path.clone(), format!("{} [{}]", source, tool)))
+        })
+        .collect()
+}
 
-        if !result.unchanged.is_empty() {
-            println!("  {}:", "Unchanged".dimmed());
-            for file in &result.unchanged {
-                println!("    {} {}", "=".dimmed(), file.dimmed());
-            }
+/// Find target paths produced by more than one change
+///
+/// Merged sections sharing a file (e.g., Claude mcp and settings in
+/// settings.local.json) are combined on apply and don't count as overlaps.
+fn find_overlaps(changes: &[(String, PendingChange)]) -> Vec<Overlap> {
+    let mut by_path: Vec<(&str, Vec<&(String, PendingChange)>)> = Vec::new();
+    for entry in changes {
+        match by_path.iter_mut().find(|(path, _)| *path == entry.1.path) {
+            Some((_, group)) => group.push(entry),
+            None => by_path.push((&entry.1.path, vec![entry])),
         }
+    }
 
-        if !result.skipped.is_empty() {
-            println!("  {}:", "Skipped".dimmed());
-            for file in &result.skipped {
-                println!("    {} {}", "-".dimmed(), file.dimmed());
+    by_path
+        .into_iter()
+        .filter(|(_, group)| group.len() > 1 && group.iter().any(|(_, c)| c.source.is_some()))
+        .map(|(path, group)| {
+            let mut tools: Vec<&str> = Vec::new();
+            for (tool, _) in &group {
+                if !tools.contains(&tool.as_str()) {
+                    tools.push(tool);
+                }
+            }
+            Overlap {
+                path: path.to_string(),
+                tool: tools.join(", "),
+                sources: group
+                    .iter()
+                    .map(|(_, c)| match &c.source {
+                        Some(source) => source.clone(),
+                        None => format!("{}/ (merged)", c.section),
+                    })
+                    .collect(),
             }
+        })
+        .collect()
+}
+
+/// Run every adapter's apply with simulated writes and correct the scan verdicts
+///
+/// Scans only check whether merged files (memory, settings, mcp, hooks) exist; the
+/// simulation builds their final content exactly as a real apply would, so dry-run
+/// reports the same created/updated/unchanged outcome. Returns lines added/removed
+/// per (tool, path) for created and updated files.
+pub(crate) fn simulate_apply(
+    preset_files: &PresetFiles,
+    tools: &[Box<dyn ToolAdapter>],
+    target_dir: &Path,
+    ctx: &ApplyContext,
+    all_changes: &mut [(String, PendingChange)],
+) -> Result<BTreeMap<(String, String), (usize, usize)>> {
+    let ctx = ctx.for_dry_run();
+    let simulated = (|| {
+        let mut mode = ConflictMode::Force;
+        let mut results = vec![(
+            "Root".to_string(),
+            apply_root_files(&preset_files.root, target_dir, &mut mode, &ctx)?,
+        )];
+        for tool in tools {
+            let result = tool.apply(preset_files, target_dir, &mut mode, &ctx)?;
+            results.push((tool.name().to_string(), result));
         }
+        Ok::<_, AidotError>(results)
+    })();
 
-        if !result.created.is_empty() {
-            println!("  {}:", "Created".green());
-            for file in &result.created {
-                println!("    {} {}", "+".green(), file.white());
+    let mut line_stats = BTreeMap::new();
+    // A file merged from several sections (e.g. mcp and settings) is scanned once per section
+    let mut reconciled: HashSet<usize> = HashSet::new();
+    for (tool_name, result) in simulated? {
+        let verdicts = [
+            (&result.created, false, false),
+            (&result.updated, true, false),
+            (&result.unchanged, true, true),
+        ];
+        for (paths, is_conflict, is_identical) in verdicts {
+            for path in paths {
+                let found = (0..all_changes.len()).find(|i| {
+                    let (tool, change) = &all_changes[*i];
+                    *tool == tool_name && change.path == *path && !reconciled.contains(i)
+                });
+                if let Some(i) = found {
+                    reconciled.insert(i);
+                    all_changes[i].1.is_conflict = is_conflict;
+                    all_changes[i].1.is_identical = is_identical;
+                }
             }
         }
+        for (path, stats) in result.line_stats {
+            line_stats.insert((tool_name.clone(), path), stats);
+        }
+    }
+    Ok(line_stats)
+}
+
+/// Write only the files that don't exist yet (every conflict is skipped)
+///
+/// Returns the created and failed files per tool; the skipped conflicts are
+/// handled by the second, regular apply.
+fn apply_new_files(
+    preset_files: &PresetFiles,
+    tools: &[Box<dyn ToolAdapter>],
+    target_dir: &Path,
+    ctx: &ApplyContext,
+) -> Result<Vec<(String, ApplyResult)>> {
+    let mut mode = ConflictMode::Skip;
+    let mut results = Vec::new();
+    if !preset_files.root.is_empty() {
+        results.push((
+            "Root".to_string(),
+            apply_root_files(&preset_files.root, target_dir, &mut mode, ctx)?,
+        ));
+    }
+    for tool in tools {
+        results.push((
+            tool.name().to_string(),
+            tool.apply(preset_files, target_dir, &mut mode, ctx)?,
+        ));
+    }
+    for (_, result) in &mut results {
+        result.skipped.clear();
+        result.unchanged.clear();
+    }
+    results.retain(|(_, result)| !result.created.is_empty() || !result.failed.is_empty());
+    Ok(results)
+}
+
+/// Drop files already handled by the new-files pass from a second-pass result
+///
+/// The second pass sees the files created by the first one as unchanged.
+fn without_first_pass(
+    result: &mut ApplyResult,
+    tool_name: &str,
+    first_pass: &[(String, ApplyResult)],
+) {
+    let Some((_, first)) = first_pass.iter().find(|(name, _)| name == tool_name) else {
+        return;
+    };
+    let handled = |path: &String| {
+        first.created.contains(path) || first.failed.iter().any(|(failed, _)| failed == path)
+    };
+    for paths in [
+        &mut result.created,
+        &mut result.updated,
+        &mut result.skipped,
+        &mut result.unchanged,
+    ] {
+        paths.retain(|path| !handled(path));
+    }
+    result.failed.retain(|(path, _)| !handled(path));
+}
 
-        if !result.updated.is_empty() {
-            println!("  {}:", "Updated".yellow());
-            for file in &result.updated {
-                println!("    {} {}", "~".yellow(), file.white());
+/// Fold the new-files pass into the per-tool results of the regular apply
+fn merge_first_pass(
+    results: &mut Vec<(String, ApplyResult)>,
+    first_pass: Vec<(String, ApplyResult)>,
+) {
+    for (tool_name, first) in first_pass {
+        match results.iter_mut().find(|(name, _)| *name == tool_name) {
+            Some((_, result)) => {
+                result.created.splice(0..0, first.created);
+                result.failed.splice(0..0, first.failed);
+                result.line_stats.extend(first.line_stats);
             }
+            None => results.push((tool_name, first)),
         }
     }
 }
 
 /// Apply root files directly to target directory
-fn apply_root_files(
+pub(crate) fn apply_root_files(
     root_files: &[crate::adapters::traits::PresetFile],
     target_dir: &Path,
     conflict_mode: &mut ConflictMode,
+    ctx: &ApplyContext,
 ) -> Result<ApplyResult> {
     let mut result = ApplyResult::new();
 
     for file in root_files {
         let target_path = target_dir.join(&file.relative_path);
 
-        write_with_conflict(
+        write_preset_file(
+            file,
             &target_path,
             &file.content,
             conflict_mode,
+            ctx,
             &mut result,
             &file.relative_path,
         )?;
@@ -307,12 +2002,76 @@ fn apply_root_files(
     Ok(result)
 }
 
+/// Ask which changed files to apply; each target path is one item (decisions keyed by path)
+///
+/// A merged file (e.g., settings.local.json from mcp/ and settings/) can only be
+/// included or excluded as a whole. Returns None when the user cancels.
+fn select_changes(changes: &[(String, PendingChange)]) -> Result<Option<HashMap<String, bool>>> {
+    // (path, sections, tools, is_conflict) in scan order
+    let mut targets: Vec<(String, Vec<String>, Vec<String>, bool)> = Vec::new();
+    for (tool_name, change) in changes.iter().filter(|(_, c)| !c.is_identical) {
+        match targets.iter_mut().find(|(path, ..)| *path == change.path) {
+            Some((_, sections, tools, is_conflict)) => {
+                if !sections.contains(&change.section) {
+                    sections.push(change.section.clone());
+                }
+                if !tools.contains(tool_name) {
+                    tools.push(tool_name.clone());
+                }
+                *is_conflict |= change.is_conflict;
+            }
+            None => targets.push((
+                change.path.clone(),
+                vec![change.section.clone()],
+                vec![tool_name.clone()],
+                change.is_conflict,
+            )),
+        }
+    }
+
+    let items: Vec<String> = targets
+        .iter()
+        .map(|(path, sections, tools, is_conflict)| {
+            let action = if *is_conflict {
+                "UPDATE".yellow().bold()
+            } else {
+                "CREATE".green().bold()
+            };
+            let sections = if sections.len() > 1 {
+                format!("(merged: {})", sections.join(", "))
+            } else {
+                format!("({})", sections[0])
+            };
+            format!(
+                "{} {} {} {}",
+                action,
+                path.white(),
+                sections.dimmed(),
+                format!("[{}]", tools.join(", ")).dimmed()
+            )
+        })
+        .collect();
+
+    let Some(checked) = select::multi_select("Select files to apply:", &items)? else {
+        return Ok(None);
+    };
+    println!();
+    Ok(Some(
+        targets
+            .into_iter()
+            .zip(checked)
+            .map(|((path, ..), write)| (path, write))
+            .collect(),
+    ))
+}
+
 /// Pre-resolve all conflicts interactively before applying
 /// Show diff for all conflicting files and collect user decisions
 fn pre_resolve_conflicts(
     conflicts: &[&(String, PendingChange)],
     target_dir: &Path,
-) -> HashMap<String, bool> {
+    ctx: &ApplyContext,
+) -> Option<HashMap<String, bool>> {
     let mut decisions = HashMap::new();
 
     println!("\n{}", "Resolving conflicts interactively...".cyan().bold());
@@ -362,13 +2121,13 @@ fn pre_resolve_conflicts(
         // Auto-display diff if available
         if let (Some(ref existing), Some(ref preset)) = (&existing_content, &change.preset_content)
         {
-            ConflictMode::print_diff(&change.path, existing, preset);
+            ConflictMode::print_diff(ctx, &change.path, existing, preset);
         }
 
         let diff_available = existing_content.is_some() && change.preset_content.is_some();
 
         loop {
-            let decision = ConflictMode::ask_user(&change.path, diff_available);
+            let decision = ConflictMode::ask_user(ctx, &change.path, diff_available);
             match decision {
                 ConflictDecision::Overwrite => {
                     decisions.insert(change.path.clone(), true);
@@ -404,10 +2163,11 @@ fn pre_resolve_conflicts(
                     if let (Some(ref existing), Some(ref preset)) =
                         (&existing_content, &change.preset_content)
                     {
-                        ConflictMode::print_diff(&change.path, existing, preset);
+                        ConflictMode::print_diff(ctx, &change.path, existing, preset);
                     }
                     // Ask again
                 }
+                ConflictDecision::Quit => return None,
             }
         }
 
@@ -415,11 +2175,93 @@ fn pre_resolve_conflicts(
     }
 
     println!();
-    decisions
+    Some(decisions)
+}
+
+/// Ask a yes/no question (defaults to no)
+pub(crate) fn confirm(prompt: &str) -> Result<bool> {
+    print!("{} {} ", prompt.yellow(), "[y/N]".dimmed());
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Drop `--exclude-tools` entries from the detected tools
+///
+/// Returns true (after explaining why) when nothing is left to apply.
+fn exclude_detected(detected: &mut Vec<Box<dyn ToolAdapter>>, excluded: &[&str]) -> bool {
+    if excluded.is_empty() {
+        return false;
+    }
+    let (kept, dropped): (Vec<_>, Vec<_>) = std::mem::take(detected)
+        .into_iter()
+        .partition(|tool| !excluded.contains(&tool.name()));
+    *detected = kept;
+    if !dropped.is_empty() {
+        let names: Vec<&str> = dropped.iter().map(|tool| tool.name()).collect();
+        println!(
+            "{}",
+            format!("Excluding {} (--exclude-tools)", names.join(", ")).dimmed()
+        );
+    }
+    if detected.is_empty() {
+        println!(
+            "{}",
+            "All detected tools are excluded by --exclude-tools; nothing to apply.".yellow()
+        );
+        println!(
+            "Drop a tool from --exclude-tools or use '{}' to pick tools explicitly.",
+            "--tools".cyan()
+        );
+        return true;
+    }
+    false
+}
+
+/// Ask which tools to set up when none is detected
+///
+/// Returns `None` when the answer is empty or stdin is closed.
+fn ask_tools_to_create() -> Result<Option<Vec<String>>> {
+    loop {
+        print!(
+            "{} {} ",
+            "Which tools should be set up?".cyan(),
+            format!("({}, comma-separated; empty to cancel)", tool_choices()).dimmed()
+        );
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input)? == 0 {
+            return Ok(None);
+        }
+        let selected = parse_tool_list(&input);
+        if selected.is_empty() {
+            return Ok(None);
+        }
+        match resolve_tool_filter(&selected) {
+            Ok(_) => {
+                println!();
+                return Ok(Some(selected));
+            }
+            Err(e) => println!("  {}", e.to_string().red()),
+        }
+    }
+}
+
+/// Split a comma- or space-separated tool list ("claude, cursor")
+fn parse_tool_list(input: &str) -> Vec<String> {
+    input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
 }
 
 /// Ask user how to handle conflicts
-fn ask_conflict_resolution(conflict_count: usize) -> Result<ConflictMode> {
+fn ask_conflict_resolution(conflict_count: usize, ctx: &ApplyContext) -> Result<ConflictMode> {
     println!(
         "{} {} {}",
         conflict_count.to_string().yellow().bold(),
@@ -433,8 +2275,10 @@ fn ask_conflict_resolution(conflict_count: usize) -> Result<ConflictMode> {
     print!("\n{} ", "Your choice:".cyan());
     io::stdout().flush()?;
 
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
+    // Stdin closed: follow the configured default policy
+    let Some(input) = ctx.read_answer() else {
+        return Ok(ConflictMode::from_policy(ctx.no_input_policy));
+    };
 
     match input.trim().to_lowercase().as_str() {
         "f" | "force" => Ok(ConflictMode::Force),
@@ -450,3 +2294,261 @@ fn ask_conflict_resolution(conflict_count: usize) -> Result<ConflictMode> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::claude_code::ClaudeCodeAdapter;
+    use crate::adapters::traits::{PresetFile, PresetFiles, ToolAdapter};
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_dedup_sources() {
+        let sources = ["common", "team", "common", "./local", "team"]
+            .map(String::from)
+            .to_vec();
+        let (unique, duplicates) = dedup_sources(sources);
+        assert_eq!(unique, vec!["common", "team", "./local"]);
+        assert_eq!(duplicates, vec!["common", "team"]);
+    }
+
+    #[test]
+    fn test_new_files_applied_before_conflicts() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join(".claude/rules")).unwrap();
+        fs::write(temp_dir.path().join(".claude/rules/style.md"), "# Local").unwrap();
+
+        let preset_files = PresetFiles {
+            rules: ["style.md", "testing.md"]
+                .iter()
+                .map(|name| PresetFile {
+                    relative_path: format!("rules/{}", name),
+                    content: "# Preset".to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let tools: Vec<Box<dyn ToolAdapter>> =
+            vec![Box::new(ClaudeCodeAdapter::new(temp_dir.path()))];
+
+        // First pass: only the new file is written, the conflict is left alone
+        let first_pass = apply_new_files(
+            &preset_files,
+            &tools,
+            temp_dir.path(),
+            &ApplyContext::default(),
+        )
+        .unwrap();
+        assert_eq!(first_pass.len(), 1);
+        assert_eq!(first_pass[0].1.created, vec![".claude/rules/testing.md"]);
+        assert!(first_pass[0].1.skipped.is_empty());
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join(".claude/rules/style.md")).unwrap(),
+            "# Local"
+        );
+
+        // Second pass resolves the conflict; the new file still counts as created
+        let mut mode = ConflictMode::Force;
+        let mut result = tools[0]
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut mode,
+                &ApplyContext::default(),
+            )
+            .unwrap();
+        without_first_pass(&mut result, "Claude Code", &first_pass);
+        assert!(result.unchanged.is_empty());
+        let mut results = vec![("Claude Code".to_string(), result)];
+        merge_first_pass(&mut results, first_pass);
+        assert_eq!(results[0].1.created, vec![".claude/rules/testing.md"]);
+        assert_eq!(results[0].1.updated, vec![".claude/rules/style.md"]);
+    }
+
+    #[test]
+    fn test_conflict_mode_shared_between_root_and_tools() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("AGENTS.md"), "# Local agents").unwrap();
+        fs::create_dir_all(temp_dir.path().join(".claude/rules")).unwrap();
+        fs::write(temp_dir.path().join(".claude/rules/style.md"), "# Local").unwrap();
+
+        let preset_files = PresetFiles {
+            root: vec![PresetFile {
+                relative_path: "AGENTS.md".to_string(),
+                content: "# Preset agents".to_string(),
+                ..Default::default()
+            }],
+            rules: vec![PresetFile {
+                relative_path: "rules/style.md".to_string(),
+                content: "# Preset".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        // Root file overwritten by its decision, the rest falls back to "skip all"
+        let mut mode = ConflictMode::PreResolved {
+            decisions: HashMap::from([("AGENTS.md".to_string(), true)]),
+            fallback_all: Some(false),
+        };
+        let root_result = apply_root_files(
+            &preset_files.root,
+            temp_dir.path(),
+            &mut mode,
+            &ApplyContext::default(),
+        )
+        .unwrap();
+        let adapter = ClaudeCodeAdapter::new(temp_dir.path());
+        let tool_result = adapter
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut mode,
+                &ApplyContext::default(),
+            )
+            .unwrap();
+
+        assert_eq!(root_result.updated, vec!["AGENTS.md".to_string()]);
+        assert_eq!(tool_result.skipped.len(), 1);
+        assert_eq!(
+            fs::read_to_string(temp_dir.path().join(".claude/rules/style.md")).unwrap(),
+            "# Local"
+        );
+
+        // A quit during root resolution stops every later tool as well
+        let mut mode = ConflictMode::Quit;
+        apply_root_files(
+            &preset_files.root,
+            temp_dir.path(),
+            &mut mode,
+            &ApplyContext::default(),
+        )
+        .unwrap();
+        let tool_result = adapter
+            .apply(
+                &preset_files,
+                temp_dir.path(),
+                &mut mode,
+                &ApplyContext::default(),
+            )
+            .unwrap();
+        assert!(tool_result.created.is_empty() && tool_result.updated.is_empty());
+    }
+
+    #[test]
+    fn test_tree_counts() {
+        let line = |sign| TreeLine {
+            sign,
+            path: "a.md".to_string(),
+            reason: None,
+        };
+        let lines = [line("="), line("+"), line("="), line("~"), line("+")];
+        let refs: Vec<&TreeLine> = lines.iter().collect();
+        assert_eq!(tree_counts(&refs), "+2 ~1 =2");
+        assert_eq!(tree_counts(&[]), "");
+    }
+
+    #[test]
+    fn test_parse_tool_list() {
+        assert_eq!(
+            parse_tool_list("claude, cursor\n"),
+            vec!["claude", "cursor"]
+        );
+        assert_eq!(parse_tool_list("copilot claude"), vec!["copilot", "claude"]);
+        assert!(parse_tool_list("  \n").is_empty());
+    }
+
+    fn change(path: &str, section: &str, source: Option<&str>) -> PendingChange {
+        PendingChange {
+            path: path.to_string(),
+            section: section.to_string(),
+            is_conflict: false,
+            is_identical: false,
+            preset_content: None,
+            source: source.map(str::to_string),
+            modified_since_apply: false,
+        }
+    }
+
+    #[test]
+    fn test_shadowed_root_files() {
+        let changes = vec![
+            (
+                "Root".to_string(),
+                change(".cursorrules", "root", Some(".cursorrules")),
+            ),
+            (
+                "Root".to_string(),
+                change(".editorconfig", "root", Some(".editorconfig")),
+            ),
+            ("Cursor".to_string(), change(".cursorrules", "memory", None)),
+            (
+                "Amazon Q".to_string(),
+                change(".amazonq/rules/a.md", "rules", Some("rules/a.md")),
+            ),
+        ];
+
+        assert_eq!(
+            shadowed_root_files(&changes),
+            vec![(
+                ".cursorrules".to_string(),
+                "memory/ (merged) [Cursor]".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_find_overlaps() {
+        let changes = vec![
+            (
+                "Cursor".to_string(),
+                change(".cursor/rules/a.mdc", "rules", Some("rules/a.md")),
+            ),
+            (
+                "Cursor".to_string(),
+                change(".cursor/rules/a.mdc", "rules", Some("rules/a.mdc")),
+            ),
+            (
+                "Claude Code".to_string(),
+                change(".claude/settings.local.json", "mcp", None),
+            ),
+            (
+                "Claude Code".to_string(),
+                change(".claude/settings.local.json", "settings", None),
+            ),
+            (
+                "Claude Code".to_string(),
+                change(".claude/rules/a.md", "rules", Some("rules/a.md")),
+            ),
+        ];
+
+        assert_eq!(
+            find_overlaps(&changes),
+            vec![Overlap {
+                path: ".cursor/rules/a.mdc".to_string(),
+                tool: "Cursor".to_string(),
+                sources: vec!["rules/a.md".to_string(), "rules/a.mdc".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_section_sizes() {
+        let content = "intro\n# Style\nuse tabs\n```\n# not a heading\n```\n## Setup\nrun it\n\n---\n\n## Setup\nrun it\n";
+        let sections = section_sizes(content);
+        let summary: Vec<(&str, usize, usize)> = sections
+            .iter()
+            .map(|s| (s.heading.as_str(), s.bytes, s.count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("# Style", 41, 1),
+                ("## Setup", 38, 2),
+                ("(before the first heading)", 6, 1),
+            ]
+        );
+    }
+}
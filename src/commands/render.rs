@@ -0,0 +1,149 @@
+use super::pull::apply_root_files;
+use crate::adapters::conflict::{ApplyContext, ConflictMode};
+use crate::adapters::detector::{all_tools, resolve_tool_filter, select_tools};
+use crate::adapters::traits::ApplyResult;
+use crate::error::{AidotError, Result};
+use crate::output::display_path;
+use crate::preset::config::Normalize;
+use crate::preset::parser::parse_preset_cached;
+use crate::repository::resolve_repository_source;
+use colored::Colorize;
+use std::fs;
+use std::path::Path;
+
+/// Write what a preset turns into for each tool into an empty directory (`aidot render`)
+///
+/// Runs the same conversion as pull into `out_dir` with every file overwritten, but
+/// needs no project: nothing is detected, prompted, recorded in the manifest or
+/// hooked. Without `tools_filter` every supported tool is rendered. The output is a
+/// plain file tree, so it can be inspected or snapshot-tested in CI.
+pub fn render_preset(
+    repo_source: &str,
+    out_dir: &Path,
+    tools_filter: Option<&[String]>,
+) -> Result<()> {
+    ensure_empty_dir(out_dir)?;
+
+    let preset_path = resolve_repository_source(repo_source)?;
+    let (config, mut preset_files) = parse_preset_cached(&preset_path)?;
+    preset_files.apply_normalize(config.apply.normalize);
+    preset_files.apply_line_endings(config.apply.line_endings);
+    // `when` conditions are judged against the empty output directory
+    let skipped = preset_files.retain_conditions(&config.when, out_dir);
+
+    let tools = all_tools(out_dir, &config.tools);
+    let tools = match tools_filter {
+        Some(filter) => select_tools(tools, &resolve_tool_filter(filter)?),
+        None => tools,
+    };
+    if tools.is_empty() {
+        return Err(AidotError::InvalidInput(
+            "No tools selected by --tools".to_string(),
+        ));
+    }
+
+    println!(
+        "{} '{}' {} {}\n",
+        "Rendering preset".cyan().bold(),
+        repo_source.white(),
+        "into".cyan(),
+        display_path(out_dir).white()
+    );
+
+    let mut ctx = ApplyContext::default();
+    ctx.root = Some(out_dir.to_path_buf());
+    ctx.json_final_newline = config.apply.normalize != Normalize::None;
+
+    // Root files first: a tool section writing the same path wins, as in pull
+    let mut mode = ConflictMode::Force;
+    let mut rendered = vec![(
+        "Root".to_string(),
+        apply_root_files(&preset_files.root, out_dir, &mut mode, &ctx)?,
+    )];
+    for tool in &tools {
+        rendered.push((
+            tool.name().to_string(),
+            tool.apply(&preset_files, out_dir, &mut mode, &ctx)?,
+        ));
+    }
+
+    let mut total = 0;
+    for (name, result) in rendered {
+        let files = written_files(&result);
+        if files.is_empty() {
+            continue;
+        }
+        total += files.len();
+        println!("{} ({} files)", name.white().bold(), files.len());
+        for file in files {
+            println!("  {} {}", "+".green(), file);
+        }
+    }
+    if !skipped.is_empty() {
+        println!(
+            "\n{} {}",
+            skipped.len().to_string().white().bold(),
+            "file(s) left out by their `when` conditions".yellow()
+        );
+    }
+    println!(
+        "\n{} {} file(s) written to {}",
+        "✓".green(),
+        total,
+        display_path(out_dir)
+    );
+    Ok(())
+}
+
+/// The output directory may not exist yet, but must not hold anything
+fn ensure_empty_dir(out_dir: &Path) -> Result<()> {
+    if out_dir.exists() {
+        if !out_dir.is_dir() {
+            return Err(AidotError::InvalidInput(format!(
+                "{} is not a directory",
+                out_dir.display()
+            )));
+        }
+        if fs::read_dir(out_dir)?.next().is_some() {
+            return Err(AidotError::InvalidInput(format!(
+                "{} is not empty; render into a new or empty directory",
+                out_dir.display()
+            )));
+        }
+    }
+    fs::create_dir_all(out_dir)?;
+    Ok(())
+}
+
+/// Files a render wrote, sorted and without repeats (merged files count once)
+fn written_files(result: &ApplyResult) -> Vec<String> {
+    let mut files: Vec<String> = result
+        .created
+        .iter()
+        .chain(&result.updated)
+        .chain(&result.unchanged)
+        .cloned()
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_ensure_empty_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let out = temp_dir.path().join("out/nested");
+        ensure_empty_dir(&out).unwrap();
+        assert!(out.is_dir());
+        ensure_empty_dir(&out).unwrap();
+
+        fs::write(out.join("leftover.md"), "").unwrap();
+        assert!(ensure_empty_dir(&out).is_err());
+        assert!(ensure_empty_dir(&out.join("leftover.md")).is_err());
+    }
+}
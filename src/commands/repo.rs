@@ -1,9 +1,15 @@
+use crate::adapters::traits::SECTIONS;
+use crate::archive::{self, ArchiveFormat};
+use crate::cache;
 use crate::config::{self, Config, Repository, SourceType};
 use crate::error::{AidotError, Result};
 use crate::git;
+use crate::output::display_path;
+use crate::preset::config::Metadata;
+use crate::preset::{parse_preset, PresetConfig};
 use crate::repository;
 use colored::Colorize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Process a local path and validate it as a preset directory
 fn process_local_path(url: &str) -> Result<(String, SourceType)> {
@@ -39,26 +45,242 @@ fn process_local_path(url: &str) -> Result<(String, SourceType)> {
         )));
     }
 
+    verify_preset(&canonical)?;
+
     Ok((canonical.to_string_lossy().to_string(), SourceType::Local))
 }
 
+/// Parse a preset as pull would, rejecting it on failure
+///
+/// Sections configured without a matching directory only produce a warning,
+/// since pull treats them as empty.
+fn verify_preset(preset_dir: &Path) -> Result<()> {
+    let (config, _) = parse_preset(preset_dir)
+        .map_err(|e| AidotError::InvalidPreset(format!("{} ({})", e, preset_dir.display())))?;
+
+    println!(
+        "  {} {} {}",
+        "Preset:".cyan(),
+        config.metadata.name.white().bold(),
+        config.metadata.version.dimmed()
+    );
+    for line in config.metadata.provenance() {
+        println!("    {}", line.dimmed());
+    }
+
+    for section in SECTIONS {
+        let Some(dir) = config.section_dir(section) else {
+            continue;
+        };
+        if !preset_dir.join(&dir).is_dir() {
+            println!(
+                "{} [{}] is configured but {}/ does not exist",
+                "Warning:".yellow(),
+                section,
+                dir
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Normalize a Git URL before storing it, telling the user when it changed
+fn normalized_git_url(url: &str) -> String {
+    let normalized = repository::canonical_git_url(url);
+    if normalized != url {
+        println!(
+            "{} {} → {}",
+            "Normalized URL:".dimmed(),
+            url.dimmed(),
+            normalized.white()
+        );
+    }
+    normalized
+}
+
+/// Refuse to register a Git repository (and subdirectory) under a second name
+fn check_duplicate_url(cfg: &Config, name: &str, url: &str, subdir: Option<&str>) -> Result<()> {
+    let existing = cfg.repositories.iter().find(|repo| {
+        repo.name != name
+            && repo.source_type == SourceType::Git
+            && repository::normalize_git_url(&repo.url) == url
+            && repo.subdir.as_deref() == subdir
+    });
+    match existing {
+        Some(repo) => Err(AidotError::InvalidInput(format!(
+            "{} is already registered as '{}'.
+            Use that name, or remove it first: aidot repo remove {}",
+            display_url(url, subdir),
+            repo.name,
+            repo.name
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Clone a Git repository into a temporary directory and verify the preset in it
+fn verify_git_preset(url: &str, subdir: Option<&str>) -> Result<()> {
+    cache::require_online("--verify")?;
+    println!("{}", "Verifying repository...".cyan());
+    let tmp_dir = tempfile::tempdir()?;
+    let clone_path = tmp_dir.path().join("repo");
+    git::clone_repository(url, &clone_path)?;
+
+    let preset_dir = match subdir {
+        Some(subdir) => clone_path.join(subdir),
+        None => clone_path,
+    };
+    if !preset_dir.join(".aidot-config.toml").exists() {
+        return Err(AidotError::InvalidPreset(format!(
+            "Not a valid preset repository (missing .aidot-config.toml{}): {}",
+            subdir.map(|s| format!(" in '{}'", s)).unwrap_or_default(),
+            url
+        )));
+    }
+    verify_preset(&preset_dir)
+}
+
+/// Download an archive into a temporary directory and verify the preset in it
+fn verify_archive_preset(url: &str, subdir: Option<&str>, sha256: Option<&str>) -> Result<()> {
+    cache::require_online("--verify")?;
+    println!("{}", "Verifying archive...".cyan());
+    let format = ArchiveFormat::from_name(url)
+        .ok_or_else(|| AidotError::InvalidInput(format!("Unknown archive format: {}", url)))?;
+    let bytes = archive::download(url)?;
+    if let Some(expected) = sha256 {
+        archive::verify_sha256(&bytes, expected, url)?;
+    }
+    let tmp_dir = tempfile::tempdir()?;
+    let archive_path = tmp_dir.path().join("archive");
+    std::fs::write(&archive_path, &bytes)?;
+    let extract_dir = tmp_dir.path().join("extracted");
+    archive::extract(&archive_path, format, &extract_dir)?;
+
+    let root = archive::content_root(&extract_dir)?;
+    let preset_dir = match subdir {
+        Some(subdir) => root.join(subdir),
+        None => root,
+    };
+    if !preset_dir.join(".aidot-config.toml").exists() {
+        return Err(AidotError::InvalidPreset(format!(
+            "Not a valid preset archive (missing .aidot-config.toml{}): {}",
+            subdir.map(|s| format!(" in '{}'", s)).unwrap_or_default(),
+            url
+        )));
+    }
+    verify_preset(&preset_dir)
+}
+
+/// Validate a `--sha256` value (64 hex digits) and lowercase it
+fn normalize_sha256(sha256: Option<String>, source_type: &SourceType) -> Result<Option<String>> {
+    let Some(sha256) = sha256 else {
+        return Ok(None);
+    };
+    if *source_type != SourceType::Archive {
+        return Err(AidotError::InvalidInput(
+            "--sha256 only applies to archive URLs (.tar.gz, .tgz, .zip)".to_string(),
+        ));
+    }
+    let sha256 = sha256.trim().to_ascii_lowercase();
+    if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AidotError::InvalidInput(format!(
+            "Invalid --sha256 '{}': expected 64 hexadecimal digits",
+            sha256
+        )));
+    }
+    Ok(Some(sha256))
+}
+
+/// Trim and deduplicate tags given to `repo add --tag` (case-insensitive)
+fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_string();
+        if tag.is_empty() || tag.contains(char::is_whitespace) {
+            return Err(AidotError::InvalidInput(format!(
+                "Invalid tag '{}': tags must be non-empty and contain no spaces",
+                tag
+            )));
+        }
+        if !normalized.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            normalized.push(tag);
+        }
+    }
+    Ok(normalized)
+}
+
 /// Add a new repository or local preset
+#[allow(clippy::too_many_arguments)]
 pub fn add_repo(
     name: String,
     url: String,
     local: bool,
     default: bool,
     description: Option<String>,
+    subdir: Option<String>,
+    verify: bool,
+    tags: Vec<String>,
+    sha256: Option<String>,
 ) -> Result<()> {
+    let tags = normalize_tags(tags)?;
+
+    // Preset root inside the repository: --subdir or `<url>#path=<subdir>`
+    let (base_url, url_subdir) = repository::split_subdir(&url);
+    // A bare name that is not a path here is looked up in AIDOT_PRESET_PATH
+    let found = if Path::new(base_url).exists() {
+        None
+    } else {
+        repository::search_preset_path(base_url)
+    };
+    let local = local || found.is_some();
+    let base_url = match found {
+        Some(path) => {
+            println!(
+                "{} Found '{}' in {}: {}",
+                "Note:".yellow(),
+                base_url,
+                repository::PRESET_PATH_ENV,
+                display_path(&path)
+            );
+            path.to_string_lossy().to_string()
+        }
+        None => base_url.to_string(),
+    };
+    let subdir = match (subdir.as_deref(), url_subdir) {
+        (Some(flag), Some(fragment)) if flag != fragment => {
+            return Err(AidotError::InvalidInput(format!(
+                "Conflicting subdirectories: --subdir '{}' and #path={}",
+                flag, fragment
+            )));
+        }
+        (flag, fragment) => flag
+            .or(fragment)
+            .map(repository::normalize_subdir)
+            .transpose()?,
+    };
+    // Local presets store the subdirectory as part of the path
+    let local_url = match &subdir {
+        Some(subdir) => PathBuf::from(&base_url)
+            .join(subdir)
+            .to_string_lossy()
+            .to_string(),
+        None => base_url.clone(),
+    };
+    let url = base_url;
+
     // Determine source type: explicit --local flag, URL pattern, or auto-detect local path
     let (resolved_url, source_type, is_local) = if local {
         // Explicit --local flag: treat as local path
-        let (resolved, source) = process_local_path(&url)?;
+        let (resolved, source) = process_local_path(&local_url)?;
         (resolved, source, true)
+    } else if repository::is_archive_url(&url) {
+        // Release archive: downloaded over HTTP(S), git is not needed
+        (url.clone(), config::SourceType::Archive, false)
     } else if repository::is_git_url(&url) {
         // URL pattern detected: treat as Git repository
         git::check_git_available()?;
-        (url.clone(), config::SourceType::Git, false)
+        (normalized_git_url(&url), config::SourceType::Git, false)
     } else {
         // Not a URL pattern: check if it's an existing local path
         let path = PathBuf::from(&url);
@@ -70,7 +292,7 @@ pub fn add_repo(
 
         if absolute_path.exists() {
             // Path exists: auto-detect as local preset
-            let (resolved, source) = process_local_path(&url)?;
+            let (resolved, source) = process_local_path(&local_url)?;
             println!(
                 "{} Auto-detected as local path. Use --local flag to make this explicit.",
                 "Note:".yellow()
@@ -86,13 +308,32 @@ pub fn add_repo(
                 \x20 aidot repo add {} https://github.com/user/repo.git\n\
                 \n\
                 For local presets, use --local flag with an existing directory:\n\
-                \x20 aidot repo add {} /path/to/preset --local",
-                url, name, name
+                \x20 aidot repo add {} /path/to/preset --local\n\
+                \n\
+                Bare names are also looked up in {} (when set).",
+                url,
+                name,
+                name,
+                repository::PRESET_PATH_ENV
             )));
         }
     };
 
     let local = is_local;
+    let subdir = if local { None } else { subdir };
+    let sha256 = normalize_sha256(sha256, &source_type)?;
+
+    let mut cfg = Config::load()?;
+    if source_type == SourceType::Git {
+        check_duplicate_url(&cfg, &name, &resolved_url, subdir.as_deref())?;
+    }
+
+    // Local presets are always parsed above; Git repositories and archives only with --verify
+    if verify && source_type == SourceType::Archive {
+        verify_archive_preset(&resolved_url, subdir.as_deref(), sha256.as_deref())?;
+    } else if verify && !local {
+        verify_git_preset(&resolved_url, subdir.as_deref())?;
+    }
 
     let type_label = if local {
         "local preset".yellow()
@@ -104,10 +345,9 @@ pub fn add_repo(
         "Adding".cyan(),
         type_label,
         name.white().bold(),
-        resolved_url.dimmed()
+        display_url(&resolved_url, subdir.as_deref()).dimmed()
     );
 
-    let mut cfg = Config::load()?;
     let repo = Repository {
         name: name.clone(),
         url: resolved_url,
@@ -115,6 +355,10 @@ pub fn add_repo(
         default,
         cached_at: None,
         description,
+        subdir,
+        sha256,
+        trust_hooks: false,
+        tags,
     };
     cfg.add_repository(repo)?;
 
@@ -134,10 +378,17 @@ pub fn add_repo(
     Ok(())
 }
 
-/// List all registered repositories
-pub fn list_repos() -> Result<()> {
+/// List all registered repositories (only those with `tag` when given)
+pub fn list_repos(tag: Option<&str>) -> Result<()> {
     let cfg = Config::load()?;
-    if cfg.repositories.is_empty() {
+    let repositories: Vec<&Repository> = cfg
+        .repositories
+        .iter()
+        .filter(|repo| tag.is_none_or(|tag| repo.has_tag(tag)))
+        .collect();
+    if let (true, Some(tag)) = (repositories.is_empty(), tag) {
+        println!("{}", format!("No repositories tagged '{}'.", tag).yellow());
+    } else if repositories.is_empty() {
         println!("{}", "No repositories registered.".yellow());
         println!(
             "{}",
@@ -145,14 +396,20 @@ pub fn list_repos() -> Result<()> {
         );
     } else {
         println!("{}", "Registered repositories:".cyan().bold());
-        for repo in &cfg.repositories {
+        for repo in repositories {
             let mut flags = Vec::new();
             if repo.source_type == SourceType::Local {
                 flags.push("local".yellow().to_string());
             }
+            if repo.is_missing_local() {
+                flags.push("missing".red().bold().to_string());
+            }
             if repo.default {
                 flags.push("default".green().to_string());
             }
+            if repo.trust_hooks {
+                flags.push("trusted hooks".red().to_string());
+            }
             let flags_str = if flags.is_empty() {
                 String::new()
             } else {
@@ -162,18 +419,162 @@ pub fn list_repos() -> Result<()> {
                 "  {} {} {}{}",
                 "•".cyan(),
                 repo.name.white().bold(),
-                repo.url.dimmed(),
+                display_url(&repo.url, repo.subdir.as_deref()).dimmed(),
                 flags_str
             );
             if let Some(desc) = &repo.description {
                 println!("    {}", desc.dimmed());
             }
+            if !repo.tags.is_empty() {
+                println!("    {}", format!("tags: {}", repo.tags.join(", ")).dimmed());
+            }
+            if let Some(metadata) = preset_metadata(repo) {
+                for line in metadata.provenance() {
+                    println!("    {}", line.dimmed());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Metadata of a registered preset, read from its local path or cached clone
+///
+/// Never fetches: a Git repository that was not pulled yet has no metadata to show.
+pub(crate) fn preset_metadata(repo: &Repository) -> Option<Metadata> {
+    let root = match repo.source_type {
+        SourceType::Local => PathBuf::from(&repo.url),
+        SourceType::Git | SourceType::Archive => cache::get_cache_path(&repo.name).ok()?,
+    };
+    let preset_dir = match &repo.subdir {
+        Some(subdir) => root.join(subdir),
+        None => root,
+    };
+    PresetConfig::load(&preset_dir)
+        .ok()
+        .map(|config| config.metadata)
+}
+
+/// A problem found by `repo doctor`, with commands that fix it
+pub(crate) struct RepoProblem {
+    pub message: String,
+    pub suggestions: Vec<String>,
+}
+
+/// Check that a registered repository can be used by pull
+pub(crate) fn check_repository(repo: &Repository) -> Option<RepoProblem> {
+    let remove = format!("aidot repo remove {}", repo.name);
+    match repo.source_type {
+        SourceType::Local => {
+            let path = Path::new(&repo.url);
+            if !path.is_dir() {
+                Some(RepoProblem {
+                    message: format!("local path does not exist: {}", repo.url),
+                    suggestions: vec![
+                        format!("aidot repo set-url {} <new-path> --local", repo.name),
+                        remove,
+                    ],
+                })
+            } else if !path.join(".aidot-config.toml").exists() {
+                Some(RepoProblem {
+                    message: format!("missing .aidot-config.toml in {}", repo.url),
+                    suggestions: vec![format!("aidot init {}", repo.url), remove],
+                })
+            } else {
+                None
+            }
+        }
+        // Offline: pull can only use what is cached
+        SourceType::Git | SourceType::Archive if cache::is_offline() => {
+            let cached = cache::get_cache_path(&repo.name).is_ok_and(|path| path.is_dir());
+            (!cached).then(|| RepoProblem {
+                message: "not cached, and the remote is not checked offline".to_string(),
+                suggestions: vec![
+                    format!("aidot cache update {} (while online)", repo.name),
+                    remove,
+                ],
+            })
+        }
+        SourceType::Archive => archive::check_url(&repo.url).err().map(|e| RepoProblem {
+            message: format!("archive not reachable: {}", e),
+            suggestions: vec![
+                "check the URL and network".to_string(),
+                format!("aidot repo set-url {} <new-url>", repo.name),
+                remove,
+            ],
+        }),
+        SourceType::Git => git::check_remote(&repository::canonical_git_url(&repo.url))
+            .err()
+            .map(|e| RepoProblem {
+                message: format!("remote not reachable: {}", e),
+                suggestions: vec![
+                    "check the URL, network and Git credentials".to_string(),
+                    format!("aidot repo set-url {} <new-url>", repo.name),
+                    remove,
+                ],
+            }),
+    }
+}
+
+/// Check every registered repository (local path exists, Git remote reachable)
+///
+/// Fails when any repository has a problem, so it can gate scripts.
+pub fn doctor_repos() -> Result<()> {
+    let cfg = Config::load()?;
+    if cfg.repositories.is_empty() {
+        println!("{}", "No repositories registered.".yellow());
+        return Ok(());
+    }
+
+    println!("{}", "Checking registered repositories...".cyan().bold());
+    let mut problems = 0;
+    for repo in &cfg.repositories {
+        match check_repository(repo) {
+            None => println!(
+                "  {} {} {}",
+                "✓".green(),
+                repo.name.white().bold(),
+                display_url(&repo.url, repo.subdir.as_deref()).dimmed()
+            ),
+            Some(problem) => {
+                problems += 1;
+                println!(
+                    "  {} {} {}",
+                    "✗".red(),
+                    repo.name.white().bold(),
+                    problem.message.red()
+                );
+                for suggestion in &problem.suggestions {
+                    println!("      {} {}", "→".dimmed(), suggestion.cyan());
+                }
+            }
         }
     }
 
+    if problems > 0 {
+        return Err(AidotError::RepositoryNotFound(format!(
+            "{} of {} repositories have problems",
+            problems,
+            cfg.repositories.len()
+        )));
+    }
+    println!(
+        "
+{}",
+        "All repositories are reachable.".green()
+    );
     Ok(())
 }
 
+/// Repository URL with its preset subdirectory (e.g., `https://.../presets.git#path=frontend`)
+fn display_url(url: &str, subdir: Option<&str>) -> String {
+    match subdir {
+        Some(subdir) => format!("{}#path={}", url, subdir),
+        None => url.to_string(),
+    }
+}
+
 /// Remove a registered repository
 pub fn remove_repo(name: &str) -> Result<()> {
     let mut config = Config::load()?;
@@ -205,3 +606,80 @@ pub fn set_default_repo(name: &str, value: bool) -> Result<()> {
 
     Ok(())
 }
+
+/// Change the URL or local path of a registered repository
+///
+/// Local paths are validated like `repo add --local`; the cached clone is removed
+/// so the next pull clones from the new location.
+pub fn set_repo_url(name: &str, url: String, local: bool, sha256: Option<String>) -> Result<()> {
+    let mut cfg = Config::load()?;
+    if !cfg.repositories.iter().any(|r| r.name == name) {
+        return Err(AidotError::RepositoryNotFound(name.to_string()));
+    }
+
+    let (resolved_url, source_type) = if local {
+        process_local_path(&url)?
+    } else if repository::is_archive_url(&url) {
+        (url, SourceType::Archive)
+    } else if repository::is_git_url(&url) {
+        git::check_git_available()?;
+        (normalized_git_url(&url), SourceType::Git)
+    } else {
+        return Err(AidotError::InvalidInput(format!(
+            "'{}' is not a valid Git URL (http://, https://, git@, ssh://, git://).\n\
+            For local presets, use --local:\n\
+            \x20 aidot repo set-url {} /path/to/preset --local",
+            url, name
+        )));
+    };
+
+    let sha256 = normalize_sha256(sha256, &source_type)?;
+    if source_type == SourceType::Git {
+        let subdir = cfg
+            .repositories
+            .iter()
+            .find(|r| r.name == name)
+            .and_then(|r| r.subdir.clone());
+        check_duplicate_url(&cfg, name, &resolved_url, subdir.as_deref())?;
+    }
+    cfg.set_url(name, resolved_url.clone(), source_type, sha256)?;
+    let cache_removed = crate::cache::remove_cache(name)?;
+
+    println!(
+        "{} Repository '{}' now points to {}",
+        "✓".green(),
+        name.white().bold(),
+        resolved_url.dimmed()
+    );
+    if cache_removed {
+        println!(
+            "  {}",
+            "Cached clone removed; it will be re-cloned on next use.".dimmed()
+        );
+    }
+
+    Ok(())
+}
+
+/// Trust (or stop trusting) a repository's pre/post apply hooks
+pub fn trust_repo_hooks(name: &str, trust: bool) -> Result<()> {
+    let mut config = Config::load()?;
+    config.set_trust_hooks(name, trust)?;
+    if trust {
+        println!(
+            "{} Hooks of '{}' {}",
+            "✓".green(),
+            name.white().bold(),
+            "will run on pull (after confirmation)".yellow()
+        );
+    } else {
+        println!(
+            "{} Hooks of '{}' {}",
+            "✓".green(),
+            name.white().bold(),
+            "are no longer trusted".green()
+        );
+    }
+
+    Ok(())
+}
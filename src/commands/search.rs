@@ -0,0 +1,292 @@
+use crate::error::{AidotError, Result};
+use colored::Colorize;
+use serde::Deserialize;
+
+/// GitHub topic that marks a repository as an aidot preset
+const PRESET_TOPIC: &str = "aidot-preset";
+
+const GITHUB_SEARCH_API: &str = "https://api.github.com/search/repositories";
+
+/// A public preset found by `aidot search`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PresetListing {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub stars: u64,
+}
+
+/// Subset of the GitHub repository search response
+#[derive(Debug, Deserialize)]
+struct GitHubSearchResponse {
+    #[serde(default)]
+    items: Vec<GitHubRepo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubRepo {
+    full_name: String,
+    clone_url: String,
+    description: Option<String>,
+    #[serde(default)]
+    stargazers_count: u64,
+}
+
+/// Search public presets
+///
+/// By default queries GitHub for repositories tagged with the `aidot-preset` topic.
+/// With `manifest`, a JSON list of `{ name, url, description, stars }` is fetched
+/// instead and filtered locally.
+pub fn search_presets(query: Option<String>, manifest: Option<String>, limit: usize) -> Result<()> {
+    crate::cache::require_online("Searching presets")?;
+    let query = query.unwrap_or_default();
+
+    let (source, listings) = match &manifest {
+        Some(url) => {
+            let body = fetch(url)?;
+            let mut listings = filter_listings(parse_manifest(&body)?, &query);
+            listings.sort_by_key(|l| std::cmp::Reverse(l.stars));
+            (url.clone(), listings)
+        }
+        None => {
+            let url = github_search_url(&query, limit);
+            let body = fetch(&url)?;
+            (
+                format!("GitHub topic '{}'", PRESET_TOPIC),
+                parse_github_response(&body)?,
+            )
+        }
+    };
+    let listings: Vec<_> = listings.into_iter().take(limit).collect();
+
+    println!("{} {}\n", "Searching presets in".cyan(), source.white());
+
+    if listings.is_empty() {
+        println!("{}", "No presets found.".yellow());
+        return Ok(());
+    }
+
+    print_table(&listings);
+
+    println!();
+    println!("{}", "Register a preset with:".cyan());
+    for listing in &listings {
+        println!(
+            "  aidot repo add {} {}",
+            repo_name_suggestion(&listing.name).white(),
+            listing.url.white()
+        );
+    }
+
+    Ok(())
+}
+
+/// GitHub search URL for preset repositories, most starred first
+fn github_search_url(query: &str, limit: usize) -> String {
+    let mut q = format!("topic:{}", PRESET_TOPIC);
+    for term in query.split_whitespace() {
+        q.push('+');
+        q.push_str(&encode_query(term));
+    }
+    format!(
+        "{}?q={}&sort=stars&order=desc&per_page={}",
+        GITHUB_SEARCH_API,
+        q,
+        limit.clamp(1, 100)
+    )
+}
+
+/// Percent-encode a search term for use in a query string
+fn encode_query(term: &str) -> String {
+    term.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// GET a URL and return the body, mapping failures to a readable network error
+fn fetch(url: &str) -> Result<String> {
+    let client = reqwest::blocking::Client::builder()
+        .user_agent(concat!("aidot/", env!("CARGO_PKG_VERSION")))
+        .timeout(std::time::Duration::from_secs(15))
+        .build()
+        .map_err(|e| AidotError::Network(e.to_string()))?;
+
+    let response = client.get(url).send().map_err(|e| {
+        AidotError::Network(format!(
+            "Could not reach {} ({}). Check your network connection.",
+            url_host(url),
+            e.without_url()
+        ))
+    })?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(AidotError::Network(format!(
+            "{} returned HTTP {}",
+            url, status
+        )));
+    }
+
+    response
+        .text()
+        .map_err(|e| AidotError::Network(e.to_string()))
+}
+
+/// Host part of a URL for error messages ("https://api.github.com/x" → "api.github.com")
+fn url_host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    rest.split(['/', '?']).next().unwrap_or(rest)
+}
+
+/// Parse a GitHub repository search response
+fn parse_github_response(body: &str) -> Result<Vec<PresetListing>> {
+    let response: GitHubSearchResponse = serde_json::from_str(body)?;
+    Ok(response
+        .items
+        .into_iter()
+        .map(|repo| PresetListing {
+            name: repo.full_name,
+            url: repo.clone_url,
+            description: repo.description,
+            stars: repo.stargazers_count,
+        })
+        .collect())
+}
+
+/// Parse a preset manifest (JSON array of listings)
+fn parse_manifest(body: &str) -> Result<Vec<PresetListing>> {
+    serde_json::from_str(body).map_err(|e| {
+        AidotError::InvalidInput(format!(
+            "Invalid preset manifest (expected a JSON array of {{ name, url, description, stars }}): {}",
+            e
+        ))
+    })
+}
+
+/// Keep listings whose name or description contains every query term (case-insensitive)
+fn filter_listings(listings: Vec<PresetListing>, query: &str) -> Vec<PresetListing> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    listings
+        .into_iter()
+        .filter(|listing| {
+            let haystack = format!(
+                "{} {}",
+                listing.name,
+                listing.description.as_deref().unwrap_or_default()
+            )
+            .to_lowercase();
+            terms.iter().all(|term| haystack.contains(term))
+        })
+        .collect()
+}
+
+/// Suggested local repository name ("owner/team-preset" → "team-preset")
+fn repo_name_suggestion(name: &str) -> &str {
+    name.rsplit('/').next().unwrap_or(name)
+}
+
+/// Print listings as a name / stars / description table
+fn print_table(listings: &[PresetListing]) {
+    const MAX_DESCRIPTION: usize = 60;
+
+    let name_width = listings
+        .iter()
+        .map(|l| l.name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("NAME".len());
+
+    // Pad before coloring so ANSI codes don't skew column widths
+    println!(
+        "  {}  {}  {}",
+        format!("{:<name_width$}", "NAME").bold(),
+        format!("{:>6}", "STARS").bold(),
+        "DESCRIPTION".bold()
+    );
+    for listing in listings {
+        let description = listing.description.as_deref().unwrap_or("-");
+        let description = if description.chars().count() > MAX_DESCRIPTION {
+            let truncated: String = description.chars().take(MAX_DESCRIPTION - 1).collect();
+            format!("{}…", truncated)
+        } else {
+            description.to_string()
+        };
+        println!(
+            "  {}  {}  {}",
+            format!("{:<name_width$}", listing.name).white(),
+            format!("{:>6}", listing.stars).yellow(),
+            description.dimmed()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_github_search_url() {
+        let url = github_search_url("rust web", 10);
+        assert_eq!(
+            url,
+            "https://api.github.com/search/repositories?q=topic:aidot-preset+rust+web&sort=stars&order=desc&per_page=10"
+        );
+        assert!(github_search_url("c++", 500).contains("+c%2B%2B&"));
+        assert!(github_search_url("", 500).ends_with("per_page=100"));
+    }
+
+    #[test]
+    fn test_parse_github_response() {
+        let body = r#"{
+            "total_count": 1,
+            "items": [{
+                "full_name": "team/rust-preset",
+                "clone_url": "https://github.com/team/rust-preset.git",
+                "description": null,
+                "stargazers_count": 42
+            }]
+        }"#;
+        let listings = parse_github_response(body).unwrap();
+        assert_eq!(listings.len(), 1);
+        assert_eq!(listings[0].name, "team/rust-preset");
+        assert_eq!(listings[0].url, "https://github.com/team/rust-preset.git");
+        assert_eq!(listings[0].stars, 42);
+        assert!(listings[0].description.is_none());
+    }
+
+    #[test]
+    fn test_parse_manifest_and_filter() {
+        let body = r#"[
+            { "name": "rust-preset", "url": "https://example.com/rust.git", "description": "Rust rules", "stars": 3 },
+            { "name": "web", "url": "https://example.com/web.git" }
+        ]"#;
+        let listings = parse_manifest(body).unwrap();
+        assert_eq!(listings.len(), 2);
+        assert_eq!(listings[1].stars, 0);
+
+        let filtered = filter_listings(listings.clone(), "RUST rules");
+        assert_eq!(filtered, vec![listings[0].clone()]);
+        assert_eq!(filter_listings(listings, "").len(), 2);
+
+        assert!(parse_manifest("{}").is_err());
+    }
+
+    #[test]
+    fn test_repo_name_suggestion() {
+        assert_eq!(repo_name_suggestion("team/rust-preset"), "rust-preset");
+        assert_eq!(repo_name_suggestion("web"), "web");
+    }
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(url_host(GITHUB_SEARCH_API), "api.github.com");
+        assert_eq!(url_host("https://example.com?x=1"), "example.com");
+    }
+}
@@ -0,0 +1,47 @@
+use crate::error::{AidotError, Result};
+use crate::secret::{self, ENCRYPTED_SUFFIX};
+use colored::Colorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Encrypt a preset file to `<file>.enc` with the shared secret key
+///
+/// The plaintext file is left in place; it must not be committed to the preset.
+pub fn encrypt_file(file: &Path) -> Result<()> {
+    let key = secret::require_key()?;
+    let content = fs::read_to_string(file)?;
+
+    let mut output = file.as_os_str().to_os_string();
+    output.push(ENCRYPTED_SUFFIX);
+    let output = PathBuf::from(output);
+    fs::write(&output, secret::encrypt(&content, &key)?)?;
+
+    println!(
+        "{} {}",
+        "✓ Encrypted to".green().bold(),
+        output.display().to_string().white()
+    );
+    println!(
+        "{}",
+        format!(
+            "Remove {} (or add it to .gitignore) so the plaintext is not committed.",
+            file.display()
+        )
+        .yellow()
+    );
+    Ok(())
+}
+
+/// Print the decrypted content of a `*.enc` file to stdout
+pub fn decrypt_file(file: &Path) -> Result<()> {
+    if !file.to_string_lossy().ends_with(ENCRYPTED_SUFFIX) {
+        return Err(AidotError::InvalidInput(format!(
+            "{} is not an encrypted file (*{})",
+            file.display(),
+            ENCRYPTED_SUFFIX
+        )));
+    }
+    let key = secret::require_key()?;
+    print!("{}", secret::decrypt(&fs::read_to_string(file)?, &key)?);
+    Ok(())
+}
@@ -1,19 +1,49 @@
-use crate::adapters::detector::get_detected_tool_names;
-use crate::config::Config;
+use super::repo::preset_metadata;
+use crate::adapters::detector::{get_detected_tool_names, get_global_tool_configs};
+use crate::cache;
+use crate::config::{Config, Repository, SourceType};
 use crate::error::Result;
+use crate::git;
+use crate::output::{self, display_path};
+use crate::preset::config::Metadata;
 use colored::Colorize;
 use std::env;
 use std::fs;
 use std::path::Path;
 
+/// Entries listed per subdirectory by default
+const DEFAULT_MAX_ENTRIES: usize = 5;
+
+/// How much of the subdirectories in tool config directories is listed
+#[derive(Debug, Clone, Copy)]
+pub struct TreeLimits {
+    /// Levels listed below the top-level entries (None = unlimited)
+    pub depth: Option<usize>,
+    /// Entries listed per subdirectory (None = unlimited)
+    pub max_entries: Option<usize>,
+}
+
+impl TreeLimits {
+    /// Limits from --depth / --all: one level with 5 entries each by default,
+    /// `all` lifts the entry limit and, without `depth`, the depth limit (full tree)
+    pub fn new(depth: Option<usize>, all: bool) -> Self {
+        Self {
+            depth: if all { depth } else { Some(depth.unwrap_or(1)) },
+            max_entries: if all { None } else { Some(DEFAULT_MAX_ENTRIES) },
+        }
+    }
+}
+
 /// Show current configuration status
-pub fn show_status() -> Result<()> {
+///
+/// With `global`, user-level tool configuration in the home directory is shown as well.
+pub fn show_status(global: bool, limits: TreeLimits) -> Result<()> {
     let current_dir = env::current_dir()?;
 
     println!(
         "{} {}\n",
         "Project:".cyan().bold(),
-        current_dir.display().to_string().white()
+        display_path(&current_dir).white()
     );
 
     // Show detected tools
@@ -25,7 +55,12 @@ pub fn show_status() -> Result<()> {
         println!("  {}", "No LLM tools detected".yellow());
     } else {
         for tool in &detected_tools {
-            println!("  {} {}", "✓".green(), tool.name.white());
+            println!(
+                "  {} {} {}",
+                "✓".green(),
+                tool.name.white(),
+                format!("({})", tool.reason).dimmed()
+            );
         }
     }
     println!();
@@ -37,7 +72,7 @@ pub fn show_status() -> Result<()> {
     let claude_dir = current_dir.join(".claude");
     if claude_dir.exists() {
         println!("\n  {}:", "Claude Code".white().bold());
-        show_dir_contents(&claude_dir, "    ")?;
+        show_dir_contents(&claude_dir, "    ", limits)?;
     }
 
     // Cursor
@@ -55,7 +90,7 @@ pub fn show_status() -> Result<()> {
             );
         }
         if cursor_dir.exists() {
-            show_dir_contents(&cursor_dir, "    ")?;
+            show_dir_contents(&cursor_dir, "    ", limits)?;
         }
     }
 
@@ -76,13 +111,20 @@ pub fn show_status() -> Result<()> {
             );
         }
         if github_dir.join("prompts").exists() {
-            show_dir_contents(&github_dir.join("prompts"), "    ")?;
+            show_dir_contents(&github_dir.join("prompts"), "    ", limits)?;
         }
         if github_dir.join("agents").exists() {
-            show_dir_contents(&github_dir.join("agents"), "    ")?;
+            show_dir_contents(&github_dir.join("agents"), "    ", limits)?;
         }
     }
 
+    // Amazon Q
+    let amazonq_dir = current_dir.join(".amazonq");
+    if amazonq_dir.exists() {
+        println!("\n  {}:", "Amazon Q".white().bold());
+        show_dir_contents(&amazonq_dir, "    ", limits)?;
+    }
+
     // VS Code MCP
     if vscode_dir.join("mcp.json").exists() {
         println!("\n  {}:", "VS Code (MCP)".white().bold());
@@ -97,6 +139,26 @@ pub fn show_status() -> Result<()> {
 
     println!();
 
+    if global {
+        println!("{}", "═══ Global Configuration ═══".cyan().bold());
+        let configs: Vec<_> = get_global_tool_configs()
+            .into_iter()
+            .filter(|config| config.exists)
+            .collect();
+        if configs.is_empty() {
+            println!("  {}", "No global tool configuration found".dimmed());
+        }
+        for config in &configs {
+            println!(
+                "\n  {} {}:",
+                config.name.white().bold(),
+                format!("({})", config.display).dimmed()
+            );
+            show_dir_contents(&config.dir, "    ", limits)?;
+        }
+        println!();
+    }
+
     // Show registered repositories
     println!("{}", "═══ Registered Repositories ═══".cyan().bold());
     let config = Config::load()?;
@@ -110,9 +172,12 @@ pub fn show_status() -> Result<()> {
     } else {
         for repo in &config.repositories {
             let mut flags = Vec::new();
-            if repo.source_type == crate::config::SourceType::Local {
+            if repo.source_type == SourceType::Local {
                 flags.push("local".yellow());
             }
+            if repo.is_missing_local() {
+                flags.push("missing".red().bold());
+            }
             if repo.default {
                 flags.push("default".green());
             }
@@ -130,13 +195,27 @@ pub fn show_status() -> Result<()> {
                 )
             };
 
+            let location = match repo.source_type {
+                SourceType::Local => display_path(Path::new(&repo.url)),
+                SourceType::Git | SourceType::Archive => repo.url.clone(),
+            };
             println!(
                 "  {} {} {}{}",
                 "•".cyan(),
                 repo.name.white().bold(),
-                repo.url.dimmed(),
+                location.dimmed(),
                 flags_str
             );
+            let metadata = preset_metadata(repo);
+            // Reads the cached preset, so only on request
+            if output::is_verbose() {
+                println!("    {}", revision_summary(repo, metadata.as_ref()).dimmed());
+            }
+            if let Some(metadata) = metadata {
+                for line in metadata.provenance() {
+                    println!("    {}", line.dimmed());
+                }
+            }
         }
     }
 
@@ -145,8 +224,34 @@ pub fn show_status() -> Result<()> {
     Ok(())
 }
 
+/// Cached revision and preset version of a repository, e.g. `cached: main @ 1a2b3c4, version 1.2.0`
+fn revision_summary(repo: &Repository, metadata: Option<&Metadata>) -> String {
+    let mut parts = Vec::new();
+    if repo.source_type != SourceType::Local {
+        let cached = cache::get_cache_path(&repo.name)
+            .ok()
+            .filter(|path| path.is_dir());
+        let Some(path) = cached else {
+            return "not cached".to_string();
+        };
+        parts.push(match repo.source_type {
+            SourceType::Git => match git::head_commit(&path) {
+                Ok((Some(branch), id)) => format!("cached: {} @ {}", branch, id),
+                Ok((None, id)) => format!("cached: {} (detached)", id),
+                Err(_) => "cached: no commit".to_string(),
+            },
+            _ => "cached: archive".to_string(),
+        });
+    }
+    parts.push(match metadata {
+        Some(metadata) => format!("version {}", metadata.version),
+        None => "version unknown".to_string(),
+    });
+    parts.join(", ")
+}
+
 /// Show contents of a directory
-fn show_dir_contents(dir: &Path, indent: &str) -> Result<()> {
+fn show_dir_contents(dir: &Path, indent: &str, limits: TreeLimits) -> Result<()> {
     let dir_name = dir
         .file_name()
         .map(|n| n.to_string_lossy().to_string())
@@ -164,13 +269,7 @@ fn show_dir_contents(dir: &Path, indent: &str) -> Result<()> {
                 "•".cyan(),
                 format!("{}/{}", dir_name, name).white()
             );
-            // Optionally show subdirectory contents (one level deep)
-            if let Ok(subdir) = fs::read_dir(&path) {
-                for subentry in subdir.take(5).flatten() {
-                    let subname = subentry.file_name().to_string_lossy().to_string();
-                    println!("{}  {} {}", indent, "·".dimmed(), subname.dimmed());
-                }
-            }
+            show_subdir_contents(&path, &format!("{}  ", indent), 1, limits);
         } else {
             let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
             println!(
@@ -186,3 +285,42 @@ fn show_dir_contents(dir: &Path, indent: &str) -> Result<()> {
 
     Ok(())
 }
+
+/// List a subdirectory within the limits, noting entries left out as "(+N more)"
+fn show_subdir_contents(dir: &Path, indent: &str, level: usize, limits: TreeLimits) {
+    if limits.depth.is_some_and(|depth| level > depth) {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let shown = limits
+        .max_entries
+        .unwrap_or(entries.len())
+        .min(entries.len());
+    for entry in &entries[..shown] {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+        if path.is_dir() {
+            println!(
+                "{}{} {}",
+                indent,
+                "·".dimmed(),
+                format!("{}/", name).dimmed()
+            );
+            show_subdir_contents(&path, &format!("{}  ", indent), level + 1, limits);
+        } else {
+            println!("{}{} {}", indent, "·".dimmed(), name.dimmed());
+        }
+    }
+    if entries.len() > shown {
+        println!(
+            "{}{}",
+            indent,
+            format!("(+{} more)", entries.len() - shown).dimmed()
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use super::pull::confirm;
+use crate::error::Result;
+use crate::manifest::Manifest;
+use colored::Colorize;
+use std::fs;
+
+/// Remove files previously applied from a preset
+///
+/// Only files recorded in the project manifest (1:1 and root files) are removed.
+/// Merged files such as CLAUDE.md or settings.local.json are left untouched, files
+/// still recorded for another preset are kept, and files edited since the last apply
+/// are kept (and stay recorded) unless `force` is set.
+pub fn unapply_preset(preset_source: &str, dry_run: bool, yes: bool, force: bool) -> Result<()> {
+    let target_dir = std::env::current_dir()?;
+    let mut manifest = Manifest::load(&target_dir)?;
+
+    let entries: Vec<_> = manifest
+        .files
+        .iter()
+        .filter(|e| e.preset == preset_source)
+        .cloned()
+        .collect();
+
+    if entries.is_empty() {
+        println!(
+            "{} '{}'",
+            "No applied files recorded for".yellow(),
+            preset_source.white()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} {} {}",
+        "Files applied from".cyan(),
+        format!("'{}'", preset_source).white(),
+        format!("({}):", entries.len()).cyan()
+    );
+    for entry in &entries {
+        if let Some(other) = manifest.other_preset(preset_source, &entry.path) {
+            println!(
+                "  {} {} {} {}",
+                "KEEP".yellow().bold(),
+                entry.path.white(),
+                format!("[{}]", entry.tool).dimmed(),
+                format!("(still applied by {})", other).dimmed()
+            );
+            continue;
+        }
+        if !force && entry.is_modified(&target_dir) {
+            println!(
+                "  {} {} {} {}",
+                "KEEP".yellow().bold(),
+                entry.path.white(),
+                format!("[{}]", entry.tool).dimmed(),
+                "(modified, kept; --force deletes it)".dimmed()
+            );
+            continue;
+        }
+        let missing = if target_dir.join(&entry.path).exists() {
+            String::new()
+        } else {
+            " (already removed)".to_string()
+        };
+        println!(
+            "  {} {} {}{}",
+            "DELETE".red().bold(),
+            entry.path.white(),
+            format!("[{}]", entry.tool).dimmed(),
+            missing.dimmed()
+        );
+    }
+    println!();
+
+    if dry_run {
+        println!("{}", "═══ DRY RUN MODE ═══".yellow().bold());
+        println!("{}", "Run without --dry-run to remove these files.".cyan());
+        return Ok(());
+    }
+
+    if !yes && !confirm("Remove these files?")? {
+        println!("{}", "Operation cancelled.".yellow());
+        return Ok(());
+    }
+
+    let mut removed = 0;
+    let mut kept = Vec::new();
+    for entry in manifest.remove_preset(preset_source) {
+        // Another preset's entry stays in the manifest and keeps the file
+        if manifest.other_preset(preset_source, &entry.path).is_some() {
+            continue;
+        }
+        if !force && entry.is_modified(&target_dir) {
+            println!("  {} {} (modified, kept)", "!".yellow(), entry.path.white());
+            kept.push(entry);
+            continue;
+        }
+        let path = target_dir.join(&entry.path);
+        if path.exists() {
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    let kept_count = kept.len();
+    // Kept files stay recorded so a later `unapply --force` can still remove them
+    manifest.files.extend(kept);
+    manifest.save(&target_dir)?;
+
+    println!(
+        "{} {} file(s) removed",
+        "✓".green(),
+        removed.to_string().white().bold()
+    );
+    if kept_count > 0 {
+        println!(
+            "{} {} modified file(s) kept (use --force to remove them too)",
+            "!".yellow(),
+            kept_count.to_string().white().bold()
+        );
+    }
+
+    Ok(())
+}
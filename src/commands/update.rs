@@ -1,3 +1,4 @@
+use crate::archive::{self, ArchiveFormat};
 use crate::error::Result;
 use colored::Colorize;
 
@@ -60,6 +61,7 @@ fn get_archive_ext() -> &'static str {
 
 /// Check for updates and optionally update to the latest version
 pub fn check_update(check_only: bool, include_prerelease: bool) -> Result<()> {
+    crate::cache::require_online("Checking for updates")?;
     let current_version = VERSION.strip_prefix('v').unwrap_or(VERSION);
     let target = get_target();
 
@@ -217,27 +219,11 @@ pub fn check_update(check_only: bool, include_prerelease: bool) -> Result<()> {
     std::fs::create_dir_all(&tmp_extract_dir)
         .map_err(|e| crate::error::AidotError::UpdateError(e.to_string()))?;
 
-    #[cfg(target_os = "windows")]
-    {
-        let archive_file = std::fs::File::open(&tmp_archive_path)
-            .map_err(|e| crate::error::AidotError::UpdateError(e.to_string()))?;
-        let mut archive = zip::ZipArchive::new(archive_file)
-            .map_err(|e| crate::error::AidotError::UpdateError(e.to_string()))?;
-        archive
-            .extract(&tmp_extract_dir)
-            .map_err(|e| crate::error::AidotError::UpdateError(e.to_string()))?;
-    }
-
-    #[cfg(not(target_os = "windows"))]
-    {
-        let archive_file = std::fs::File::open(&tmp_archive_path)
-            .map_err(|e| crate::error::AidotError::UpdateError(e.to_string()))?;
-        let decoder = flate2::read::GzDecoder::new(archive_file);
-        let mut archive = tar::Archive::new(decoder);
-        archive
-            .unpack(&tmp_extract_dir)
-            .map_err(|e| crate::error::AidotError::UpdateError(e.to_string()))?;
-    }
+    let format = ArchiveFormat::from_name(&asset.name).ok_or_else(|| {
+        crate::error::AidotError::UpdateError(format!("Unknown archive format: {}", asset.name))
+    })?;
+    archive::extract(&tmp_archive_path, format, &tmp_extract_dir)
+        .map_err(|e| crate::error::AidotError::UpdateError(e.to_string()))?;
 
     // Replace current binary
     let new_binary = tmp_extract_dir
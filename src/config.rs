@@ -1,16 +1,35 @@
 use crate::error::{AidotError, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Global configuration stored in ~/.aidot/config.toml
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Config {
+    /// Conflict handling when no answer can be given (--yes, piped or closed stdin)
     #[serde(default)]
-    pub repositories: Vec<Repository>,
+    pub conflict_policy: ConflictPolicy,
+
+    /// Order tools are applied in without --tools (e.g., ["cursor", "claude"]);
+    /// unlisted tools follow in the default order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_order: Vec<String>,
+
+    /// Conflict diffs of files with more lines are summarized by size (default 2000, 0 = no limit)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff_max_lines: Option<usize>,
+
+    /// Warn after pull about merged files (CLAUDE.md, .cursorrules, ...) larger than
+    /// this many KB (default 512, 0 = never)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merged_warn_kb: Option<u64>,
+
+    /// Cache settings ([cache])
+    #[serde(default, skip_serializing_if = "CacheSettings::is_default")]
+    pub cache: CacheSettings,
 
     #[serde(default)]
-    pub history: Vec<HistoryEntry>,
+    pub repositories: Vec<Repository>,
 }
 
 /// Source type for repository
@@ -20,6 +39,43 @@ pub enum SourceType {
     #[default]
     Git,
     Local,
+    /// Release archive (`.tar.gz`, `.tgz`, `.zip`) downloaded over HTTP(S), no git needed
+    Archive,
+}
+
+/// Default conflict policy used without an interactive answer
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    /// Keep existing files (only new files are created)
+    #[default]
+    Skip,
+    /// Overwrite existing files
+    Force,
+}
+
+impl ConflictPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictPolicy::Skip => "skip",
+            ConflictPolicy::Force => "force",
+        }
+    }
+}
+
+/// Settings of the clone cache in ~/.aidot/cache/
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CacheSettings {
+    /// Size limit of the whole cache (e.g., "500MB"); least recently used clones
+    /// are removed when a pull goes over it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_size: Option<String>,
+}
+
+impl CacheSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 /// Repository entry in global configuration
@@ -35,14 +91,30 @@ pub struct Repository {
     pub cached_at: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Preset root inside the repository (e.g., "presets/frontend" for monorepos)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subdir: Option<String>,
+    /// Expected SHA-256 of an archive source (hex); the download is rejected on mismatch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Run the preset's pre/post apply hooks without --run-hooks
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub trust_hooks: bool,
+    /// Labels for grouping repositories (repo add --tag, list/pull --tag)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
-/// History entry for tracking applied presets
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct HistoryEntry {
-    pub project: String,
-    pub timestamp: String,
-    pub repositories: Vec<String>,
+impl Repository {
+    /// Whether the repository has a tag (case-insensitive)
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Whether this is a local preset whose directory no longer exists
+    pub fn is_missing_local(&self) -> bool {
+        self.source_type == SourceType::Local && !Path::new(&self.url).is_dir()
+    }
 }
 
 impl Config {
@@ -137,6 +209,45 @@ impl Config {
         repo.default = default;
         self.save()
     }
+
+    /// Set whether a repository's apply hooks are trusted
+    pub fn set_trust_hooks(&mut self, name: &str, trust: bool) -> Result<()> {
+        let repo = self
+            .repositories
+            .iter_mut()
+            .find(|r| r.name == name)
+            .ok_or_else(|| AidotError::RepositoryNotFound(name.to_string()))?;
+
+        repo.trust_hooks = trust;
+        self.save()
+    }
+
+    /// Point a repository at a new URL or local path
+    ///
+    /// The cached clone timestamp is reset, local presets drop their subdirectory
+    /// (it is part of the path), and the archive checksum is replaced by `sha256`.
+    pub fn set_url(
+        &mut self,
+        name: &str,
+        url: String,
+        source_type: SourceType,
+        sha256: Option<String>,
+    ) -> Result<()> {
+        let repo = self
+            .repositories
+            .iter_mut()
+            .find(|r| r.name == name)
+            .ok_or_else(|| AidotError::RepositoryNotFound(name.to_string()))?;
+
+        if source_type == SourceType::Local {
+            repo.subdir = None;
+        }
+        repo.url = url;
+        repo.source_type = source_type;
+        repo.sha256 = sha256;
+        repo.cached_at = None;
+        self.save()
+    }
 }
 
 #[cfg(test)]
@@ -147,7 +258,32 @@ mod tests {
     fn test_config_default() {
         let config = Config::default();
         assert_eq!(config.repositories.len(), 0);
-        assert_eq!(config.history.len(), 0);
+        assert_eq!(config.conflict_policy, ConflictPolicy::Skip);
+    }
+
+    #[test]
+    fn test_conflict_policy_serialization() {
+        let mut config = Config {
+            conflict_policy: ConflictPolicy::Force,
+            ..Default::default()
+        };
+        config.repositories.push(Repository {
+            name: "repo".to_string(),
+            url: "https://github.com/test/repo".to_string(),
+            source_type: SourceType::Git,
+            default: false,
+            cached_at: None,
+            description: None,
+            subdir: None,
+            sha256: None,
+            trust_hooks: false,
+            tags: Vec::new(),
+        });
+
+        let toml = toml::to_string_pretty(&config).unwrap();
+        assert!(toml.contains("conflict_policy = \"force\""));
+        let deserialized: Config = toml::from_str(&toml).unwrap();
+        assert_eq!(deserialized.conflict_policy, ConflictPolicy::Force);
     }
 
     #[test]
@@ -159,6 +295,10 @@ mod tests {
             default: true,
             cached_at: Some("2026-01-11T00:00:00Z".to_string()),
             description: Some("Test repository".to_string()),
+            subdir: None,
+            sha256: None,
+            trust_hooks: false,
+            tags: Vec::new(),
         };
 
         let toml = toml::to_string(&repo).unwrap();
@@ -179,6 +319,10 @@ mod tests {
             default: false,
             cached_at: None,
             description: Some("Local preset".to_string()),
+            subdir: None,
+            sha256: None,
+            trust_hooks: false,
+            tags: Vec::new(),
         };
 
         let toml = toml::to_string(&repo).unwrap();
@@ -194,21 +338,6 @@ mod tests {
         assert_eq!(source_type, SourceType::Git);
     }
 
-    #[test]
-    fn test_history_entry() {
-        let entry = HistoryEntry {
-            project: "/home/user/project".to_string(),
-            timestamp: "2026-01-12T10:00:00Z".to_string(),
-            repositories: vec!["common".to_string(), "team-config".to_string()],
-        };
-
-        let toml = toml::to_string(&entry).unwrap();
-        let deserialized: HistoryEntry = toml::from_str(&toml).unwrap();
-
-        assert_eq!(deserialized.project, "/home/user/project");
-        assert_eq!(deserialized.repositories.len(), 2);
-    }
-
     #[test]
     fn test_config_with_repositories() {
         let mut config = Config::default();
@@ -220,6 +349,10 @@ mod tests {
             default: true,
             cached_at: None,
             description: None,
+            subdir: None,
+            sha256: None,
+            trust_hooks: false,
+            tags: Vec::new(),
         };
 
         let repo2 = Repository {
@@ -229,6 +362,10 @@ mod tests {
             default: false,
             cached_at: None,
             description: Some("Local repo".to_string()),
+            subdir: None,
+            sha256: None,
+            trust_hooks: false,
+            tags: Vec::new(),
         };
 
         config.repositories.push(repo1);
@@ -255,5 +392,24 @@ mod tests {
         assert!(!repo.default); // default is false
         assert!(repo.cached_at.is_none());
         assert!(repo.description.is_none());
+        assert!(repo.tags.is_empty());
+    }
+
+    #[test]
+    fn test_repository_tags() {
+        let repo: Repository = toml::from_str(
+            "name = \"web\"\nurl = \"https://example.com/web\"\ntags = [\"frontend\", \"eslint\"]\n",
+        )
+        .unwrap();
+        assert!(repo.has_tag("frontend"));
+        assert!(repo.has_tag("ESLint"));
+        assert!(!repo.has_tag("backend"));
+
+        // No tags: the key is left out, so older versions read the file unchanged
+        let untagged = Repository {
+            tags: Vec::new(),
+            ..repo
+        };
+        assert!(!toml::to_string(&untagged).unwrap().contains("tags"));
     }
 }
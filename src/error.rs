@@ -34,8 +34,17 @@ pub enum AidotError {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("Failed to apply {0} file(s)")]
+    ApplyFailed(usize),
+
+    #[error("Network error: {0}")]
+    Network(String),
+
     #[error("Update error: {0}")]
     UpdateError(String),
+
+    #[error("Secret error: {0}")]
+    Secret(String),
 }
 
 /// Result type alias for aidot operations
@@ -1,4 +1,6 @@
 use crate::error::{AidotError, Result};
+use crate::output;
+use crate::progress;
 use git2::Repository;
 use std::path::Path;
 use std::process::Command;
@@ -29,6 +31,81 @@ pub fn check_git_available() -> Result<()> {
     }
 }
 
+/// How a repository URL is fetched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transport {
+    Https,
+    Ssh,
+    Other,
+}
+
+fn transport_of(url: &str) -> Transport {
+    if url.starts_with("https://") || url.starts_with("http://") {
+        Transport::Https
+    } else if url.starts_with("ssh://") || (url.contains('@') && !url.contains("://")) {
+        // ssh://host/repo or scp-like git@host:repo
+        Transport::Ssh
+    } else {
+        Transport::Other
+    }
+}
+
+/// Check everything a clone or pull of `url` needs before the cache is touched
+///
+/// Besides git itself, HTTPS URLs need git's `git-remote-https` helper (missing from
+/// minimal git builds without libcurl) and SSH URLs need an `ssh` client in PATH.
+pub fn check_transport(url: &str) -> Result<()> {
+    check_git_available()?;
+    match transport_of(url) {
+        Transport::Https if !has_https_helper() => Err(AidotError::Git(format!(
+            "git cannot fetch HTTPS URLs: 'git-remote-https' is missing from {}.
+             Your git was built without libcurl; install the full git package              (e.g. apt install git, not git-minimal) or use an SSH URL.",
+            git_exec_path().unwrap_or_else(|| "git --exec-path".to_string())
+        ))),
+        Transport::Ssh if !crate::adapters::helpers::is_command_available("ssh") => {
+            Err(AidotError::Git(
+                "SSH URLs need an ssh client, but 'ssh' is not in PATH.
+                 Install OpenSSH (e.g. apt install openssh-client) or use an HTTPS URL."
+                    .to_string(),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Directory holding git's helper programs (`git --exec-path`)
+fn git_exec_path() -> Option<String> {
+    let output = Command::new("git").arg("--exec-path").output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Whether git has its HTTPS transport (assumed present when it cannot be told)
+fn has_https_helper() -> bool {
+    let Some(exec_path) = git_exec_path() else {
+        return true;
+    };
+    let dir = Path::new(&exec_path);
+    dir.join("git-remote-https").exists() || dir.join("git-remote-https.exe").exists()
+}
+
+/// Version and transports of the bundled libgit2 (used for local repository checks)
+pub fn libgit2_info() -> String {
+    let version = git2::Version::get();
+    let (major, minor, patch) = version.libgit2_version();
+    let yes_no = |supported: bool| if supported { "yes" } else { "no" };
+    format!(
+        "libgit2 {}.{}.{} (https: {}, ssh: {})",
+        major,
+        minor,
+        patch,
+        yes_no(version.https()),
+        yes_no(version.ssh())
+    )
+}
+
 /// Clone a Git repository using system git CLI
 /// This provides better compatibility with SSH agents, credential helpers, and various auth methods
 fn clone_with_git_cli(url: &str, target_path: &Path) -> Result<()> {
@@ -55,7 +132,8 @@ fn clone_with_git_cli(url: &str, target_path: &Path) -> Result<()> {
 }
 
 /// Pull latest changes using system git CLI
-fn pull_with_git_cli(repo_path: &Path) -> Result<()> {
+/// Returns the git stdout summary on success
+fn pull_with_git_cli(repo_path: &Path) -> Result<String> {
     let output = Command::new("git")
         .args(["pull", "--ff-only"])
         .current_dir(repo_path)
@@ -68,13 +146,7 @@ fn pull_with_git_cli(repo_path: &Path) -> Result<()> {
         })?;
 
     if output.status.success() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        if stdout.contains("Already up to date") || stdout.contains("Already up-to-date") {
-            println!("Already up-to-date");
-        } else {
-            println!("{}", stdout);
-        }
-        Ok(())
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
         Err(AidotError::Git(format!("Failed to pull: {}", stderr)))
@@ -84,26 +156,385 @@ fn pull_with_git_cli(repo_path: &Path) -> Result<()> {
 /// Clone a Git repository to the specified path
 pub fn clone_repository(url: &str, target_path: &Path) -> Result<()> {
     check_git_available()?;
-    println!("Cloning repository from {}...", url);
+    output::info(&format!("Cloning repository from {}...", url));
 
     // Use system git CLI for better SSH/auth compatibility
-    clone_with_git_cli(url, target_path)?;
-    println!("Repository cloned successfully");
+    let spinner = progress::spinner("Cloning...");
+    let result = clone_with_git_cli(url, target_path);
+    spinner.finish_and_clear();
+    result?;
+    output::info("Repository cloned successfully");
     Ok(())
 }
 
 /// Pull latest changes from a Git repository
 pub fn pull_repository(repo_path: &Path) -> Result<()> {
     check_git_available()?;
-    println!("Updating repository at {}...", repo_path.display());
+    output::info(&format!(
+        "Updating repository at {}...",
+        repo_path.display()
+    ));
 
     // Use system git CLI for better SSH/auth compatibility
-    pull_with_git_cli(repo_path)?;
-    println!("Repository updated successfully");
+    let spinner = progress::spinner("Fetching...");
+    let result = pull_with_git_cli(repo_path);
+    spinner.finish_and_clear();
+    let stdout = result?;
+    if stdout.contains("Already up to date") || stdout.contains("Already up-to-date") {
+        output::info("Already up-to-date");
+    } else {
+        output::info(stdout.trim_end());
+    }
+    output::info("Repository updated successfully");
     Ok(())
 }
 
+/// Check that a remote repository is reachable (`git ls-remote`)
+///
+/// Credential prompts are disabled so an unreachable private repository fails
+/// instead of waiting for input.
+pub fn check_remote(url: &str) -> Result<()> {
+    check_git_available()?;
+    let output = Command::new("git")
+        .args(["ls-remote", "--quiet", url, "HEAD"])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|e| AidotError::Git(format!("Failed to execute git command: {}", e)))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(AidotError::Git(stderr.trim().to_string()))
+    }
+}
+
 /// Check if a path is a valid Git repository
 pub fn is_git_repository(path: &Path) -> bool {
     Repository::open(path).is_ok()
 }
+
+/// Files with uncommitted changes in a repository (modified, staged, deleted or untracked)
+pub fn local_changes(repo_path: &Path) -> Result<Vec<String>> {
+    let repo = Repository::open(repo_path).map_err(|e| AidotError::Git(e.message().to_string()))?;
+    let mut options = git2::StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .map_err(|e| AidotError::Git(e.message().to_string()))?;
+    Ok(statuses
+        .iter()
+        .filter(|entry| entry.status() != git2::Status::CURRENT)
+        .filter_map(|entry| entry.path().map(str::to_string))
+        .collect())
+}
+
+/// Branch checked out in a repository (None when HEAD is detached) and the short id of its HEAD commit
+pub fn head_commit(repo_path: &Path) -> Result<(Option<String>, String)> {
+    let repo = Repository::open(repo_path).map_err(|e| AidotError::Git(e.message().to_string()))?;
+    let head = repo
+        .head()
+        .map_err(|e| AidotError::Git(e.message().to_string()))?;
+    let commit = head
+        .peel_to_commit()
+        .map_err(|e| AidotError::Git(e.message().to_string()))?;
+    let branch = if head.is_branch() {
+        head.shorthand().map(str::to_string)
+    } else {
+        None
+    };
+    let id = commit.id().to_string();
+    Ok((branch, id[..7].to_string()))
+}
+
+/// URL of a repository's `origin` remote
+pub fn remote_url(repo_path: &Path) -> Result<String> {
+    let repo = Repository::open(repo_path).map_err(|e| AidotError::Git(e.message().to_string()))?;
+    let remote = repo
+        .find_remote("origin")
+        .map_err(|e| AidotError::Git(e.message().to_string()))?;
+    remote
+        .url()
+        .map(str::to_string)
+        .ok_or_else(|| AidotError::Git("origin remote has no URL".to_string()))
+}
+
+/// Of `paths` (relative to `project_dir`), those tracked by the enclosing Git repository
+/// and not ignored, i.e. changes to them end up in the next commit
+///
+/// Returns an empty list when `project_dir` is not inside a Git repository.
+pub fn tracked_paths(project_dir: &Path, paths: &[String]) -> Vec<String> {
+    let Ok(repo) = Repository::discover(project_dir) else {
+        return Vec::new();
+    };
+    let (Some(workdir), Ok(index)) = (repo.workdir(), repo.index()) else {
+        return Vec::new();
+    };
+    let Ok(workdir) = workdir.canonicalize() else {
+        return Vec::new();
+    };
+
+    paths
+        .iter()
+        .filter(|path| {
+            let Ok(absolute) = project_dir.join(path).canonicalize() else {
+                return false;
+            };
+            let Ok(relative) = absolute.strip_prefix(&workdir) else {
+                return false;
+            };
+            !repo.is_path_ignored(relative).unwrap_or(false)
+                && index.get_path(relative, 0).is_some()
+        })
+        .cloned()
+        .collect()
+}
+
+/// Outcome of `commit_paths`
+#[derive(Debug, Default)]
+pub struct CommitOutcome {
+    /// Short id of the new commit (None when nothing differed from HEAD)
+    pub id: Option<String>,
+    /// Paths left out because they are ignored by .gitignore
+    pub ignored: Vec<String>,
+}
+
+fn git_error(e: git2::Error) -> AidotError {
+    AidotError::Git(e.message().to_string())
+}
+
+/// Check that the repository enclosing `project_dir` can take a commit from `pull --commit`
+///
+/// Refuses a merge/rebase in progress, unresolved conflicts and already staged changes,
+/// so the commit only ever contains files written by aidot.
+pub fn ensure_committable(project_dir: &Path) -> Result<()> {
+    let repo = Repository::discover(project_dir).map_err(|_| {
+        AidotError::Git(format!(
+            "{} is not inside a git repository (required by --commit)",
+            project_dir.display()
+        ))
+    })?;
+    if repo.is_bare() {
+        return Err(AidotError::Git(
+            "cannot commit to a bare repository".to_string(),
+        ));
+    }
+    if repo.state() != git2::RepositoryState::Clean {
+        return Err(AidotError::Git(format!(
+            "a git operation is in progress ({:?}); finish or abort it before using --commit",
+            repo.state()
+        )));
+    }
+
+    let index = repo.index().map_err(git_error)?;
+    if index.has_conflicts() {
+        return Err(AidotError::Git(
+            "the index has unresolved conflicts; resolve them before using --commit".to_string(),
+        ));
+    }
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let staged = repo
+        .diff_tree_to_index(head_tree.as_ref(), Some(&index), None)
+        .map_err(git_error)?;
+    if staged.deltas().len() > 0 {
+        return Err(AidotError::Git(
+            "there are already staged changes; commit or unstage them before using --commit"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Stage `paths` (relative to `project_dir`) and commit them with `message`
+///
+/// Ignored files are left out. No commit is made when the staged files match HEAD.
+pub fn commit_paths(project_dir: &Path, paths: &[String], message: &str) -> Result<CommitOutcome> {
+    let repo = Repository::discover(project_dir).map_err(git_error)?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| AidotError::Git("cannot commit to a bare repository".to_string()))?
+        .canonicalize()?;
+
+    let mut outcome = CommitOutcome::default();
+    let mut index = repo.index().map_err(git_error)?;
+    let mut staged = 0;
+    for path in paths {
+        let absolute = project_dir.join(path).canonicalize()?;
+        let Ok(relative) = absolute.strip_prefix(&workdir) else {
+            return Err(AidotError::Git(format!(
+                "{} is outside the git repository",
+                path
+            )));
+        };
+        if repo.is_path_ignored(relative).unwrap_or(false) {
+            outcome.ignored.push(path.clone());
+            continue;
+        }
+        index.add_path(relative).map_err(git_error)?;
+        staged += 1;
+    }
+    index.write().map_err(git_error)?;
+
+    let tree_id = index.write_tree().map_err(git_error)?;
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let unchanged = match &parent {
+        Some(parent) => parent.tree_id() == tree_id,
+        None => staged == 0,
+    };
+    if unchanged {
+        return Ok(outcome);
+    }
+
+    let signature = repo.signature().map_err(|_| {
+        AidotError::Git(
+            "no git identity configured; set user.name and user.email first".to_string(),
+        )
+    })?;
+    let tree = repo.find_tree(tree_id).map_err(git_error)?;
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    let id = repo
+        .commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .map_err(git_error)?;
+    outcome.id = Some(id.to_string()[..7].to_string());
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_transport_of() {
+        assert_eq!(
+            transport_of("https://github.com/team/presets.git"),
+            Transport::Https
+        );
+        assert_eq!(
+            transport_of("git@github.com:team/presets.git"),
+            Transport::Ssh
+        );
+        assert_eq!(
+            transport_of("ssh://git@example.com/team/presets.git"),
+            Transport::Ssh
+        );
+        assert_eq!(
+            transport_of("git://example.com/presets.git"),
+            Transport::Other
+        );
+        assert_eq!(transport_of("/srv/git/presets.git"), Transport::Other);
+    }
+
+    #[test]
+    fn test_head_commit() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        assert!(head_commit(temp_dir.path()).is_err());
+
+        let signature = git2::Signature::now("t", "t@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let id = repo
+            .commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .unwrap();
+        let (branch, short) = head_commit(temp_dir.path()).unwrap();
+        assert!(branch.is_some());
+        assert_eq!(short, id.to_string()[..7]);
+
+        repo.set_head_detached(id).unwrap();
+        assert_eq!(head_commit(temp_dir.path()).unwrap().0, None);
+    }
+
+    #[test]
+    fn test_tracked_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        fs::create_dir_all(temp_dir.path().join(".claude")).unwrap();
+        fs::write(temp_dir.path().join(".claude/settings.local.json"), "{}").unwrap();
+        fs::write(temp_dir.path().join(".claude/new.md"), "# New").unwrap();
+
+        let mut index = repo.index().unwrap();
+        index
+            .add_path(Path::new(".claude/settings.local.json"))
+            .unwrap();
+        index.write().unwrap();
+
+        let paths = vec![
+            ".claude/settings.local.json".to_string(),
+            ".claude/new.md".to_string(),
+        ];
+        assert_eq!(
+            tracked_paths(temp_dir.path(), &paths),
+            vec![".claude/settings.local.json".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_local_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        assert!(local_changes(temp_dir.path()).unwrap().is_empty());
+
+        fs::write(temp_dir.path().join("rules.md"), "# Rules").unwrap();
+        assert_eq!(local_changes(temp_dir.path()).unwrap(), vec!["rules.md"]);
+
+        repo.remote("origin", "https://example.com/team/preset.git")
+            .unwrap();
+        assert_eq!(
+            remote_url(temp_dir.path()).unwrap(),
+            "https://example.com/team/preset.git"
+        );
+    }
+
+    #[test]
+    fn test_commit_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = Repository::init(temp_dir.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+
+        fs::create_dir_all(temp_dir.path().join(".claude")).unwrap();
+        fs::write(temp_dir.path().join(".gitignore"), "*.local.json\n").unwrap();
+        fs::write(temp_dir.path().join(".claude/CLAUDE.md"), "# Memory").unwrap();
+        fs::write(temp_dir.path().join(".claude/settings.local.json"), "{}").unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "not applied").unwrap();
+        ensure_committable(temp_dir.path()).unwrap();
+
+        let paths = vec![
+            ".claude/CLAUDE.md".to_string(),
+            ".claude/settings.local.json".to_string(),
+        ];
+        let outcome = commit_paths(temp_dir.path(), &paths, "aidot: apply team preset").unwrap();
+        assert!(outcome.id.is_some());
+        assert_eq!(outcome.ignored, vec![".claude/settings.local.json"]);
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.message(), Some("aidot: apply team preset"));
+        let tree = head.tree().unwrap();
+        assert!(tree.get_path(Path::new(".claude/CLAUDE.md")).is_ok());
+        assert!(tree.get_path(Path::new("notes.txt")).is_err());
+
+        // Same content again: nothing to commit
+        let outcome = commit_paths(temp_dir.path(), &paths, "again").unwrap();
+        assert!(outcome.id.is_none());
+
+        // Files staged by the user block --commit
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("notes.txt")).unwrap();
+        index.write().unwrap();
+        assert!(ensure_committable(temp_dir.path()).is_err());
+    }
+}
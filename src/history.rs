@@ -0,0 +1,168 @@
+use crate::error::Result;
+use crate::manifest::Manifest;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Projects presets were applied to, stored in ~/.aidot/state/history.json
+///
+/// Written by pull and read by `cache update` to list the projects a changed
+/// repository affects. Projects whose directory no longer exists are dropped on load.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct History {
+    #[serde(default)]
+    pub projects: Vec<HistoryEntry>,
+}
+
+/// History entry for tracking applied presets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub project: String,
+    /// Time of the last pull in this project, whichever repository it applied
+    pub timestamp: String,
+    pub repositories: Vec<String>,
+    /// Time each repository was last pulled here
+    pub pulled: BTreeMap<String, String>,
+}
+
+impl History {
+    /// Get the history file path (~/.aidot/state/history.json)
+    pub fn file() -> Result<PathBuf> {
+        Ok(Manifest::state_dir()?.join("history.json"))
+    }
+
+    /// Load the history, without projects that were moved or deleted
+    pub fn load() -> Result<Self> {
+        let history_file = Self::file()?;
+        if !history_file.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&history_file)?;
+        let mut history: Self = serde_json::from_str(&content)?;
+        history.retain_existing();
+        Ok(history)
+    }
+
+    /// Save the history
+    pub fn save(&self) -> Result<()> {
+        let state_dir = Manifest::state_dir()?;
+        if !state_dir.exists() {
+            fs::create_dir_all(&state_dir)?;
+        }
+
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(Self::file()?, content)?;
+        Ok(())
+    }
+
+    /// Drop entries whose project directory no longer exists
+    fn retain_existing(&mut self) {
+        self.projects
+            .retain(|entry| Path::new(&entry.project).is_dir());
+    }
+
+    /// Note that `repositories` were applied to `project` at `timestamp` (not saved)
+    ///
+    /// Each project has one entry; repositories are added to it once, keeping the
+    /// order in which they were first applied.
+    pub fn record(&mut self, project: &str, repositories: &[String], timestamp: &str) {
+        let index = match self.projects.iter().position(|h| h.project == project) {
+            Some(index) => index,
+            None => {
+                self.projects.push(HistoryEntry {
+                    project: project.to_string(),
+                    timestamp: String::new(),
+                    repositories: Vec::new(),
+                    pulled: BTreeMap::new(),
+                });
+                self.projects.len() - 1
+            }
+        };
+        let entry = &mut self.projects[index];
+        entry.timestamp = timestamp.to_string();
+        for repo in repositories {
+            if !entry.repositories.contains(repo) {
+                entry.repositories.push(repo.clone());
+            }
+            entry.pulled.insert(repo.clone(), timestamp.to_string());
+        }
+    }
+
+    /// Projects a repository was applied to, as recorded by pull
+    pub fn projects_using(&self, repository: &str) -> Vec<&HistoryEntry> {
+        self.projects
+            .iter()
+            .filter(|h| h.repositories.iter().any(|r| r == repository))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_history_entry() {
+        let entry = HistoryEntry {
+            project: "/home/user/project".to_string(),
+            timestamp: "2026-01-12T10:00:00Z".to_string(),
+            repositories: vec!["common".to_string(), "team-config".to_string()],
+            pulled: BTreeMap::new(),
+        };
+
+        let json = serde_json::to_string(&entry).unwrap();
+        let deserialized: HistoryEntry = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(deserialized.project, "/home/user/project");
+        assert_eq!(deserialized.repositories.len(), 2);
+    }
+
+    #[test]
+    fn test_record_history() {
+        let mut history = History::default();
+        let repos = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        history.record("/work/a", &repos(&["common"]), "2026-01-01T00:00:00Z");
+        history.record("/work/b", &repos(&["team"]), "2026-01-02T00:00:00Z");
+        history.record(
+            "/work/a",
+            &repos(&["team", "common"]),
+            "2026-01-03T00:00:00Z",
+        );
+
+        assert_eq!(history.projects.len(), 2);
+        assert_eq!(history.projects[0].repositories, repos(&["common", "team"]));
+        assert_eq!(history.projects[0].timestamp, "2026-01-03T00:00:00Z");
+        assert_eq!(history.projects[1].pulled["team"], "2026-01-02T00:00:00Z");
+        history.record("/work/a", &repos(&["common"]), "2026-01-04T00:00:00Z");
+        assert_eq!(history.projects[0].pulled["team"], "2026-01-03T00:00:00Z");
+        assert_eq!(history.projects[0].pulled["common"], "2026-01-04T00:00:00Z");
+        let projects: Vec<&str> = history
+            .projects_using("team")
+            .iter()
+            .map(|h| h.project.as_str())
+            .collect();
+        assert_eq!(projects, vec!["/work/a", "/work/b"]);
+        assert!(history.projects_using("other").is_empty());
+    }
+
+    #[test]
+    fn test_retain_existing_drops_missing_projects() {
+        let project = TempDir::new().unwrap();
+        let gone = project.path().join("gone");
+        let mut history = History::default();
+        let repos = vec!["team".to_string()];
+        history.record(project.path().to_str().unwrap(), &repos, "t");
+        history.record(gone.to_str().unwrap(), &repos, "t");
+
+        history.retain_existing();
+
+        assert_eq!(history.projects.len(), 1);
+        assert_eq!(
+            history.projects[0].project,
+            project.path().to_str().unwrap()
+        );
+    }
+}
@@ -1,20 +1,31 @@
 mod adapters;
+mod archive;
 mod cache;
 mod cli;
 mod commands;
 mod config;
 mod error;
 mod git;
+mod history;
+mod log;
+mod manifest;
+mod output;
 mod preset;
+mod progress;
+mod report;
 mod repository;
+mod secret;
+mod select;
 
+use adapters::conflict::SymlinkPolicy;
 use clap::Parser;
-use cli::{CacheCommands, Cli, Commands, RepoCommands};
+use cli::{CacheCommands, Cli, Commands, ConfigCommands, RepoCommands, SecretCommands};
 use colored::Colorize;
 use error::Result;
 
 fn main() {
     if let Err(e) = run() {
+        log::error(&e.to_string());
         eprintln!("{} {}", "Error:".red().bold(), e);
         std::process::exit(1);
     }
@@ -22,15 +33,35 @@ fn main() {
 
 fn run() -> Result<()> {
     let cli = Cli::parse();
+    output::init(cli.quiet, cli.verbose);
+    output::init_colors(cli.no_color, cli.diff_theme)?;
+    cache::set_offline(cli.offline || cache::offline_from_env());
+
+    // Pull always keeps a run log; other commands only with --log
+    if let Some(path) = &cli.log {
+        log::init(Some(path), cli.verbose)?;
+    } else if matches!(cli.command, Commands::Pull { .. }) {
+        // A missing or read-only ~/.aidot/logs/ must not stop the pull
+        let _ = log::init(None, cli.verbose);
+    }
 
     match cli.command {
         Commands::Init {
             from_existing,
+            encrypt,
             interactive,
             force,
+            repo_url,
             path,
         } => {
-            commands::init_preset(path, from_existing, interactive, force)?;
+            commands::init_preset(
+                path,
+                from_existing,
+                encrypt,
+                interactive,
+                force,
+                repo_url.as_deref(),
+            )?;
         }
 
         Commands::Repo(repo_cmd) => match repo_cmd {
@@ -40,40 +71,117 @@ fn run() -> Result<()> {
                 local,
                 default,
                 description,
+                subdir,
+                verify,
+                tags,
+                sha256,
             } => {
-                commands::add_repo(name, url, local, default, description)?;
+                commands::add_repo(
+                    name,
+                    url,
+                    local,
+                    default,
+                    description,
+                    subdir,
+                    verify,
+                    tags,
+                    sha256,
+                )?;
             }
 
-            RepoCommands::List => {
-                commands::list_repos()?;
+            RepoCommands::List { tag } => {
+                commands::list_repos(tag.as_deref())?;
+            }
+
+            RepoCommands::Doctor => {
+                commands::doctor_repos()?;
             }
 
             RepoCommands::Remove { name } => {
                 commands::remove_repo(&name)?;
             }
 
-            RepoCommands::SetDefault { name, value } => {
+            RepoCommands::SetDefault { name, value, off } => {
+                let value = if off { false } else { value.unwrap_or(true) };
                 commands::set_default_repo(&name, value)?;
             }
+
+            RepoCommands::UnsetDefault { name } => {
+                commands::set_default_repo(&name, false)?;
+            }
+
+            RepoCommands::SetUrl {
+                name,
+                url,
+                local,
+                sha256,
+            } => {
+                commands::set_repo_url(&name, url, local, sha256)?;
+            }
+
+            RepoCommands::TrustHooks { name, off } => {
+                commands::trust_repo_hooks(&name, !off)?;
+            }
         },
 
         Commands::Pull {
             repositories,
+            tag,
             tools,
+            exclude_tools,
             dry_run,
+            reload,
             force,
             skip,
+            yes,
+            line_endings,
+            prune,
+            only,
+            allow_overlap,
+            run_hooks,
+            show_skipped,
+            continue_on_error,
+            pager,
+            follow_symlinks,
+            replace_symlinks,
+            global,
+            compose,
+            reset_cache,
+            no_cache,
+            strict,
+            interactive_preview,
+            commit,
+            commit_message,
+            report,
         } => {
+            cache::set_reset_cache(reset_cache);
+            preset::parse_cache::set_enabled(!no_cache);
             let repos_to_apply: Vec<String> = if repositories.is_empty() {
-                // Apply all default repositories
+                // Apply all default repositories (with --tag, only those tagged)
                 let cfg = config::Config::load()?;
                 let defaults: Vec<String> = cfg
                     .repositories
                     .iter()
                     .filter(|r| r.default)
+                    .filter(|r| tag.as_deref().is_none_or(|tag| r.has_tag(tag)))
                     .map(|r| r.name.clone())
                     .collect();
 
+                if let (true, Some(tag)) = (defaults.is_empty(), &tag) {
+                    println!(
+                        "{}",
+                        format!("No default repositories tagged '{}'.", tag).yellow()
+                    );
+                    println!(
+                        "{}",
+                        format!(
+                            "Run 'aidot repo list --tag {}' to see tagged repositories.",
+                            tag
+                        )
+                        .dimmed()
+                    );
+                    return Ok(());
+                }
                 if defaults.is_empty() {
                     println!("{}", "No default repositories configured.".yellow());
                     println!(
@@ -97,22 +205,101 @@ fn run() -> Result<()> {
             } else {
                 repositories
             };
-
-            // Apply each repository sequentially
-            for (i, repo_source) in repos_to_apply.iter().enumerate() {
-                if repos_to_apply.len() > 1 {
+            // The same preset twice would only be applied again
+            let (repos_to_apply, duplicates) = commands::dedup_sources(repos_to_apply);
+            if cli.verbose {
+                for repo in &duplicates {
                     println!(
-                        "\n{} [{}/{}] {}",
-                        "═══".cyan(),
-                        (i + 1).to_string().white().bold(),
-                        repos_to_apply.len().to_string().white().bold(),
-                        repo_source.white().bold()
+                        "{}",
+                        format!("Skipping duplicate repository '{}'", repo).dimmed()
                     );
                 }
-                commands::pull_preset(repo_source.clone(), tools.clone(), dry_run, force, skip)?;
             }
 
-            if repos_to_apply.len() > 1 {
+            let options = commands::PullOptions {
+                tools_filter: tools,
+                exclude_tools,
+                dry_run,
+                force,
+                skip,
+                yes,
+                line_endings,
+                prune,
+                only,
+                allow_overlap,
+                run_hooks,
+                verbose: cli.verbose,
+                show_skipped,
+                continue_on_error,
+                pager,
+                symlinks: if follow_symlinks {
+                    SymlinkPolicy::Follow
+                } else if replace_symlinks {
+                    SymlinkPolicy::Replace
+                } else {
+                    SymlinkPolicy::Skip
+                },
+                global,
+                strict,
+                interactive_preview,
+                commit,
+                commit_message,
+                reload,
+                tool_order: Vec::new(),
+                merged_warn_bytes: None,
+                conflict_policy: Default::default(),
+                diff_max_lines: None,
+            };
+
+            // Detected once for all repositories
+            let target = commands::PullTarget::resolve(global)?;
+
+            // A single report covers all repositories
+            let mut apply_report = report::Report::new(&std::env::current_dir()?);
+
+            // Apply all repositories at once (--compose) or each sequentially (stops when the user quits)
+            let mut aborted = false;
+            let mut failed = 0;
+            if compose && repos_to_apply.len() > 1 {
+                let summary = commands::pull_composed(&repos_to_apply, &target, &options)?;
+                apply_report.entries.extend(summary.entries);
+                failed += summary.failed.len();
+                aborted = summary.aborted;
+            } else {
+                for (i, repo_source) in repos_to_apply.iter().enumerate() {
+                    if repos_to_apply.len() > 1 {
+                        println!(
+                            "\n{} [{}/{}] {}",
+                            "═══".cyan(),
+                            (i + 1).to_string().white().bold(),
+                            repos_to_apply.len().to_string().white().bold(),
+                            repo_source.white().bold()
+                        );
+                    }
+                    let summary = commands::pull_preset(repo_source.clone(), &target, &options)?;
+                    apply_report.entries.extend(summary.entries);
+                    failed += summary.failed.len();
+                    if summary.aborted {
+                        aborted = true;
+                        break;
+                    }
+                }
+            }
+
+            if let Some(report_path) = report {
+                apply_report.write(&report_path)?;
+                println!(
+                    "\n{} {}",
+                    "Report written to".cyan(),
+                    report_path.display().to_string().white()
+                );
+            }
+
+            if failed > 0 {
+                return Err(error::AidotError::ApplyFailed(failed));
+            }
+
+            if repos_to_apply.len() > 1 && !aborted {
                 println!(
                     "\n{} {} repositories applied successfully!",
                     "✓".green().bold(),
@@ -121,25 +308,109 @@ fn run() -> Result<()> {
             }
         }
 
-        Commands::Detect => {
-            commands::detect_tools()?;
+        Commands::Lint {
+            path,
+            fix,
+            check_urls,
+        } => {
+            commands::lint_preset(path, fix, check_urls)?;
+        }
+
+        Commands::Search {
+            query,
+            manifest,
+            limit,
+        } => {
+            commands::search_presets(query, manifest, limit)?;
+        }
+
+        Commands::Detect { global } => {
+            commands::detect_tools(global)?;
+        }
+
+        Commands::Doctor { json } => {
+            commands::run_doctor(json)?;
         }
 
-        Commands::Status => {
-            commands::show_status()?;
+        Commands::Status { global, depth, all } => {
+            commands::show_status(global, commands::TreeLimits::new(depth, all))?;
         }
 
         Commands::Cache(cache_cmd) => match cache_cmd {
-            CacheCommands::Update { name, all } => {
-                commands::update_cache(name, all)?;
+            CacheCommands::Update {
+                name,
+                all,
+                reset_cache,
+                apply,
+            } => {
+                cache::set_reset_cache(reset_cache);
+                commands::update_cache(name, all, apply)?;
             }
-            CacheCommands::Clear => {
-                commands::clear_cache()?;
+            CacheCommands::Clear { yes } => {
+                commands::clear_cache(yes)?;
+            }
+            CacheCommands::Gc { max_size, dry_run } => {
+                commands::gc_cache(max_size, dry_run)?;
             }
         },
 
-        Commands::Diff { repository } => {
-            commands::show_diff(repository)?;
+        Commands::Secret(secret_cmd) => match secret_cmd {
+            SecretCommands::Encrypt { file } => {
+                commands::encrypt_file(&file)?;
+            }
+            SecretCommands::Decrypt { file } => {
+                commands::decrypt_file(&file)?;
+            }
+        },
+
+        Commands::Config(config_cmd) => match config_cmd {
+            ConfigCommands::Export { output } => {
+                commands::export_config(output)?;
+            }
+            ConfigCommands::Import {
+                file,
+                on_conflict,
+                remap,
+            } => {
+                commands::import_config(&file, on_conflict, &remap)?;
+            }
+        },
+
+        Commands::Unapply {
+            repository,
+            dry_run,
+            yes,
+            force,
+        } => {
+            commands::unapply_preset(&repository, dry_run, yes, force)?;
+        }
+
+        Commands::Diff {
+            repository,
+            by_section,
+            json,
+            tools,
+            no_cache,
+        } => {
+            preset::parse_cache::set_enabled(!no_cache);
+            commands::show_diff(
+                repository,
+                commands::DiffOptions {
+                    tools_filter: tools,
+                    by_section,
+                    json,
+                },
+            )?;
+        }
+
+        Commands::Render {
+            repository,
+            out,
+            tools,
+            no_cache,
+        } => {
+            preset::parse_cache::set_enabled(!no_cache);
+            commands::render_preset(&repository, &out, tools.as_deref())?;
         }
 
         Commands::Update { check, prerelease } => {
@@ -0,0 +1,257 @@
+use crate::config::Config;
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Record of files applied to a project, stored in ~/.aidot/state/{project-hash}.json
+///
+/// Only 1:1 mapped files and root files are recorded. Merged files (e.g., CLAUDE.md,
+/// settings.local.json) may contain user content, so they are never reported as orphaned.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Absolute project path this manifest belongs to
+    pub project: String,
+    #[serde(default)]
+    pub files: Vec<ManifestEntry>,
+}
+
+/// A single file written by `aidot pull`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Path relative to the project root (e.g., ".claude/rules/style.md")
+    pub path: String,
+    /// Tool name (e.g., "Claude Code") or "Root"
+    pub tool: String,
+    /// Preset section (e.g., "rules")
+    pub section: String,
+    /// Preset source as given to pull (repository name, path, or URL)
+    pub preset: String,
+    /// SHA-256 of the file as written by the last apply (absent in older manifests)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+impl ManifestEntry {
+    /// Whether the file on disk differs from what the last apply wrote
+    ///
+    /// False when no hash was recorded (older manifests) or the file cannot be read.
+    pub fn is_modified(&self, project_dir: &Path) -> bool {
+        match (&self.hash, fs::read(project_dir.join(&self.path))) {
+            (Some(hash), Ok(content)) => content_hash(&content) != *hash,
+            _ => false,
+        }
+    }
+}
+
+impl Manifest {
+    /// Get the state directory path (~/.aidot/state/)
+    pub fn state_dir() -> Result<PathBuf> {
+        Ok(Config::config_dir()?.join("state"))
+    }
+
+    /// Get the manifest file path for a project
+    pub fn file_for(project_dir: &Path) -> Result<PathBuf> {
+        Ok(Self::state_dir()?.join(format!("{}.json", project_hash(project_dir))))
+    }
+
+    /// Load the manifest of a project (empty if nothing was applied yet)
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        let manifest_file = Self::file_for(project_dir)?;
+
+        if !manifest_file.exists() {
+            return Ok(Self {
+                project: project_key(project_dir),
+                files: Vec::new(),
+            });
+        }
+
+        let content = fs::read_to_string(&manifest_file)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Save the manifest of a project
+    pub fn save(&self, project_dir: &Path) -> Result<()> {
+        let state_dir = Self::state_dir()?;
+        if !state_dir.exists() {
+            fs::create_dir_all(&state_dir)?;
+        }
+
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(Self::file_for(project_dir)?, content)?;
+        Ok(())
+    }
+
+    /// Check whether a path is recorded for a preset
+    pub fn contains(&self, preset: &str, path: &str) -> bool {
+        self.files
+            .iter()
+            .any(|e| e.preset == preset && e.path == path)
+    }
+
+    /// Whether a recorded file was changed on disk after aidot applied it
+    ///
+    /// Checks the first entry for `path` that has a hash (see [`ManifestEntry::is_modified`]).
+    pub fn is_modified_since_apply(&self, project_dir: &Path, path: &str) -> bool {
+        self.files
+            .iter()
+            .find(|e| e.path == path && e.hash.is_some())
+            .is_some_and(|e| e.is_modified(project_dir))
+    }
+
+    /// Entries previously applied by `preset` for one of `tools` that it no longer produces
+    ///
+    /// Paths still claimed by another preset are not orphaned.
+    pub fn orphans(
+        &self,
+        preset: &str,
+        tools: &[&str],
+        current_paths: &HashSet<String>,
+    ) -> Vec<ManifestEntry> {
+        self.files
+            .iter()
+            .filter(|e| e.preset == preset && tools.contains(&e.tool.as_str()))
+            .filter(|e| !current_paths.contains(&e.path))
+            .filter(|e| self.other_preset(preset, &e.path).is_none())
+            .cloned()
+            .collect()
+    }
+
+    /// Another preset that still has `path` recorded, if any
+    pub fn other_preset(&self, preset: &str, path: &str) -> Option<&str> {
+        self.files
+            .iter()
+            .find(|e| e.path == path && e.preset != preset)
+            .map(|e| e.preset.as_str())
+    }
+
+    /// Replace the entries of `preset` for `tools` with `entries`
+    pub fn replace(&mut self, preset: &str, tools: &[&str], entries: Vec<ManifestEntry>) {
+        self.files
+            .retain(|e| !(e.preset == preset && tools.contains(&e.tool.as_str())));
+        self.files.extend(entries);
+    }
+
+    /// Remove and return all entries of `preset`
+    pub fn remove_preset(&mut self, preset: &str) -> Vec<ManifestEntry> {
+        let (removed, kept) = self.files.drain(..).partition(|e| e.preset == preset);
+        self.files = kept;
+        removed
+    }
+}
+
+/// SHA-256 of file content as lowercase hex
+pub fn content_hash(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Canonical project path used as the manifest key (and in the apply history)
+pub(crate) fn project_key(project_dir: &Path) -> String {
+    project_dir
+        .canonicalize()
+        .unwrap_or_else(|_| project_dir.to_path_buf())
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Stable short hash of the canonical project path
+fn project_hash(project_dir: &Path) -> String {
+    let digest = Sha256::digest(project_key(project_dir).as_bytes());
+    digest[..8].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(path: &str, tool: &str, preset: &str) -> ManifestEntry {
+        ManifestEntry {
+            path: path.to_string(),
+            tool: tool.to_string(),
+            section: "rules".to_string(),
+            preset: preset.to_string(),
+            hash: None,
+        }
+    }
+
+    #[test]
+    fn test_orphans() {
+        let manifest = Manifest {
+            project: "/project".to_string(),
+            files: vec![
+                entry(".claude/rules/a.md", "Claude Code", "team"),
+                entry(".claude/rules/old.md", "Claude Code", "team"),
+                entry(".cursor/rules/old.md", "Cursor", "team"),
+                entry(".claude/rules/shared.md", "Claude Code", "team"),
+                entry(".claude/rules/shared.md", "Claude Code", "common"),
+            ],
+        };
+        let current: HashSet<String> = [".claude/rules/a.md".to_string()].into();
+
+        let orphans = manifest.orphans("team", &["Claude Code"], &current);
+        let paths: Vec<_> = orphans.iter().map(|e| e.path.as_str()).collect();
+        // Cursor is not in scope and shared.md is still claimed by "common"
+        assert_eq!(paths, vec![".claude/rules/old.md"]);
+    }
+
+    #[test]
+    fn test_replace_and_remove_preset() {
+        let mut manifest = Manifest {
+            project: "/project".to_string(),
+            files: vec![
+                entry(".claude/rules/old.md", "Claude Code", "team"),
+                entry(".cursor/rules/old.md", "Cursor", "team"),
+                entry(".claude/rules/c.md", "Claude Code", "common"),
+            ],
+        };
+
+        manifest.replace(
+            "team",
+            &["Claude Code"],
+            vec![entry(".claude/rules/new.md", "Claude Code", "team")],
+        );
+        assert!(manifest.contains("team", ".claude/rules/new.md"));
+        assert!(manifest.contains("team", ".cursor/rules/old.md"));
+        assert!(!manifest.contains("team", ".claude/rules/old.md"));
+
+        let removed = manifest.remove_preset("team");
+        assert_eq!(removed.len(), 2);
+        assert_eq!(manifest.files.len(), 1);
+        assert_eq!(manifest.files[0].preset, "common");
+    }
+
+    #[test]
+    fn test_is_modified_since_apply() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.md"), "applied").unwrap();
+        fs::write(temp_dir.path().join("b.md"), "untracked").unwrap();
+        let mut manifest = Manifest::default();
+        manifest.files.push(ManifestEntry {
+            hash: Some(content_hash(b"applied")),
+            ..entry("a.md", "Root", "team")
+        });
+        manifest.files.push(entry("b.md", "Root", "team"));
+
+        assert!(!manifest.is_modified_since_apply(temp_dir.path(), "a.md"));
+        fs::write(temp_dir.path().join("a.md"), "edited").unwrap();
+        assert!(manifest.is_modified_since_apply(temp_dir.path(), "a.md"));
+
+        // No recorded hash (older manifest) or no file: nothing to compare
+        assert!(!manifest.is_modified_since_apply(temp_dir.path(), "b.md"));
+        assert!(!manifest.is_modified_since_apply(temp_dir.path(), "missing.md"));
+    }
+
+    #[test]
+    fn test_project_hash_is_stable() {
+        let temp_dir = TempDir::new().unwrap();
+        let hash = project_hash(temp_dir.path());
+        assert_eq!(hash.len(), 16);
+        assert_eq!(hash, project_hash(temp_dir.path()));
+    }
+}
@@ -0,0 +1,213 @@
+use crate::config::Config;
+use crate::error::{AidotError, Result};
+use colored::{ColoredString, Colorize};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Environment variable selecting the diff color theme
+pub const DIFF_THEME_ENV: &str = "AIDOT_DIFF_THEME";
+
+/// Quiet mode flag, set once from the global `--quiet` CLI option
+static QUIET: OnceLock<bool> = OnceLock::new();
+/// Verbose mode flag, set once from the global `--verbose` CLI option
+static VERBOSE: OnceLock<bool> = OnceLock::new();
+/// Diff theme, set once from `--diff-theme` / AIDOT_DIFF_THEME / `--no-color`
+static DIFF_THEME: OnceLock<DiffTheme> = OnceLock::new();
+
+/// Color scheme of file diffs (conflict prompts, pull previews)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum DiffTheme {
+    /// Red removals, green additions
+    #[default]
+    Default,
+    /// Blue removals, yellow additions (readable with red-green color blindness)
+    Colorblind,
+    /// Stronger colors and undimmed context, for light terminal backgrounds
+    Light,
+    /// No colors: lines are told apart by their -, + and space prefixes only
+    Plain,
+}
+
+/// Parts of a diff a theme colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffPart {
+    Removed,
+    Added,
+    Context,
+    Hunk,
+    Path,
+}
+
+impl DiffTheme {
+    /// Style `text` as the given part of a diff
+    pub fn paint(self, text: &str, part: DiffPart) -> ColoredString {
+        match (self, part) {
+            (DiffTheme::Plain, _) => text.normal(),
+            (DiffTheme::Default, DiffPart::Removed) => text.red(),
+            (DiffTheme::Default, DiffPart::Added) => text.green(),
+            (DiffTheme::Colorblind, DiffPart::Removed) => text.blue(),
+            (DiffTheme::Colorblind, DiffPart::Added) => text.yellow(),
+            (DiffTheme::Default | DiffTheme::Colorblind, DiffPart::Hunk) => text.cyan(),
+            (DiffTheme::Default | DiffTheme::Colorblind, _) => text.dimmed(),
+            (DiffTheme::Light, DiffPart::Removed) => text.red().bold(),
+            (DiffTheme::Light, DiffPart::Added) => text.green().bold(),
+            (DiffTheme::Light, DiffPart::Hunk) => text.blue(),
+            (DiffTheme::Light, _) => text.normal(),
+        }
+    }
+}
+
+/// Initialize color settings from the global `--no-color` / `--diff-theme` options
+///
+/// `--no-color` turns off all colors and implies the plain diff theme. Without
+/// `--diff-theme`, the theme comes from AIDOT_DIFF_THEME.
+pub fn init_colors(no_color: bool, diff_theme: Option<DiffTheme>) -> Result<()> {
+    let theme = if no_color {
+        colored::control::set_override(false);
+        DiffTheme::Plain
+    } else {
+        match diff_theme {
+            Some(theme) => theme,
+            None => theme_from_env(std::env::var(DIFF_THEME_ENV).ok().as_deref())?,
+        }
+    };
+    let _ = DIFF_THEME.set(theme);
+    Ok(())
+}
+
+fn theme_from_env(value: Option<&str>) -> Result<DiffTheme> {
+    let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+        return Ok(DiffTheme::default());
+    };
+    <DiffTheme as clap::ValueEnum>::from_str(value, true).map_err(|_| {
+        AidotError::InvalidInput(format!(
+            "Unknown {} '{}' (expected default, colorblind, light or plain)",
+            DIFF_THEME_ENV, value
+        ))
+    })
+}
+
+/// Theme used to color diffs
+pub fn diff_theme() -> DiffTheme {
+    DIFF_THEME.get().copied().unwrap_or_default()
+}
+
+/// Initialize output settings from global CLI flags
+pub fn init(quiet: bool, verbose: bool) {
+    let _ = QUIET.set(quiet);
+    let _ = VERBOSE.set(verbose);
+}
+
+/// Check if quiet mode is enabled
+pub fn is_quiet() -> bool {
+    QUIET.get().copied().unwrap_or(false)
+}
+
+/// Check if verbose mode is enabled
+pub fn is_verbose() -> bool {
+    VERBOSE.get().copied().unwrap_or(false)
+}
+
+/// Print an informational message to stdout unless quiet mode is enabled
+pub fn info(message: &str) {
+    if !is_quiet() {
+        println!("{}", message);
+    }
+}
+
+/// A path shortened for display (the full path with --verbose)
+///
+/// Cached clones show as `cache:<name>/...`, paths inside the current directory
+/// as `./...` and paths under the home directory as `~/...`.
+pub fn display_path(path: &Path) -> String {
+    if is_verbose() {
+        return path.display().to_string();
+    }
+    let cwd = std::env::current_dir().ok();
+    let home = dirs::home_dir();
+    let cache = Config::cache_dir().ok();
+    shorten_path(path, cwd.as_deref(), home.as_deref(), cache.as_deref())
+}
+
+fn shorten_path(
+    path: &Path,
+    cwd: Option<&Path>,
+    home: Option<&Path>,
+    cache: Option<&Path>,
+) -> String {
+    let under = |base: Option<&Path>| {
+        base.and_then(|base| path.strip_prefix(base).ok())
+            .filter(|rest| !rest.as_os_str().is_empty())
+            .map(|rest| rest.to_string_lossy().replace('\\', "/"))
+    };
+    if let Some(rest) = under(cache) {
+        return format!("cache:{}", rest);
+    }
+    if let Some(rest) = under(cwd) {
+        return format!("./{}", rest);
+    }
+    tilde_path(path, home)
+}
+
+/// Show a path under the home directory as `~/...`
+pub fn tilde_path(path: &Path, home: Option<&Path>) -> String {
+    match home.and_then(|home| path.strip_prefix(home).ok()) {
+        Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Some(rest) => format!("~/{}", rest.to_string_lossy().replace('\\', "/")),
+        None => path.display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_from_env() {
+        assert_eq!(theme_from_env(None).unwrap(), DiffTheme::Default);
+        assert_eq!(theme_from_env(Some(" ")).unwrap(), DiffTheme::Default);
+        assert_eq!(
+            theme_from_env(Some("Colorblind")).unwrap(),
+            DiffTheme::Colorblind
+        );
+        assert_eq!(theme_from_env(Some("plain")).unwrap(), DiffTheme::Plain);
+        assert!(theme_from_env(Some("solarized")).is_err());
+    }
+
+    #[test]
+    fn test_tilde_path() {
+        let home = Path::new("/home/me");
+        assert_eq!(
+            tilde_path(Path::new("/home/me/.claude"), Some(home)),
+            "~/.claude"
+        );
+        assert_eq!(tilde_path(home, Some(home)), "~");
+        assert_eq!(
+            tilde_path(Path::new("/etc/aidot"), Some(home)),
+            "/etc/aidot"
+        );
+        assert_eq!(
+            tilde_path(Path::new("/home/me/.claude"), None),
+            "/home/me/.claude"
+        );
+    }
+
+    #[test]
+    fn test_shorten_path() {
+        let home = Some(Path::new("/home/me"));
+        let cwd = Some(Path::new("/home/me/project"));
+        let cache = Some(Path::new("/home/me/.aidot/cache"));
+        let short = |path: &str| shorten_path(Path::new(path), cwd, home, cache);
+
+        assert_eq!(short("/home/me/.aidot/cache/team"), "cache:team");
+        assert_eq!(
+            short("/home/me/.aidot/cache/team/presets/web"),
+            "cache:team/presets/web"
+        );
+        assert_eq!(short("/home/me/project/presets/local"), "./presets/local");
+        // The current directory itself is shown from home, not as "."
+        assert_eq!(short("/home/me/project"), "~/project");
+        assert_eq!(short("/home/me/presets"), "~/presets");
+        assert_eq!(short("/srv/presets"), "/srv/presets");
+    }
+}
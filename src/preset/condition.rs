@@ -0,0 +1,284 @@
+use crate::adapters::helpers::has_frontmatter;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Conditions under which a preset file is applied
+///
+/// Declared either in the file's front matter:
+/// ```text
+/// ---
+/// when: { os: [macos, linux], files_exist: [Cargo.toml] }
+/// ---
+/// ```
+/// or per glob in `.aidot-config.toml`:
+/// ```toml
+/// [when."rules/rust-*.md"]
+/// files_exist = ["Cargo.toml"]
+/// ```
+/// Every listed key must hold; within a key, `os` matches any of the values
+/// while `files_exist` requires all of the paths.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Condition {
+    /// Operating systems as reported by `std::env::consts::OS` (e.g., "macos", "linux", "windows")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub os: Vec<String>,
+
+    /// Paths (relative to the target directory) that must exist
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files_exist: Vec<String>,
+}
+
+impl Condition {
+    /// Check the condition against the current OS and target directory
+    ///
+    /// Returns the reason when the condition is not met.
+    pub fn unmet_reason(&self, target_dir: &Path) -> Option<String> {
+        self.unmet_reason_for_os(std::env::consts::OS, target_dir)
+    }
+
+    fn unmet_reason_for_os(&self, current_os: &str, target_dir: &Path) -> Option<String> {
+        if !self.os.is_empty() && !self.os.iter().any(|os| os.eq_ignore_ascii_case(current_os)) {
+            return Some(format!(
+                "os is {} (requires {})",
+                current_os,
+                self.os.join(", ")
+            ));
+        }
+        self.files_exist
+            .iter()
+            .find(|path| !target_dir.join(path).exists())
+            .map(|path| format!("{} not found", path))
+    }
+}
+
+/// Parse the `when:` condition from a file's YAML front matter
+///
+/// Supports the inline form (`when: { os: [macos] }`) and the block form:
+/// ```text
+/// when:
+///   os: [macos]
+///   files_exist:
+///     - Cargo.toml
+/// ```
+/// Returns `None` when there is no front matter or no `when:` key.
+pub fn parse_frontmatter_condition(content: &str) -> Option<Condition> {
+    if !has_frontmatter(content) {
+        return None;
+    }
+    let after_opening = &content.trim_start()[3..];
+    let frontmatter = &after_opening[..after_opening.find("\n---")?];
+
+    let mut lines = frontmatter.lines().peekable();
+    while let Some(line) = lines.next() {
+        let Some(value) = line.strip_prefix("when:") else {
+            continue;
+        };
+        let value = value.trim();
+        let mut condition = Condition::default();
+
+        if let Some(inner) = value.strip_prefix('{') {
+            for (key, list) in split_inline_map(inner.trim_end_matches('}')) {
+                set_key(&mut condition, key, parse_list(list));
+            }
+            return Some(condition);
+        }
+
+        // Block form: indented keys, values inline or as "- item" lines
+        let mut current_key: Option<String> = None;
+        while let Some(next) = lines.peek() {
+            if !next.starts_with(' ') && !next.starts_with('\t') {
+                break;
+            }
+            let entry = lines.next().unwrap_or_default().trim();
+            if let Some(item) = entry.strip_prefix("- ") {
+                if let Some(key) = &current_key {
+                    set_key(&mut condition, key, vec![unquote(item)]);
+                }
+            } else if let Some((key, list)) = entry.split_once(':') {
+                let key = key.trim().to_string();
+                if !list.trim().is_empty() {
+                    set_key(&mut condition, &key, parse_list(list));
+                }
+                current_key = Some(key);
+            }
+        }
+        return Some(condition);
+    }
+    None
+}
+
+/// Remove the `when:` key from a file's front matter so it does not end up in the
+/// applied file; front matter holding nothing else is removed as a whole
+///
+/// Returns `None` when there is no `when:` key to remove.
+pub fn strip_frontmatter_condition(content: &str) -> Option<String> {
+    if !has_frontmatter(content) {
+        return None;
+    }
+    let leading = content.len() - content.trim_start().len();
+    let after_opening = &content[leading + 3..];
+    let end = after_opening.find("\n---")?;
+    let rest = &after_opening[end + 4..];
+
+    let mut kept = Vec::new();
+    let mut found = false;
+    let mut in_when = false;
+    for line in after_opening[..end].split('\n') {
+        let line = line.trim_end_matches('\r');
+        if in_when && (line.starts_with(' ') || line.starts_with('\t')) {
+            continue;
+        }
+        in_when = line.starts_with("when:");
+        if in_when {
+            found = true;
+        } else {
+            kept.push(line);
+        }
+    }
+    if !found {
+        return None;
+    }
+
+    let newline = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    if kept.iter().all(|line| line.trim().is_empty()) {
+        let body = rest
+            .strip_prefix("\r\n")
+            .or_else(|| rest.strip_prefix('\n'))
+            .unwrap_or(rest);
+        return Some(format!("{}{}", &content[..leading], body));
+    }
+    Some(format!(
+        "{}---{}{}---{}",
+        &content[..leading],
+        kept.join(newline),
+        newline,
+        rest
+    ))
+}
+
+fn set_key(condition: &mut Condition, key: &str, values: Vec<String>) {
+    match key.trim() {
+        "os" => condition.os.extend(values),
+        "files_exist" => condition.files_exist.extend(values),
+        _ => {}
+    }
+}
+
+/// Split `os: [a, b], files_exist: [c]` into key/value pairs (commas inside brackets are kept)
+fn split_inline_map(inner: &str) -> Vec<(&str, &str)> {
+    let mut pairs = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            ',' if depth == 0 => {
+                pairs.push(&inner[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    pairs.push(&inner[start..]);
+    pairs
+        .into_iter()
+        .filter_map(|pair| pair.split_once(':'))
+        .collect()
+}
+
+/// Parse `[a, "b"]` or a single scalar into a list of values
+fn parse_list(value: &str) -> Vec<String> {
+    let value = value.trim();
+    let inner = value
+        .strip_prefix('[')
+        .and_then(|v| v.strip_suffix(']'))
+        .unwrap_or(value);
+    inner
+        .split(',')
+        .map(unquote)
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+fn unquote(value: &str) -> String {
+    value
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_inline_condition() {
+        let content = "---\ndescription: Rust\nwhen: { os: [macos, \"linux\"], files_exist: [Cargo.toml] }\n---\n# Rust\n";
+        let condition = parse_frontmatter_condition(content).unwrap();
+        assert_eq!(condition.os, vec!["macos", "linux"]);
+        assert_eq!(condition.files_exist, vec!["Cargo.toml"]);
+    }
+
+    #[test]
+    fn test_parse_block_condition() {
+        let content =
+            "---\nwhen:\n  os: [windows]\n  files_exist:\n    - package.json\n    - 'tsconfig.json'\nglobs: \"*.ts\"\n---\nBody";
+        let condition = parse_frontmatter_condition(content).unwrap();
+        assert_eq!(condition.os, vec!["windows"]);
+        assert_eq!(condition.files_exist, vec!["package.json", "tsconfig.json"]);
+    }
+
+    #[test]
+    fn test_parse_without_condition() {
+        assert!(parse_frontmatter_condition("# No front matter\nwhen: {os: [linux]}").is_none());
+        assert!(parse_frontmatter_condition("---\nglobs: \"*.rs\"\n---\nBody").is_none());
+    }
+
+    #[test]
+    fn test_strip_condition() {
+        let content = "---\ndescription: Rust\nwhen:\n  os: [linux]\n  files_exist:\n    - Cargo.toml\nglobs: \"*.rs\"\n---\n# Rust\n";
+        assert_eq!(
+            strip_frontmatter_condition(content).unwrap(),
+            "---\ndescription: Rust\nglobs: \"*.rs\"\n---\n# Rust\n"
+        );
+
+        // Nothing else in the front matter: it goes away
+        let content = "---\r\nwhen: { os: [linux] }\r\n---\r\n# Rust\r\n";
+        assert_eq!(strip_frontmatter_condition(content).unwrap(), "# Rust\r\n");
+
+        assert!(strip_frontmatter_condition("---\nglobs: \"*.rs\"\n---\nBody").is_none());
+        assert!(strip_frontmatter_condition("# Body\nwhen: { os: [linux] }").is_none());
+    }
+
+    #[test]
+    fn test_unmet_reason() {
+        let temp_dir = TempDir::new().unwrap();
+        let condition = Condition {
+            os: vec!["macos".to_string()],
+            files_exist: vec!["Cargo.toml".to_string()],
+        };
+
+        assert_eq!(
+            condition.unmet_reason_for_os("linux", temp_dir.path()),
+            Some("os is linux (requires macos)".to_string())
+        );
+        assert_eq!(
+            condition.unmet_reason_for_os("macos", temp_dir.path()),
+            Some("Cargo.toml not found".to_string())
+        );
+
+        fs::write(temp_dir.path().join("Cargo.toml"), "").unwrap();
+        assert_eq!(
+            condition.unmet_reason_for_os("MacOS", temp_dir.path()),
+            None
+        );
+        assert_eq!(Condition::default().unmet_reason(temp_dir.path()), None);
+    }
+}
@@ -1,11 +1,24 @@
+use super::condition::Condition;
+use crate::adapters::helpers::{glob_error, glob_match, is_glob};
 use crate::error::{AidotError, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
 /// Preset configuration from .aidot-config.toml
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PresetConfig {
+    /// Extra globs skipped in every section, on top of README.md, .DS_Store etc.
+    /// (top-level `ignore = [...]`, before the first table)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+
+    /// Sections left empty on purpose, not warned about when their directory holds
+    /// no files (top-level `allow_empty = ["mcp"]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow_empty: Vec<String>,
+
     pub metadata: Metadata,
 
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -21,7 +34,7 @@ pub struct PresetConfig {
     pub mcp: Option<DirectorySection>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub hooks: Option<DirectorySection>,
+    pub hooks: Option<HooksSection>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub agents: Option<DirectorySection>,
@@ -34,6 +47,18 @@ pub struct PresetConfig {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub root: Option<DirectorySection>,
+
+    /// Per-tool target path overrides ([tools.claude], [tools.cursor], [tools.copilot], [tools.amazonq])
+    #[serde(default, skip_serializing_if = "ToolsSection::is_empty")]
+    pub tools: ToolsSection,
+
+    /// Options controlling how files are written ([apply])
+    #[serde(default, skip_serializing_if = "ApplySection::is_default")]
+    pub apply: ApplySection,
+
+    /// Conditions keyed by preset path glob ([when."rules/rust-*.md"])
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub when: BTreeMap<String, Condition>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +67,32 @@ pub struct Metadata {
     pub version: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Maintainers (e.g., "Jane Doe <jane@example.com>")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    /// Documentation or project page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    /// Git URL of the preset repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+}
+
+impl Metadata {
+    /// Where the preset comes from and who maintains it, one `label: value` line each
+    pub fn provenance(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if !self.authors.is_empty() {
+            lines.push(format!("authors: {}", self.authors.join(", ")));
+        }
+        if let Some(homepage) = &self.homepage {
+            lines.push(format!("homepage: {}", homepage));
+        }
+        if let Some(repository) = &self.repository {
+            lines.push(format!("repository: {}", repository));
+        }
+        lines
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,11 +101,209 @@ pub struct RulesSection {
     pub files: Vec<String>,
     #[serde(default)]
     pub directory: Option<String>,
+    #[serde(flatten)]
+    pub filter: FileFilter,
+    /// Files read first, in this order (paths relative to the section directory)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub order: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DirectorySection {
     pub directory: String,
+    #[serde(flatten)]
+    pub filter: FileFilter,
+    /// Files read first, in this order (paths relative to the section directory)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub order: Vec<String>,
+}
+
+/// `include` / `exclude` globs narrowing the files read from a section
+///
+/// Globs match paths relative to the section directory (e.g., `draft-*.md`,
+/// `backend/**`); without a `/` they match the file name at any depth.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileFilter {
+    /// Only read files matching one of these globs (all files when empty)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// Skip files matching one of these globs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    /// File extensions read in addition to the section's defaults (e.g., `["txt"]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<String>,
+}
+
+impl FileFilter {
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Whether a path (relative to the section directory) passes the filter
+    pub fn matches(&self, path: &str) -> bool {
+        let included =
+            self.include.is_empty() || self.include.iter().any(|pattern| glob_match(pattern, path));
+        included && !self.exclude.iter().any(|pattern| glob_match(pattern, path))
+    }
+}
+
+/// [hooks] section
+///
+/// `directory` holds tool hook definitions (hooks/*.json) merged into each tool.
+/// `[hooks.pre_apply]` / `[hooks.post_apply]` are commands aidot itself runs in the
+/// project around apply; they only run with --run-hooks or for trusted repositories.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HooksSection {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub directory: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_apply: Option<ApplyHook>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_apply: Option<ApplyHook>,
+    #[serde(flatten)]
+    pub filter: FileFilter,
+    /// Files read first, in this order (paths relative to the section directory)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub order: Vec<String>,
+    /// How hooks for the same event from several files are combined (default: "concat")
+    #[serde(default, skip_serializing_if = "MergeStrategy::is_default")]
+    pub merge: MergeStrategy,
+}
+
+/// How definitions are combined: hooks for the same event across preset files,
+/// and a section's files across composed presets (`pull --compose`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeStrategy {
+    /// Append later definitions to earlier ones
+    #[default]
+    Concat,
+    /// The last definition replaces earlier ones
+    Replace,
+}
+
+impl MergeStrategy {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl ToolsSection {
+    /// Apply the overrides of a later preset on top of these (per tool, later wins)
+    pub fn compose(&mut self, later: ToolsSection) {
+        let pairs = [
+            (&mut self.claude, later.claude),
+            (&mut self.cursor, later.cursor),
+            (&mut self.copilot, later.copilot),
+            (&mut self.amazonq, later.amazonq),
+        ];
+        for (paths, later_paths) in pairs {
+            if later_paths.is_some() {
+                *paths = later_paths;
+            }
+        }
+    }
+}
+
+impl HooksSection {
+    /// Whether hook definition files (hooks/) are part of the preset
+    pub fn has_directory(&self) -> bool {
+        self.directory.is_some()
+    }
+}
+
+/// Shell command run around apply (e.g., `command = "npm install"`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplyHook {
+    pub command: String,
+}
+
+/// Per-tool target path overrides
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolsSection {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claude: Option<ToolPaths>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<ToolPaths>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub copilot: Option<ToolPaths>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amazonq: Option<ToolPaths>,
+}
+
+impl ToolsSection {
+    pub fn is_empty(&self) -> bool {
+        self.claude.is_none()
+            && self.cursor.is_none()
+            && self.copilot.is_none()
+            && self.amazonq.is_none()
+    }
+}
+
+/// Target path overrides for a single tool, relative to the project root
+/// (e.g., `rules_dir = ".claude/myrules/"`). Unset fields use the adapter defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolPaths {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rules_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commands_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agents_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skills_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mcp_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hooks_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings_file: Option<String>,
+}
+
+/// Options controlling how preset files are written
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApplySection {
+    /// Line ending policy for written files (default: "lf")
+    #[serde(default)]
+    pub line_endings: LineEnding,
+    /// Format normalization before writing (default: "auto")
+    #[serde(default)]
+    pub normalize: Normalize,
+}
+
+/// Format normalization applied to files before they are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Normalize {
+    /// JSON files generated by merging (mcp, hooks, settings) end with a newline
+    #[default]
+    Auto,
+    /// Also Markdown preset files: front matter at the very start, one trailing newline
+    All,
+    /// Write everything as it is produced (generated JSON has no trailing newline)
+    None,
+}
+
+impl ApplySection {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Line ending policy applied to preset file contents
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    /// Convert CRLF to LF
+    #[default]
+    Lf,
+    /// Convert LF to CRLF
+    Crlf,
+    /// Keep line endings as they are in the preset
+    Preserve,
 }
 
 impl PresetConfig {
@@ -71,8 +320,73 @@ impl PresetConfig {
 
         let content = fs::read_to_string(&config_file)?;
         let config: PresetConfig = toml::from_str(&content)?;
+        if let Some(error) = config.globs().into_iter().find_map(glob_error) {
+            return Err(AidotError::InvalidPreset(format!(
+                "{} in {}",
+                error,
+                config_file.display()
+            )));
+        }
         Ok(config)
     }
+
+    /// Every glob the config declares (ignore, include/exclude, globbed rules files, when keys)
+    fn globs(&self) -> Vec<&str> {
+        let filters = [
+            self.rules.as_ref().map(|s| &s.filter),
+            self.memory.as_ref().map(|s| &s.filter),
+            self.commands.as_ref().map(|s| &s.filter),
+            self.mcp.as_ref().map(|s| &s.filter),
+            self.hooks.as_ref().map(|s| &s.filter),
+            self.agents.as_ref().map(|s| &s.filter),
+            self.skills.as_ref().map(|s| &s.filter),
+            self.settings.as_ref().map(|s| &s.filter),
+            self.root.as_ref().map(|s| &s.filter),
+        ];
+        let rules_files = self.rules.iter().flat_map(|rules| &rules.files);
+        self.ignore
+            .iter()
+            .chain(
+                filters
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|f| f.include.iter().chain(&f.exclude)),
+            )
+            .chain(rules_files.filter(|entry| is_glob(entry)))
+            .chain(self.when.keys())
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Directory a section is read from, relative to the preset root
+    ///
+    /// `[rules] directory = "guidelines/"` reads rules from guidelines/ (returned as
+    /// "guidelines"). None when the section is not declared, or for [hooks] without a
+    /// `directory`. [rules] without a `directory` uses rules/.
+    pub fn section_dir(&self, section: &str) -> Option<String> {
+        let directory = match section {
+            "rules" => self
+                .rules
+                .as_ref()
+                .map(|rules| rules.directory.as_deref().unwrap_or("rules")),
+            "hooks" => self.hooks.as_ref().and_then(|h| h.directory.as_deref()),
+            "memory" => self.memory.as_ref().map(|s| s.directory.as_str()),
+            "commands" => self.commands.as_ref().map(|s| s.directory.as_str()),
+            "mcp" => self.mcp.as_ref().map(|s| s.directory.as_str()),
+            "agents" => self.agents.as_ref().map(|s| s.directory.as_str()),
+            "skills" => self.skills.as_ref().map(|s| s.directory.as_str()),
+            "settings" => self.settings.as_ref().map(|s| s.directory.as_str()),
+            "root" => self.root.as_ref().map(|s| s.directory.as_str()),
+            _ => None,
+        }?;
+        let directory = directory.replace('\\', "/");
+        Some(
+            directory
+                .trim_start_matches("./")
+                .trim_end_matches('/')
+                .to_string(),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -99,6 +413,120 @@ directory = "rules/"
         assert!(loaded.rules.is_some());
     }
 
+    #[test]
+    fn test_preset_config_rejects_invalid_globs() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_content = r#"
+[metadata]
+name = "test-preset"
+version = "1.0.0"
+
+[rules]
+directory = "rules/"
+exclude = ["draft-[ab.md"]
+"#;
+        fs::write(temp_dir.path().join(".aidot-config.toml"), config_content).unwrap();
+
+        let err = PresetConfig::load(temp_dir.path()).unwrap_err();
+        assert!(
+            err.to_string().contains("invalid glob 'draft-[ab.md'"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_preset_config_tool_path_overrides() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_content = r#"
+[metadata]
+name = "test-preset"
+version = "1.0.0"
+
+[tools.claude]
+rules_dir = ".claude/myrules/"
+memory_file = "CLAUDE.md"
+"#;
+        fs::write(temp_dir.path().join(".aidot-config.toml"), config_content).unwrap();
+
+        let loaded = PresetConfig::load(temp_dir.path()).unwrap();
+        let claude = loaded.tools.claude.unwrap();
+        assert_eq!(claude.rules_dir.as_deref(), Some(".claude/myrules/"));
+        assert_eq!(claude.memory_file.as_deref(), Some("CLAUDE.md"));
+        assert!(claude.commands_dir.is_none());
+        assert!(loaded.tools.cursor.is_none());
+    }
+
+    #[test]
+    fn test_preset_config_line_endings() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = "[metadata]\nname = \"test-preset\"\nversion = \"1.0.0\"\n";
+        fs::write(temp_dir.path().join(".aidot-config.toml"), base).unwrap();
+        let loaded = PresetConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(loaded.apply.line_endings, LineEnding::Lf);
+
+        let config_content = format!("{}\n[apply]\nline_endings = \"crlf\"\n", base);
+        fs::write(temp_dir.path().join(".aidot-config.toml"), config_content).unwrap();
+        let loaded = PresetConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(loaded.apply.line_endings, LineEnding::Crlf);
+        assert_eq!(loaded.apply.normalize, Normalize::Auto);
+
+        let config_content = format!("{}\n[apply]\nnormalize = \"all\"\n", base);
+        fs::write(temp_dir.path().join(".aidot-config.toml"), config_content).unwrap();
+        let loaded = PresetConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(loaded.apply.normalize, Normalize::All);
+    }
+
+    #[test]
+    fn test_hooks_section_with_apply_hooks() {
+        let content = r#"
+[metadata]
+name = "test-preset"
+version = "1.0.0"
+
+[hooks.post_apply]
+command = "npm install"
+"#;
+        let config: PresetConfig = toml::from_str(content).unwrap();
+        let hooks = config.hooks.unwrap();
+        assert!(!hooks.has_directory());
+        assert_eq!(hooks.merge, MergeStrategy::Concat);
+        assert!(hooks.pre_apply.is_none());
+        assert_eq!(hooks.post_apply.unwrap().command, "npm install");
+
+        let content = "[metadata]\nname = \"p\"\nversion = \"1\"\n\n[hooks]\ndirectory = \"hooks/\"\nmerge = \"replace\"\n";
+        let config: PresetConfig = toml::from_str(content).unwrap();
+        let hooks = config.hooks.unwrap();
+        assert!(hooks.has_directory());
+        assert_eq!(hooks.merge, MergeStrategy::Replace);
+    }
+
+    #[test]
+    fn test_section_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let write = |body: &str| {
+            fs::write(
+                temp_dir.path().join(".aidot-config.toml"),
+                format!("[metadata]\nname = \"t\"\nversion = \"1.0.0\"\n\n{}", body),
+            )
+            .unwrap();
+        };
+
+        write("[rules]\ndirectory = \"./guidelines/\"\n\n[memory]\ndirectory = \"docs\\\\context\"\n\n[hooks]\npost_apply = { command = \"true\" }\n");
+        let config = PresetConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.section_dir("rules").as_deref(), Some("guidelines"));
+        assert_eq!(
+            config.section_dir("memory").as_deref(),
+            Some("docs/context")
+        );
+        assert_eq!(config.section_dir("hooks"), None);
+        assert_eq!(config.section_dir("commands"), None);
+
+        write("[rules]\nfiles = [\"AGENTS.md\"]\n");
+        let config = PresetConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.section_dir("rules").as_deref(), Some("rules"));
+    }
+
     #[test]
     fn test_preset_config_load_missing_file() {
         let temp_dir = TempDir::new().unwrap();
@@ -111,6 +539,8 @@ directory = "rules/"
         let rules = RulesSection {
             files: vec!["rules/test.md".to_string()],
             directory: Some("rules/".to_string()),
+            filter: FileFilter::default(),
+            order: Vec::new(),
         };
 
         let toml = toml::to_string(&rules).unwrap();
@@ -124,12 +554,28 @@ directory = "rules/"
     fn test_directory_section() {
         let section = DirectorySection {
             directory: "commands/".to_string(),
+            filter: FileFilter::default(),
+            order: Vec::new(),
         };
 
         let toml = toml::to_string(&section).unwrap();
         let deserialized: DirectorySection = toml::from_str(&toml).unwrap();
 
         assert_eq!(deserialized.directory, "commands/");
+        assert!(deserialized.filter.is_empty());
+    }
+
+    #[test]
+    fn test_directory_section_filter() {
+        let section: DirectorySection = toml::from_str(
+            "directory = \"rules/\"\ninclude = [\"*.md\"]\nexclude = [\"draft-*.md\"]\n",
+        )
+        .unwrap();
+        assert!(section.filter.matches("style.md"));
+        assert!(section.filter.matches("backend/api.md"));
+        assert!(!section.filter.matches("draft-ideas.md"));
+        assert!(!section.filter.matches("notes.txt"));
+        assert!(FileFilter::default().matches("anything"));
     }
 
     #[test]
@@ -138,6 +584,9 @@ directory = "rules/"
             name: "test".to_string(),
             version: "2.0.0".to_string(),
             description: Some("Test description".to_string()),
+            authors: vec!["Jane Doe <jane@example.com>".to_string()],
+            homepage: None,
+            repository: Some("https://github.com/team/preset.git".to_string()),
         };
 
         let toml = toml::to_string(&metadata).unwrap();
@@ -149,5 +598,27 @@ directory = "rules/"
             deserialized.description,
             Some("Test description".to_string())
         );
+        assert_eq!(deserialized.authors, metadata.authors);
+        assert_eq!(deserialized.repository, metadata.repository);
+        assert!(!toml.contains("homepage"));
+        assert_eq!(
+            deserialized.provenance(),
+            vec![
+                "authors: Jane Doe <jane@example.com>",
+                "repository: https://github.com/team/preset.git"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_metadata_without_provenance() {
+        let metadata: Metadata = toml::from_str("name = \"old\"\nversion = \"1.0.0\"\n").unwrap();
+        assert!(metadata.authors.is_empty());
+        assert!(metadata.homepage.is_none());
+        assert!(metadata.provenance().is_empty());
+        assert_eq!(
+            toml::to_string(&metadata).unwrap(),
+            "name = \"old\"\nversion = \"1.0.0\"\n"
+        );
     }
 }
@@ -0,0 +1,615 @@
+use crate::adapters::claude_code::claude_hook_problems;
+use crate::adapters::helpers::{content_matches, has_frontmatter};
+use crate::adapters::traits::{PresetFile, PresetFiles, SECTIONS};
+use crate::error::Result;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Prefixes added by `aidot init --from-existing` to files extracted from other tools
+const EXTRACTED_PREFIXES: &[&str] = &["cursor-", "copilot-", "amazonq-"];
+
+/// How serious a lint issue is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Breaks apply or behaves differently per platform
+    Error,
+    /// Likely a mistake
+    Warning,
+    /// Cosmetic
+    Info,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+}
+
+/// A single lint finding for a preset file
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintIssue {
+    pub severity: Severity,
+    /// Path relative to the preset root (e.g., "rules/style.md")
+    pub path: String,
+    pub message: String,
+    /// Whether `aidot lint --fix` can fix it
+    pub fixable: bool,
+}
+
+impl LintIssue {
+    fn new(severity: Severity, path: &str, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            path: path.to_string(),
+            message: message.into(),
+            fixable: false,
+        }
+    }
+}
+
+/// Lint the Markdown files of the rules and commands sections, and the hooks JSON files
+pub fn lint_preset_files(preset_files: &PresetFiles) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    for files in [&preset_files.rules, &preset_files.commands] {
+        let markdown: Vec<&PresetFile> = files
+            .iter()
+            .filter(|f| f.relative_path.ends_with(".md"))
+            .collect();
+        for file in &markdown {
+            lint_file(file, &mut issues);
+        }
+        lint_duplicate_titles(&markdown, &mut issues);
+        lint_case_collisions(files, &mut issues);
+        lint_extracted_duplicates(&markdown, &mut issues);
+    }
+    lint_links(preset_files, &mut issues);
+    for file in &preset_files.hooks {
+        lint_hooks_file(file, &mut issues);
+    }
+    issues.sort_by(|a, b| a.severity.cmp(&b.severity).then(a.path.cmp(&b.path)));
+    issues
+}
+
+/// Hooks files must be valid JSON; files using Claude's event names must follow its format
+fn lint_hooks_file(file: &PresetFile, issues: &mut Vec<LintIssue>) {
+    let path = &file.relative_path;
+    if !path.ends_with(".json") {
+        return;
+    }
+    match serde_json::from_str::<serde_json::Value>(&file.content) {
+        Ok(config) => {
+            for problem in claude_hook_problems(&config) {
+                issues.push(LintIssue::new(Severity::Warning, path, problem));
+            }
+        }
+        Err(e) => issues.push(LintIssue::new(
+            Severity::Error,
+            path,
+            format!("invalid JSON: {}", e),
+        )),
+    }
+}
+
+/// Per-file checks: empty file, broken frontmatter, duplicate headings, trailing whitespace
+fn lint_file(file: &PresetFile, issues: &mut Vec<LintIssue>) {
+    let path = &file.relative_path;
+
+    if file.content.trim().is_empty() {
+        issues.push(LintIssue::new(Severity::Warning, path, "empty file"));
+        return;
+    }
+
+    if file.content.trim_start().starts_with("---") && !has_frontmatter(&file.content) {
+        issues.push(LintIssue::new(
+            Severity::Error,
+            path,
+            "broken frontmatter (opening '---' without a closing '---')",
+        ));
+    }
+
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    for (i, heading) in headings(&file.content) {
+        if let Some(first) = seen.insert(heading, i) {
+            issues.push(LintIssue::new(
+                Severity::Warning,
+                path,
+                format!(
+                    "duplicate heading '{}' (lines {} and {})",
+                    heading,
+                    first + 1,
+                    i + 1
+                ),
+            ));
+        }
+    }
+
+    let trailing = file
+        .content
+        .lines()
+        .filter(|line| line.trim_end() != *line)
+        .count();
+    if trailing > 0 {
+        issues.push(LintIssue {
+            fixable: true,
+            ..LintIssue::new(
+                Severity::Info,
+                path,
+                format!("trailing whitespace on {} line(s)", trailing),
+            )
+        });
+    }
+}
+
+/// Files in the same section sharing the same title (first heading)
+fn lint_duplicate_titles(files: &[&PresetFile], issues: &mut Vec<LintIssue>) {
+    let mut titles: HashMap<&str, &str> = HashMap::new();
+    for file in files {
+        let Some((_, title)) = headings(&file.content).next() else {
+            continue;
+        };
+        if let Some(other) = titles.insert(title, &file.relative_path) {
+            issues.push(LintIssue::new(
+                Severity::Warning,
+                &file.relative_path,
+                format!("same title '{}' as {}", title, other),
+            ));
+        }
+    }
+}
+
+/// Paths differing only in case collide on case-insensitive file systems (Windows, macOS)
+fn lint_case_collisions(files: &[PresetFile], issues: &mut Vec<LintIssue>) {
+    let mut seen: HashMap<String, &str> = HashMap::new();
+    for file in files {
+        if let Some(other) = seen.insert(file.relative_path.to_lowercase(), &file.relative_path) {
+            issues.push(LintIssue::new(
+                Severity::Error,
+                &file.relative_path,
+                format!("differs from {} only in case", other),
+            ));
+        }
+    }
+}
+
+/// `cursor-x.md` / `copilot-x.md` extracted by init with the same content as `x.md`
+fn lint_extracted_duplicates(files: &[&PresetFile], issues: &mut Vec<LintIssue>) {
+    for file in files {
+        let (dir, name) = match file.relative_path.rsplit_once('/') {
+            Some((dir, name)) => (format!("{}/", dir), name),
+            None => (String::new(), file.relative_path.as_str()),
+        };
+        let Some(original_name) = EXTRACTED_PREFIXES
+            .iter()
+            .find_map(|prefix| name.strip_prefix(prefix))
+        else {
+            continue;
+        };
+        let original_path = format!("{}{}", dir, original_name);
+        if let Some(original) = files.iter().find(|f| f.relative_path == original_path) {
+            if content_matches(&original.content, &file.content) {
+                issues.push(LintIssue::new(
+                    Severity::Warning,
+                    &file.relative_path,
+                    format!("same content as {} (extracted duplicate)", original_path),
+                ));
+            }
+        }
+    }
+}
+
+/// Relative links in rules and commands that point at no file of the preset
+///
+/// Links are resolved against the linking file, so `./other.md` in `rules/a.md` must
+/// be `rules/other.md`; `/x.md` starts at the preset root. Anchors (`#usage`) and
+/// URLs are not checked here (see `check_urls`).
+fn lint_links(preset_files: &PresetFiles, issues: &mut Vec<LintIssue>) {
+    // Root files are relative to root/, not to the preset root
+    let known: HashSet<&str> = SECTIONS
+        .iter()
+        .filter(|section| **section != "root")
+        .flat_map(|section| preset_files.section(section))
+        .map(|file| file.relative_path.as_str())
+        .collect();
+    let exists = |path: &str| {
+        let dir = format!("{}/", path);
+        known.contains(path) || known.iter().any(|known| known.starts_with(&dir))
+    };
+
+    for file in preset_files.rules.iter().chain(&preset_files.commands) {
+        if !file.relative_path.ends_with(".md") {
+            continue;
+        }
+        for (i, target) in markdown_links(&file.content) {
+            if has_scheme(target) || target.starts_with('#') {
+                continue;
+            }
+            let problem = match resolve_link(&file.relative_path, target) {
+                None => "points outside the preset",
+                Some(path) if path.is_empty() || exists(&path) => continue,
+                Some(_) => "no such file in the preset",
+            };
+            issues.push(LintIssue::new(
+                Severity::Warning,
+                &file.relative_path,
+                format!("broken link '{}' (line {}): {}", target, i + 1, problem),
+            ));
+        }
+    }
+}
+
+/// Check the http(s) links of rules and commands with `check`, one request per URL
+///
+/// Used by `aidot lint --check-urls`; a URL failing the check is reported for every
+/// file linking to it.
+pub fn check_urls(
+    preset_files: &PresetFiles,
+    check: impl Fn(&str) -> Result<()>,
+) -> Vec<LintIssue> {
+    let mut results: HashMap<&str, Option<String>> = HashMap::new();
+    let mut issues = Vec::new();
+    for file in preset_files.rules.iter().chain(&preset_files.commands) {
+        if !file.relative_path.ends_with(".md") {
+            continue;
+        }
+        for (i, url) in markdown_links(&file.content) {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                continue;
+            }
+            let failure = results
+                .entry(url)
+                .or_insert_with(|| check(url).err().map(|e| e.to_string()));
+            if let Some(error) = failure {
+                issues.push(LintIssue::new(
+                    Severity::Warning,
+                    &file.relative_path,
+                    format!("unreachable link {} (line {}): {}", url, i + 1, error),
+                ));
+            }
+        }
+    }
+    issues
+}
+
+/// Targets of inline links (`[x](target)`, `![x](target)`) and reference definitions
+/// (`[x]: target`) outside code, as (line index, target)
+fn markdown_links(content: &str) -> Vec<(usize, &str)> {
+    let mut links = Vec::new();
+    let mut in_code = false;
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        if let Some(target) = trimmed
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once("]:"))
+            .map(|(_, target)| target)
+        {
+            links.extend(link_target(target).map(|target| (i, target)));
+            continue;
+        }
+        // Backtick spans hold code, not links
+        for (j, segment) in line.split('`').enumerate() {
+            if j % 2 == 1 {
+                continue;
+            }
+            let mut rest = segment;
+            while let Some(start) = rest.find("](") {
+                rest = &rest[start + 2..];
+                let mut depth = 0;
+                let end = rest
+                    .char_indices()
+                    .find(|(_, c)| match c {
+                        '(' => {
+                            depth += 1;
+                            false
+                        }
+                        ')' if depth == 0 => true,
+                        ')' => {
+                            depth -= 1;
+                            false
+                        }
+                        _ => false,
+                    })
+                    .map_or(rest.len(), |(end, _)| end);
+                links.extend(link_target(&rest[..end]).map(|target| (i, target)));
+                rest = &rest[end..];
+            }
+        }
+    }
+    links
+}
+
+/// The destination of a link without its title (`path "title"`, `<path with spaces>`)
+fn link_target(destination: &str) -> Option<&str> {
+    let destination = destination.trim();
+    let target = match destination.strip_prefix('<') {
+        Some(rest) => rest.split_once('>').map_or(rest, |(target, _)| target),
+        None => destination.split_whitespace().next().unwrap_or(""),
+    };
+    (!target.is_empty()).then_some(target)
+}
+
+/// Whether a link target starts with a URI scheme (`https:`, `mailto:`, ...)
+fn has_scheme(target: &str) -> bool {
+    target.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.len() > 1
+            && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+/// Preset-relative path a link of `from` points to, without `#anchor` and `?query`
+///
+/// `None` when it climbs above the preset root.
+fn resolve_link(from: &str, target: &str) -> Option<String> {
+    let path = target
+        .split(['#', '?'])
+        .next()
+        .unwrap_or("")
+        .replace("%20", " ");
+    let mut parts: Vec<&str> = Vec::new();
+    if !path.starts_with('/') {
+        parts.extend(from.split('/'));
+        // The directory of the linking file
+        parts.pop();
+    }
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            part => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// Markdown headings outside fenced code blocks as (line index, heading text)
+fn headings(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut in_code = false;
+    content.lines().enumerate().filter_map(move |(i, line)| {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            return None;
+        }
+        if in_code || !trimmed.starts_with('#') {
+            return None;
+        }
+        let text = trimmed.trim_start_matches('#');
+        // "#tag" is not a heading
+        if !text.starts_with(' ') {
+            return None;
+        }
+        Some((i, trimmed))
+    })
+}
+
+/// Apply automatic fixes (trailing whitespace) to the preset files on disk
+///
+/// Returns the paths of the files that were rewritten.
+pub fn fix_issues(preset_dir: &Path, issues: &[LintIssue]) -> Result<Vec<String>> {
+    let mut fixed = Vec::new();
+    for issue in issues.iter().filter(|i| i.fixable) {
+        let path = preset_dir.join(&issue.path);
+        let content = fs::read_to_string(&path)?;
+        let trimmed = trim_trailing_whitespace(&content);
+        if trimmed != content {
+            fs::write(&path, trimmed)?;
+            fixed.push(issue.path.clone());
+        }
+    }
+    Ok(fixed)
+}
+
+/// Remove trailing whitespace from each line, keeping line endings
+fn trim_trailing_whitespace(content: &str) -> String {
+    content
+        .split_inclusive('\n')
+        .map(|line| {
+            let (body, ending) = match line.strip_suffix("\r\n") {
+                Some(body) => (body, "\r\n"),
+                None => match line.strip_suffix('\n') {
+                    Some(body) => (body, "\n"),
+                    None => (line, ""),
+                },
+            };
+            format!("{}{}", body.trim_end(), ending)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn file(path: &str, content: &str) -> PresetFile {
+        PresetFile {
+            relative_path: path.to_string(),
+            content: content.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn messages(issues: &[LintIssue]) -> Vec<String> {
+        issues
+            .iter()
+            .map(|i| format!("{} {}: {}", i.severity.as_str(), i.path, i.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_file_checks() {
+        let preset_files = PresetFiles {
+            rules: vec![
+                file("rules/empty.md", "  \n"),
+                file("rules/broken.md", "---\nglobs: \"*.rs\"\n# Rule"),
+                file(
+                    "rules/dup.md",
+                    "# Dup\n\n## Usage\n\n```\n## Usage\n```\n\n## Usage  \n",
+                ),
+            ],
+            ..Default::default()
+        };
+
+        let issues = lint_preset_files(&preset_files);
+        assert_eq!(
+            messages(&issues),
+            vec![
+                "error rules/broken.md: broken frontmatter (opening '---' without a closing '---')",
+                "warning rules/dup.md: duplicate heading '## Usage' (lines 3 and 9)",
+                "warning rules/empty.md: empty file",
+                "info rules/dup.md: trailing whitespace on 1 line(s)",
+            ]
+        );
+        assert!(issues[3].fixable);
+    }
+
+    #[test]
+    fn test_lint_links() {
+        let preset_files = PresetFiles {
+            rules: vec![
+                file(
+                    "rules/style.md",
+                    "# Style\n\nSee [naming](./naming.md#case), [build](../commands/build.md \"Build\")\n\
+                     and [docs](https://example.com/x) or [top](#style).\n\
+                     `[code](missing.md)`\n\n```\n[fenced](missing.md)\n```\n\
+                     [gone](./gone.md) [up](../../outside.md)\n\n[ref]: /skills/review\n",
+                ),
+                file("rules/naming.md", "# Naming"),
+            ],
+            commands: vec![file("commands/build.md", "# Build")],
+            skills: vec![file("skills/review/SKILL.md", "# Review")],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            messages(&lint_preset_files(&preset_files)),
+            vec![
+                "warning rules/style.md: broken link './gone.md' (line 10): no such file in the preset",
+                "warning rules/style.md: broken link '../../outside.md' (line 10): points outside the preset",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_urls() {
+        let preset_files = PresetFiles {
+            rules: vec![
+                file(
+                    "rules/a.md",
+                    "[ok](https://example.com/ok) [bad](https://example.com/bad)",
+                ),
+                file(
+                    "rules/b.md",
+                    "[bad again](https://example.com/bad) [mail](mailto:x@y.z)",
+                ),
+            ],
+            ..Default::default()
+        };
+        let checked = std::cell::RefCell::new(Vec::new());
+        let issues = check_urls(&preset_files, |url| {
+            checked.borrow_mut().push(url.to_string());
+            if url.ends_with("bad") {
+                Err(crate::error::AidotError::Network(
+                    "404 Not Found".to_string(),
+                ))
+            } else {
+                Ok(())
+            }
+        });
+        // Each URL is requested once
+        assert_eq!(checked.borrow().len(), 2);
+        assert_eq!(issues.len(), 2);
+        assert!(issues[1]
+            .message
+            .starts_with("unreachable link https://example.com/bad (line 1)"));
+        assert_eq!(issues[1].path, "rules/b.md");
+    }
+
+    #[test]
+    fn test_lint_cross_file_checks() {
+        let preset_files = PresetFiles {
+            rules: vec![
+                file("rules/style.md", "# Style\n\nUse tabs."),
+                file("rules/cursor-style.md", "# Style\r\n\r\nUse tabs.\r\n"),
+                file("rules/Style.md", "# Other"),
+            ],
+            commands: vec![file("commands/build.md", "# Style")],
+            ..Default::default()
+        };
+
+        let issues = lint_preset_files(&preset_files);
+        assert_eq!(
+            messages(&issues),
+            vec![
+                "error rules/Style.md: differs from rules/style.md only in case",
+                "warning rules/cursor-style.md: same title '# Style' as rules/style.md",
+                "warning rules/cursor-style.md: same content as rules/style.md (extracted duplicate)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_hooks_files() {
+        let preset_files = PresetFiles {
+            hooks: vec![
+                file("hooks/broken.json", "{ \"PreToolUse\": "),
+                file("hooks/legacy.json", "{ \"command\": \"fmt\" }"),
+                file(
+                    "hooks/typo.json",
+                    "{ \"PreToolUse\": [], \"PostToolUSE\": [{ \"hooks\": [] }] }",
+                ),
+                file(
+                    "hooks/entries.json",
+                    "{ \"hooks\": { \"Stop\": [{ \"matcher\": \"\" }, { \"hooks\": [{ \"type\": \"command\" }] }] } }",
+                ),
+            ],
+            ..Default::default()
+        };
+
+        let issues = lint_preset_files(&preset_files);
+        assert_eq!(
+            messages(&issues),
+            vec![
+                "error hooks/broken.json: invalid JSON: EOF while parsing a value at line 1 column 16",
+                "warning hooks/entries.json: 'Stop'[0] has no \"hooks\" array",
+                "warning hooks/entries.json: 'Stop'[1] has a command hook without \"command\"",
+                "warning hooks/typo.json: unknown hook event 'PostToolUSE'",
+                "warning hooks/typo.json: not in Claude's event format, so it is stored under its file name",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fix_trailing_whitespace() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("rules")).unwrap();
+        let path = temp_dir.path().join("rules/a.md");
+        fs::write(&path, "# A  \r\nline\t\r\nend ").unwrap();
+
+        let preset_files = PresetFiles {
+            rules: vec![file("rules/a.md", &fs::read_to_string(&path).unwrap())],
+            ..Default::default()
+        };
+        let issues = lint_preset_files(&preset_files);
+        let fixed = fix_issues(temp_dir.path(), &issues).unwrap();
+
+        assert_eq!(fixed, vec!["rules/a.md".to_string()]);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# A\r\nline\r\nend");
+    }
+}
@@ -1,4 +1,7 @@
+pub mod condition;
 pub mod config;
+pub mod lint;
+pub mod parse_cache;
 pub mod parser;
 
 pub use config::PresetConfig;
@@ -0,0 +1,152 @@
+use crate::adapters::traits::PresetFiles;
+use crate::cache::ARCHIVE_META_DIR;
+use crate::config::Config;
+use crate::error::Result;
+use crate::preset::parser::is_executable;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::UNIX_EPOCH;
+use walkdir::WalkDir;
+
+/// Whether pull/diff reuse parsed presets (off with --no-cache)
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Turn the parse cache on or off for this run (--no-cache)
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Parsed preset files stored in ~/.aidot/parse-cache/{preset-hash}.json
+///
+/// Files are stored before decryption, so secrets never reach the cache in plain text.
+#[derive(Deserialize)]
+struct CachedParse {
+    fingerprint: String,
+    files: PresetFiles,
+    warnings: Vec<String>,
+}
+
+/// `CachedParse` as written, borrowing the fresh parse
+#[derive(Serialize)]
+struct CachedParseRef<'a> {
+    fingerprint: &'a str,
+    files: &'a PresetFiles,
+    warnings: &'a [String],
+}
+
+/// Fingerprint of every file in a preset (path, size, mtime, executable bit), ignoring
+/// .git/ and the bookkeeping of extracted archives
+///
+/// Any added, removed or edited file changes it, which invalidates the cached parse.
+/// The aidot version is included so an upgrade never reads an older format.
+pub fn fingerprint(preset_dir: &Path) -> String {
+    let mut entries: Vec<String> = WalkDir::new(preset_dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git" && e.file_name() != ARCHIVE_META_DIR)
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let metadata = fs::metadata(e.path()).ok()?;
+            let modified = metadata
+                .modified()
+                .ok()?
+                .duration_since(UNIX_EPOCH)
+                .ok()?
+                .as_nanos();
+            let path = e.path().strip_prefix(preset_dir).ok()?;
+            Some(format!(
+                "{}\t{}\t{}\t{}",
+                path.to_string_lossy().replace('\\', "/"),
+                metadata.len(),
+                modified,
+                is_executable(e.path())
+            ))
+        })
+        .collect();
+    entries.sort();
+
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+    for entry in &entries {
+        hasher.update(entry.as_bytes());
+        hasher.update(b"\n");
+    }
+    hex(&hasher.finalize())
+}
+
+/// Cached files and warnings of a preset, if its fingerprint still matches
+pub fn load(preset_dir: &Path, fingerprint: &str) -> Option<(PresetFiles, Vec<String>)> {
+    let content = fs::read_to_string(cache_file(preset_dir).ok()?).ok()?;
+    let cached: CachedParse = serde_json::from_str(&content).ok()?;
+    (cached.fingerprint == fingerprint).then_some((cached.files, cached.warnings))
+}
+
+/// Store a fresh parse (failures only mean the next run parses again)
+pub fn store(preset_dir: &Path, fingerprint: &str, files: &PresetFiles, warnings: &[String]) {
+    let Ok(path) = cache_file(preset_dir) else {
+        return;
+    };
+    let cached = CachedParseRef {
+        fingerprint,
+        files,
+        warnings,
+    };
+    if let (Some(dir), Ok(content)) = (path.parent(), serde_json::to_string(&cached)) {
+        let _ = fs::create_dir_all(dir).and_then(|_| fs::write(&path, content));
+    }
+}
+
+fn cache_file(preset_dir: &Path) -> Result<PathBuf> {
+    let key = preset_dir
+        .canonicalize()
+        .unwrap_or_else(|_| preset_dir.to_path_buf());
+    let digest = Sha256::digest(key.to_string_lossy().as_bytes());
+    Ok(dir()?.join(format!("{}.json", hex(&digest[..8]))))
+}
+
+/// Directory of cached parses (~/.aidot/parse-cache/)
+pub fn dir() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("parse-cache"))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_fingerprint_tracks_preset_files() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("rules")).unwrap();
+        fs::write(temp_dir.path().join("rules/style.md"), "# Style").unwrap();
+        let original = fingerprint(temp_dir.path());
+        assert_eq!(fingerprint(temp_dir.path()), original);
+
+        // Git metadata is not part of the preset
+        fs::create_dir_all(temp_dir.path().join(".git")).unwrap();
+        fs::write(temp_dir.path().join(".git/FETCH_HEAD"), "abc").unwrap();
+        assert_eq!(fingerprint(temp_dir.path()), original);
+        fs::create_dir_all(temp_dir.path().join(ARCHIVE_META_DIR)).unwrap();
+        fs::write(temp_dir.path().join(ARCHIVE_META_DIR).join("sha256"), "abc").unwrap();
+        assert_eq!(fingerprint(temp_dir.path()), original);
+
+        fs::write(temp_dir.path().join("rules/style.md"), "# Style, longer").unwrap();
+        let edited = fingerprint(temp_dir.path());
+        assert_ne!(edited, original);
+
+        fs::write(temp_dir.path().join("rules/extra.md"), "").unwrap();
+        assert_ne!(fingerprint(temp_dir.path()), edited);
+    }
+}
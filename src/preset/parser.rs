@@ -1,6 +1,12 @@
-use crate::adapters::traits::{PresetFile, PresetFiles};
+use crate::adapters::helpers::strip_bom;
+use crate::adapters::helpers::{glob_match, is_glob, resolves_inside, unsafe_path_reason};
+use crate::adapters::traits::{PresetFile, PresetFiles, SECTIONS};
 use crate::error::{AidotError, Result};
+use crate::preset::config::FileFilter;
+use crate::preset::parse_cache;
 use crate::preset::PresetConfig;
+use crate::secret;
+use colored::Colorize;
 use std::fs;
 use std::path::Path;
 use walkdir::WalkDir;
@@ -9,89 +15,616 @@ use walkdir::WalkDir;
 /// These should be managed through dedicated sections (rules/, memory/, etc.)
 const RESERVED_DIRS: &[&str] = &[".claude", ".cursor", ".github", ".vscode"];
 
+/// File extensions each section reads by default
+///
+/// skills/ and root/ hold scripts and assets of any type, so they read every file.
+/// A section's `extensions = [...]` adds more; files listed in `[rules] files` are
+/// always read.
+const SECTION_EXTENSIONS: &[(&str, &[&str])] = &[
+    ("rules", &["md", "mdc"]),
+    ("memory", &["md"]),
+    ("commands", &["md"]),
+    ("agents", &["md"]),
+    ("mcp", &["json"]),
+    ("hooks", &["json"]),
+    ("settings", &["json"]),
+];
+
+/// Files never read from a section directory: directory docs and OS/VCS leftovers
+const IGNORED_FILES: &[&str] = &[
+    "README.md",
+    "README",
+    ".DS_Store",
+    "Thumbs.db",
+    "desktop.ini",
+    ".gitkeep",
+    ".keep",
+];
+/// The leftovers also ignored in root/, where README.md and dotfiles are real content
+const ROOT_IGNORED_FILES: &[&str] = &[".DS_Store", "Thumbs.db", "desktop.ini", ".gitkeep", ".keep"];
+
 /// Parse a preset repository directory
 pub fn parse_preset(preset_dir: &Path) -> Result<(PresetConfig, PresetFiles)> {
-    // Load configuration
-    let config = PresetConfig::load(preset_dir)?;
+    parse_preset_with(preset_dir, false)
+}
+
+/// Parse a preset, reusing the parse cache while its files are unchanged
+///
+/// Used by pull and diff, which often read the same cached clone back to back.
+/// Encrypted files are cached as they are and decrypted on every call.
+pub fn parse_preset_cached(preset_dir: &Path) -> Result<(PresetConfig, PresetFiles)> {
+    parse_preset_with(preset_dir, parse_cache::is_enabled())
+}
+
+/// Like [`parse_preset_cached`], for a configuration the caller already loaded
+pub fn parse_loaded_preset_cached(
+    preset_dir: &Path,
+    config: PresetConfig,
+) -> Result<(PresetConfig, PresetFiles)> {
+    parse_config_with(preset_dir, config, parse_cache::is_enabled())
+}
 
-    // Parse files from each section
+fn parse_preset_with(preset_dir: &Path, use_cache: bool) -> Result<(PresetConfig, PresetFiles)> {
+    parse_config_with(preset_dir, PresetConfig::load(preset_dir)?, use_cache)
+}
+
+fn parse_config_with(
+    preset_dir: &Path,
+    config: PresetConfig,
+    use_cache: bool,
+) -> Result<(PresetConfig, PresetFiles)> {
+    check_section_dirs(preset_dir, &config)?;
+    for problem in section_layout_problems(preset_dir, &config) {
+        eprintln!("{} {}", "Warning:".yellow(), problem);
+    }
+
+    let fingerprint = use_cache.then(|| parse_cache::fingerprint(preset_dir));
+    let cached = fingerprint
+        .as_deref()
+        .and_then(|fingerprint| parse_cache::load(preset_dir, fingerprint));
+    let (mut preset_files, warnings) = match cached {
+        Some(cached) => cached,
+        None => {
+            let mut warnings = Vec::new();
+            let preset_files = read_preset_files(preset_dir, &config, &mut warnings)?;
+            if let Some(fingerprint) = &fingerprint {
+                parse_cache::store(preset_dir, fingerprint, &preset_files, &warnings);
+            }
+            (preset_files, warnings)
+        }
+    };
+    preset_files.preset = config.metadata.name.clone();
+    // Replayed from the cache too, so a cached parse warns like a fresh one
+    // (stderr keeps machine-readable output such as diff --json intact)
+    for warning in &warnings {
+        eprintln!("{} {}", "Warning:".yellow(), warning);
+    }
+
+    // Decrypt *.enc files; undecryptable ones are skipped so the rest still applies
+    if preset_files.has_encrypted() {
+        let key = secret::load_key()?;
+        for (path, reason) in preset_files.decrypt_secrets(key.as_deref()) {
+            eprintln!(
+                "{} skipped encrypted file {} ({})",
+                "Warning:".yellow(),
+                path,
+                reason
+            );
+        }
+    }
+
+    Ok((config, preset_files))
+}
+
+/// Read every section's files (before decryption), collecting warnings
+fn read_preset_files(
+    preset_dir: &Path,
+    config: &PresetConfig,
+    warnings: &mut Vec<String>,
+) -> Result<PresetFiles> {
+    // Parse files from each section, each from its configured directory
     let mut preset_files = PresetFiles::default();
+    let dir = |section: &str| config.section_dir(section).unwrap_or_default();
 
     // Parse rules
-    if config.rules.is_some() {
-        preset_files.rules = parse_directory(preset_dir, "rules")?;
+    if let Some(rules) = &config.rules {
+        let rules_dir = dir("rules");
+        preset_files.rules = if rules.files.is_empty() {
+            parse_section(
+                preset_dir,
+                "rules",
+                &rules_dir,
+                &rules.filter,
+                &rules.order,
+                &config.ignore,
+                warnings,
+            )?
+        } else {
+            let files = parse_listed_files(preset_dir, &rules.files, &rules_dir, &config.ignore)?;
+            apply_filter(
+                files,
+                "rules",
+                &rules_dir,
+                &rules.filter,
+                warnings,
+                |file| file.section_path("rules"),
+            )
+        };
     }
 
     // Parse memory
-    if config.memory.is_some() {
-        preset_files.memory = parse_directory(preset_dir, "memory")?;
+    if let Some(memory) = &config.memory {
+        preset_files.memory = parse_section(
+            preset_dir,
+            "memory",
+            &dir("memory"),
+            &memory.filter,
+            &memory.order,
+            &config.ignore,
+            warnings,
+        )?;
     }
 
     // Parse commands
-    if config.commands.is_some() {
-        preset_files.commands = parse_directory(preset_dir, "commands")?;
+    if let Some(commands) = &config.commands {
+        preset_files.commands = parse_section(
+            preset_dir,
+            "commands",
+            &dir("commands"),
+            &commands.filter,
+            &commands.order,
+            &config.ignore,
+            warnings,
+        )?;
     }
 
     // Parse MCP
-    if config.mcp.is_some() {
-        preset_files.mcp = parse_directory(preset_dir, "mcp")?;
+    if let Some(mcp) = &config.mcp {
+        preset_files.mcp = parse_section(
+            preset_dir,
+            "mcp",
+            &dir("mcp"),
+            &mcp.filter,
+            &mcp.order,
+            &config.ignore,
+            warnings,
+        )?;
     }
 
     // Parse hooks
-    if config.hooks.is_some() {
-        preset_files.hooks = parse_directory(preset_dir, "hooks")?;
+    if let Some(hooks) = config.hooks.as_ref().filter(|h| h.has_directory()) {
+        preset_files.hooks = parse_section(
+            preset_dir,
+            "hooks",
+            &dir("hooks"),
+            &hooks.filter,
+            &hooks.order,
+            &config.ignore,
+            warnings,
+        )?;
+        preset_files.hooks_merge = hooks.merge;
     }
 
     // Parse agents
-    if config.agents.is_some() {
-        preset_files.agents = parse_directory(preset_dir, "agents")?;
+    if let Some(agents) = &config.agents {
+        preset_files.agents = parse_section(
+            preset_dir,
+            "agents",
+            &dir("agents"),
+            &agents.filter,
+            &agents.order,
+            &config.ignore,
+            warnings,
+        )?;
     }
 
     // Parse skills
-    if config.skills.is_some() {
-        preset_files.skills = parse_directory(preset_dir, "skills")?;
+    if let Some(skills) = &config.skills {
+        preset_files.skills = parse_section(
+            preset_dir,
+            "skills",
+            &dir("skills"),
+            &skills.filter,
+            &skills.order,
+            &config.ignore,
+            warnings,
+        )?;
     }
 
     // Parse settings
-    if config.settings.is_some() {
-        preset_files.settings = parse_directory(preset_dir, "settings")?;
+    if let Some(settings) = &config.settings {
+        preset_files.settings = parse_section(
+            preset_dir,
+            "settings",
+            &dir("settings"),
+            &settings.filter,
+            &settings.order,
+            &config.ignore,
+            warnings,
+        )?;
     }
 
     // Parse root files
-    if config.root.is_some() {
-        preset_files.root = parse_root_directory(preset_dir, "root")?;
+    if let Some(root) = &config.root {
+        let root_dir = dir("root");
+        let files = parse_root_directory(preset_dir, &root_dir, &config.ignore)?;
+        preset_files.root =
+            apply_filter(files, "root", &root_dir, &root.filter, warnings, |file| {
+                file.relative_path.clone()
+            });
     }
 
-    Ok((config, preset_files))
+    let empty = empty_sections(preset_dir, config, &preset_files, warnings);
+    if !empty.is_empty() {
+        warnings.push(format!(
+            "declared sections have no files and apply nothing: {} \
+             (list them in `allow_empty` in .aidot-config.toml if this is intended)",
+            empty.join(", ")
+        ));
+    }
+
+    // Paths are joined to the target directory on apply: none may point outside it
+    for file in SECTIONS.iter().flat_map(|s| preset_files.section(s)) {
+        if let Some(reason) = unsafe_path_reason(&file.relative_path) {
+            return Err(AidotError::InvalidPreset(format!(
+                "Unsafe file path '{}' ({}): preset paths must stay inside the project",
+                file.relative_path, reason
+            )));
+        }
+    }
+
+    Ok(preset_files)
+}
+
+/// Refuse section directories that resolve outside the preset
+///
+/// A `directory` must be a relative path without `..` (checked before touching the
+/// file system), and once symlinks are followed it must still lie inside `preset_dir`.
+/// This keeps an untrusted preset from reading files elsewhere on the machine.
+fn check_section_dirs(preset_dir: &Path, config: &PresetConfig) -> Result<()> {
+    for section in SECTIONS {
+        let Some(dir) = config.section_dir(section) else {
+            continue;
+        };
+        let reason = unsafe_path_reason(&dir).or_else(|| {
+            (!resolves_inside(preset_dir, &preset_dir.join(&dir)))
+                .then_some("resolves outside the preset")
+        });
+        if let Some(reason) = reason {
+            return Err(AidotError::InvalidPreset(format!(
+                "[{}] directory '{}' must stay inside the preset ({})",
+                section, dir, reason
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Mismatches between section directories and config sections
+///
+/// A section directory without its config section is ignored by `parse_preset`, and a
+/// declared section without its directory applies nothing. Both are usually mistakes.
+pub fn section_layout_problems(preset_dir: &Path, config: &PresetConfig) -> Vec<String> {
+    let has_hooks_dir = config.hooks.as_ref().is_some_and(|h| h.has_directory());
+    // (section, declared, directory expected when declared)
+    let sections = [
+        (
+            "rules",
+            config.rules.is_some(),
+            // Listed files may live anywhere in the preset
+            config.rules.as_ref().is_some_and(|r| r.files.is_empty()),
+        ),
+        ("memory", config.memory.is_some(), true),
+        ("commands", config.commands.is_some(), true),
+        ("mcp", config.mcp.is_some(), true),
+        ("hooks", has_hooks_dir, true),
+        ("agents", config.agents.is_some(), true),
+        ("skills", config.skills.is_some(), true),
+        ("settings", config.settings.is_some(), true),
+        ("root", config.root.is_some(), true),
+    ];
+
+    let mut problems = Vec::new();
+    for (section, declared, expects_dir) in sections {
+        if declared {
+            let dir = config.section_dir(section).unwrap_or_default();
+            if expects_dir && !preset_dir.join(&dir).is_dir() {
+                problems.push(format!(
+                    "[{}] is declared but the {}/ directory does not exist",
+                    section, dir
+                ));
+            }
+        } else if preset_dir.join(section).is_dir() {
+            if section == "hooks" && config.hooks.is_some() {
+                problems.push(
+                    "hooks/ directory exists but [hooks] has no directory = \"hooks/\", so it is ignored"
+                        .to_string(),
+                );
+            } else {
+                problems.push(format!(
+                    "{0}/ directory exists but the config has no [{0}] section, so it is ignored",
+                    section
+                ));
+            }
+        }
+    }
+    problems
+}
+
+/// Declared sections whose directory exists but yielded no files, as `mcp (mcp/)`
+///
+/// Missing directories and filters matching nothing are warned about already;
+/// sections in `allow_empty` are left out.
+fn empty_sections(
+    preset_dir: &Path,
+    config: &PresetConfig,
+    preset_files: &PresetFiles,
+    warnings: &[String],
+) -> Vec<String> {
+    let listed_rules = config.rules.as_ref().is_some_and(|r| !r.files.is_empty());
+    SECTIONS
+        .iter()
+        .filter(|section| !(**section == "rules" && listed_rules))
+        .filter(|section| !config.allow_empty.iter().any(|allowed| allowed == *section))
+        .filter(|section| preset_files.section(section).is_empty())
+        .filter(|section| {
+            let tag = format!("[{}]", section);
+            !warnings.iter().any(|warning| warning.starts_with(&tag))
+        })
+        .filter_map(|section| {
+            let dir = config.section_dir(section)?;
+            preset_dir
+                .join(&dir)
+                .is_dir()
+                .then(|| format!("{} ({}/)", section, dir))
+        })
+        .collect()
+}
+
+/// Parse a section directory (`dir`, e.g. "rules") and narrow it down with the
+/// section's include/exclude globs
+///
+/// Files come in merge order (see `sort_for_merge`), so merged files such as
+/// CLAUDE.md are built the same way on every platform.
+fn parse_section(
+    preset_dir: &Path,
+    section: &str,
+    dir: &str,
+    filter: &FileFilter,
+    order: &[String],
+    ignore: &[String],
+    warnings: &mut Vec<String>,
+) -> Result<Vec<PresetFile>> {
+    let mut files = parse_directory(preset_dir, dir, ignore)?;
+    sort_for_merge(&mut files, section, order);
+    for entry in order {
+        let listed = format!("{}/{}", dir, entry.replace('\\', "/"));
+        if !files.iter().any(|file| file.relative_path == listed) {
+            warnings.push(format!(
+                "[{}] order lists '{}', which is not in {}/",
+                section, entry, dir
+            ));
+        }
+    }
+    let files = retain_allowed_extensions(files, section, &filter.extensions, warnings);
+    Ok(apply_filter(
+        files,
+        section,
+        dir,
+        filter,
+        warnings,
+        |file| file.section_path(section),
+    ))
+}
+
+/// Sort section files into merge order
+///
+/// Files listed in `order` (relative to the section directory) come first, in that
+/// order. The rest follow by path, where numeric prefixes compare as numbers
+/// (`2-setup.md` before `10-deploy.md`) and numbered names precede unnumbered ones.
+fn sort_for_merge(files: &mut [PresetFile], section: &str, order: &[String]) {
+    files.sort_by_cached_key(|file| {
+        let path = file.section_path(section);
+        let listed = order
+            .iter()
+            .position(|entry| entry.replace('\\', "/") == path)
+            .unwrap_or(order.len());
+        (listed, merge_key(&path))
+    });
+}
+
+/// Per path component: (unnumbered, numeric prefix, remainder)
+fn merge_key(path: &str) -> Vec<(bool, u64, String)> {
+    path.split('/')
+        .map(|component| {
+            let digits = component.len()
+                - component
+                    .trim_start_matches(|c: char| c.is_ascii_digit())
+                    .len();
+            match component[..digits].parse::<u64>() {
+                Ok(number) => (false, number, component[digits..].to_string()),
+                Err(_) => (true, 0, component.to_string()),
+            }
+        })
+        .collect()
+}
+
+/// Read files listed explicitly (`[rules] files = [...]`), in the listed order
+///
+/// Entries are paths relative to the preset root. Entries with glob characters
+/// expand to the matching files in sorted order; a literal entry that does not
+/// exist is an error. Files listed twice are read once. Paths inside `section_dir`
+/// keep their layout below it on apply.
+fn parse_listed_files(
+    preset_dir: &Path,
+    entries: &[String],
+    section_dir: &str,
+    ignore: &[String],
+) -> Result<Vec<PresetFile>> {
+    let mut files: Vec<PresetFile> = Vec::new();
+
+    for entry in entries {
+        let entry = entry.replace('\\', "/");
+        if let Some(reason) = unsafe_path_reason(&entry) {
+            return Err(AidotError::InvalidPreset(format!(
+                "Listed file '{}' points outside the preset ({})",
+                entry, reason
+            )));
+        }
+
+        let matched: Vec<PresetFile> = if is_glob(&entry) {
+            let top = entry.split('/').next().unwrap_or_default();
+            let mut matched: Vec<PresetFile> = parse_directory(preset_dir, top, ignore)?
+                .into_iter()
+                .filter(|file| glob_match(&entry, &file.relative_path))
+                .collect();
+            sort_for_merge(&mut matched, top, &[]);
+            matched
+        } else {
+            let path = preset_dir.join(&entry);
+            if !path.is_file() {
+                return Err(AidotError::InvalidPreset(format!(
+                    "Listed file '{}' does not exist",
+                    entry
+                )));
+            }
+            ensure_inside_preset(preset_dir, &path, &entry)?;
+            vec![PresetFile {
+                relative_path: entry.clone(),
+                content: strip_bom(&fs::read_to_string(&path)?).to_string(),
+                executable: is_executable(&path),
+                section_dir: String::new(),
+            }]
+        };
+
+        for mut file in matched {
+            file.section_dir = section_dir.to_string();
+            if !files.iter().any(|f| f.relative_path == file.relative_path) {
+                files.push(file);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Drop files whose extension the section does not read, with a warning for each
+///
+/// Keeps stray files (notes.txt, a README.html export) from being applied as, say,
+/// a rule. Encrypted files are judged by their name without `.enc`.
+fn retain_allowed_extensions(
+    mut files: Vec<PresetFile>,
+    section: &str,
+    extra: &[String],
+    warnings: &mut Vec<String>,
+) -> Vec<PresetFile> {
+    let Some((_, defaults)) = SECTION_EXTENSIONS.iter().find(|(s, _)| *s == section) else {
+        return files;
+    };
+    let allowed: Vec<String> = defaults
+        .iter()
+        .map(|ext| ext.to_string())
+        .chain(
+            extra
+                .iter()
+                .map(|ext| ext.trim_start_matches('.').to_lowercase()),
+        )
+        .collect();
+
+    files.retain(|file| {
+        let name = file.section_path(section);
+        let name = name.strip_suffix(secret::ENCRYPTED_SUFFIX).unwrap_or(&name);
+        let extension = Path::new(name)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if allowed.contains(&extension) {
+            return true;
+        }
+        warnings.push(format!(
+            "skipped {}: [{}] reads .{} files (add extensions = [\"{}\"] to read it)",
+            file.relative_path,
+            section,
+            allowed.join(", ."),
+            extension
+        ));
+        false
+    });
+    files
+}
+
+/// Keep files passing the filter, warning when a non-empty section ends up empty
+fn apply_filter(
+    mut files: Vec<PresetFile>,
+    section: &str,
+    dir: &str,
+    filter: &FileFilter,
+    warnings: &mut Vec<String>,
+    section_path: impl Fn(&PresetFile) -> String,
+) -> Vec<PresetFile> {
+    if filter.is_empty() || files.is_empty() {
+        return files;
+    }
+    files.retain(|file| filter.matches(&section_path(file)));
+    if files.is_empty() {
+        warnings.push(format!(
+            "[{}] include/exclude globs match no files in {}/",
+            section, dir
+        ));
+    }
+    files
+}
+
+/// Whether a file (path relative to its section directory) is skipped before reading
+///
+/// Section directories skip `IGNORED_FILES` and hidden files or directories. root/
+/// keeps dotfiles such as .editorconfig and only skips `ROOT_IGNORED_FILES`. The
+/// preset's `ignore` globs apply to both.
+fn is_ignored(path: &str, root: bool, ignore: &[String]) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let builtin = if root {
+        ROOT_IGNORED_FILES
+    } else {
+        IGNORED_FILES
+    };
+    builtin
+        .iter()
+        .any(|ignored| ignored.eq_ignore_ascii_case(name))
+        || (!root && path.split('/').any(|component| component.starts_with('.')))
+        || ignore.iter().any(|pattern| glob_match(pattern, path))
 }
 
 /// Parse root directory files and validate they don't contain reserved directories
-fn parse_root_directory(preset_dir: &Path, subdir: &str) -> Result<Vec<PresetFile>> {
+fn parse_root_directory(
+    preset_dir: &Path,
+    subdir: &str,
+    ignore: &[String],
+) -> Result<Vec<PresetFile>> {
     let target_dir = preset_dir.join(subdir);
     let mut files = Vec::new();
 
     if !target_dir.exists() {
         return Ok(files);
     }
+    ensure_inside_preset(preset_dir, &target_dir, subdir)?;
 
     for entry in WalkDir::new(&target_dir)
+        .sort_by_file_name()
         .into_iter()
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_file())
     {
         let path = entry.path();
-        let content = fs::read_to_string(path)?;
-
         // Get relative path from root/ directory (not from preset root)
-        let relative_from_root = path
-            .strip_prefix(&target_dir)
-            .unwrap()
-            .to_string_lossy()
-            .replace('\\', "/");
+        let relative_from_root = to_relative_path(path, &target_dir);
+        if is_ignored(&relative_from_root, true, ignore) {
+            continue;
+        }
+        let content = strip_bom(&fs::read_to_string(path)?).to_string();
 
-        // Validate: check if path starts with reserved directories
+        // Validate: check if path is inside a reserved directory (.cursorrules is fine)
         for reserved in RESERVED_DIRS {
-            if relative_from_root.starts_with(reserved) {
+            if relative_from_root.split('/').next() == Some(*reserved) {
                 return Err(AidotError::InvalidPreset(format!(
                     "root/ cannot contain '{}/' - use dedicated sections instead\n\
                      Hint: Move {} files to the appropriate sections:\n  \
@@ -107,51 +640,121 @@ fn parse_root_directory(preset_dir: &Path, subdir: &str) -> Result<Vec<PresetFil
         files.push(PresetFile {
             relative_path: relative_from_root,
             content,
+            executable: is_executable(path),
+            section_dir: String::new(),
         });
     }
 
     Ok(files)
 }
 
-/// Parse all files in a directory recursively
-fn parse_directory(preset_dir: &Path, subdir: &str) -> Result<Vec<PresetFile>> {
+/// Parse all files in a directory recursively, skipping ignored ones (see `is_ignored`)
+fn parse_directory(preset_dir: &Path, subdir: &str, ignore: &[String]) -> Result<Vec<PresetFile>> {
     let target_dir = preset_dir.join(subdir);
     let mut files = Vec::new();
 
     if !target_dir.exists() {
         return Ok(files);
     }
+    ensure_inside_preset(preset_dir, &target_dir, subdir)?;
 
     for entry in WalkDir::new(&target_dir)
+        .sort_by_file_name()
         .into_iter()
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_file())
     {
         let path = entry.path();
-        let content = fs::read_to_string(path)?;
+        if is_ignored(&to_relative_path(path, &target_dir), false, ignore) {
+            continue;
+        }
+        let content = strip_bom(&fs::read_to_string(path)?).to_string();
 
         // Get relative path from preset root
-        let relative_path = path
-            .strip_prefix(preset_dir)
-            .unwrap()
-            .to_string_lossy()
-            .replace('\\', "/");
+        let relative_path = to_relative_path(path, preset_dir);
 
         files.push(PresetFile {
             relative_path,
             content,
+            executable: is_executable(path),
+            section_dir: subdir.to_string(),
         });
     }
 
     Ok(files)
 }
 
+/// Refuse to read a section directory or listed file that a symlink points outside the preset
+///
+/// Symlinked files inside section directories are not followed by the directory walk.
+fn ensure_inside_preset(preset_dir: &Path, path: &Path, display: &str) -> Result<()> {
+    if resolves_inside(preset_dir, path) {
+        Ok(())
+    } else {
+        Err(AidotError::InvalidPreset(format!(
+            "'{}' is a symlink pointing outside the preset",
+            display
+        )))
+    }
+}
+
+/// Relative path of a preset file, always `/`-separated
+///
+/// Every `PresetFile::relative_path` goes through here, so adapters, scan and diff
+/// only ever deal with `/`.
+fn to_relative_path(path: &Path, base: &Path) -> String {
+    path.strip_prefix(base)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Check whether a preset file has any executable bit set (Unix only)
+///
+/// Uses `fs::metadata`, so a symlinked preset file reports its target's mode.
+#[cfg(unix)]
+pub(crate) fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .map(|m| m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn is_executable(_path: &Path) -> bool {
+    false
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_section_layout_problems() {
+        let temp_dir = TempDir::new().unwrap();
+        let preset_dir = temp_dir.path();
+        fs::create_dir_all(preset_dir.join("rules")).unwrap();
+        fs::create_dir_all(preset_dir.join("hooks")).unwrap();
+        fs::create_dir_all(preset_dir.join("memory")).unwrap();
+
+        let config: PresetConfig = toml::from_str(
+            "[metadata]\nname = \"p\"\nversion = \"1.0.0\"\n\n[memory]\ndirectory = \"memory/\"\n\n[commands]\ndirectory = \"commands/\"\n\n[hooks]\npost_apply = { command = \"true\" }\n",
+        )
+        .unwrap();
+        assert_eq!(
+            section_layout_problems(preset_dir, &config),
+            vec![
+                "rules/ directory exists but the config has no [rules] section, so it is ignored",
+                "[commands] is declared but the commands/ directory does not exist",
+                "hooks/ directory exists but [hooks] has no directory = \"hooks/\", so it is ignored",
+            ]
+        );
+    }
+
     #[test]
     fn test_parse_directory() {
         let temp_dir = TempDir::new().unwrap();
@@ -161,12 +764,73 @@ mod tests {
         fs::create_dir_all(preset_dir.join("rules")).unwrap();
         fs::write(preset_dir.join("rules/test.md"), "# Test Rule").unwrap();
 
-        let files = parse_directory(preset_dir, "rules").unwrap();
+        let files = parse_directory(preset_dir, "rules", &[]).unwrap();
         assert_eq!(files.len(), 1);
         assert_eq!(files[0].relative_path, "rules/test.md");
         assert_eq!(files[0].content, "# Test Rule");
     }
 
+    #[test]
+    fn test_parse_directory_skips_meta_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let preset_dir = temp_dir.path();
+
+        fs::create_dir_all(preset_dir.join("rules/.drafts")).unwrap();
+        fs::create_dir_all(preset_dir.join("root")).unwrap();
+        fs::write(preset_dir.join("rules/style.md"), "# Style").unwrap();
+        fs::write(preset_dir.join("rules/README.md"), "# About these rules").unwrap();
+        fs::write(preset_dir.join("rules/NOTES.md"), "# Notes").unwrap();
+        // Not UTF-8: reading it would fail the whole preset
+        fs::write(preset_dir.join("rules/.DS_Store"), [0xff, 0xfe, 0x00]).unwrap();
+        fs::write(preset_dir.join("rules/.gitkeep"), "").unwrap();
+        fs::write(preset_dir.join("rules/.drafts/wip.md"), "# WIP").unwrap();
+        fs::write(preset_dir.join("root/.editorconfig"), "root = true").unwrap();
+        fs::write(preset_dir.join("root/README.md"), "# Project").unwrap();
+        fs::write(preset_dir.join("root/Thumbs.db"), [0xff]).unwrap();
+
+        let ignore = vec!["NOTES.md".to_string()];
+        let paths = |files: Vec<PresetFile>| -> Vec<String> {
+            files.into_iter().map(|f| f.relative_path).collect()
+        };
+        assert_eq!(
+            paths(parse_directory(preset_dir, "rules", &ignore).unwrap()),
+            vec!["rules/style.md"]
+        );
+        // root/ keeps dotfiles and README.md
+        assert_eq!(
+            paths(parse_root_directory(preset_dir, "root", &[]).unwrap()),
+            vec![".editorconfig", "README.md"]
+        );
+    }
+
+    #[test]
+    fn test_parse_directory_nested_uses_forward_slashes() {
+        let temp_dir = TempDir::new().unwrap();
+        let preset_dir = temp_dir.path();
+
+        fs::create_dir_all(preset_dir.join("skills").join("review")).unwrap();
+        fs::write(
+            preset_dir.join("skills").join("review").join("SKILL.md"),
+            "# Review",
+        )
+        .unwrap();
+
+        let files = parse_directory(preset_dir, "skills", &[]).unwrap();
+        assert_eq!(files[0].relative_path, "skills/review/SKILL.md");
+    }
+
+    #[test]
+    fn test_parse_directory_strips_bom() {
+        let temp_dir = TempDir::new().unwrap();
+        let preset_dir = temp_dir.path();
+
+        fs::create_dir_all(preset_dir.join("rules")).unwrap();
+        fs::write(preset_dir.join("rules/test.md"), "\u{feff}# Test Rule\r\n").unwrap();
+
+        let files = parse_directory(preset_dir, "rules", &[]).unwrap();
+        assert_eq!(files[0].content, "# Test Rule\r\n");
+    }
+
     #[test]
     fn test_parse_root_directory() {
         let temp_dir = TempDir::new().unwrap();
@@ -181,7 +845,7 @@ mod tests {
         .unwrap();
         fs::write(preset_dir.join("root/.prettierrc"), "{}").unwrap();
 
-        let files = parse_root_directory(preset_dir, "root").unwrap();
+        let files = parse_root_directory(preset_dir, "root", &[]).unwrap();
         assert_eq!(files.len(), 2);
 
         // Check that relative paths don't include "root/" prefix
@@ -199,7 +863,7 @@ mod tests {
         fs::create_dir_all(preset_dir.join("root/config")).unwrap();
         fs::write(preset_dir.join("root/config/settings.json"), "{}").unwrap();
 
-        let files = parse_root_directory(preset_dir, "root").unwrap();
+        let files = parse_root_directory(preset_dir, "root", &[]).unwrap();
         assert_eq!(files.len(), 1);
         assert_eq!(files[0].relative_path, "config/settings.json");
     }
@@ -213,7 +877,7 @@ mod tests {
         fs::create_dir_all(preset_dir.join("root/.claude")).unwrap();
         fs::write(preset_dir.join("root/.claude/rules.md"), "# Rules").unwrap();
 
-        let result = parse_root_directory(preset_dir, "root");
+        let result = parse_root_directory(preset_dir, "root", &[]);
         assert!(result.is_err());
 
         let err = result.unwrap_err();
@@ -230,9 +894,14 @@ mod tests {
         fs::create_dir_all(preset_dir.join("root/.cursor")).unwrap();
         fs::write(preset_dir.join("root/.cursor/config.json"), "{}").unwrap();
 
-        let result = parse_root_directory(preset_dir, "root");
+        let result = parse_root_directory(preset_dir, "root", &[]);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains(".cursor"));
+
+        // Only the directory is reserved, not names sharing its prefix
+        fs::remove_dir_all(preset_dir.join("root/.cursor")).unwrap();
+        fs::write(preset_dir.join("root/.cursorrules"), "# Rules").unwrap();
+        assert!(parse_root_directory(preset_dir, "root", &[]).is_ok());
     }
 
     #[test]
@@ -243,7 +912,7 @@ mod tests {
         fs::create_dir_all(preset_dir.join("root/.github")).unwrap();
         fs::write(preset_dir.join("root/.github/copilot.md"), "# Copilot").unwrap();
 
-        let result = parse_root_directory(preset_dir, "root");
+        let result = parse_root_directory(preset_dir, "root", &[]);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains(".github"));
     }
@@ -256,8 +925,377 @@ mod tests {
         fs::create_dir_all(preset_dir.join("root/.vscode")).unwrap();
         fs::write(preset_dir.join("root/.vscode/settings.json"), "{}").unwrap();
 
-        let result = parse_root_directory(preset_dir, "root");
+        let result = parse_root_directory(preset_dir, "root", &[]);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains(".vscode"));
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_directory_detects_executable() {
+        use std::os::unix::fs::PermissionsExt;
+        let temp_dir = TempDir::new().unwrap();
+        let preset_dir = temp_dir.path();
+
+        fs::create_dir_all(preset_dir.join("skills")).unwrap();
+        fs::write(preset_dir.join("skills/run.sh"), "#!/bin/sh").unwrap();
+        fs::write(preset_dir.join("skills/notes.md"), "# Notes").unwrap();
+        fs::set_permissions(
+            preset_dir.join("skills/run.sh"),
+            fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+
+        let files = parse_directory(preset_dir, "skills", &[]).unwrap();
+        let script = files.iter().find(|f| f.relative_path == "skills/run.sh");
+        let notes = files.iter().find(|f| f.relative_path == "skills/notes.md");
+        assert!(script.unwrap().executable);
+        assert!(!notes.unwrap().executable);
+    }
+
+    #[test]
+    fn test_parse_preset_include_exclude() {
+        let temp_dir = TempDir::new().unwrap();
+        let preset_dir = temp_dir.path();
+
+        fs::write(
+            preset_dir.join(".aidot-config.toml"),
+            "[metadata]\nname = \"t\"\nversion = \"1.0.0\"\n\n\
+             [rules]\ndirectory = \"rules/\"\nexclude = [\"draft-*.md\"]\n\n\
+             [skills]\ndirectory = \"skills/\"\ninclude = [\"review/**\"]\n",
+        )
+        .unwrap();
+        fs::create_dir_all(preset_dir.join("rules/backend")).unwrap();
+        fs::create_dir_all(preset_dir.join("skills/review")).unwrap();
+        fs::create_dir_all(preset_dir.join("skills/deploy")).unwrap();
+        fs::write(preset_dir.join("rules/style.md"), "# Style").unwrap();
+        fs::write(preset_dir.join("rules/draft-api.md"), "# Draft").unwrap();
+        fs::write(preset_dir.join("rules/backend/draft-db.md"), "# Draft").unwrap();
+        fs::write(preset_dir.join("skills/review/SKILL.md"), "# Review").unwrap();
+        fs::write(preset_dir.join("skills/deploy/SKILL.md"), "# Deploy").unwrap();
+
+        let (_, files) = parse_preset(preset_dir).unwrap();
+        let rules: Vec<_> = files
+            .rules
+            .iter()
+            .map(|f| f.relative_path.as_str())
+            .collect();
+        assert_eq!(rules, vec!["rules/style.md"]);
+        let skills: Vec<_> = files
+            .skills
+            .iter()
+            .map(|f| f.relative_path.as_str())
+            .collect();
+        assert_eq!(skills, vec!["skills/review/SKILL.md"]);
+    }
+
+    #[test]
+    fn test_empty_sections_are_reported_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let preset_dir = temp_dir.path();
+        let write_config = |extra: &str| {
+            fs::write(
+                preset_dir.join(".aidot-config.toml"),
+                format!(
+                    "{}[metadata]\nname = \"t\"\nversion = \"1.0.0\"\n\n\
+                     [rules]\ndirectory = \"rules/\"\n\n\
+                     [mcp]\ndirectory = \"mcp/\"\n\n\
+                     [agents]\ndirectory = \"agents/\"\n\n\
+                     [skills]\ndirectory = \"skills/\"\n",
+                    extra
+                ),
+            )
+            .unwrap();
+        };
+        for dir in ["rules", "mcp", "agents"] {
+            fs::create_dir_all(preset_dir.join(dir)).unwrap();
+        }
+        fs::write(preset_dir.join("rules/style.md"), "# Style").unwrap();
+        fs::write(preset_dir.join("agents/.gitkeep"), "").unwrap();
+
+        let read = || {
+            let config = PresetConfig::load(preset_dir).unwrap();
+            let mut warnings = Vec::new();
+            read_preset_files(preset_dir, &config, &mut warnings).unwrap();
+            warnings
+        };
+
+        write_config("");
+        let warnings = read();
+        // One line for all of them; skills/ is missing and warned about separately
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].contains("mcp (mcp/), agents (agents/)"));
+
+        write_config("allow_empty = [\"mcp\", \"agents\"]\n");
+        assert!(read().is_empty());
+    }
+
+    #[test]
+    fn test_parse_preset_section_extensions() {
+        let temp_dir = TempDir::new().unwrap();
+        let preset_dir = temp_dir.path();
+
+        fs::write(
+            preset_dir.join(".aidot-config.toml"),
+            "[metadata]\nname = \"t\"\nversion = \"1.0.0\"\n\n\
+             [rules]\ndirectory = \"rules/\"\n\n\
+             [mcp]\ndirectory = \"mcp/\"\nextensions = [\".jsonc\"]\n\n\
+             [skills]\ndirectory = \"skills/\"\n",
+        )
+        .unwrap();
+        for dir in ["rules", "mcp", "skills"] {
+            fs::create_dir_all(preset_dir.join(dir)).unwrap();
+        }
+        fs::write(preset_dir.join("rules/style.md"), "# Style").unwrap();
+        fs::write(preset_dir.join("rules/rust.MDC"), "# Rust").unwrap();
+        fs::write(preset_dir.join("rules/notes.txt"), "todo").unwrap();
+        fs::write(preset_dir.join("rules/Makefile"), "all:").unwrap();
+        fs::write(preset_dir.join("mcp/github.json"), "{}").unwrap();
+        fs::write(preset_dir.join("mcp/local.jsonc"), "{}").unwrap();
+        fs::write(preset_dir.join("mcp/readme.md"), "# MCP").unwrap();
+        fs::write(preset_dir.join("skills/run.sh"), "#!/bin/sh").unwrap();
+
+        let (_, files) = parse_preset(preset_dir).unwrap();
+        let paths = |files: &[PresetFile]| -> Vec<String> {
+            files.iter().map(|f| f.relative_path.clone()).collect()
+        };
+        assert_eq!(
+            paths(&files.rules),
+            vec!["rules/rust.MDC", "rules/style.md"]
+        );
+        assert_eq!(
+            paths(&files.mcp),
+            vec!["mcp/github.json", "mcp/local.jsonc"]
+        );
+        // skills/ reads any file type
+        assert_eq!(paths(&files.skills), vec!["skills/run.sh"]);
+    }
+
+    #[test]
+    fn test_parse_preset_custom_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let preset_dir = temp_dir.path();
+
+        fs::write(
+            preset_dir.join(".aidot-config.toml"),
+            "[metadata]\nname = \"t\"\nversion = \"1.0.0\"\n\n\
+             [rules]\ndirectory = \"guidelines/\"\nexclude = [\"draft-*.md\"]\n\n\
+             [memory]\ndirectory = \"docs/context/\"\norder = [\"stack.md\"]\n\n\
+             [commands]\ndirectory = \"prompts/\"\n",
+        )
+        .unwrap();
+        fs::create_dir_all(preset_dir.join("guidelines/backend")).unwrap();
+        fs::create_dir_all(preset_dir.join("docs/context")).unwrap();
+        fs::create_dir_all(preset_dir.join("rules")).unwrap();
+        fs::write(preset_dir.join("guidelines/backend/style.md"), "# Style").unwrap();
+        fs::write(preset_dir.join("guidelines/draft-api.md"), "# Draft").unwrap();
+        fs::write(preset_dir.join("docs/context/about.md"), "About").unwrap();
+        fs::write(preset_dir.join("docs/context/stack.md"), "Stack").unwrap();
+        fs::write(preset_dir.join("rules/ignored.md"), "# Ignored").unwrap();
+
+        let (config, files) = parse_preset(preset_dir).unwrap();
+        assert_eq!(files.rules.len(), 1);
+        assert_eq!(files.rules[0].relative_path, "guidelines/backend/style.md");
+        assert_eq!(files.rules[0].section_path("rules"), "backend/style.md");
+        let memory: Vec<_> = files
+            .memory
+            .iter()
+            .map(|f| f.section_path("memory"))
+            .collect();
+        assert_eq!(memory, vec!["stack.md", "about.md"]);
+
+        assert_eq!(
+            section_layout_problems(preset_dir, &config),
+            vec!["[commands] is declared but the prompts/ directory does not exist"]
+        );
+    }
+
+    #[test]
+    fn test_parse_preset_rules_files_in_listed_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let preset_dir = temp_dir.path();
+        let config = |files: &str| {
+            format!(
+                "[metadata]\nname = \"t\"\nversion = \"1.0.0\"\n\n[rules]\nfiles = {}\n",
+                files
+            )
+        };
+
+        fs::create_dir_all(preset_dir.join("rules/extra")).unwrap();
+        for name in ["a.md", "b.md", "c.md", "ignored.txt", "extra/d.md"] {
+            fs::write(preset_dir.join("rules").join(name), name).unwrap();
+        }
+
+        fs::write(
+            preset_dir.join(".aidot-config.toml"),
+            config(r#"["rules/c.md", "rules/*.md", "rules/a.md"]"#),
+        )
+        .unwrap();
+        let (_, files) = parse_preset(preset_dir).unwrap();
+        let rules: Vec<_> = files
+            .rules
+            .iter()
+            .map(|f| f.relative_path.as_str())
+            .collect();
+        assert_eq!(rules, vec!["rules/c.md", "rules/a.md", "rules/b.md"]);
+        assert_eq!(files.rules[0].content, "c.md");
+
+        fs::write(
+            preset_dir.join(".aidot-config.toml"),
+            config(r#"["rules/a.md", "rules/missing.md"]"#),
+        )
+        .unwrap();
+        let err = parse_preset(preset_dir).unwrap_err().to_string();
+        assert!(err.contains("rules/missing.md"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_preset_rejects_paths_outside() {
+        let temp_dir = TempDir::new().unwrap();
+        let preset_dir = temp_dir.path().join("preset");
+        let outside = temp_dir.path().join("outside");
+        fs::create_dir_all(&preset_dir).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("secret.md"), "secret").unwrap();
+
+        for files in [r#"["../outside/secret.md"]"#, r#"["/etc/hostname"]"#] {
+            fs::write(
+                preset_dir.join(".aidot-config.toml"),
+                format!(
+                    "[metadata]\nname = \"t\"\nversion = \"1.0.0\"\n\n[rules]\nfiles = {}\n",
+                    files
+                ),
+            )
+            .unwrap();
+            let err = parse_preset(&preset_dir).unwrap_err().to_string();
+            assert!(err.contains("points outside the preset"), "{}", err);
+        }
+
+        // Section directories must be relative and free of '..'
+        for (directory, reason) in [
+            ("../outside/", "'..' component"),
+            ("memory/../../outside", "'..' component"),
+            ("/etc/", "absolute path"),
+            ("./", "empty path"),
+        ] {
+            fs::write(
+                preset_dir.join(".aidot-config.toml"),
+                format!(
+                    "[metadata]\nname = \"t\"\nversion = \"1.0.0\"\n\n[memory]\ndirectory = \"{}\"\n",
+                    directory
+                ),
+            )
+            .unwrap();
+            let err = parse_preset(&preset_dir).unwrap_err().to_string();
+            assert!(err.contains("[memory] directory"), "{}", err);
+            assert!(err.contains(reason), "{}", err);
+        }
+
+        // A section directory symlinked elsewhere is not read
+        #[cfg(unix)]
+        {
+            fs::write(
+                preset_dir.join(".aidot-config.toml"),
+                "[metadata]\nname = \"t\"\nversion = \"1.0.0\"\n\n[memory]\ndirectory = \"memory/\"\n",
+            )
+            .unwrap();
+            std::os::unix::fs::symlink(&outside, preset_dir.join("memory")).unwrap();
+            let err = parse_preset(&preset_dir).unwrap_err().to_string();
+            assert!(err.contains("resolves outside the preset"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_sort_for_merge() {
+        let file = |path: &str| PresetFile {
+            relative_path: path.to_string(),
+            ..Default::default()
+        };
+        let paths = |files: &[PresetFile]| {
+            files
+                .iter()
+                .map(|f| f.relative_path.clone())
+                .collect::<Vec<_>>()
+        };
+        let mut files = vec![
+            file("memory/b.md"),
+            file("memory/10-deploy.md"),
+            file("memory/a.md"),
+            file("memory/2-setup.md"),
+            file("memory/01-intro.md"),
+        ];
+
+        sort_for_merge(&mut files, "memory", &[]);
+        assert_eq!(
+            paths(&files),
+            vec![
+                "memory/01-intro.md",
+                "memory/2-setup.md",
+                "memory/10-deploy.md",
+                "memory/a.md",
+                "memory/b.md"
+            ]
+        );
+
+        sort_for_merge(
+            &mut files,
+            "memory",
+            &["b.md".to_string(), "a.md".to_string()],
+        );
+        assert_eq!(
+            paths(&files),
+            vec![
+                "memory/b.md",
+                "memory/a.md",
+                "memory/01-intro.md",
+                "memory/2-setup.md",
+                "memory/10-deploy.md"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_traversal_order_is_independent_of_creation_order() {
+        let names = ["z.md", "a.md", "nested/b.md", "m.md", "nested/a.md"];
+        let parse = |names: &[&str]| {
+            let temp_dir = TempDir::new().unwrap();
+            let preset_dir = temp_dir.path();
+            for name in names {
+                for section in ["rules", "root"] {
+                    let path = preset_dir.join(section).join(name);
+                    fs::create_dir_all(path.parent().unwrap()).unwrap();
+                    fs::write(path, name).unwrap();
+                }
+            }
+            let paths = |files: Vec<PresetFile>| {
+                files
+                    .into_iter()
+                    .map(|f| f.relative_path)
+                    .collect::<Vec<_>>()
+            };
+            (
+                paths(parse_directory(preset_dir, "rules", &[]).unwrap()),
+                paths(parse_root_directory(preset_dir, "root", &[]).unwrap()),
+            )
+        };
+
+        let mut reversed = names;
+        reversed.reverse();
+        let (rules, root) = parse(&names);
+        assert_eq!(
+            rules,
+            vec![
+                "rules/a.md",
+                "rules/m.md",
+                "rules/nested/a.md",
+                "rules/nested/b.md",
+                "rules/z.md"
+            ]
+        );
+        assert_eq!(
+            root,
+            vec!["a.md", "m.md", "nested/a.md", "nested/b.md", "z.md"]
+        );
+        assert_eq!(parse(&reversed), (rules, root));
+    }
 }
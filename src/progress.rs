@@ -0,0 +1,45 @@
+use crate::output;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::io::IsTerminal;
+use std::time::Duration;
+
+/// Check if progress indicators should be drawn
+/// Disabled in quiet mode or when stdout/stderr is not a terminal (pipes, CI logs, redirects)
+pub fn is_enabled() -> bool {
+    !output::is_quiet() && std::io::stdout().is_terminal() && std::io::stderr().is_terminal()
+}
+
+/// Create a spinner with a message (hidden when progress is disabled)
+pub fn spinner(message: &str) -> ProgressBar {
+    if !is_enabled() {
+        return ProgressBar::hidden();
+    }
+
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(
+        ProgressStyle::with_template("{spinner:.cyan} {msg}")
+            .unwrap()
+            .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏ "),
+    );
+    pb.set_message(message.to_string());
+    pb.enable_steady_tick(Duration::from_millis(80));
+    pb
+}
+
+/// Create a progress bar for `total` files (hidden when progress is disabled)
+///
+/// Renders as: `Applying [=====>    ] 12/48 files`
+pub fn file_bar(message: &str, total: u64) -> ProgressBar {
+    if !is_enabled() || total == 0 {
+        return ProgressBar::hidden();
+    }
+
+    let pb = ProgressBar::new(total);
+    pb.set_style(
+        ProgressStyle::with_template("{msg} [{bar:30.cyan/blue}] {pos}/{len} files")
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    pb.set_message(message.to_string());
+    pb
+}
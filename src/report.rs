@@ -0,0 +1,187 @@
+use crate::error::Result;
+use crate::manifest::ManifestEntry;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Apply report written by `aidot pull --report <path>`
+///
+/// Entries embed the manifest fields (path, tool, section, preset), so a JSON report
+/// lists exactly what a rollback/unapply needs to undo.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Report {
+    /// Absolute project path
+    pub project: String,
+    /// When the report was written (UTC, RFC 3339)
+    pub generated_at: String,
+    #[serde(default)]
+    pub entries: Vec<ReportEntry>,
+}
+
+/// Outcome for a single file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportEntry {
+    #[serde(flatten)]
+    pub file: ManifestEntry,
+    pub status: FileStatus,
+    /// When the preset was applied (UTC, RFC 3339)
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileStatus {
+    Created,
+    Updated,
+    Skipped,
+    Unchanged,
+    /// Could not be written (--continue-on-error)
+    Failed,
+    /// Removed by --prune
+    Deleted,
+}
+
+impl FileStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            FileStatus::Created => "created",
+            FileStatus::Updated => "updated",
+            FileStatus::Skipped => "skipped",
+            FileStatus::Unchanged => "unchanged",
+            FileStatus::Failed => "failed",
+            FileStatus::Deleted => "deleted",
+        }
+    }
+}
+
+impl Report {
+    pub fn new(project_dir: &Path) -> Self {
+        Self {
+            project: project_dir.to_string_lossy().replace('\\', "/"),
+            generated_at: String::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Write the report as JSON (`.json` extension) or Markdown (anything else)
+    pub fn write(&mut self, path: &Path) -> Result<()> {
+        self.generated_at = now_rfc3339();
+
+        let is_json = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        let content = if is_json {
+            serde_json::to_string_pretty(self)?
+        } else {
+            self.to_markdown()
+        };
+
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent)?;
+            }
+        }
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Render the report as a Markdown table
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::from("# aidot pull report\n\n");
+        md.push_str(&format!("- Project: `{}`\n", self.project));
+        md.push_str(&format!("- Generated: {}\n\n", self.generated_at));
+        md.push_str("| Status | File | Tool | Section | Preset | Timestamp |\n");
+        md.push_str("|--------|------|------|---------|--------|-----------|\n");
+        for entry in &self.entries {
+            md.push_str(&format!(
+                "| {} | `{}` | {} | {} | {} | {} |\n",
+                entry.status.as_str(),
+                entry.file.path,
+                entry.file.tool,
+                entry.file.section,
+                entry.file.preset,
+                entry.timestamp
+            ));
+        }
+        md
+    }
+}
+
+/// Current time in UTC formatted as RFC 3339 (e.g., "2026-01-12T10:00:00Z")
+pub fn now_rfc3339() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format_rfc3339(secs)
+}
+
+/// Format seconds since the Unix epoch as RFC 3339 in UTC
+fn format_rfc3339(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_format_rfc3339() {
+        assert_eq!(format_rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_rfc3339(1_768_212_000), "2026-01-12T10:00:00Z");
+        assert_eq!(format_rfc3339(951_782_400), "2000-02-29T00:00:00Z");
+    }
+
+    #[test]
+    fn test_report_write_json_and_markdown() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut report = Report::new(temp_dir.path());
+        report.entries.push(ReportEntry {
+            file: ManifestEntry {
+                path: ".claude/rules/style.md".to_string(),
+                tool: "Claude Code".to_string(),
+                section: "rules".to_string(),
+                preset: "team".to_string(),
+                hash: None,
+            },
+            status: FileStatus::Created,
+            timestamp: "2026-01-12T10:00:00Z".to_string(),
+        });
+
+        let json_path = temp_dir.path().join("report.json");
+        report.write(&json_path).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(json["entries"][0]["path"], ".claude/rules/style.md");
+        assert_eq!(json["entries"][0]["status"], "created");
+
+        let md_path = temp_dir.path().join("report.md");
+        report.write(&md_path).unwrap();
+        let md = fs::read_to_string(&md_path).unwrap();
+        assert!(md.contains("| created | `.claude/rules/style.md` | Claude Code | rules | team |"));
+    }
+}
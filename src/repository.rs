@@ -1,7 +1,16 @@
+use crate::archive::ArchiveFormat;
 use crate::cache;
 use crate::config::{Config, SourceType};
-use crate::error::Result;
-use std::path::PathBuf;
+use crate::error::{AidotError, Result};
+use crate::log;
+use crate::output;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+/// Directories searched for local presets given by a bare name (`team-preset`)
+///
+/// Separated like `PATH`: `:` on Unix, `;` on Windows.
+pub const PRESET_PATH_ENV: &str = "AIDOT_PRESET_PATH";
 
 /// Determine if a string is a Git URL
 pub fn is_git_url(source: &str) -> bool {
@@ -12,16 +21,141 @@ pub fn is_git_url(source: &str) -> bool {
         || source.starts_with("git://")
 }
 
+/// Hosts that serve a repository both with and without the `.git` suffix
+const HOSTS_WITH_OPTIONAL_GIT_SUFFIX: &[&str] = &["github.com", "gitlab.com", "bitbucket.org"];
+
+/// Canonical form of a Git URL, so one repository always maps to one URL
+///
+/// - surrounding whitespace and trailing slashes are removed
+/// - the scheme and host are lowercased
+/// - `ssh://user@host/path` without a port becomes the scp form `user@host:path`
+/// - on hosts that accept both (GitHub, GitLab, Bitbucket) the `.git` suffix is added
+///
+/// # Examples
+/// ```
+/// normalize_git_url("https://GitHub.com/team/presets/")
+/// // → "https://github.com/team/presets.git"
+/// normalize_git_url("ssh://git@github.com/team/presets")
+/// // → "git@github.com:team/presets.git"
+/// ```
+pub fn normalize_git_url(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    let (prefix, host, path) = match url.split_once("://") {
+        Some((scheme, rest)) => {
+            let scheme = scheme.to_ascii_lowercase();
+            let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+            let (user, host) = match authority.rsplit_once('@') {
+                Some((user, host)) => (Some(user), host),
+                None => (None, authority),
+            };
+            let host = host.to_ascii_lowercase();
+            match user {
+                // Without a port, ssh:// and scp form are the same address
+                Some(user) if scheme == "ssh" && !host.contains(':') && !path.is_empty() => {
+                    (format!("{}@{}:", user, host), host, path)
+                }
+                Some(user) => (format!("{}://{}@{}/", scheme, user, host), host, path),
+                None => (format!("{}://{}/", scheme, host), host, path),
+            }
+        }
+        None => match url.split_once(':') {
+            // scp form: user@host:path
+            Some((authority, path)) => {
+                let (user, host) = authority.rsplit_once('@').unwrap_or(("", authority));
+                let host = host.to_ascii_lowercase();
+                let user = if user.is_empty() {
+                    String::new()
+                } else {
+                    format!("{}@", user)
+                };
+                (format!("{}{}:", user, host), host, path)
+            }
+            None => return url.to_string(),
+        },
+    };
+    let path = path.trim_end_matches('/');
+    let path = if HOSTS_WITH_OPTIONAL_GIT_SUFFIX.contains(&host.as_str())
+        && !path.is_empty()
+        && !path.ends_with(".git")
+    {
+        format!("{}.git", path)
+    } else {
+        path.to_string()
+    };
+    format!("{}{}", prefix, path)
+}
+
+/// Normalize a Git URL, logging the original and the result when they differ
+pub fn canonical_git_url(url: &str) -> String {
+    let normalized = normalize_git_url(url);
+    if normalized != url {
+        log::info(&format!("Normalized Git URL {} -> {}", url, normalized));
+    }
+    normalized
+}
+
+/// Determine if a string is an HTTP(S) URL of a release archive (.tar.gz, .tgz, .zip)
+pub fn is_archive_url(source: &str) -> bool {
+    (source.starts_with("http://") || source.starts_with("https://"))
+        && ArchiveFormat::from_name(source).is_some()
+}
+
+/// Split a `#path=<subdir>` suffix from a repository URL
+///
+/// # Examples
+/// ```
+/// split_subdir("https://github.com/team/presets.git#path=presets/frontend")
+/// // → ("https://github.com/team/presets.git", Some("presets/frontend"))
+/// ```
+pub fn split_subdir(url: &str) -> (&str, Option<&str>) {
+    match url.split_once("#path=") {
+        Some((base, subdir)) if !subdir.is_empty() => (base, Some(subdir)),
+        Some((base, _)) => (base, None),
+        None => (url, None),
+    }
+}
+
+/// Validate a preset subdirectory (relative, no `..`) and normalize separators
+pub fn normalize_subdir(subdir: &str) -> Result<String> {
+    let normalized = subdir.replace('\\', "/").trim_matches('/').to_string();
+    let invalid = normalized.is_empty()
+        || Path::new(subdir).is_absolute()
+        || normalized.split('/').any(|part| part == "..");
+    if invalid {
+        return Err(AidotError::InvalidInput(format!(
+            "Invalid preset subdirectory '{}': must be a relative path inside the repository",
+            subdir
+        )));
+    }
+    Ok(normalized)
+}
+
+/// Join a preset subdirectory onto a repository root and ensure it holds a preset
+fn join_subdir(root: PathBuf, subdir: Option<&str>, source: &str) -> Result<PathBuf> {
+    let Some(subdir) = subdir else {
+        return Ok(root);
+    };
+    let path = root.join(normalize_subdir(subdir)?);
+    if !path.join(".aidot-config.toml").exists() {
+        return Err(AidotError::InvalidPreset(format!(
+            "Missing .aidot-config.toml in subdirectory '{}' of '{}'",
+            subdir, source
+        )));
+    }
+    Ok(path)
+}
+
 /// Resolve a repository source to a local path
 ///
 /// # Arguments
 /// * `source` - Can be:
 ///   - A registered repository name (e.g., "common")
-///   - A Git URL (e.g., "https://github.com/user/repo")
+///   - A Git URL (e.g., "https://github.com/user/repo"), optionally with `#path=<subdir>`
+///   - An archive URL (e.g., "https://example.com/preset.tar.gz"), optionally with `#path=<subdir>`
 ///   - A local file path (e.g., "./my-preset")
 ///
 /// # Returns
-/// The local path to the preset directory
+/// The local path to the preset directory (the repository subdirectory if one is set)
 pub fn resolve_repository_source(source: &str) -> Result<PathBuf> {
     // Check if it's a local path (direct input)
     let local_path = PathBuf::from(source);
@@ -37,45 +171,117 @@ pub fn resolve_repository_source(source: &str) -> Result<PathBuf> {
                 // Local preset: return path directly (no caching)
                 let path = PathBuf::from(&repo.url);
                 if path.exists() {
-                    println!("Using local preset: {}", repo.url);
+                    output::info(&format!(
+                        "Using local preset: {}",
+                        output::display_path(&path)
+                    ));
                     return Ok(path);
                 } else {
-                    return Err(crate::error::AidotError::RepositoryNotFound(format!(
+                    return Err(AidotError::RepositoryNotFound(format!(
                         "Local preset path does not exist: {}",
                         repo.url
                     )));
                 }
             }
             SourceType::Git => {
-                // Git repository: use cache
-                let cache_path = cache::ensure_cached(&repo.name, &repo.url)?;
-                return Ok(cache_path);
+                // Git repository: use cache (URLs registered before normalization are normalized here)
+                let cache_path = cache::ensure_cached(&repo.name, &canonical_git_url(&repo.url))?;
+                return join_subdir(cache_path, repo.subdir.as_deref(), &repo.name);
+            }
+            SourceType::Archive => {
+                let cache_path =
+                    cache::ensure_archive_cached(&repo.name, &repo.url, repo.sha256.as_deref())?;
+                return join_subdir(cache_path, repo.subdir.as_deref(), &repo.name);
             }
         }
     }
 
-    // Check if it's a Git URL
-    if is_git_url(source) {
-        // Create a temporary name from URL
-        let repo_name = url_to_repo_name(source);
-        let cache_path = cache::ensure_cached(&repo_name, source)?;
-        return Ok(cache_path);
+    // Check if it's an archive or Git URL
+    let (url, subdir) = split_subdir(source);
+    if is_archive_url(url) {
+        let cache_path = cache::ensure_archive_cached(&url_to_repo_name(url), url, None)?;
+        return join_subdir(cache_path, subdir, url);
+    }
+    if is_git_url(url) {
+        // Create a temporary name from URL (`repo` and `repo.git/` share one cache)
+        let url = canonical_git_url(url);
+        let repo_name = url_to_repo_name(&url);
+        let cache_path = cache::ensure_cached(&repo_name, &url)?;
+        return join_subdir(cache_path, subdir, &url);
     }
 
-    Err(crate::error::AidotError::RepositoryNotFound(format!(
+    // Last: a bare name of a preset in AIDOT_PRESET_PATH
+    if let Some(path) = search_preset_path(source) {
+        output::info(&format!(
+            "Using local preset: {}",
+            output::display_path(&path)
+        ));
+        return Ok(path);
+    }
+
+    Err(AidotError::RepositoryNotFound(format!(
         "Repository '{}' not found. It must be a local path, registered repository name, or Git URL.",
         source
     )))
 }
 
-/// Convert a Git URL to a repository name for caching
+/// Whether `source` is a plain name (no path separator, drive or URL scheme)
+fn is_bare_name(source: &str) -> bool {
+    !source.is_empty() && source != "." && source != ".." && !source.contains(['/', '\\', ':'])
+}
+
+/// Presets named `name` in the directories of `search_path`, in search order
+///
+/// Only directories holding a `.aidot-config.toml` count; empty entries are ignored.
+fn find_in_preset_path(name: &str, search_path: &OsStr) -> Vec<PathBuf> {
+    std::env::split_paths(search_path)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map(|dir| dir.join(name))
+        .filter(|path| path.join(".aidot-config.toml").is_file())
+        .collect()
+}
+
+/// Locate a preset given by a bare name in `AIDOT_PRESET_PATH`
+///
+/// The first directory of the list that holds it wins, like `PATH`; matches in later
+/// directories are reported as shadowed. `None` when the variable is unset, `name`
+/// is a path or URL, or no directory holds it.
+pub fn search_preset_path(name: &str) -> Option<PathBuf> {
+    if !is_bare_name(name) {
+        return None;
+    }
+    let search_path = std::env::var_os(PRESET_PATH_ENV)?;
+    let mut found = find_in_preset_path(name, &search_path).into_iter();
+    let first = found.next()?;
+    log::info(&format!(
+        "found preset '{}' in {}: {}",
+        name,
+        PRESET_PATH_ENV,
+        first.display()
+    ));
+    for shadowed in found {
+        output::info(&format!(
+            "Note: '{}' is also in {} (shadowed by {}, which comes first in {})",
+            name,
+            output::display_path(&shadowed),
+            output::display_path(&first),
+            PRESET_PATH_ENV
+        ));
+    }
+    Some(first)
+}
+
+/// Convert a Git or archive URL to a repository name for caching
 fn url_to_repo_name(url: &str) -> String {
     // Extract repo name from URL
     // e.g., https://github.com/user/repo.git -> repo
-    url.trim_end_matches(".git")
-        .split('/')
-        .next_back()
-        .unwrap_or("temp-repo")
+    //       https://example.com/preset-1.0.tar.gz -> preset-1.0
+    let url = url.split(['?', '#']).next().unwrap_or(url);
+    let name = url.split('/').next_back().unwrap_or("temp-repo");
+    [".git", ".tar.gz", ".tgz", ".zip"]
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext))
+        .unwrap_or(name)
         .to_string()
 }
 
@@ -94,6 +300,55 @@ mod tests {
         assert!(!is_git_url("repo-name"));
     }
 
+    #[test]
+    fn test_split_subdir() {
+        assert_eq!(
+            split_subdir("https://github.com/team/presets.git#path=presets/frontend"),
+            (
+                "https://github.com/team/presets.git",
+                Some("presets/frontend")
+            )
+        );
+        assert_eq!(
+            split_subdir("https://github.com/team/presets.git"),
+            ("https://github.com/team/presets.git", None)
+        );
+        assert_eq!(
+            split_subdir("https://x/repo#path="),
+            ("https://x/repo", None)
+        );
+    }
+
+    #[test]
+    fn test_normalize_subdir() {
+        assert_eq!(
+            normalize_subdir("presets/frontend/").unwrap(),
+            "presets/frontend"
+        );
+        assert_eq!(
+            normalize_subdir("presets\\frontend").unwrap(),
+            "presets/frontend"
+        );
+        assert!(normalize_subdir("../outside").is_err());
+        assert!(normalize_subdir("/abs").is_err());
+        assert!(normalize_subdir("").is_err());
+    }
+
+    #[test]
+    fn test_join_subdir_requires_config() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        std::fs::create_dir_all(root.join("presets/frontend")).unwrap();
+
+        let err = join_subdir(root.clone(), Some("presets/frontend"), "team").unwrap_err();
+        assert!(err.to_string().contains(".aidot-config.toml"));
+
+        std::fs::write(root.join("presets/frontend/.aidot-config.toml"), "").unwrap();
+        let path = join_subdir(root.clone(), Some("presets/frontend"), "team").unwrap();
+        assert_eq!(path, root.join("presets/frontend"));
+        assert_eq!(join_subdir(root.clone(), None, "team").unwrap(), root);
+    }
+
     #[test]
     fn test_url_to_repo_name() {
         assert_eq!(url_to_repo_name("https://github.com/user/repo.git"), "repo");
@@ -102,5 +357,83 @@ mod tests {
             url_to_repo_name("git@github.com:user/my-preset.git"),
             "my-preset"
         );
+        assert_eq!(
+            url_to_repo_name("https://example.com/releases/preset-1.0.tar.gz?dl=1"),
+            "preset-1.0"
+        );
+    }
+
+    #[test]
+    fn test_normalize_git_url() {
+        let same = [
+            "https://github.com/team/presets",
+            "https://github.com/team/presets.git",
+            "https://GitHub.com/team/presets/",
+            " https://github.com/team/presets.git/ ",
+        ];
+        for url in same {
+            assert_eq!(
+                normalize_git_url(url),
+                "https://github.com/team/presets.git",
+                "{}",
+                url
+            );
+        }
+
+        assert_eq!(
+            normalize_git_url("ssh://git@github.com/team/presets"),
+            "git@github.com:team/presets.git"
+        );
+        assert_eq!(
+            normalize_git_url("git@GitHub.com:team/presets.git"),
+            "git@github.com:team/presets.git"
+        );
+        // A port only exists in ssh:// form
+        assert_eq!(
+            normalize_git_url("ssh://git@git.example.com:2222/team/presets/"),
+            "ssh://git@git.example.com:2222/team/presets"
+        );
+        // Other hosts may only serve the exact path: the suffix is left as given
+        assert_eq!(
+            normalize_git_url("https://git.example.com/team/presets"),
+            "https://git.example.com/team/presets"
+        );
+        assert_eq!(
+            normalize_git_url("git://git.example.com/presets.git"),
+            "git://git.example.com/presets.git"
+        );
+    }
+
+    #[test]
+    fn test_is_archive_url() {
+        assert!(is_archive_url("https://example.com/preset.tar.gz"));
+        assert!(is_archive_url("http://example.com/preset.zip"));
+        assert!(!is_archive_url("https://github.com/user/repo.git"));
+        assert!(!is_archive_url("./preset.tar.gz"));
+    }
+
+    #[test]
+    fn test_find_in_preset_path() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (first, second) = (temp_dir.path().join("a"), temp_dir.path().join("b"));
+        for dir in [&first, &second] {
+            std::fs::create_dir_all(dir.join("team")).unwrap();
+            std::fs::write(dir.join("team/.aidot-config.toml"), "").unwrap();
+        }
+        // Not a preset: no .aidot-config.toml
+        std::fs::create_dir_all(first.join("docs")).unwrap();
+
+        let search_path = std::env::join_paths([&first, &second]).unwrap();
+        assert_eq!(
+            find_in_preset_path("team", &search_path),
+            vec![first.join("team"), second.join("team")]
+        );
+        assert!(find_in_preset_path("docs", &search_path).is_empty());
+
+        assert!(is_bare_name("team-preset"));
+        assert!(!is_bare_name("./team"));
+        assert!(!is_bare_name("presets/team"));
+        assert!(!is_bare_name("git@github.com:team"));
+        assert!(!is_bare_name(".."));
     }
 }
@@ -0,0 +1,152 @@
+use crate::config::Config;
+use crate::error::{AidotError, Result};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use sha2::Sha256;
+use std::fs;
+use std::path::PathBuf;
+
+/// Suffix of encrypted preset files (e.g., settings/secrets.json.enc → settings/secrets.json)
+pub const ENCRYPTED_SUFFIX: &str = ".enc";
+/// Environment variable holding the shared secret key
+pub const KEY_ENV: &str = "AIDOT_SECRET_KEY";
+
+/// Header of the encrypted file format
+///
+/// An encrypted file is one line: `aidot-enc:v2:` followed by base64 of
+/// `salt (16) || nonce (12) || ciphertext || tag (16)`.
+/// The key is derived from the secret key with PBKDF2-HMAC-SHA256 over the salt, and
+/// the content is sealed with AES-256-GCM using the header as associated data.
+const HEADER: &str = "aidot-enc:v2:";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const PBKDF2_ROUNDS: u32 = 100_000;
+
+/// Key file used when AIDOT_SECRET_KEY is not set (~/.aidot/secret.key)
+pub fn key_file() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("secret.key"))
+}
+
+/// Shared secret key from AIDOT_SECRET_KEY or ~/.aidot/secret.key (None if neither is set)
+pub fn load_key() -> Result<Option<String>> {
+    if let Ok(key) = std::env::var(KEY_ENV) {
+        if !key.trim().is_empty() {
+            return Ok(Some(key.trim().to_string()));
+        }
+    }
+    let path = key_file()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let key = fs::read_to_string(&path)?;
+    if key.trim().is_empty() {
+        return Err(AidotError::Secret(format!("{} is empty", path.display())));
+    }
+    Ok(Some(key.trim().to_string()))
+}
+
+/// Shared secret key, or an error explaining where to set it
+pub fn require_key() -> Result<String> {
+    load_key()?.ok_or_else(|| {
+        AidotError::Secret(format!(
+            "No secret key: set {} or write the key to ~/.aidot/secret.key",
+            KEY_ENV
+        ))
+    })
+}
+
+/// Encrypt text into the aidot-enc:v2 format
+pub fn encrypt(plaintext: &str, key: &str) -> Result<String> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut salt)
+        .and_then(|_| getrandom::getrandom(&mut nonce))
+        .map_err(|e| AidotError::Secret(format!("No random source: {}", e)))?;
+
+    let sealed = cipher(key, &salt)
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext.as_bytes(),
+                aad: HEADER.as_bytes(),
+            },
+        )
+        .map_err(|_| AidotError::Secret("encryption failed".to_string()))?;
+
+    let mut payload = Vec::with_capacity(SALT_LEN + NONCE_LEN + sealed.len());
+    payload.extend_from_slice(&salt);
+    payload.extend_from_slice(&nonce);
+    payload.extend_from_slice(&sealed);
+
+    Ok(format!("{}{}\n", HEADER, BASE64.encode(payload)))
+}
+
+/// Decrypt an aidot-enc:v2 file (fails on a wrong key or modified content)
+pub fn decrypt(encrypted: &str, key: &str) -> Result<String> {
+    let encoded = encrypted
+        .trim()
+        .strip_prefix(HEADER)
+        .ok_or_else(|| AidotError::Secret("not an aidot-enc:v2 file".to_string()))?;
+    let payload = BASE64
+        .decode(encoded)
+        .map_err(|_| AidotError::Secret("invalid base64 content".to_string()))?;
+    if payload.len() < SALT_LEN + NONCE_LEN + TAG_LEN {
+        return Err(AidotError::Secret("content is truncated".to_string()));
+    }
+
+    let (salt, rest) = payload.split_at(SALT_LEN);
+    let (nonce, sealed) = rest.split_at(NONCE_LEN);
+    let data = cipher(key, salt)
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: sealed,
+                aad: HEADER.as_bytes(),
+            },
+        )
+        .map_err(|_| AidotError::Secret("wrong key or modified file".to_string()))?;
+    String::from_utf8(data).map_err(|_| AidotError::Secret("content is not UTF-8".to_string()))
+}
+
+/// AES-256-GCM keyed with PBKDF2-HMAC-SHA256 of the secret key over `salt`
+fn cipher(key: &str, salt: &[u8]) -> Aes256Gcm {
+    let mut derived = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(key.as_bytes(), salt, PBKDF2_ROUNDS, &mut derived);
+    Aes256Gcm::new(&derived.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let plaintext = "{\n  \"env\": { \"GITHUB_TOKEN\": \"ghp_secret\" }\n}\n";
+        let encrypted = encrypt(plaintext, "team-key").unwrap();
+        assert!(encrypted.starts_with(HEADER));
+        assert!(!encrypted.contains("ghp_secret"));
+        assert_eq!(decrypt(&encrypted, "team-key").unwrap(), plaintext);
+
+        // Random salt/nonce: the same text never encrypts to the same file
+        assert_ne!(encrypt(plaintext, "team-key").unwrap(), encrypted);
+    }
+
+    #[test]
+    fn test_decrypt_rejects_wrong_key_and_tampering() {
+        let encrypted = encrypt("secret", "team-key").unwrap();
+        assert!(decrypt(&encrypted, "other-key").is_err());
+
+        let mut payload = BASE64
+            .decode(encrypted.trim().strip_prefix(HEADER).unwrap())
+            .unwrap();
+        payload[SALT_LEN + NONCE_LEN] ^= 1;
+        let tampered = format!("{}{}", HEADER, BASE64.encode(payload));
+        assert!(decrypt(&tampered, "team-key").is_err());
+
+        assert!(decrypt("plain text", "team-key").is_err());
+        assert!(decrypt("aidot-enc:v2:AAAA", "team-key").is_err());
+    }
+}
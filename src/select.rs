@@ -0,0 +1,122 @@
+use colored::Colorize;
+use console::{Key, Term};
+use std::io;
+
+/// Checkbox list state: cursor position and which items are checked
+#[derive(Debug)]
+struct Selection {
+    checked: Vec<bool>,
+    cursor: usize,
+}
+
+/// What a key press did to the selection
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Continue,
+    Confirm,
+    Cancel,
+}
+
+impl Selection {
+    fn new(len: usize) -> Self {
+        Self {
+            checked: vec![true; len],
+            cursor: 0,
+        }
+    }
+
+    fn handle(&mut self, key: &Key) -> Outcome {
+        let len = self.checked.len();
+        match key {
+            Key::ArrowUp | Key::Char('k') => self.cursor = (self.cursor + len - 1) % len,
+            Key::ArrowDown | Key::Char('j') => self.cursor = (self.cursor + 1) % len,
+            Key::Char(' ') => self.checked[self.cursor] = !self.checked[self.cursor],
+            // Toggle all: check everything unless everything is already checked
+            Key::Char('a') => {
+                let all = self.checked.iter().all(|c| *c);
+                self.checked.iter_mut().for_each(|c| *c = !all);
+            }
+            Key::Enter => return Outcome::Confirm,
+            Key::Escape | Key::Char('q') => return Outcome::Cancel,
+            _ => {}
+        }
+        Outcome::Continue
+    }
+}
+
+/// Let the user check or uncheck items with the keyboard (all start checked)
+///
+/// ↑/↓ (or k/j) move, space toggles, `a` toggles all, enter confirms, q/esc cancels.
+/// Returns which items are checked, or None when cancelled.
+pub fn multi_select(prompt: &str, items: &[String]) -> io::Result<Option<Vec<bool>>> {
+    let mut selection = Selection::new(items.len());
+    if items.is_empty() {
+        return Ok(Some(selection.checked));
+    }
+
+    let term = Term::stdout();
+    println!(
+        "{} {}",
+        prompt.cyan(),
+        "(↑/↓ move, space toggle, a all, enter confirm, q cancel)".dimmed()
+    );
+    term.hide_cursor()?;
+    let outcome = loop {
+        for (i, item) in items.iter().enumerate() {
+            let marker = if i == selection.cursor { ">" } else { " " };
+            let checkbox = if selection.checked[i] { "[x]" } else { "[ ]" };
+            let line = format!("{} {} {}", marker, checkbox, item);
+            if i == selection.cursor {
+                term.write_line(&line.bold().to_string())?;
+            } else {
+                term.write_line(&line)?;
+            }
+        }
+        let key = match term.read_key() {
+            Ok(key) => key,
+            Err(e) => {
+                term.show_cursor()?;
+                return Err(e);
+            }
+        };
+        match selection.handle(&key) {
+            Outcome::Continue => term.clear_last_lines(items.len())?,
+            outcome => break outcome,
+        }
+    };
+    term.show_cursor()?;
+
+    Ok(match outcome {
+        Outcome::Confirm => Some(selection.checked),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_keys() {
+        let mut selection = Selection::new(3);
+        assert_eq!(selection.checked, vec![true, true, true]);
+
+        selection.handle(&Key::ArrowDown);
+        assert_eq!(selection.handle(&Key::Char(' ')), Outcome::Continue);
+        assert_eq!(selection.checked, vec![true, false, true]);
+
+        // The cursor wraps around
+        selection.handle(&Key::ArrowUp);
+        selection.handle(&Key::ArrowUp);
+        assert_eq!(selection.cursor, 2);
+
+        // Not everything checked: `a` checks all, then unchecks all
+        selection.handle(&Key::Char('a'));
+        assert_eq!(selection.checked, vec![true, true, true]);
+        selection.handle(&Key::Char('a'));
+        assert_eq!(selection.checked, vec![false, false, false]);
+
+        assert_eq!(selection.handle(&Key::Enter), Outcome::Confirm);
+        assert_eq!(selection.handle(&Key::Escape), Outcome::Cancel);
+    }
+}
@@ -9,11 +9,28 @@ use std::fs;
 use std::process::Command;
 use tempfile::TempDir;
 
-/// Helper to run aidot command
+thread_local! {
+    /// Home directory of the running test (each test runs on its own thread), so
+    /// state, logs and caches written under ~/.aidot never reach the real home
+    static TEST_HOME: TempDir = TempDir::new().unwrap();
+}
+
+/// Helper to run aidot command (with the test's own home directory)
 fn run_aidot(args: &[&str], cwd: &std::path::Path) -> std::process::Output {
+    TEST_HOME.with(|home| run_aidot_with_home(args, cwd, home.path()))
+}
+
+/// Helper to run aidot command with an isolated home directory (~/.aidot)
+fn run_aidot_with_home(
+    args: &[&str],
+    cwd: &std::path::Path,
+    home: &std::path::Path,
+) -> std::process::Output {
     Command::new(env!("CARGO_BIN_EXE_aidot"))
         .args(args)
         .current_dir(cwd)
+        .env("HOME", home)
+        .env("USERPROFILE", home)
         .output()
         .expect("Failed to execute aidot")
 }
@@ -88,6 +105,28 @@ fn test_init_with_force_overwrites() {
     assert!(content.contains("[metadata]"));
 }
 
+#[test]
+fn test_init_in_project_with_tool_settings_asks_first() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join(".claude")).unwrap();
+
+    // No answer on stdin: nothing is created
+    let output = run_aidot(&["init"], temp_dir.path());
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("already has LLM tool settings (.claude)"));
+    assert!(stdout.contains("--from-existing"));
+    assert!(stdout.contains("Operation cancelled."));
+    assert!(!temp_dir.path().join(".aidot-config.toml").exists());
+    assert!(!temp_dir.path().join("rules").exists());
+
+    // --force skips the warning
+    let output = run_aidot(&["init", "--force"], temp_dir.path());
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Warning:"));
+    assert!(temp_dir.path().join(".aidot-config.toml").exists());
+}
+
 #[test]
 fn test_detect_command() {
     let temp_dir = TempDir::new().unwrap();
@@ -102,6 +141,47 @@ fn test_detect_command() {
     assert!(stdout.contains("Claude Code") || stdout.contains("detected"));
 }
 
+#[test]
+fn test_detect_and_status_global() {
+    let home_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    fs::create_dir_all(home_dir.path().join(".claude/commands")).unwrap();
+    fs::write(home_dir.path().join(".claude/settings.json"), "{}").unwrap();
+
+    let output = run_aidot_with_home(&["detect", "--global"], project_dir.path(), home_dir.path());
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Global configuration"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(
+        stdout.contains("Claude Code (~/.claude)"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(
+        stdout.contains("Cursor (not found: ~/.cursor)"),
+        "stdout: {}",
+        stdout
+    );
+
+    // Without --global the home directory is not inspected
+    let output = run_aidot_with_home(&["detect"], project_dir.path(), home_dir.path());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Global configuration"));
+
+    let output = run_aidot_with_home(&["status", "--global"], project_dir.path(), home_dir.path());
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Global Configuration"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(stdout.contains("settings.json"), "stdout: {}", stdout);
+}
+
 #[test]
 fn test_pull_from_local_preset() {
     let _temp_dir = TempDir::new().unwrap();
@@ -130,6 +210,54 @@ fn test_pull_from_local_preset() {
     assert!(project_dir.path().join(".claude").exists());
 }
 
+#[test]
+fn test_pull_custom_section_directories() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+
+    fs::write(
+        preset_dir.path().join(".aidot-config.toml"),
+        r#"[metadata]
+name = "custom-dirs"
+version = "1.0.0"
+
+[rules]
+directory = "guidelines/"
+
+[commands]
+directory = "prompts/"
+"#,
+    )
+    .unwrap();
+    fs::create_dir_all(preset_dir.path().join("guidelines/backend")).unwrap();
+    fs::create_dir_all(preset_dir.path().join("prompts")).unwrap();
+    fs::write(
+        preset_dir.path().join("guidelines/backend/style.md"),
+        "# Style",
+    )
+    .unwrap();
+    fs::write(preset_dir.path().join("prompts/build.md"), "# Build").unwrap();
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let output = run_aidot(
+        &["pull", preset_dir.path().to_str().unwrap(), "--force"],
+        project_dir.path(),
+    );
+    assert!(
+        output.status.success(),
+        "pull should succeed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let claude = project_dir.path().join(".claude");
+    assert_eq!(
+        fs::read_to_string(claude.join("rules/backend/style.md")).unwrap(),
+        "# Style"
+    );
+    assert!(claude.join("commands/build.md").exists());
+    assert!(!claude.join("rules/guidelines").exists());
+}
+
 #[test]
 fn test_pull_dry_run() {
     let preset_dir = TempDir::new().unwrap();
@@ -184,6 +312,164 @@ fn test_diff_command() {
     );
 }
 
+#[test]
+fn test_diff_with_tools_filter() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+
+    create_test_preset(preset_dir.path());
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+
+    // Cursor is not set up, but --tools compares it anyway (like pull)
+    let output = run_aidot(&["diff", preset, "--tools", "cursor"], project_dir.path());
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("═══ Cursor ═══"));
+    assert!(!stdout.contains("═══ Claude Code ═══"));
+    assert!(stdout.contains("Cursor is not set up in this project"));
+    assert!(stdout.contains("--tools cursor"));
+
+    // Unknown names fail like in pull
+    let output = run_aidot(&["diff", preset, "--tools", "cursr"], project_dir.path());
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_render_writes_tool_trees_without_project() {
+    let preset_dir = TempDir::new().unwrap();
+    let work_dir = TempDir::new().unwrap();
+    create_test_preset(preset_dir.path());
+    let preset = preset_dir.path().to_str().unwrap();
+    let out = work_dir.path().join("out");
+    let out_arg = out.to_str().unwrap();
+
+    let output = run_aidot(
+        &[
+            "render",
+            preset,
+            "--out",
+            out_arg,
+            "--tools",
+            "claude,cursor",
+        ],
+        work_dir.path(),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(out.join(".claude/rules/test.md").exists());
+    assert!(out.join(".claude/CLAUDE.md").exists());
+    assert!(out.join(".cursor/rules/test.md").exists());
+    assert!(!out.join(".github").exists());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(".claude/rules/test.md"));
+
+    // A second render into the same directory is refused
+    let output = run_aidot(&["render", preset, "--out", out_arg], work_dir.path());
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not empty"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_diff_reports_mode_changes() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+    create_test_preset(preset_dir.path());
+    let chmod = |path: &std::path::Path, mode: u32| {
+        fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+    };
+    chmod(&preset_dir.path().join("commands/build.md"), 0o755);
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let output = run_aidot_with_home(&["pull", preset], project_dir.path(), home_dir.path());
+    assert!(output.status.success());
+    let summary = |home: &std::path::Path| -> serde_json::Value {
+        let output = run_aidot_with_home(&["diff", preset, "--json"], project_dir.path(), home);
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+    let before = summary(home_dir.path());
+    assert_eq!(before["mode_changed"], 0);
+
+    // Same content without the executable bit: reported separately from modified
+    let applied = project_dir.path().join(".claude/commands/build.md");
+    chmod(&applied, 0o644);
+    let counts = summary(home_dir.path());
+    assert_eq!(counts["mode_changed"], 1);
+    assert_eq!(counts["modified"], before["modified"]);
+    let output = run_aidot_with_home(&["diff", preset], project_dir.path(), home_dir.path());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Mode changed files"));
+
+    // Permissions are not managed for non-executable preset files
+    chmod(&project_dir.path().join(".claude/rules/test.md"), 0o755);
+    assert_eq!(summary(home_dir.path())["mode_changed"], 1);
+
+    // pull restores the bit
+    let output = run_aidot_with_home(&["pull", preset], project_dir.path(), home_dir.path());
+    assert!(output.status.success());
+    assert_eq!(summary(home_dir.path())["mode_changed"], 0);
+}
+
+#[test]
+fn test_diff_compares_merged_files_by_content() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+    create_test_preset(preset_dir.path());
+    fs::create_dir_all(project_dir.path().join(".cursor")).unwrap();
+    fs::create_dir_all(project_dir.path().join(".github")).unwrap();
+
+    let pull = || {
+        let output = run_aidot_with_home(
+            &["pull", preset, "--force"],
+            project_dir.path(),
+            home_dir.path(),
+        );
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    };
+    let summary = || -> serde_json::Value {
+        let output = run_aidot_with_home(
+            &["diff", preset, "--json", "--by-section"],
+            project_dir.path(),
+            home_dir.path(),
+        );
+        serde_json::from_slice(&output.stdout).unwrap()
+    };
+    pull();
+    let cursorrules = project_dir.path().join(".cursorrules");
+    let applied = fs::read_to_string(&cursorrules).unwrap();
+
+    // Appended memory is not appended again, so an applied project is up to date
+    pull();
+    assert_eq!(fs::read_to_string(&cursorrules).unwrap(), applied);
+    let counts = summary();
+    assert_eq!(counts["modified"], 0, "{}", counts);
+    assert_eq!(counts["new"], 0, "{}", counts);
+
+    // Only the preset's own memory block counts as applied
+    fs::write(
+        preset_dir.path().join("memory/context.md"),
+        "# Context\n\nUpdated context info.",
+    )
+    .unwrap();
+    // One memory file per tool (Cursor, Copilot, and Claude Code if detected)
+    let counts = summary();
+    assert!(counts["modified"].as_u64().unwrap() >= 2, "{}", counts);
+    assert_eq!(counts["modified"], counts["sections"]["memory"]["modified"]);
+}
+
 #[test]
 fn test_status_command() {
     let temp_dir = TempDir::new().unwrap();
@@ -199,6 +485,182 @@ fn test_status_command() {
     assert!(stdout.contains("Claude") || stdout.contains("Cursor") || stdout.contains("detected"));
 }
 
+#[test]
+fn test_status_tree_limits() {
+    let temp_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    let commands = temp_dir.path().join(".claude/commands");
+    fs::create_dir_all(commands.join("build")).unwrap();
+    for i in 1..=6 {
+        fs::write(commands.join(format!("cmd{}.md", i)), "# Cmd").unwrap();
+    }
+    fs::write(commands.join("build/staging.md"), "# Staging").unwrap();
+
+    // Default: one level, 5 entries, the rest summarized
+    let output = run_aidot_with_home(&["status"], temp_dir.path(), home_dir.path());
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("cmd4.md"), "stdout: {}", stdout);
+    assert!(!stdout.contains("cmd6.md"), "stdout: {}", stdout);
+    assert!(stdout.contains("(+2 more)"), "stdout: {}", stdout);
+    assert!(!stdout.contains("staging.md"), "stdout: {}", stdout);
+
+    // --all: every entry of the whole tree
+    let output = run_aidot_with_home(&["status", "--all"], temp_dir.path(), home_dir.path());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("cmd6.md"), "stdout: {}", stdout);
+    assert!(stdout.contains("staging.md"), "stdout: {}", stdout);
+    assert!(!stdout.contains("more)"), "stdout: {}", stdout);
+
+    // --depth 2 goes one level further, still 5 entries per directory
+    let output = run_aidot_with_home(
+        &["status", "--depth", "2"],
+        temp_dir.path(),
+        home_dir.path(),
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("staging.md"), "stdout: {}", stdout);
+    assert!(stdout.contains("(+2 more)"), "stdout: {}", stdout);
+}
+
+#[test]
+fn test_status_verbose_shows_repository_versions() {
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    create_test_preset(preset_dir.path());
+    let home = home_dir.path();
+    let preset = preset_dir.path().to_str().unwrap();
+    for args in [
+        vec!["repo", "add", "local", preset, "--local"],
+        vec!["repo", "add", "team", "https://github.com/team/presets.git"],
+    ] {
+        assert!(run_aidot_with_home(&args, project_dir.path(), home)
+            .status
+            .success());
+    }
+
+    let output = run_aidot_with_home(&["status"], project_dir.path(), home);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("version 1.0.0"), "stdout: {}", stdout);
+
+    let output = run_aidot_with_home(&["-v", "status"], project_dir.path(), home);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("version 1.0.0"), "stdout: {}", stdout);
+    assert!(stdout.contains("not cached"), "stdout: {}", stdout);
+}
+
+#[test]
+fn test_pull_force_twice_is_idempotent() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    create_test_preset(preset_dir.path());
+    for (dir, file, content) in [
+        ("memory", "memory/team.md", "# Team\n\nShared notes\n"),
+        ("settings", "settings/base.json", "{\"model\": \"fast\"}\n"),
+        ("mcp", "mcp/github.json", "{\"command\": \"gh-mcp\"}\n"),
+    ] {
+        fs::create_dir_all(preset_dir.path().join(dir)).unwrap();
+        fs::write(preset_dir.path().join(file), content).unwrap();
+    }
+    let mut config = fs::read_to_string(preset_dir.path().join(".aidot-config.toml")).unwrap();
+    config.push_str("\n[settings]\ndirectory = \"settings/\"\n\n[mcp]\ndirectory = \"mcp/\"\n");
+    fs::write(preset_dir.path().join(".aidot-config.toml"), config).unwrap();
+
+    let preset = preset_dir.path().to_str().unwrap();
+    let pull = || {
+        let output = run_aidot_with_home(
+            &[
+                "pull",
+                preset,
+                "--tools",
+                "claude,cursor,copilot,amazonq",
+                "--force",
+                "--report",
+                "report.json",
+            ],
+            project_dir.path(),
+            home_dir.path(),
+        );
+        assert!(output.status.success(), "{:?}", output);
+        let report: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(project_dir.path().join("report.json")).unwrap(),
+        )
+        .unwrap();
+        report["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| {
+                (
+                    e["path"].as_str().unwrap().to_string(),
+                    e["status"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    let read = |path: &str| fs::read_to_string(project_dir.path().join(path)).unwrap();
+    let shared = [".cursorrules", ".github/copilot-instructions.md"];
+
+    pull();
+    let first: Vec<String> = shared.iter().map(|path| read(path)).collect();
+    let changed: Vec<_> = pull()
+        .into_iter()
+        .filter(|(_, status)| status != "unchanged")
+        .collect();
+    assert!(
+        changed.is_empty(),
+        "changed on the second pull: {:?}",
+        changed
+    );
+    let second: Vec<String> = shared.iter().map(|path| read(path)).collect();
+    assert_eq!(first, second);
+
+    // An edited memory file replaces its earlier text instead of appending a copy
+    fs::write(
+        preset_dir.path().join("memory/team.md"),
+        "# Team\n\nUpdated notes\n",
+    )
+    .unwrap();
+    pull();
+    for path in shared {
+        let content = read(path);
+        assert!(content.contains("Updated notes"), "{}: {}", path, content);
+        assert!(!content.contains("Shared notes"), "{}: {}", path, content);
+    }
+    assert!(pull().iter().all(|(_, status)| status == "unchanged"));
+
+    // A renamed or deleted memory file takes its block with it
+    fs::rename(
+        preset_dir.path().join("memory/team.md"),
+        preset_dir.path().join("memory/crew.md"),
+    )
+    .unwrap();
+    fs::remove_file(preset_dir.path().join("memory/context.md")).unwrap();
+    pull();
+    for path in shared {
+        let content = read(path);
+        assert_eq!(
+            content.matches("Updated notes").count(),
+            1,
+            "{}: {}",
+            path,
+            content
+        );
+        assert!(content.contains(" crew\n"), "{}: {}", path, content);
+        assert!(
+            !content.contains("Project context info"),
+            "{}: {}",
+            path,
+            content
+        );
+        assert_eq!(content.matches("<!-- aidot:memory:").count(), 1);
+    }
+    assert!(pull().iter().all(|(_, status)| status == "unchanged"));
+}
+
 #[test]
 fn test_repo_list_empty() {
     let temp_dir = TempDir::new().unwrap();
@@ -238,6 +700,116 @@ fn test_pull_with_tools_filter() {
     assert!(stdout.contains("Claude") || output.status.success());
 }
 
+#[test]
+fn test_pull_exclude_tools() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+
+    create_test_preset(preset_dir.path());
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+    fs::create_dir_all(project_dir.path().join(".cursor")).unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+
+    let output = run_aidot(
+        &["pull", preset, "--dry-run", "--exclude-tools", "cursor"],
+        project_dir.path(),
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("  - Claude Code"));
+    assert!(!stdout.contains("  - Cursor"));
+
+    // A tool may not be both selected and excluded
+    let output = run_aidot(
+        &[
+            "pull",
+            preset,
+            "--dry-run",
+            "--tools",
+            "claude,cursor",
+            "--exclude-tools",
+            "cursor",
+        ],
+        project_dir.path(),
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("both --tools and --exclude-tools"));
+
+    // Excluding everything detected is not an error
+    let output = run_aidot(
+        &[
+            "pull",
+            preset,
+            "--exclude-tools",
+            "claude,cursor,copilot,amazonq",
+        ],
+        project_dir.path(),
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("All detected tools are excluded"));
+    assert!(!project_dir.path().join(".claude/CLAUDE.md").exists());
+}
+
+#[test]
+fn test_pull_tool_order_from_config_and_tools_flag() {
+    let home_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+
+    create_test_preset(preset_dir.path());
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+    fs::create_dir_all(project_dir.path().join(".cursor")).unwrap();
+    fs::create_dir_all(home_dir.path().join(".aidot")).unwrap();
+    fs::write(
+        home_dir.path().join(".aidot/config.toml"),
+        "tool_order = [\"cursor\"]\n",
+    )
+    .unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+    let position = |stdout: &str, tool: &str| stdout.find(&format!("  - {}", tool)).unwrap();
+
+    // Detected tools follow tool_order, unlisted ones come after
+    let output = run_aidot_with_home(
+        &["pull", preset, "--dry-run"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(position(&stdout, "Cursor") < position(&stdout, "Claude Code"));
+
+    // --tools order wins
+    let output = run_aidot_with_home(
+        &["pull", preset, "--dry-run", "--tools", "claude,cursor"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(position(&stdout, "Claude Code") < position(&stdout, "Cursor"));
+
+    // An unknown name in tool_order is reported as a config error
+    fs::write(
+        home_dir.path().join(".aidot/config.toml"),
+        "tool_order = [\"cursr\"]\n",
+    )
+    .unwrap();
+    let output = run_aidot_with_home(
+        &["pull", preset, "--dry-run"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("tool_order"), "stderr: {}", stderr);
+    assert!(
+        stderr.contains("Did you mean 'cursor'?"),
+        "stderr: {}",
+        stderr
+    );
+}
+
 #[test]
 fn test_help_command() {
     let temp_dir = TempDir::new().unwrap();
@@ -261,3 +833,2691 @@ fn test_version_command() {
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("aidot") || stdout.contains("0.1"));
 }
+
+#[test]
+fn test_pull_with_ambiguous_tools_filter() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+
+    create_test_preset(preset_dir.path());
+
+    // "c" matches both Claude Code and Cursor
+    let output = run_aidot(
+        &["pull", preset_dir.path().to_str().unwrap(), "--tools", "c"],
+        project_dir.path(),
+    );
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Ambiguous"), "stderr: {}", stderr);
+    assert!(!project_dir.path().join(".cursor").exists());
+}
+
+#[test]
+fn test_pull_with_mistyped_tool_suggests_closest() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+
+    create_test_preset(preset_dir.path());
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let output = run_aidot(
+        &[
+            "pull",
+            preset_dir.path().to_str().unwrap(),
+            "--tools",
+            "cluade",
+        ],
+        project_dir.path(),
+    );
+
+    // The suggestion is only a hint: nothing is applied
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Did you mean 'claude'?"),
+        "stderr: {}",
+        stderr
+    );
+    assert!(!project_dir.path().join(".claude/rules").exists());
+}
+
+#[test]
+fn test_pull_tools_filter_undetected_requires_confirmation() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+
+    create_test_preset(preset_dir.path());
+
+    // Cursor is not set up: without --yes the prompt reads EOF and cancels
+    let output = run_aidot(
+        &[
+            "pull",
+            preset_dir.path().to_str().unwrap(),
+            "--tools",
+            "cursor",
+        ],
+        project_dir.path(),
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("(will be created)"));
+    assert!(stdout.contains("cancelled"));
+    assert!(!project_dir.path().join(".cursor").exists());
+
+    // With --yes the directory is created
+    let output = run_aidot(
+        &[
+            "pull",
+            preset_dir.path().to_str().unwrap(),
+            "--tools",
+            "cursor",
+            "--yes",
+        ],
+        project_dir.path(),
+    );
+    assert!(output.status.success());
+    assert!(project_dir.path().join(".cursor").exists());
+}
+
+#[test]
+fn test_repo_set_default_value_optional() {
+    let temp_dir = TempDir::new().unwrap();
+
+    // Value may be omitted, --off conflicts with an explicit value
+    let output = run_aidot(&["repo", "set-default", "--help"], temp_dir.path());
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[VALUE]"));
+    assert!(stdout.contains("--off"));
+
+    let output = run_aidot(
+        &["repo", "set-default", "missing-repo", "true", "--off"],
+        temp_dir.path(),
+    );
+    assert!(!output.status.success());
+
+    let output = run_aidot(&["repo", "unset-default", "--help"], temp_dir.path());
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_repo_set_and_unset_default() {
+    let preset_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    create_test_preset(preset_dir.path());
+    let preset = preset_dir.path().to_str().unwrap();
+
+    let run = |args: &[&str]| {
+        let output = run_aidot_with_home(args, preset_dir.path(), home_dir.path());
+        assert!(output.status.success(), "{:?}: {:?}", args, output);
+    };
+    let is_default = || {
+        let config: toml::Value = toml::from_str(
+            &fs::read_to_string(home_dir.path().join(".aidot/config.toml")).unwrap(),
+        )
+        .unwrap();
+        let repo = &config["repositories"].as_array().unwrap()[0];
+        assert_eq!(repo["name"].as_str(), Some("team"));
+        repo["default"].as_bool().unwrap()
+    };
+
+    run(&["repo", "add", "team", preset, "--local"]);
+    assert!(!is_default());
+
+    // Without a value the repository becomes a default
+    run(&["repo", "set-default", "team"]);
+    assert!(is_default());
+    run(&["repo", "set-default", "team", "--off"]);
+    assert!(!is_default());
+    run(&["repo", "set-default", "team", "true"]);
+    assert!(is_default());
+    run(&["repo", "unset-default", "team"]);
+    assert!(!is_default());
+
+    let output = run_aidot_with_home(
+        &["repo", "set-default", "missing-repo"],
+        preset_dir.path(),
+        home_dir.path(),
+    );
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_pull_normalizes_bom_and_line_endings() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+
+    create_test_preset(preset_dir.path());
+    fs::write(
+        preset_dir.path().join("commands/build.md"),
+        "\u{feff}# Build\r\n\r\nBuild command.\r\n",
+    )
+    .unwrap();
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    // Default: BOM removed, CRLF → LF
+    let output = run_aidot(
+        &["pull", preset_dir.path().to_str().unwrap(), "--force"],
+        project_dir.path(),
+    );
+    assert!(output.status.success());
+    let written = fs::read_to_string(project_dir.path().join(".claude/commands/build.md")).unwrap();
+    assert_eq!(written, "# Build\n\nBuild command.\n");
+
+    // --line-endings crlf forces CRLF
+    let output = run_aidot(
+        &[
+            "pull",
+            preset_dir.path().to_str().unwrap(),
+            "--force",
+            "--line-endings",
+            "crlf",
+        ],
+        project_dir.path(),
+    );
+    assert!(output.status.success());
+    let written = fs::read_to_string(project_dir.path().join(".claude/commands/build.md")).unwrap();
+    assert_eq!(written, "# Build\r\n\r\nBuild command.\r\n");
+}
+
+#[test]
+fn test_pull_detects_files_modified_since_apply() {
+    let home_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+
+    create_test_preset(preset_dir.path());
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let output = run_aidot_with_home(
+        &["pull", preset, "--force"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+
+    // The manifest records the hash of each applied file
+    let state_dir = home_dir.path().join(".aidot/state");
+    let manifest_file = fs::read_dir(&state_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.file_name().unwrap() != "history.json")
+        .unwrap();
+    let manifest = fs::read_to_string(manifest_file).unwrap();
+    assert!(manifest.contains("\"hash\""), "manifest: {}", manifest);
+
+    fs::write(
+        project_dir.path().join(".claude/commands/build.md"),
+        "# Mine",
+    )
+    .unwrap();
+    fs::write(preset_dir.path().join("rules/test.md"), "# Test Rule v2").unwrap();
+
+    let output = run_aidot_with_home(
+        &["pull", preset, "--dry-run"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = |path: &str| {
+        stdout
+            .lines()
+            .find(|l| l.contains(path))
+            .unwrap_or_default()
+            .to_string()
+    };
+    // Edited locally vs. only changed in the preset
+    assert!(
+        line("commands/build.md").contains("modified locally"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(!line("rules/test.md").contains("modified locally"));
+}
+
+#[test]
+fn test_pull_global_applies_to_home_directory() {
+    let home_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+
+    create_test_preset(preset_dir.path());
+    fs::create_dir_all(home_dir.path().join(".claude")).unwrap();
+
+    let output = run_aidot_with_home(
+        &["pull", preset, "--global", "--yes"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "stdout: {}", stdout);
+    assert!(
+        stdout.contains("affects every project"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(home_dir.path().join(".claude/commands/build.md").exists());
+    assert!(home_dir.path().join(".claude/CLAUDE.md").exists());
+    assert!(!project_dir.path().join(".claude").exists());
+
+    // Copilot has no global layout aidot can write
+    let output = run_aidot_with_home(
+        &["pull", preset, "--global", "--yes", "--tools", "copilot"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be applied with --global"));
+
+    // A plain pull in the home directory would change the global configuration too
+    let output = run_aidot_with_home(&["pull", preset, "--yes"], home_dir.path(), home_dir.path());
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--global"));
+}
+
+#[test]
+fn test_pull_writes_run_log() {
+    let home_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+
+    create_test_preset(preset_dir.path());
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let output = run_aidot_with_home(
+        &["pull", preset, "--force"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+
+    let logs: Vec<_> = fs::read_dir(home_dir.path().join(".aidot/logs"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert_eq!(logs.len(), 1);
+    let log = fs::read_to_string(&logs[0]).unwrap();
+    assert!(log.contains("tools: Claude Code"), "log: {}", log);
+    assert!(
+        log.contains("[Claude Code] created .claude/commands/build.md"),
+        "log: {}",
+        log
+    );
+
+    // --log chooses the file; errors end up in it too
+    let log_path = project_dir.path().join("aidot.log");
+    let output = run_aidot_with_home(
+        &["pull", "no-such-repo", "--log", log_path.to_str().unwrap()],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(!output.status.success());
+    let log = fs::read_to_string(&log_path).unwrap();
+    assert!(log.contains("ERROR"), "log: {}", log);
+}
+
+#[test]
+fn test_orphaned_files_prune_and_unapply() {
+    let home_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+
+    create_test_preset(preset_dir.path());
+    fs::write(preset_dir.path().join("commands/old.md"), "# Old").unwrap();
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let output = run_aidot_with_home(
+        &["pull", preset, "--force"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+    let old_cmd = project_dir.path().join(".claude/commands/old.md");
+    assert!(old_cmd.exists());
+
+    // Removed from preset → reported as orphaned, kept without --prune
+    fs::remove_file(preset_dir.path().join("commands/old.md")).unwrap();
+    let output = run_aidot_with_home(&["diff", preset], project_dir.path(), home_dir.path());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Orphaned"), "stdout: {}", stdout);
+    assert!(stdout.contains("--prune"));
+
+    let output = run_aidot_with_home(
+        &["pull", preset, "--force"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+    assert!(old_cmd.exists());
+
+    let output = run_aidot_with_home(
+        &["pull", preset, "--force", "--prune"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+    assert!(!old_cmd.exists());
+
+    // A second preset applying the same rule keeps it recorded
+    let common_dir = TempDir::new().unwrap();
+    let common = common_dir.path().to_str().unwrap();
+    create_test_preset(common_dir.path());
+    fs::remove_file(common_dir.path().join("commands/build.md")).unwrap();
+    fs::write(
+        common_dir.path().join("rules/shared.md"),
+        "# Shared\n\nShared rule.",
+    )
+    .unwrap();
+    fs::write(
+        preset_dir.path().join("rules/shared.md"),
+        "# Shared\n\nShared rule.",
+    )
+    .unwrap();
+    fs::write(
+        preset_dir.path().join(".aidot-config.toml"),
+        fs::read_to_string(preset_dir.path().join(".aidot-config.toml"))
+            .unwrap()
+            .replace(
+                "[\"rules/test.md\"]",
+                "[\"rules/test.md\", \"rules/shared.md\"]",
+            ),
+    )
+    .unwrap();
+    fs::write(
+        common_dir.path().join(".aidot-config.toml"),
+        fs::read_to_string(common_dir.path().join(".aidot-config.toml"))
+            .unwrap()
+            .replace("[\"rules/test.md\"]", "[\"rules/shared.md\"]"),
+    )
+    .unwrap();
+    for source in [preset, common] {
+        let output = run_aidot_with_home(
+            &["pull", source, "--force"],
+            project_dir.path(),
+            home_dir.path(),
+        );
+        assert!(output.status.success());
+    }
+    let shared_rule = project_dir.path().join(".claude/rules/shared.md");
+    assert!(shared_rule.exists());
+
+    // unapply removes the remaining 1:1 files but keeps merged, shared and edited ones
+    let edited_rule = project_dir.path().join(".claude/rules/test.md");
+    assert!(edited_rule.exists());
+    fs::write(&edited_rule, "# Test Rule\n\nTuned for this project.").unwrap();
+    let output = run_aidot_with_home(
+        &["unapply", preset, "--yes"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("(modified, kept)"), "stdout: {}", stdout);
+    assert!(
+        stdout.contains(&format!("(still applied by {})", common)),
+        "stdout: {}",
+        stdout
+    );
+    assert!(!project_dir
+        .path()
+        .join(".claude/commands/build.md")
+        .exists());
+    assert!(project_dir.path().join(".claude/CLAUDE.md").exists());
+    assert!(edited_rule.exists());
+    assert!(shared_rule.exists());
+
+    // The kept file stays recorded, so --force removes it later
+    let output = run_aidot_with_home(
+        &["unapply", preset, "--yes", "--force"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+    assert!(!edited_rule.exists());
+
+    // The other preset still owns the shared rule and can remove it
+    let output = run_aidot_with_home(
+        &["unapply", common, "--yes"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+    assert!(!shared_rule.exists());
+}
+
+#[test]
+fn test_prune_keeps_orphans_modified_after_apply() {
+    let home_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+
+    create_test_preset(preset_dir.path());
+    fs::write(preset_dir.path().join("commands/old.md"), "# Old").unwrap();
+    fs::write(preset_dir.path().join("commands/notes.md"), "# Notes").unwrap();
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+    let pull = |args: &[&str]| {
+        let output = run_aidot_with_home(
+            &[&["pull", preset][..], args].concat(),
+            project_dir.path(),
+            home_dir.path(),
+        );
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    pull(&["--force"]);
+
+    fs::remove_file(preset_dir.path().join("commands/old.md")).unwrap();
+    fs::remove_file(preset_dir.path().join("commands/notes.md")).unwrap();
+    let notes = project_dir.path().join(".claude/commands/notes.md");
+    fs::write(&notes, "# Notes\n\nMy own additions").unwrap();
+
+    let stdout = pull(&["--yes", "--prune"]);
+    assert!(stdout.contains("(modified, kept)"), "stdout: {}", stdout);
+    assert!(!project_dir.path().join(".claude/commands/old.md").exists());
+    assert_eq!(
+        fs::read_to_string(&notes).unwrap(),
+        "# Notes\n\nMy own additions"
+    );
+
+    // Still tracked: --force prunes it too
+    let stdout = pull(&["--force", "--prune"]);
+    assert!(stdout.contains("DELETE"), "stdout: {}", stdout);
+    assert!(!notes.exists());
+}
+
+#[test]
+fn test_pull_non_interactive_uses_default_conflict_policy() {
+    let home_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+
+    create_test_preset(preset_dir.path());
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let output = run_aidot_with_home(
+        &["pull", preset, "--force"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+    let build_cmd = project_dir.path().join(".claude/commands/build.md");
+    fs::write(&build_cmd, "# Local edit").unwrap();
+
+    // Stdin is not a terminal: no prompt, default policy is skip
+    let output = run_aidot_with_home(&["pull", preset], project_dir.path(), home_dir.path());
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Applying default policy (skip)"),
+        "stdout: {}",
+        stdout
+    );
+    assert_eq!(fs::read_to_string(&build_cmd).unwrap(), "# Local edit");
+
+    // conflict_policy = "force" in the global config overwrites instead
+    let config_file = home_dir.path().join(".aidot/config.toml");
+    let config = fs::read_to_string(&config_file).unwrap();
+    assert!(config.contains("conflict_policy = \"skip\""));
+    fs::write(
+        &config_file,
+        config.replace("conflict_policy = \"skip\"", "conflict_policy = \"force\""),
+    )
+    .unwrap();
+    let output = run_aidot_with_home(&["pull", preset], project_dir.path(), home_dir.path());
+    assert!(output.status.success());
+    assert!(fs::read_to_string(&build_cmd)
+        .unwrap()
+        .contains("Build command."));
+}
+
+#[test]
+fn test_pull_only_glob() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+
+    create_test_preset(preset_dir.path());
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    // No match → clear error, nothing written
+    let output = run_aidot(
+        &["pull", preset, "--only", "agents/*.md"],
+        project_dir.path(),
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("No preset files match"),
+        "stderr: {}",
+        stderr
+    );
+
+    let output = run_aidot(
+        &[
+            "pull",
+            preset,
+            "--only",
+            "commands/*.md",
+            "--only",
+            "agents/*.md",
+        ],
+        project_dir.path(),
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("agents/*.md"));
+    assert!(project_dir
+        .path()
+        .join(".claude/commands/build.md")
+        .exists());
+    assert!(!project_dir.path().join(".claude/rules/test.md").exists());
+    assert!(!project_dir.path().join(".claude/CLAUDE.md").exists());
+}
+
+#[test]
+fn test_pull_skips_files_with_unmet_conditions() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+
+    create_test_preset(preset_dir.path());
+    fs::write(
+        preset_dir.path().join("rules/test.md"),
+        "---\nwhen: { files_exist: [Cargo.toml] }\n---\n# Rust rule",
+    )
+    .unwrap();
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let output = run_aidot(
+        &["pull", preset, "--yes", "--show-skipped"],
+        project_dir.path(),
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("rules/test.md") && stdout.contains("Cargo.toml not found"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(!project_dir.path().join(".claude/rules/test.md").exists());
+    assert!(project_dir
+        .path()
+        .join(".claude/commands/build.md")
+        .exists());
+
+    // Once the condition holds, the file is applied
+    fs::write(project_dir.path().join("Cargo.toml"), "").unwrap();
+    let output = run_aidot(&["pull", preset, "--yes"], project_dir.path());
+    assert!(output.status.success());
+    // The condition itself is not copied into the applied rule
+    assert_eq!(
+        fs::read_to_string(project_dir.path().join(".claude/rules/test.md")).unwrap(),
+        "# Rust rule"
+    );
+}
+
+#[test]
+fn test_pull_writes_report() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+
+    create_test_preset(preset_dir.path());
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let output = run_aidot(
+        &[
+            "pull",
+            preset_dir.path().to_str().unwrap(),
+            "--report",
+            "out/report.json",
+        ],
+        project_dir.path(),
+    );
+    assert!(output.status.success());
+
+    let report = fs::read_to_string(project_dir.path().join("out/report.json")).unwrap();
+    let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+    let entries = report["entries"].as_array().unwrap();
+    let rule = entries
+        .iter()
+        .find(|e| e["path"] == ".claude/rules/test.md")
+        .expect("rule entry");
+    assert_eq!(rule["status"], "created");
+    assert_eq!(rule["tool"], "Claude Code");
+    assert_eq!(rule["section"], "rules");
+    assert!(rule["timestamp"].as_str().unwrap().ends_with('Z'));
+}
+
+#[test]
+fn test_pull_dry_run_matches_apply_for_merged_files() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    let preset = preset_dir.path();
+
+    create_test_preset(preset);
+    let mut config = fs::read_to_string(preset.join(".aidot-config.toml")).unwrap();
+    config.push_str("\n[mcp]\ndirectory = \"mcp/\"\n\n[settings]\ndirectory = \"settings/\"\n");
+    fs::write(preset.join(".aidot-config.toml"), config).unwrap();
+    fs::create_dir_all(preset.join("mcp")).unwrap();
+    fs::create_dir_all(preset.join("settings")).unwrap();
+    fs::write(
+        preset.join("mcp/github.json"),
+        r#"{"mcpServers": {"github": {"command": "gh"}}}"#,
+    )
+    .unwrap();
+    fs::write(preset.join("settings/model.json"), r#"{"model": "opus"}"#).unwrap();
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+    fs::create_dir_all(project_dir.path().join(".cursor")).unwrap();
+
+    let preset_arg = preset.to_str().unwrap();
+    let output = run_aidot_with_home(
+        &["pull", preset_arg, "--force"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+
+    // Merged memory and settings change, mcp stays the same, a new rule appears
+    fs::write(preset.join("memory/context.md"), "# Context\n\nUpdated.").unwrap();
+    fs::write(preset.join("settings/model.json"), r#"{"model": "sonnet"}"#).unwrap();
+    fs::write(preset.join("rules/new.md"), "# New").unwrap();
+    let config = fs::read_to_string(preset.join(".aidot-config.toml"))
+        .unwrap()
+        .replace(
+            r#"files = ["rules/test.md"]"#,
+            r#"files = ["rules/test.md", "rules/new.md"]"#,
+        );
+    fs::write(preset.join(".aidot-config.toml"), config).unwrap();
+
+    let output = run_aidot_with_home(
+        &["pull", preset_arg, "--force", "--dry-run"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut predicted: Vec<(String, String)> = stdout
+        .lines()
+        .filter_map(|line| {
+            let mut words = line.split_whitespace();
+            let status = match words.next()? {
+                "CREATE" => "created",
+                "UPDATE" => "updated",
+                "UNCHANGED" => "unchanged",
+                _ => return None,
+            };
+            Some((words.next()?.to_string(), status.to_string()))
+        })
+        .collect();
+    predicted.sort();
+
+    let output = run_aidot_with_home(
+        &["pull", preset_arg, "--force", "--report", "report.json"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(project_dir.path().join("report.json")).unwrap())
+            .unwrap();
+    let mut applied: Vec<(String, String)> = report["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| {
+            (
+                e["path"].as_str().unwrap().to_string(),
+                e["status"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    applied.sort();
+
+    assert_eq!(predicted, applied, "dry-run output:\n{}", stdout);
+    let status_of = |path: &str| {
+        applied
+            .iter()
+            .find(|(p, _)| p == path)
+            .map(|(_, status)| status.as_str())
+    };
+    assert_eq!(status_of(".claude/CLAUDE.md"), Some("updated"));
+    // mcp and settings both merge into settings.local.json: unchanged, then updated
+    let settings: Vec<_> = applied
+        .iter()
+        .filter(|(p, _)| p == ".claude/settings.local.json")
+        .map(|(_, status)| status.as_str())
+        .collect();
+    assert_eq!(settings, vec!["unchanged", "updated"]);
+    assert_eq!(status_of(".cursor/mcp.json"), Some("unchanged"));
+    assert_eq!(status_of(".claude/rules/new.md"), Some("created"));
+    assert!(stdout.contains("+1 -1"), "stdout: {}", stdout);
+}
+
+#[test]
+fn test_pull_compose_applies_presets_once() {
+    let team_dir = TempDir::new().unwrap();
+    let personal_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+
+    for (dir, name, settings) in [
+        (
+            team_dir.path(),
+            "team",
+            r#"{"model": "opus", "permissions": {"allow": ["Bash(ls)"]}}"#,
+        ),
+        (personal_dir.path(), "personal", r#"{"model": "sonnet"}"#),
+    ] {
+        let config = format!(
+            "[metadata]\nname = \"{}\"\nversion = \"1.0.0\"\n\n[memory]\ndirectory = \"memory/\"\n\n[settings]\ndirectory = \"settings/\"\n",
+            name
+        );
+        fs::write(dir.join(".aidot-config.toml"), config).unwrap();
+        fs::create_dir_all(dir.join("memory")).unwrap();
+        fs::create_dir_all(dir.join("settings")).unwrap();
+        fs::write(
+            dir.join(format!("memory/{}.md", name)),
+            format!("{} memory", name),
+        )
+        .unwrap();
+        fs::write(dir.join("settings/settings.json"), settings).unwrap();
+    }
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let output = run_aidot_with_home(
+        &[
+            "pull",
+            team_dir.path().to_str().unwrap(),
+            personal_dir.path().to_str().unwrap(),
+            "--compose",
+            "--tools",
+            "claude",
+        ],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Composed"), "stdout: {}", stdout);
+
+    // memory: concat of both presets in order
+    let memory = fs::read_to_string(project_dir.path().join(".claude/CLAUDE.md")).unwrap();
+    let (team, personal) = (
+        memory.find("team memory").unwrap(),
+        memory.find("personal memory").unwrap(),
+    );
+    assert!(team < personal, "CLAUDE.md: {}", memory);
+
+    // settings: the last preset replaces the earlier one
+    let settings: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(project_dir.path().join(".claude/settings.local.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(settings, serde_json::json!({"model": "sonnet"}));
+}
+
+#[test]
+fn test_cache_gc_removes_least_recently_used() {
+    let home_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home = home_dir.path();
+    let cache_dir = home.join(".aidot/cache");
+    for name in ["team", "adhoc"] {
+        fs::create_dir_all(cache_dir.join(name).join(".git")).unwrap();
+        fs::write(cache_dir.join(name).join("rules.md"), "x".repeat(100)).unwrap();
+    }
+    fs::write(
+        home.join(".aidot/config.toml"),
+        "[[repositories]]\nname = \"team\"\nurl = \"https://example.com/team.git\"\n",
+    )
+    .unwrap();
+
+    // Without a limit only caches of unregistered URLs go
+    let output = run_aidot_with_home(&["cache", "gc", "--dry-run"], project_dir.path(), home);
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("would remove adhoc"), "stdout: {}", stdout);
+    assert!(!stdout.contains("team"), "stdout: {}", stdout);
+    assert!(cache_dir.join("adhoc").exists());
+
+    let output = run_aidot_with_home(&["cache", "gc"], project_dir.path(), home);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(!cache_dir.join("adhoc").exists());
+    assert!(cache_dir.join("team").exists());
+
+    // A limit also removes registered caches
+    let output = run_aidot_with_home(
+        &["cache", "gc", "--max-size", "50B"],
+        project_dir.path(),
+        home,
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(!cache_dir.join("team").exists());
+
+    let output = run_aidot_with_home(
+        &["cache", "gc", "--max-size", "lots"],
+        project_dir.path(),
+        home,
+    );
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cache_update_refuses_dirty_cache() {
+    let home_dir = TempDir::new().unwrap();
+    let origin_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let (home, origin) = (home_dir.path(), origin_dir.path());
+
+    create_test_preset(origin);
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args([
+                "-c",
+                "user.name=aidot",
+                "-c",
+                "user.email=aidot@example.com",
+            ])
+            .args(args)
+            .current_dir(origin)
+            .env("HOME", home)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?}", args);
+    };
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "preset"]);
+
+    fs::create_dir_all(home.join(".aidot")).unwrap();
+    fs::write(
+        home.join(".aidot/config.toml"),
+        format!(
+            "[[repositories]]\nname = \"team\"\nurl = {:?}\n",
+            origin.to_str().unwrap()
+        ),
+    )
+    .unwrap();
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+    let output = run_aidot_with_home(&["pull", "team"], project_dir.path(), home);
+    assert!(output.status.success());
+
+    // A hand-edited cache stops the update instead of failing the fast-forward
+    let cached_rule = home.join(".aidot/cache/team/rules/test.md");
+    fs::write(&cached_rule, "edited").unwrap();
+    let output = run_aidot_with_home(&["cache", "update", "team"], project_dir.path(), home);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("has local changes"), "stderr: {}", stderr);
+    assert!(stderr.contains("rules/test.md"), "stderr: {}", stderr);
+    assert!(stderr.contains("--reset-cache"), "stderr: {}", stderr);
+    assert_eq!(fs::read_to_string(&cached_rule).unwrap(), "edited");
+
+    // --reset-cache throws the old clone away and clones again
+    fs::write(home.join(".aidot/cache/team/leftover.md"), "junk").unwrap();
+    let output = run_aidot_with_home(
+        &["cache", "update", "team", "--reset-cache"],
+        project_dir.path(),
+        home,
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(&cached_rule).unwrap(),
+        "# Test Rule\n\nThis is a test rule."
+    );
+    assert!(!home.join(".aidot/cache/team/leftover.md").exists());
+}
+
+#[test]
+fn test_offline_uses_cache_only() {
+    let home_dir = TempDir::new().unwrap();
+    let origin_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let (home, origin) = (home_dir.path(), origin_dir.path());
+
+    create_test_preset(origin);
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args([
+                "-c",
+                "user.name=aidot",
+                "-c",
+                "user.email=aidot@example.com",
+            ])
+            .args(args)
+            .current_dir(origin)
+            .env("HOME", home)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?}", args);
+    };
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "preset"]);
+    fs::create_dir_all(home.join(".aidot")).unwrap();
+    fs::write(
+        home.join(".aidot/config.toml"),
+        format!(
+            "[[repositories]]\nname = \"team\"\nurl = {:?}\n",
+            origin.to_str().unwrap()
+        ),
+    )
+    .unwrap();
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+    assert!(
+        run_aidot_with_home(&["pull", "team"], project_dir.path(), home)
+            .status
+            .success()
+    );
+
+    // A newer commit upstream is not fetched: the cached clone is used as it is
+    fs::write(origin.join("rules/test.md"), "# Updated upstream").unwrap();
+    git(&["commit", "-q", "-am", "update"]);
+    let output = run_aidot_with_home(
+        &["pull", "team", "--offline", "--force"],
+        project_dir.path(),
+        home,
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Offline: using cached clone"));
+    let rule = fs::read_to_string(project_dir.path().join(".claude/rules/test.md")).unwrap();
+    assert!(!rule.contains("Updated upstream"));
+
+    // AIDOT_OFFLINE=1 works like --offline; cache update fails right away
+    let offline = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_aidot"))
+            .args(args)
+            .current_dir(project_dir.path())
+            .env("HOME", home)
+            .env("USERPROFILE", home)
+            .env("AIDOT_OFFLINE", "1")
+            .output()
+            .expect("Failed to execute aidot")
+    };
+    let output = offline(&["cache", "update", "--all"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("aidot is offline"));
+
+    // Nothing cached for this URL: a clear error instead of a clone attempt
+    let output = offline(&["diff", "https://git.example.invalid/team/other.git"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("is not cached"), "stderr: {}", stderr);
+}
+
+#[test]
+fn test_pull_warns_about_undeclared_section_directory() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+
+    create_test_preset(preset_dir.path());
+    let config_path = preset_dir.path().join(".aidot-config.toml");
+    let config = fs::read_to_string(&config_path)
+        .unwrap()
+        .replace("[rules]\nfiles = [\"rules/test.md\"]\n", "");
+    fs::write(&config_path, config).unwrap();
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let preset = preset_dir.path().to_str().unwrap();
+    let output = run_aidot_with_home(
+        &["pull", preset, "--dry-run"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("rules/ directory exists but the config has no [rules] section"),
+        "stderr: {}",
+        stderr
+    );
+
+    let output = run_aidot_with_home(
+        &["pull", preset, "--strict"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--strict"));
+    assert!(!project_dir.path().join(".claude/CLAUDE.md").exists());
+}
+
+#[test]
+fn test_repo_add_with_subdir() {
+    let home_dir = TempDir::new().unwrap();
+    let mono_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home = home_dir.path();
+
+    fs::create_dir_all(mono_dir.path().join("presets/frontend")).unwrap();
+    create_test_preset(&mono_dir.path().join("presets/frontend"));
+    let mono = mono_dir.path().to_str().unwrap();
+
+    // Subdirectory without .aidot-config.toml is rejected
+    fs::create_dir_all(mono_dir.path().join("presets/empty")).unwrap();
+    let output = run_aidot_with_home(
+        &[
+            "repo",
+            "add",
+            "empty",
+            mono,
+            "--local",
+            "--subdir",
+            "presets/empty",
+        ],
+        project_dir.path(),
+        home,
+    );
+    assert!(!output.status.success());
+
+    let output = run_aidot_with_home(
+        &[
+            "repo",
+            "add",
+            "frontend",
+            mono,
+            "--local",
+            "--subdir",
+            "presets/frontend",
+        ],
+        project_dir.path(),
+        home,
+    );
+    assert!(output.status.success(), "{:?}", output);
+
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+    let output = run_aidot_with_home(&["pull", "frontend"], project_dir.path(), home);
+    assert!(output.status.success());
+    assert!(project_dir.path().join(".claude/rules/test.md").exists());
+}
+
+#[test]
+fn test_repo_add_normalizes_git_url() {
+    let home_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home = home_dir.path();
+    let add = |name: &str, url: &str| {
+        run_aidot_with_home(&["repo", "add", name, url], project_dir.path(), home)
+    };
+
+    let output = add("team", "https://GitHub.com/team/presets/");
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Normalized URL:"));
+    let config = fs::read_to_string(home.join(".aidot/config.toml")).unwrap();
+    assert!(config.contains("url = \"https://github.com/team/presets.git\""));
+
+    // The same repository under another name is refused
+    let output = add("again", "https://github.com/team/presets.git");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already registered as 'team'"));
+
+    // A different subdirectory of it is a different preset
+    let output = add("web", "https://github.com/team/presets#path=web");
+    assert!(output.status.success(), "{:?}", output);
+}
+
+#[test]
+fn test_repo_tags_filter_list_and_pull() {
+    let home_dir = TempDir::new().unwrap();
+    let web_dir = TempDir::new().unwrap();
+    let api_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home = home_dir.path();
+    create_test_preset(web_dir.path());
+    create_test_preset(api_dir.path());
+    fs::write(web_dir.path().join("commands/web.md"), "# Web").unwrap();
+    fs::write(api_dir.path().join("commands/api.md"), "# Api").unwrap();
+
+    let web = web_dir.path().to_str().unwrap();
+    let api = api_dir.path().to_str().unwrap();
+    let add = |args: &[&str]| {
+        let output = run_aidot_with_home(args, project_dir.path(), home);
+        assert!(output.status.success(), "{:?}", output);
+    };
+    add(&[
+        "repo",
+        "add",
+        "web",
+        web,
+        "--local",
+        "--default",
+        "--tag",
+        "frontend",
+        "--tag",
+        "eslint",
+    ]);
+    add(&[
+        "repo",
+        "add",
+        "api",
+        api,
+        "--local",
+        "--default",
+        "--tag",
+        "backend",
+    ]);
+
+    let output = run_aidot_with_home(
+        &["repo", "list", "--tag", "Frontend"],
+        project_dir.path(),
+        home,
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("web"));
+    assert!(stdout.contains("tags: frontend, eslint"));
+    assert!(!stdout.contains("api"));
+
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+    let output = run_aidot_with_home(&["pull", "--tag", "frontend"], project_dir.path(), home);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(project_dir.path().join(".claude/commands/web.md").exists());
+    assert!(!project_dir.path().join(".claude/commands/api.md").exists());
+
+    let output = run_aidot_with_home(&["pull", "--tag", "mobile"], project_dir.path(), home);
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("No default repositories tagged 'mobile'")
+    );
+
+    let output = run_aidot_with_home(
+        &["repo", "add", "bad", web, "--local", "--tag", " "],
+        project_dir.path(),
+        home,
+    );
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_repo_add_local_rejects_unparsable_preset() {
+    let home_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+
+    // [metadata] is required
+    fs::write(
+        preset_dir.path().join(".aidot-config.toml"),
+        "[rules]\nfiles = []\n",
+    )
+    .unwrap();
+    let output = run_aidot_with_home(
+        &["repo", "add", "broken", preset, "--local"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("metadata"), "stderr: {}", stderr);
+
+    let output = run_aidot_with_home(&["repo", "list"], project_dir.path(), home_dir.path());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("broken"));
+
+    // Missing section directories are only a warning
+    create_test_preset(preset_dir.path());
+    fs::remove_dir_all(preset_dir.path().join("commands")).unwrap();
+    let output = run_aidot_with_home(
+        &["repo", "add", "ok", preset, "--local"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[commands] is configured but commands/ does not exist"));
+}
+
+#[test]
+fn test_repo_list_shows_preset_provenance() {
+    let home_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    create_test_preset(preset_dir.path());
+    let config_path = preset_dir.path().join(".aidot-config.toml");
+    let config = fs::read_to_string(&config_path).unwrap().replace(
+        "description = \"Test preset\"\n",
+        "description = \"Test preset\"\nauthors = [\"Jane Doe <jane@example.com>\"]\nhomepage = \"https://example.com/preset\"\n",
+    );
+    fs::write(&config_path, config).unwrap();
+
+    let preset = preset_dir.path().to_str().unwrap();
+    let output = run_aidot_with_home(
+        &["repo", "add", "team", preset, "--local"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("authors: Jane Doe <jane@example.com>"),
+        "{}",
+        stdout
+    );
+
+    for args in [&["repo", "list"][..], &["status"][..]] {
+        let output = run_aidot_with_home(args, project_dir.path(), home_dir.path());
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("authors: Jane Doe <jane@example.com>"),
+            "{}",
+            stdout
+        );
+        assert!(
+            stdout.contains("homepage: https://example.com/preset"),
+            "{}",
+            stdout
+        );
+    }
+}
+
+#[test]
+fn test_config_export_import_roundtrip() {
+    let home_a = TempDir::new().unwrap();
+    let home_b = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    create_test_preset(preset_dir.path());
+
+    let output = run_aidot_with_home(
+        &[
+            "repo",
+            "add",
+            "team",
+            preset_dir.path().to_str().unwrap(),
+            "--local",
+        ],
+        project_dir.path(),
+        home_a.path(),
+    );
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = run_aidot_with_home(&["config", "export"], project_dir.path(), home_a.path());
+    assert!(output.status.success(), "{:?}", output);
+    let exported = String::from_utf8_lossy(&output.stdout).to_string();
+    assert!(exported.contains("name = \"team\""));
+    assert!(!exported.contains("cached_at"));
+    assert!(!exported.contains("conflict_policy"));
+
+    let file = project_dir.path().join("aidot-config.toml");
+    fs::write(&file, &exported).unwrap();
+    let file = file.to_str().unwrap();
+
+    let output = run_aidot_with_home(
+        &["config", "import", file],
+        project_dir.path(),
+        home_b.path(),
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("1 added"));
+
+    // Importing again: skipped by default, renamed on request
+    let output = run_aidot_with_home(
+        &["config", "import", file, "--on-conflict", "rename"],
+        project_dir.path(),
+        home_b.path(),
+    );
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = run_aidot_with_home(&["repo", "list"], project_dir.path(), home_b.path());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("team-2"), "stdout: {}", stdout);
+}
+
+#[test]
+fn test_pull_without_detected_tools_suggests_tools_option() {
+    let home_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    create_test_preset(preset_dir.path());
+
+    // Empty PATH so tool CLIs installed on the machine don't count as detected
+    let output = Command::new(env!("CARGO_BIN_EXE_aidot"))
+        .args(["pull", preset_dir.path().to_str().unwrap()])
+        .current_dir(project_dir.path())
+        .env("HOME", home_dir.path())
+        .env("USERPROFILE", home_dir.path())
+        .env("PATH", "")
+        .output()
+        .expect("Failed to execute aidot");
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("No LLM tools detected"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(
+        stdout.contains("--tools claude,cursor,copilot"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(!project_dir.path().join(".claude").exists());
+}
+
+#[test]
+fn test_pull_rejects_overlapping_targets() {
+    let home_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    fs::write(
+        preset_dir.path().join(".aidot-config.toml"),
+        "[metadata]\nname = \"overlap\"\nversion = \"1.0.0\"\n\n[rules]\ndirectory = \"rules/\"\n",
+    )
+    .unwrap();
+    fs::create_dir_all(preset_dir.path().join("rules")).unwrap();
+    // Both map to .cursor/rules/a.mdc (frontmatter turns a.md into a.mdc)
+    fs::write(
+        preset_dir.path().join("rules/a.md"),
+        "---\nglobs: \"*.rs\"\n---\n# A\n",
+    )
+    .unwrap();
+    fs::write(preset_dir.path().join("rules/a.mdc"), "# B\n").unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+
+    let output = run_aidot_with_home(
+        &["pull", preset, "--tools", "cursor", "--yes"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(".cursor/rules/a.mdc ← rules/a.md, rules/a.mdc"),
+        "stderr: {}",
+        stderr
+    );
+    assert!(!project_dir.path().join(".cursor/rules/a.mdc").exists());
+
+    let output = run_aidot_with_home(
+        &[
+            "pull",
+            preset,
+            "--tools",
+            "cursor",
+            "--yes",
+            "--allow-overlap",
+        ],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success(), "{:?}", output);
+    // Files are read in sorted order, so the last one is always rules/a.mdc
+    let applied = fs::read_to_string(project_dir.path().join(".cursor/rules/a.mdc")).unwrap();
+    assert_eq!(applied, "# B\n");
+}
+
+#[test]
+fn test_pull_post_apply_hook_requires_opt_in() {
+    let home_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    create_test_preset(preset_dir.path());
+    let config_path = preset_dir.path().join(".aidot-config.toml");
+    let config = fs::read_to_string(&config_path).unwrap();
+    fs::write(
+        &config_path,
+        format!(
+            "{}\n[hooks.post_apply]\ncommand = \"echo done > hook-ran.txt\"\n",
+            config
+        ),
+    )
+    .unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+    let marker = project_dir.path().join("hook-ran.txt");
+
+    // Not run without --run-hooks
+    let output = run_aidot_with_home(
+        &["pull", preset, "--tools", "claude", "--yes"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("post_apply hook (not run)"));
+    assert!(!marker.exists());
+
+    // Not confirmed (stdin closed)
+    let output = run_aidot_with_home(
+        &[
+            "pull",
+            preset,
+            "--tools",
+            "claude",
+            "--force",
+            "--run-hooks",
+        ],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Hook skipped."));
+    assert!(!marker.exists());
+
+    let output = run_aidot_with_home(
+        &["pull", preset, "--tools", "claude", "--yes", "--run-hooks"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(marker.exists());
+}
+
+#[test]
+fn test_init_from_existing_settings_round_trip() {
+    let source_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+
+    let original = serde_json::json!({
+        "mcpServers": {
+            "github": { "command": "gh", "args": ["mcp"], "env": { "TOKEN": "x" } },
+            "db": { "url": "postgres://localhost" }
+        },
+        "permissions": { "allow": ["Bash(ls)"], "deny": [], "nested": { "a": [1, { "b": null }] } },
+        "model": "opus"
+    });
+    fs::create_dir_all(source_dir.path().join(".claude")).unwrap();
+    fs::write(
+        source_dir.path().join(".claude/settings.local.json"),
+        serde_json::to_string_pretty(&original).unwrap(),
+    )
+    .unwrap();
+
+    let output = run_aidot_with_home(
+        &["init", "--from-existing"],
+        source_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+    let output = run_aidot_with_home(
+        &["pull", source_dir.path().to_str().unwrap(), "--yes"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Extract → apply reproduces the same settings
+    let applied: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(project_dir.path().join(".claude/settings.local.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(applied, original);
+}
+
+/// Run aidot with an isolated home and the given secret key (None removes it)
+fn run_aidot_with_key(
+    args: &[&str],
+    cwd: &std::path::Path,
+    home: &std::path::Path,
+    key: Option<&str>,
+) -> std::process::Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_aidot"));
+    command
+        .args(args)
+        .current_dir(cwd)
+        .env("HOME", home)
+        .env("USERPROFILE", home);
+    match key {
+        Some(key) => command.env("AIDOT_SECRET_KEY", key),
+        None => command.env_remove("AIDOT_SECRET_KEY"),
+    };
+    command.output().expect("Failed to execute aidot")
+}
+
+#[test]
+fn test_init_encrypt_and_pull_decrypts_secrets() {
+    let source_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+
+    let original = serde_json::json!({ "env": { "GITHUB_TOKEN": "ghp_secret" } });
+    fs::create_dir_all(source_dir.path().join(".claude")).unwrap();
+    fs::write(
+        source_dir.path().join(".claude/settings.local.json"),
+        serde_json::to_string_pretty(&original).unwrap(),
+    )
+    .unwrap();
+    fs::write(source_dir.path().join("CLAUDE.md"), "# Project\n").unwrap();
+
+    // --encrypt without a key fails before writing anything
+    let output = run_aidot_with_key(
+        &["init", "--from-existing", "--encrypt"],
+        source_dir.path(),
+        home_dir.path(),
+        None,
+    );
+    assert!(!output.status.success());
+    assert!(!source_dir.path().join(".aidot-config.toml").exists());
+
+    let output = run_aidot_with_key(
+        &["init", "--from-existing", "--encrypt"],
+        source_dir.path(),
+        home_dir.path(),
+        Some("team-key"),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let encrypted: Vec<_> = fs::read_dir(source_dir.path().join("settings"))
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    assert!(!encrypted.is_empty());
+    for path in &encrypted {
+        assert!(path.to_string_lossy().ends_with(".enc"));
+        assert!(!fs::read_to_string(path).unwrap().contains("ghp_secret"));
+    }
+
+    // The same key decrypts on pull
+    let project_dir = TempDir::new().unwrap();
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+    let source = source_dir.path().to_str().unwrap();
+    let output = run_aidot_with_key(
+        &["pull", source, "--yes"],
+        project_dir.path(),
+        home_dir.path(),
+        Some("team-key"),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let applied: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(project_dir.path().join(".claude/settings.local.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(applied, original);
+
+    // Without the key only the encrypted files are skipped
+    let project_dir = TempDir::new().unwrap();
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+    let output = run_aidot_with_key(
+        &["pull", source, "--yes"],
+        project_dir.path(),
+        home_dir.path(),
+        None,
+    );
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("skipped encrypted file"), "{}", stderr);
+    assert!(!project_dir
+        .path()
+        .join(".claude/settings.local.json")
+        .exists());
+    assert!(project_dir.path().join(".claude/CLAUDE.md").exists());
+}
+
+#[test]
+fn test_secret_encrypt_decrypt_commands() {
+    let dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("token.json"), "{\"token\": \"abc\"}\n").unwrap();
+
+    let output = run_aidot_with_key(
+        &["secret", "encrypt", "token.json"],
+        dir.path(),
+        home_dir.path(),
+        Some("team-key"),
+    );
+    assert!(output.status.success());
+    let encrypted = fs::read_to_string(dir.path().join("token.json.enc")).unwrap();
+    assert!(encrypted.starts_with("aidot-enc:v2:"));
+
+    let output = run_aidot_with_key(
+        &["secret", "decrypt", "token.json.enc"],
+        dir.path(),
+        home_dir.path(),
+        Some("team-key"),
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "{\"token\": \"abc\"}\n"
+    );
+
+    // The key file is used when the environment variable is not set
+    fs::create_dir_all(home_dir.path().join(".aidot")).unwrap();
+    fs::write(home_dir.path().join(".aidot/secret.key"), "other-key\n").unwrap();
+    let output = run_aidot_with_key(
+        &["secret", "decrypt", "token.json.enc"],
+        dir.path(),
+        home_dir.path(),
+        None,
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("wrong key"));
+}
+
+#[test]
+fn test_pull_commit_stages_applied_files() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    let (project, home) = (project_dir.path(), home_dir.path());
+    let preset = preset_dir.path().to_str().unwrap();
+    create_test_preset(preset_dir.path());
+
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(project)
+            .env("HOME", home)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?}", args);
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    fs::create_dir_all(project.join(".claude")).unwrap();
+
+    // Not a git repository: nothing is written
+    let output = run_aidot_with_home(&["pull", preset, "--commit"], project, home);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not inside a git repository"));
+    assert!(!project.join(".claude/CLAUDE.md").exists());
+
+    git(&["init", "-q"]);
+    git(&["config", "user.name", "aidot"]);
+    git(&["config", "user.email", "aidot@example.com"]);
+    fs::write(project.join("notes.txt"), "unrelated").unwrap();
+
+    let output = run_aidot_with_home(&["pull", preset, "--commit"], project, home);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Committed"));
+    assert_eq!(
+        git(&["log", "--format=%s"]).trim(),
+        format!("aidot: apply {} preset", preset)
+    );
+    let committed = git(&["show", "--name-only", "--format=", "HEAD"]);
+    assert!(committed.contains(".claude/CLAUDE.md"));
+    assert!(committed.contains(".claude/commands/build.md"));
+    // Files not written by aidot stay out of the commit
+    assert!(!committed.contains("notes.txt"));
+
+    // Nothing changed: no new commit
+    let output = run_aidot_with_home(
+        &["pull", preset, "--commit", "-m", "sync presets"],
+        project,
+        home,
+    );
+    assert!(output.status.success());
+    assert_eq!(git(&["rev-list", "--count", "HEAD"]).trim(), "1");
+
+    // Changes the user already staged are never mixed into the commit
+    git(&["add", "notes.txt"]);
+    fs::write(preset_dir.path().join("memory/context.md"), "# Changed").unwrap();
+    let output = run_aidot_with_home(&["pull", preset, "--commit", "--force"], project, home);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already staged changes"));
+    assert_eq!(git(&["rev-list", "--count", "HEAD"]).trim(), "1");
+}
+
+#[test]
+fn test_pull_refuses_writes_outside_project() {
+    let preset_dir = TempDir::new().unwrap();
+    let workspace = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    let project = workspace.path().join("project");
+    fs::create_dir_all(project.join(".claude")).unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+
+    create_test_preset(preset_dir.path());
+    let config_path = preset_dir.path().join(".aidot-config.toml");
+    let config = fs::read_to_string(&config_path).unwrap();
+    fs::write(
+        &config_path,
+        format!(
+            "{}\n[tools.claude]\ncommands_dir = \"../escaped\"\n",
+            config
+        ),
+    )
+    .unwrap();
+
+    let output = run_aidot_with_home(&["pull", preset, "--force"], &project, home_dir.path());
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Refusing to write outside") && stderr.contains("../escaped/build.md"),
+        "{}",
+        stderr
+    );
+    // Rejected before the preview: nothing is listed or written, not even valid targets
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("CREATE"));
+    assert!(!workspace.path().join("escaped").exists());
+    assert_eq!(fs::read_dir(project.join(".claude")).unwrap().count(), 0);
+
+    // A symlinked directory inside the project cannot redirect writes either
+    #[cfg(unix)]
+    {
+        fs::write(&config_path, config).unwrap();
+        let outside = workspace.path().join("outside");
+        fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, project.join(".claude/commands")).unwrap();
+
+        let output = run_aidot_with_home(&["pull", preset, "--force"], &project, home_dir.path());
+        assert!(!output.status.success());
+        assert!(!outside.join("build.md").exists());
+        assert!(!project.join(".claude/rules").exists());
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pull_replace_symlinks_follows_the_conflict_mode() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+    create_test_preset(preset_dir.path());
+    let rules = project_dir.path().join(".claude/rules");
+    fs::create_dir_all(&rules).unwrap();
+    let shared = project_dir.path().join("shared-rule.md");
+    fs::write(&shared, "# Shared rule").unwrap();
+    let link = rules.join("test.md");
+    std::os::unix::fs::symlink(&shared, &link).unwrap();
+    let pull = |args: &[&str]| {
+        let output = run_aidot_with_home(
+            &[&["pull", preset, "--replace-symlinks"][..], args].concat(),
+            project_dir.path(),
+            home_dir.path(),
+        );
+        assert!(output.status.success(), "{:?}", output);
+    };
+
+    // --skip keeps the link like any other existing file
+    pull(&["--skip"]);
+    assert!(link.symlink_metadata().unwrap().file_type().is_symlink());
+    assert_eq!(fs::read_to_string(&shared).unwrap(), "# Shared rule");
+
+    pull(&["--force"]);
+    assert!(!link.symlink_metadata().unwrap().file_type().is_symlink());
+    assert_eq!(
+        fs::read_to_string(&link).unwrap(),
+        "# Test Rule\n\nThis is a test rule."
+    );
+    assert_eq!(fs::read_to_string(&shared).unwrap(), "# Shared rule");
+}
+
+#[test]
+fn test_pull_interactive_preview_without_terminal_includes_all() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+    create_test_preset(preset_dir.path());
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let output = run_aidot_with_home(
+        &["pull", preset, "--interactive-preview"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("needs a terminal"));
+    assert!(project_dir.path().join(".claude/rules/test.md").exists());
+    assert!(project_dir
+        .path()
+        .join(".claude/commands/build.md")
+        .exists());
+
+    // The selection replaces conflict handling flags
+    let output = run_aidot_with_home(
+        &["pull", preset, "--interactive-preview", "--force"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_pull_continue_on_error_reports_failed_files() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+
+    create_test_preset(preset_dir.path());
+    // A directory in the way makes writing this file fail
+    fs::create_dir_all(project_dir.path().join(".claude/commands/build.md")).unwrap();
+
+    let output = run_aidot_with_home(
+        &["pull", preset, "--force"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(".claude/commands/build.md"),
+        "stderr: {}",
+        stderr
+    );
+
+    let output = run_aidot_with_home(
+        &["pull", preset, "--force", "--continue-on-error"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Failed to apply 1 file(s):"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(stdout.contains(".claude/commands/build.md"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Failed to apply 1 file(s)"));
+    // The rest of the preset is still applied
+    assert!(project_dir.path().join(".claude/rules/test.md").exists());
+}
+
+#[test]
+fn test_repo_doctor_reports_missing_local_preset() {
+    let home_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let home = home_dir.path();
+
+    let preset_path = preset_dir.path().join("preset");
+    fs::create_dir_all(&preset_path).unwrap();
+    create_test_preset(&preset_path);
+    let output = run_aidot_with_home(
+        &[
+            "repo",
+            "add",
+            "team",
+            preset_path.to_str().unwrap(),
+            "--local",
+        ],
+        project_dir.path(),
+        home,
+    );
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = run_aidot_with_home(&["repo", "doctor"], project_dir.path(), home);
+    assert!(output.status.success(), "{:?}", output);
+
+    fs::remove_dir_all(&preset_path).unwrap();
+
+    let output = run_aidot_with_home(&["repo", "list"], project_dir.path(), home);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("[missing]"));
+
+    let output = run_aidot_with_home(&["repo", "doctor"], project_dir.path(), home);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("local path does not exist"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(stdout.contains("aidot repo remove team"));
+}
+
+#[test]
+fn test_doctor_reports_checks_as_json() {
+    let home_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let home = home_dir.path();
+
+    create_test_preset(preset_dir.path());
+    fs::create_dir_all(home.join(".aidot")).unwrap();
+    fs::write(
+        home.join(".aidot/config.toml"),
+        format!(
+            "[[repositories]]\nname = \"team\"\nurl = {:?}\nsource_type = \"local\"\n\n\
+             [[repositories]]\nname = \"gone\"\nurl = \"/nonexistent/preset\"\nsource_type = \"local\"\n",
+            preset_dir.path().to_str().unwrap()
+        ),
+    )
+    .unwrap();
+    fs::create_dir_all(project_dir.path().join(".cursor")).unwrap();
+
+    let output = run_aidot_with_home(&["doctor", "--json"], project_dir.path(), home);
+    // A missing repository fails the run, but the JSON report is still printed
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let status = |name: &str| {
+        report["checks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|check| check["name"] == name)
+            .map(|check| check["status"].as_str().unwrap().to_string())
+    };
+    assert_eq!(status("~/.aidot").as_deref(), Some("ok"));
+    assert_eq!(status("config").as_deref(), Some("ok"));
+    assert_eq!(status("repo team").as_deref(), Some("ok"));
+    assert_eq!(status("repo gone").as_deref(), Some("fail"));
+    assert_eq!(status("tools").as_deref(), Some("ok"));
+    assert!(report["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|check| check["name"] == "repo gone" && check["fix"].is_string()));
+
+    let output = run_aidot_with_home(&["doctor"], project_dir.path(), home);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("local path does not exist"), "{}", stdout);
+    assert!(stdout.contains("failure(s)"), "{}", stdout);
+}
+
+#[test]
+fn test_repo_set_url() {
+    let home_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let home = home_dir.path();
+
+    let old_path = preset_dir.path().join("old");
+    let new_path = preset_dir.path().join("new");
+    fs::create_dir_all(&old_path).unwrap();
+    create_test_preset(&old_path);
+    let output = run_aidot_with_home(
+        &["repo", "add", "team", old_path.to_str().unwrap(), "--local"],
+        project_dir.path(),
+        home,
+    );
+    assert!(output.status.success(), "{:?}", output);
+
+    // The preset moved: point the repository at its new location
+    fs::rename(&old_path, &new_path).unwrap();
+    let output = run_aidot_with_home(
+        &["repo", "set-url", "team", "./missing", "--local"],
+        project_dir.path(),
+        home,
+    );
+    assert!(!output.status.success());
+    let output = run_aidot_with_home(
+        &[
+            "repo",
+            "set-url",
+            "team",
+            new_path.to_str().unwrap(),
+            "--local",
+        ],
+        project_dir.path(),
+        home,
+    );
+    assert!(output.status.success(), "{:?}", output);
+
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+    let output = run_aidot_with_home(&["pull", "team", "--yes"], project_dir.path(), home);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(project_dir.path().join(".claude/rules/test.md").exists());
+
+    // Switching to a Git URL invalidates the cached clone
+    let cache_dir = home.join(".aidot/cache/team");
+    fs::create_dir_all(&cache_dir).unwrap();
+    let output = run_aidot_with_home(
+        &["repo", "set-url", "team", "not-a-url"],
+        project_dir.path(),
+        home,
+    );
+    assert!(!output.status.success());
+    let output = run_aidot_with_home(
+        &[
+            "repo",
+            "set-url",
+            "team",
+            "https://example.com/team/preset.git",
+        ],
+        project_dir.path(),
+        home,
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(!cache_dir.exists());
+    let output = run_aidot_with_home(&["repo", "list"], project_dir.path(), home);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("https://example.com/team/preset.git"));
+    assert!(!stdout.contains("local"));
+}
+
+#[test]
+fn test_pull_prints_section_tree() {
+    let home_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let preset = preset_dir.path().to_str().unwrap();
+    create_test_preset(preset_dir.path());
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let output = run_aidot_with_home(
+        &["pull", preset, "--yes"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Applied to Claude Code (+3)"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(stdout.contains("|- rules (+1)"), "stdout: {}", stdout);
+    assert!(stdout.contains("+ .claude/rules/test.md"));
+
+    // Sections without changes are hidden unless --verbose
+    fs::write(preset_dir.path().join("commands/build.md"), "# Build v2").unwrap();
+    let output = run_aidot_with_home(
+        &["pull", preset, "--yes", "--force"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("commands (~1)"), "stdout: {}", stdout);
+    assert!(!stdout.contains("rules (=1)"), "stdout: {}", stdout);
+    assert!(stdout.contains("2 unchanged section(s) hidden"));
+
+    let output = run_aidot_with_home(
+        &["--verbose", "pull", preset, "--yes"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("rules (=1)"), "stdout: {}", stdout);
+}
+
+#[test]
+fn test_pull_same_repository_twice_applies_once() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    fs::write(
+        preset_dir.path().join(".aidot-config.toml"),
+        "[metadata]\nname = \"common\"\nversion = \"1.0.0\"\n\n[rules]\ndirectory = \"rules/\"\n",
+    )
+    .unwrap();
+    fs::create_dir_all(preset_dir.path().join("rules")).unwrap();
+    fs::write(preset_dir.path().join("rules/style.md"), "# Style\n").unwrap();
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let preset = preset_dir.path().to_str().unwrap();
+    let output = run_aidot_with_home(
+        &["pull", preset, preset, "--tools", "claude", "--verbose"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Skipping duplicate repository"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(!stdout.contains("[1/2]"), "stdout: {}", stdout);
+    assert!(project_dir.path().join(".claude/rules/style.md").exists());
+}
+
+#[test]
+fn test_diff_reuses_parse_cache_until_preset_changes() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    fs::write(
+        preset_dir.path().join(".aidot-config.toml"),
+        "[metadata]\nname = \"team\"\nversion = \"1.0.0\"\n\n[rules]\ndirectory = \"rules/\"\n",
+    )
+    .unwrap();
+    fs::create_dir_all(preset_dir.path().join("rules")).unwrap();
+    fs::write(preset_dir.path().join("rules/style.md"), "# Style\n").unwrap();
+    fs::write(preset_dir.path().join("rules/notes.txt"), "stray").unwrap();
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let preset = preset_dir.path().to_str().unwrap();
+    let output = run_aidot_with_home(
+        &["pull", preset, "--tools", "claude"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let parsed_dir = home_dir.path().join(".aidot/parse-cache");
+    assert_eq!(fs::read_dir(&parsed_dir).unwrap().count(), 1);
+
+    let diff = |extra: &[&str]| {
+        let mut args = vec!["diff", preset, "--json"];
+        args.extend_from_slice(extra);
+        let output = run_aidot_with_home(&args, project_dir.path(), home_dir.path());
+        assert!(output.status.success(), "{:?}", output);
+        // Parse warnings are replayed from the cache
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("skipped rules/notes.txt"), "{}", stderr);
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+    assert_eq!(diff(&[])["modified"], 0);
+
+    // Editing a preset file invalidates the cached parse
+    fs::write(preset_dir.path().join("rules/style.md"), "# Style v2\n").unwrap();
+    assert_eq!(diff(&[])["modified"], 1);
+    assert_eq!(diff(&["--no-cache"])["modified"], 1);
+}
+
+#[test]
+fn test_pull_shows_short_preset_path() {
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    let preset_dir = project_dir.path().join("presets/team");
+    fs::create_dir_all(&preset_dir).unwrap();
+    create_test_preset(&preset_dir);
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let preset = preset_dir.to_str().unwrap();
+    let output = run_aidot_with_home(
+        &["pull", preset, "--tools", "claude", "--dry-run"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Loading preset from ./presets/team"),
+        "stdout: {}",
+        stdout
+    );
+
+    // --verbose shows the full path
+    let output = run_aidot_with_home(
+        &[
+            "pull",
+            preset,
+            "--tools",
+            "claude",
+            "--dry-run",
+            "--verbose",
+        ],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(&format!("Loading preset from {}", preset)),
+        "stdout: {}",
+        stdout
+    );
+}
+
+#[test]
+fn test_pull_root_file_shadowed_by_tool_section() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    fs::write(
+        preset_dir.path().join(".aidot-config.toml"),
+        "[metadata]\nname = \"team\"\nversion = \"1.0.0\"\n\n[memory]\ndirectory = \"memory/\"\n\n[root]\ndirectory = \"root/\"\n",
+    )
+    .unwrap();
+    fs::create_dir_all(preset_dir.path().join("memory")).unwrap();
+    fs::create_dir_all(preset_dir.path().join("root")).unwrap();
+    fs::write(preset_dir.path().join("memory/context.md"), "Context\n").unwrap();
+    fs::write(preset_dir.path().join("root/.cursorrules"), "root copy\n").unwrap();
+    fs::write(
+        preset_dir.path().join("root/.editorconfig"),
+        "root = true\n",
+    )
+    .unwrap();
+    fs::create_dir_all(project_dir.path().join(".cursor")).unwrap();
+
+    let output = run_aidot_with_home(
+        &[
+            "pull",
+            preset_dir.path().to_str().unwrap(),
+            "--tools",
+            "cursor",
+        ],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("root/ files also written by a tool section are skipped"),
+        "stdout: {}",
+        stdout
+    );
+
+    // The memory section wins; other root files still apply
+    let cursorrules = fs::read_to_string(project_dir.path().join(".cursorrules")).unwrap();
+    assert!(cursorrules.contains("Context"), "{}", cursorrules);
+    assert!(!cursorrules.contains("root copy"), "{}", cursorrules);
+    assert!(project_dir.path().join(".editorconfig").exists());
+}
+
+/// Serve `body` over HTTP on a local port; returns the URL and the request counter
+fn serve_archive(
+    body: Vec<u8>,
+    file_name: &str,
+) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+    use std::io::{BufRead, BufReader, Write};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!(
+        "http://{}/releases/{}",
+        listener.local_addr().unwrap(),
+        file_name
+    );
+    let requests = std::sync::Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { break };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                line.clear();
+            }
+            counter.fetch_add(1, Ordering::SeqCst);
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(header.as_bytes());
+            let _ = stream.write_all(&body);
+        }
+    });
+    (url, requests)
+}
+
+#[test]
+fn test_pull_from_archive_url() {
+    use sha2::{Digest, Sha256};
+    use std::sync::atomic::Ordering;
+
+    let home_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    create_test_preset(preset_dir.path());
+
+    // Release archives wrap the preset in one top-level directory
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    ));
+    builder
+        .append_dir_all("test-preset-1.0.0", preset_dir.path())
+        .unwrap();
+    let archive = builder.into_inner().unwrap().finish().unwrap();
+    let sha256: String = Sha256::digest(&archive)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let (url, requests) = serve_archive(archive, "preset.tar.gz");
+
+    let output = run_aidot_with_home(
+        &["repo", "add", "team", &url, "--sha256", &sha256],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success(), "{:?}", output);
+
+    let pull = || {
+        run_aidot_with_home(
+            &["pull", "team", "--tools", "claude", "--yes"],
+            project_dir.path(),
+            home_dir.path(),
+        )
+    };
+    let output = pull();
+    assert!(output.status.success(), "{:?}", output);
+    assert!(project_dir.path().join(".claude/rules/test.md").exists());
+
+    // A pinned checksum reuses the extracted archive
+    assert!(pull().status.success());
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    // A download that doesn't match the checksum is rejected
+    let output = run_aidot_with_home(
+        &["repo", "add", "bad", &url, "--sha256", &"0".repeat(64)],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+    let output = run_aidot_with_home(
+        &["pull", "bad", "--tools", "claude", "--yes"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Checksum mismatch"));
+
+    // --sha256 is only for archives
+    let output = run_aidot_with_home(
+        &[
+            "repo",
+            "add",
+            "git",
+            "https://github.com/team/preset.git",
+            "--sha256",
+            &sha256,
+        ],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_pull_prints_reload_hints_for_changed_tools() {
+    let home_dir = TempDir::new().unwrap();
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    create_test_preset(preset_dir.path());
+    let preset = preset_dir.path().to_str().unwrap();
+    let pull = || {
+        run_aidot_with_home(
+            &["pull", preset, "--tools", "claude", "--yes"],
+            project_dir.path(),
+            home_dir.path(),
+        )
+    };
+
+    let output = pull();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("To load the changes:"), "{}", stdout);
+    assert!(stdout.contains("start a new Claude Code session"));
+
+    // Nothing changed, nothing to reload
+    let output = pull();
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("To load the changes:"));
+}
+
+#[test]
+fn test_pull_several_repositories_detects_tools_once() {
+    let first_dir = TempDir::new().unwrap();
+    let second_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    create_test_preset(first_dir.path());
+    create_test_preset(second_dir.path());
+    fs::write(second_dir.path().join("rules/test.md"), "# Second\n").unwrap();
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+
+    let output = run_aidot_with_home(
+        &[
+            "pull",
+            first_dir.path().to_str().unwrap(),
+            second_dir.path().to_str().unwrap(),
+            "--force",
+        ],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("Detected").count(), 1, "stdout: {}", stdout);
+    assert!(stdout.contains("[2/2]"), "stdout: {}", stdout);
+    assert_eq!(
+        fs::read_to_string(project_dir.path().join(".claude/rules/test.md")).unwrap(),
+        "# Second\n"
+    );
+
+    // --quiet leaves the tool list out
+    let output = run_aidot_with_home(
+        &[
+            "pull",
+            first_dir.path().to_str().unwrap(),
+            "--force",
+            "--quiet",
+        ],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("tool(s):"), "stdout: {}", stdout);
+}
+
+#[test]
+fn test_preset_path_resolves_bare_names() {
+    let workspace = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    let (first, second) = (workspace.path().join("a"), workspace.path().join("b"));
+    for dir in [&first, &second] {
+        fs::create_dir_all(dir.join("team-preset")).unwrap();
+        create_test_preset(&dir.join("team-preset"));
+    }
+    let search_path = std::env::join_paths([&first, &second]).unwrap();
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_aidot"))
+            .args(args)
+            .current_dir(project_dir.path())
+            .env("HOME", home_dir.path())
+            .env("USERPROFILE", home_dir.path())
+            .env("AIDOT_PRESET_PATH", &search_path)
+            .output()
+            .expect("Failed to execute aidot")
+    };
+
+    // The first directory of the search path wins
+    let output = run(&["repo", "add", "team", "team-preset"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Found 'team-preset' in AIDOT_PRESET_PATH"));
+    assert!(stdout.contains("shadowed"), "stdout: {}", stdout);
+    let config = fs::read_to_string(home_dir.path().join(".aidot/config.toml")).unwrap();
+    let expected = first.join("team-preset").canonicalize().unwrap();
+    assert!(
+        config.contains(&expected.to_string_lossy().replace('\\', "\\\\")),
+        "config: {}",
+        config
+    );
+
+    // Unregistered bare names resolve the same way on pull
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+    let output = run(&["pull", "team-preset", "--force"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(project_dir.path().join(".claude/rules/test.md").exists());
+
+    // Without the search path the name is not found (unchanged behavior)
+    let output = run_aidot_with_home(
+        &["repo", "add", "other", "team-preset"],
+        project_dir.path(),
+        home_dir.path(),
+    );
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cache_update_lists_and_reapplies_projects() {
+    let home_dir = TempDir::new().unwrap();
+    let origin_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let other_dir = TempDir::new().unwrap();
+    let (home, origin) = (home_dir.path(), origin_dir.path());
+
+    create_test_preset(origin);
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args([
+                "-c",
+                "user.name=aidot",
+                "-c",
+                "user.email=aidot@example.com",
+            ])
+            .args(args)
+            .current_dir(origin)
+            .env("HOME", home)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?}", args);
+    };
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "preset"]);
+
+    fs::create_dir_all(home.join(".aidot")).unwrap();
+    fs::write(
+        home.join(".aidot/config.toml"),
+        format!(
+            "[[repositories]]\nname = \"team\"\nurl = {:?}\n",
+            origin.to_str().unwrap()
+        ),
+    )
+    .unwrap();
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+    let output = run_aidot_with_home(&["pull", "team"], project_dir.path(), home);
+    assert!(output.status.success());
+    let removed_dir = TempDir::new().unwrap();
+    fs::create_dir_all(removed_dir.path().join(".claude")).unwrap();
+    let output = run_aidot_with_home(&["pull", "team"], removed_dir.path(), home);
+    assert!(output.status.success());
+    let removed = removed_dir.path().canonicalize().unwrap();
+    drop(removed_dir);
+
+    // History lives in the state directory, not in config.toml
+    let config = fs::read_to_string(home.join(".aidot/config.toml")).unwrap();
+    assert!(!config.contains("history"), "config: {}", config);
+    assert!(home.join(".aidot/state/history.json").exists());
+
+    // Nothing new upstream: no notice
+    let output = run_aidot_with_home(&["cache", "update", "team"], other_dir.path(), home);
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("preset was updated"));
+
+    fs::write(origin.join("commands/deploy.md"), "# Deploy\n").unwrap();
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "new command"]);
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_aidot"))
+        .args(["cache", "update", "team", "--apply"])
+        .current_dir(other_dir.path())
+        .env("HOME", home)
+        .env("USERPROFILE", home)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    use std::io::Write;
+    child.stdin.take().unwrap().write_all(b"y\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("'team' preset was updated"),
+        "stdout: {}",
+        stdout
+    );
+    let project = project_dir.path().canonicalize().unwrap();
+    assert!(
+        stdout.contains(&project.to_string_lossy().replace('\\', "/").to_string()),
+        "stdout: {}",
+        stdout
+    );
+    // Deleted projects are dropped from the history instead of listed
+    assert!(
+        !stdout.contains(&removed.to_string_lossy().replace('\\', "/").to_string()),
+        "stdout: {}",
+        stdout
+    );
+    assert!(project_dir
+        .path()
+        .join(".claude/commands/deploy.md")
+        .exists());
+    assert!(!other_dir.path().join(".claude").exists());
+}
+
+#[test]
+fn test_pull_warns_about_large_merged_files() {
+    let preset_dir = TempDir::new().unwrap();
+    let project_dir = TempDir::new().unwrap();
+    let home_dir = TempDir::new().unwrap();
+    create_test_preset(preset_dir.path());
+    let setup = format!("## Setup\n\n{}\n", "Install the toolchain.\n".repeat(60));
+    fs::write(preset_dir.path().join("memory/context.md"), &setup).unwrap();
+    fs::write(preset_dir.path().join("memory/notes.md"), &setup).unwrap();
+    fs::create_dir_all(project_dir.path().join(".claude")).unwrap();
+    fs::create_dir_all(home_dir.path().join(".aidot")).unwrap();
+
+    let pull = || {
+        run_aidot_with_home(
+            &["pull", preset_dir.path().to_str().unwrap(), "--force"],
+            project_dir.path(),
+            home_dir.path(),
+        )
+    };
+
+    // Under the default limit nothing is reported
+    let output = pull();
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("large files slow tools down"));
+
+    fs::write(
+        home_dir.path().join(".aidot/config.toml"),
+        "merged_warn_kb = 1\n",
+    )
+    .unwrap();
+    fs::write(preset_dir.path().join("rules/test.md"), "# Changed\n").unwrap();
+    fs::write(
+        preset_dir.path().join("memory/notes.md"),
+        format!("{}Keep it short.\n", setup),
+    )
+    .unwrap();
+    let output = pull();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(".claude/CLAUDE.md is 2.") && stdout.contains("(over 1.0 KB"),
+        "stdout: {}",
+        stdout
+    );
+    assert!(
+        stdout.contains("## Setup (2×, possibly duplicated)"),
+        "stdout: {}",
+        stdout
+    );
+}